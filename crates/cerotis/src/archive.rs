@@ -0,0 +1,184 @@
+use chrono::{NaiveDateTime, Utc};
+use futures_util::TryStreamExt;
+use mongodb::{bson::{self, doc}, options::FindOptions, Database};
+use orion::{constants::{MONGO_GAME_MOVES_MODEL, MONGO_USERS_MODEL, MONGO_USER_TURNS_MODEL}, engines::{chess::pgn::write_pgn, DynGameEngine, EngineAction}, models::{game_model::Game, game_move_model::GameMoveRecord, user_game_relation_model::UserGameRelation, user_turn_model::UserTurnMapping}};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set, TransactionTrait};
+use ton::models::{game_history, game_history_players};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+
+pub const RESULT_WIN: &str = "win";
+pub const RESULT_DRAW: &str = "draw";
+
+
+// How a session ended. placements hold (user_id, place, score_change) of every ranked player, empty if nobody was ranked
+pub struct GameOutcome {
+    pub result: String,
+    pub termination_reason: String,
+    pub placements: Vec<(Uuid, u32, i32)>,
+}
+
+impl GameOutcome {
+    // Games closed without a result (abandoned, expired) use the reason as result
+    pub fn unranked(reason: &str) -> Self {
+        GameOutcome {
+            result: reason.to_string(),
+            termination_reason: reason.to_string(),
+            placements: vec![],
+        }
+    }
+}
+
+
+// Copies the current session of a finished game into the postgres history. Has to run before USER_GAME_DELETION
+// removes the mongo documents it is built from
+pub async fn archive_game(postgres_conn: &DatabaseConnection, mongo_db: &Database, engine: Option<&dyn DynGameEngine>, game: &Game, outcome: &GameOutcome) {
+    let game_id = game.id.to_string();
+    let session_id = game.session_id.clone().unwrap_or_default();
+
+    // A session can be closed more than once (result and reaper racing), only the first close is kept
+    match game_history::Entity::find_by_game_id_and_session_id(&game.id, session_id.clone()).one(postgres_conn).await {
+        Ok(None) => {},
+        Ok(Some(_)) => return,
+        Err(e) => {
+            warn!("Error while checking history of game_id={} session_id={}: {:?}" , game_id , session_id , e);
+            return;
+        }
+    }
+
+    let relations: Vec<UserGameRelation> = match mongo_db.collection::<UserGameRelation>(MONGO_USERS_MODEL).find(doc! { "game_id": game_id.clone() }, None).await {
+        Ok(cursor) => cursor.try_collect().await.unwrap_or_default(),
+        Err(e) => {
+            warn!("Error while fetching players of game_id={} for the history: {:?}" , game_id , e);
+            vec![]
+        }
+    };
+    let turn_mapping = mongo_db.collection::<UserTurnMapping>(MONGO_USER_TURNS_MODEL).find_one(doc! { "game_id": game_id.clone() }, None).await.ok().flatten();
+
+    let move_options = FindOptions::builder().sort(doc! { "state_index": 1 }).build();
+    let moves: Vec<GameMoveRecord> = match mongo_db.collection::<GameMoveRecord>(MONGO_GAME_MOVES_MODEL).find(doc! { "game_id": game_id.clone(), "session_id": session_id.clone() }, move_options).await {
+        Ok(cursor) => cursor.try_collect().await.unwrap_or_default(),
+        Err(e) => {
+            warn!("Error while fetching moves of game_id={} for the history: {:?}" , game_id , e);
+            vec![]
+        }
+    };
+
+    let history_id = Uuid::new_v4();
+    let started_at = to_naive(moves.first().map(|game_move| game_move.created_at).unwrap_or(game.created_at));
+    let ended_at = Utc::now().naive_utc();
+
+    let players: Vec<game_history_players::ActiveModel> = turn_mapping.iter().flat_map(|turn_mapping| &turn_mapping.turn_mappings).filter_map(|turn| {
+        let user_id = Uuid::parse_str(&turn.user_id).ok()?;
+        let player_type = relations.iter().find(|relation| relation.user_id == user_id).map(|relation| relation.player_type.clone()).unwrap_or_default();
+        let placement = outcome.placements.iter().find(|(placed_user_id, _, _)| *placed_user_id == user_id);
+
+        Some(game_history_players::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            game_history_id: Set(history_id),
+            user_id: Set(user_id),
+            username: Set(turn.username.clone()),
+            player_type: Set(player_type),
+            seat: Set(turn.count_id),
+            place: Set(placement.map(|(_, place, _)| *place as i32)),
+            score_change: Set(placement.map(|(_, _, score_change)| *score_change).unwrap_or_default()),
+            is_winner: Set(outcome.result == RESULT_WIN && placement.map(|(_, place, _)| *place == 1).unwrap_or_default()),
+        })
+    }).collect();
+
+    let pgn = engine
+        .filter(|engine| engine.game_type() == "chess")
+        .and_then(|engine| chess_pgn(engine, game, &relations, &moves, outcome, ended_at));
+
+    let history = game_history::ActiveModel {
+        id: Set(history_id),
+        game_id: Set(game.id),
+        session_id: Set(session_id.clone()),
+        game_type: Set(game.game_type.clone()),
+        game_name: Set(game.name.clone()),
+        result: Set(outcome.result.clone()),
+        termination_reason: Set(outcome.termination_reason.clone()),
+        time_control: Set(game.time_control.clone()),
+        move_count: Set(moves.iter().filter(|game_move| game_move.move_type != "start").count() as i32),
+        final_state: Set(moves.last().map(|game_move| game_move.public_state.clone()).filter(|public_state| !public_state.is_empty())),
+        pgn: Set(pgn),
+        started_at: Set(started_at),
+        ended_at: Set(ended_at),
+        is_private: Set(game.join_password.is_some()),
+    };
+
+    let tx = match postgres_conn.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            warn!("Error while archiving game_id={} session_id={}: {:?}" , game_id , session_id , e);
+            return;
+        }
+    };
+
+    if let Err(e) = history.insert(&tx).await {
+        warn!("Error while archiving game_id={} session_id={}: {:?}" , game_id , session_id , e);
+        return;
+    }
+
+    if !players.is_empty() {
+        if let Err(e) = game_history_players::Entity::insert_many(players).exec(&tx).await {
+            warn!("Error while archiving players of game_id={} session_id={}: {:?}" , game_id , session_id , e);
+            return;
+        }
+    }
+
+    match tx.commit().await {
+        Ok(_) => info!("Archived game_id={} session_id={} with result={}" , game_id , session_id , outcome.result),
+        Err(e) => warn!("Error while archiving game_id={} session_id={}: {:?}" , game_id , session_id , e),
+    }
+}
+
+
+// Moves are replayed from the recorded positions, the record has to start with the initial position and have no gaps
+fn chess_pgn(engine: &dyn DynGameEngine, game: &Game, relations: &[UserGameRelation], moves: &[GameMoveRecord], outcome: &GameOutcome, ended_at: NaiveDateTime) -> Option<String> {
+    let initial_state = moves.first().filter(|game_move| game_move.move_type == "start")?;
+
+    let notations: Option<Vec<String>> = moves.windows(2).map(|pair| {
+        if pair[1].state_index != pair[0].state_index + 1 {
+            return None;
+        }
+
+        engine.move_notation(&pair[0].public_state, &EngineAction::from_game_move_record(&pair[1]))
+    }).collect();
+
+    let username_of = |player_type: &str| relations.iter()
+        .find(|relation| relation.player_type == player_type)
+        .map(|relation| relation.username.clone())
+        .unwrap_or_else(|| "?".to_string());
+
+    let winner_type = outcome.placements.iter()
+        .find(|(_, place, _)| *place == 1)
+        .and_then(|(user_id, _, _)| relations.iter().find(|relation| relation.user_id == *user_id))
+        .map(|relation| relation.player_type.as_str());
+
+    let result = match (outcome.result.as_str(), winner_type) {
+        (RESULT_DRAW, _) => "1/2-1/2",
+        (RESULT_WIN, Some("white")) => "1-0",
+        (RESULT_WIN, Some("black")) => "0-1",
+        _ => "*",
+    };
+
+    let tags = [
+        ("Event", game.name.clone()),
+        ("Site", "vortex".to_string()),
+        ("Date", ended_at.format("%Y.%m.%d").to_string()),
+        ("Round", "-".to_string()),
+        ("White", username_of("white")),
+        ("Black", username_of("black")),
+        ("Result", result.to_string()),
+        ("Termination", outcome.termination_reason.clone()),
+    ];
+
+    Some(write_pgn(&tags, &initial_state.public_state, &notations?, result))
+}
+
+
+fn to_naive(date_time: bson::DateTime) -> NaiveDateTime {
+    chrono::DateTime::from_timestamp_millis(date_time.timestamp_millis()).unwrap_or_default().naive_utc()
+}
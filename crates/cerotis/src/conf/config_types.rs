@@ -60,10 +60,18 @@ pub struct TopicProperties {
 pub struct KafkaConfiguration {
     pub broker: BrokerProperties,
     pub consumer: Vec<ConsumerConfiguration>,
+    pub producer: ProducerProperties,
     pub schema_registry: SchemaRegistryProperties,
     pub topic: TopicConfiguration,
 }
 
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct ProducerProperties {
+    pub client_id: String,
+    pub transactional_id: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[allow(unused)]
 pub struct ServerConfiguration {
@@ -103,3 +111,30 @@ pub struct PostgresDatabaseUrl {
 pub struct RedisDBUrl {
     pub url: String,
 }
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct ScribbleConfiguration {
+    // Text file with one word per line, the built in list is used when missing
+    pub word_list_path: Option<String>,
+    pub rounds: Option<u32>,
+    pub draw_time_seconds: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[allow(unused)]
+pub struct ReaperConfiguration {
+    pub interval_secs: u64,
+    // Time since the last update after which a game of that status is considered abandoned
+    pub lobby_ttl_secs: u64,
+    pub in_progress_ttl_secs: u64,
+    pub game_over_ttl_secs: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[allow(unused)]
+pub struct LeaderboardConfiguration {
+    pub check_interval_secs: u64,
+    // Leaderboards are rebuilt from Postgres once a day, on the first check after this hour (UTC)
+    pub rebuild_hour_utc: u32,
+}
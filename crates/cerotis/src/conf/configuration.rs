@@ -3,7 +3,7 @@ use std::{env, sync::atomic::AtomicU16};
 use config::{Config, ConfigError, File};
 use serde::Deserialize;
 use std::sync::atomic::Ordering::SeqCst;
-use super::config_types::{KafkaConfiguration, LoggingConfiguration, MongoDatabaseConfiguration, PostgresDatabaseUrl, RedisDBUrl, ServerConfiguration};
+use super::config_types::{KafkaConfiguration, LeaderboardConfiguration, LoggingConfiguration, MongoDatabaseConfiguration, PostgresDatabaseUrl, ReaperConfiguration, RedisDBUrl, ScribbleConfiguration, ServerConfiguration};
 
 
 pub static SERVER_PORT: AtomicU16 = AtomicU16::new(0);
@@ -15,7 +15,10 @@ pub struct Configuration {
     pub postgres_url: PostgresDatabaseUrl,
     pub mongo_db: MongoDatabaseConfiguration,
     pub logging: LoggingConfiguration,
-    pub redis_url: RedisDBUrl
+    pub redis_url: RedisDBUrl,
+    pub scribble: Option<ScribbleConfiguration>,
+    pub reaper: ReaperConfiguration,
+    pub leaderboard: LeaderboardConfiguration,
 }
 
 impl Configuration {
@@ -1 +1,2 @@
-pub mod consumer;
\ No newline at end of file
+pub mod consumer;
+pub mod producer;
\ No newline at end of file
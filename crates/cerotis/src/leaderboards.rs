@@ -0,0 +1,240 @@
+use std::{collections::{HashMap, HashSet}, time::Duration};
+
+use chrono::{DateTime, NaiveDateTime, Timelike, Utc};
+use orion::{constants::{LEADERBOARD_KEY, LEADERBOARD_REBUILD_LOCK_KEY}, ratings::{category::PROVISIONAL_GAMES, leaderboard::{country_leaderboard_key, leaderboard_key, period_leaderboard_key, LeaderboardPeriod}}};
+use redis::{aio::MultiplexedConnection, AsyncCommands, AsyncIter, ExistenceCheck, RedisResult, SetExpiry, SetOptions};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect};
+use tokio::{task::JoinHandle, time::interval};
+use ton::models::{rating_history, user_ratings, users};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{conf::config_types::LeaderboardConfiguration, context::context::DynContext};
+
+
+const PERIODS: [LeaderboardPeriod; 2] = [LeaderboardPeriod::Weekly, LeaderboardPeriod::Monthly];
+// Members written per ZADD while rebuilding
+const REBUILD_BATCH_SIZE: usize = 1000;
+const REBUILD_LOCK_TTL_SECS: u64 = 2 * 24 * 60 * 60;
+
+
+pub struct LeaderboardUpdate {
+    pub user_id: Uuid,
+    pub rating: f64,
+    pub rating_change: f64,
+    // Rated games in the category, this one included
+    pub games_played: i32,
+}
+
+
+// Applies the rating changes of a finished game to the leaderboards of its category once they are committed.
+// A failed update is only logged, the nightly rebuild corrects it
+pub async fn record_rating_changes(redis_conn: &mut MultiplexedConnection, postgres_conn: &DatabaseConnection, game_type: &str, category: &str, updates: &[LeaderboardUpdate]) {
+    let user_ids: Vec<Uuid> = updates.iter().map(|update| update.user_id).collect();
+    let countries: HashMap<Uuid, String> = match users::Entity::find()
+        .select_only()
+        .column(users::Column::Id)
+        .column(users::Column::Country)
+        .filter(users::Column::Id.is_in(user_ids))
+        .filter(users::Column::Country.is_not_null())
+        .into_tuple::<(Uuid, String)>()
+        .all(postgres_conn)
+        .await
+    {
+        Ok(countries) => countries.into_iter().collect(),
+        Err(e) => {
+            warn!("Error while fetching player countries for leaderboards: {:?}" , e);
+            HashMap::new()
+        }
+    };
+
+    let now = Utc::now();
+    let mut pipe = redis::pipe();
+    for update in updates {
+        let user_id = update.user_id.to_string();
+
+        // Provisional ratings still move too much to be ranked
+        if update.games_played >= PROVISIONAL_GAMES {
+            pipe.zadd(leaderboard_key(game_type, category), &user_id, update.rating).ignore();
+            if let Some(country) = countries.get(&update.user_id) {
+                pipe.zadd(country_leaderboard_key(game_type, category, country), &user_id, update.rating).ignore();
+            }
+        }
+
+        for period in PERIODS {
+            let key = period_leaderboard_key(game_type, category, period, now);
+            pipe.zincr(&key, &user_id, update.rating_change).ignore();
+            pipe.expire_at(&key, period_expiry(period, now)).ignore();
+        }
+    }
+
+    let update_res: RedisResult<()> = pipe.query_async(redis_conn).await;
+    if let Err(e) = update_res {
+        warn!("Error while updating leaderboards of game_type={} category={}: {:?}" , game_type , category , e);
+    }
+}
+
+
+pub fn start(context: DynContext, config: LeaderboardConfiguration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut check_interval = interval(Duration::from_secs(config.check_interval_secs));
+
+        loop {
+            check_interval.tick().await;
+
+            let now = Utc::now();
+            if now.hour() < config.rebuild_hour_utc {
+                continue;
+            }
+
+            // Only one cerotis instance rebuilds per day
+            let mut redis_conn = context.get_redis_db_client();
+            let options = SetOptions::default().conditional_set(ExistenceCheck::NX).with_expiration(SetExpiry::EX(REBUILD_LOCK_TTL_SECS));
+            let locked: RedisResult<Option<String>> = redis_conn.set_options(LEADERBOARD_REBUILD_LOCK_KEY.to_owned() + &now.date_naive().to_string(), "locked", options).await;
+            if !matches!(locked, Ok(Some(_))) {
+                continue;
+            }
+
+            match rebuild(&mut redis_conn, &context.get_postgres_db_client(), now).await {
+                Ok(leaderboards) => info!("Rebuilt {} leaderboards" , leaderboards),
+                Err(e) => warn!("Error while rebuilding leaderboards: {}" , e),
+            }
+        }
+    })
+}
+
+
+// Recomputes the rating leaderboards from user_ratings and the weekly and monthly ones of the current and previous
+// period from the rating history. Games rated while this runs are only on the leaderboards again after the next one
+async fn rebuild(redis_conn: &mut MultiplexedConnection, postgres_conn: &DatabaseConnection, now: DateTime<Utc>) -> Result<usize, String> {
+    let countries: HashMap<Uuid, String> = users::Entity::find()
+        .select_only()
+        .column(users::Column::Id)
+        .column(users::Column::Country)
+        .filter(users::Column::Country.is_not_null())
+        .into_tuple::<(Uuid, String)>()
+        .all(postgres_conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+
+    let ratings: Vec<(Uuid, String, String, f64, i32)> = user_ratings::Entity::find()
+        .select_only()
+        .column(user_ratings::Column::UserId)
+        .column(user_ratings::Column::GameType)
+        .column(user_ratings::Column::Category)
+        .column(user_ratings::Column::Rating)
+        .column(user_ratings::Column::GamesPlayed)
+        .into_tuple()
+        .all(postgres_conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Every category anyone is rated in gets all its leaderboards rebuilt, empty ones are deleted
+    let mut leaderboards: HashMap<String, Vec<(f64, String)>> = HashMap::new();
+    let mut expiries: HashMap<String, i64> = HashMap::new();
+    let categories: HashSet<(String, String)> = ratings.iter().map(|(_, game_type, category, _, _)| (game_type.clone(), category.clone())).collect();
+    for (game_type, category) in &categories {
+        leaderboards.entry(leaderboard_key(game_type, category)).or_default();
+        for period in PERIODS {
+            for at in [now, period.previous(now)] {
+                let key = period_leaderboard_key(game_type, category, period, at);
+                expiries.insert(key.clone(), period_expiry(period, at));
+                leaderboards.entry(key).or_default();
+            }
+        }
+    }
+
+    for (user_id, game_type, category, rating, games_played) in ratings {
+        if games_played < PROVISIONAL_GAMES {
+            continue;
+        }
+
+        leaderboards.entry(leaderboard_key(&game_type, &category)).or_default().push((rating, user_id.to_string()));
+        if let Some(country) = countries.get(&user_id) {
+            leaderboards.entry(country_leaderboard_key(&game_type, &category, country)).or_default().push((rating, user_id.to_string()));
+        }
+    }
+
+    let history_start = PERIODS.iter().map(|period| period.start(period.previous(now))).min().unwrap();
+    let rating_changes: Vec<(Uuid, String, String, f64, f64, NaiveDateTime)> = rating_history::Entity::find()
+        .select_only()
+        .column(rating_history::Column::UserId)
+        .column(rating_history::Column::GameType)
+        .column(rating_history::Column::Category)
+        .column(rating_history::Column::RatingBefore)
+        .column(rating_history::Column::RatingAfter)
+        .column(rating_history::Column::CreatedAt)
+        .filter(rating_history::Column::CreatedAt.gte(history_start.naive_utc()))
+        .filter(rating_history::Column::Category.ne(""))
+        .into_tuple()
+        .all(postgres_conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut gains: HashMap<String, HashMap<Uuid, f64>> = HashMap::new();
+    for (user_id, game_type, category, rating_before, rating_after, created_at) in rating_changes {
+        let created_at = created_at.and_utc();
+        for period in PERIODS {
+            if created_at >= period.start(period.previous(now)) {
+                let key = period_leaderboard_key(&game_type, &category, period, created_at);
+                *gains.entry(key).or_default().entry(user_id).or_default() += rating_after - rating_before;
+            }
+        }
+    }
+    for (key, user_gains) in gains {
+        leaderboards.entry(key).or_default().extend(user_gains.into_iter().map(|(user_id, gain)| (gain, user_id.to_string())));
+    }
+
+    for (key, members) in &leaderboards {
+        replace_leaderboard(redis_conn, key, members, expiries.get(key).copied()).await.map_err(|e| e.to_string())?;
+    }
+
+    // Rating leaderboards never expire, the ones that were not rebuilt (countries nobody plays for anymore, unfinished
+    // rebuilds) are deleted. Older weekly and monthly ones expire on their own
+    let mut leftover_keys = vec![];
+    {
+        let mut keys: AsyncIter<String> = redis_conn.scan_match(LEADERBOARD_KEY.to_owned() + "*").await.map_err(|e| e.to_string())?;
+        while let Some(key) = keys.next_item().await {
+            if !leaderboards.contains_key(&key) {
+                leftover_keys.push(key);
+            }
+        }
+    }
+    for key in leftover_keys {
+        let ttl: i64 = redis_conn.ttl(&key).await.map_err(|e| e.to_string())?;
+        if ttl == -1 {
+            let _: RedisResult<()> = redis_conn.del(&key).await;
+        }
+    }
+
+    Ok(leaderboards.len())
+}
+
+
+// Written under a temporary key and renamed over the live one, readers never see a half built leaderboard
+async fn replace_leaderboard(redis_conn: &mut MultiplexedConnection, key: &str, members: &[(f64, String)], expire_at: Option<i64>) -> RedisResult<()> {
+    if members.is_empty() {
+        return redis_conn.del(key).await;
+    }
+
+    let rebuild_key = format!("{}:rebuild", key);
+    let _: () = redis_conn.del(&rebuild_key).await?;
+    for batch in members.chunks(REBUILD_BATCH_SIZE) {
+        let _: () = redis_conn.zadd_multiple(&rebuild_key, batch).await?;
+    }
+    let _: () = redis_conn.rename(&rebuild_key, key).await?;
+
+    if let Some(expire_at) = expire_at {
+        let _: () = redis_conn.expire_at(key, expire_at).await?;
+    }
+
+    Ok(())
+}
+
+
+// A weekly or monthly leaderboard is kept until the end of the following period so the last one can still be shown
+fn period_expiry(period: LeaderboardPeriod, at: DateTime<Utc>) -> i64 {
+    period.end(period.end(at)).timestamp()
+}
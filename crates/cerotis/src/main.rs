@@ -3,26 +3,29 @@ use std::{collections::HashMap, net::SocketAddr, str::FromStr, sync::{Arc, Mutex
 use api::health;
 use axum::{routing::get, Router};
 use conf::{config_types::ServerConfiguration, configuration::Configuration};
+use archive::{GameOutcome, RESULT_DRAW, RESULT_WIN};
 use context::context::{ContextImpl, DynContext};
-use mongodb::bson::{self, doc};
-use orion::{ constants::{CHESS_STATE_REDIS_KEY, CREATE_NEW_GAME_RECORD, CREATE_USER_BET, USER_GAME_DELETION, USER_GAME_EVENTS, USER_SCORE_UPDATE}, events::kafka_event::{CreateNewGamePayloadEvent, GameBetEvent, UserGameBetEvent, UserGameDeletetionEvent}, models::{chess_events::{CellPosition, ChessNormalEvent, ChessPromotionEvent}, game_bet_events::GameBetStatus, game_model::Game, user_game_event::UserGameMove, user_game_relation_model::UserGameRelation, user_score_update_event::UserScoreUpdateEvent, user_turn_model::UserTurnMapping}};
-use rdkafka::{consumer::StreamConsumer, Message};
-use redis::{AsyncCommands, RedisResult};
-use sea_orm::{prelude::Expr, ActiveValue, ColIdx, Database, EntityTrait, IntoSimpleExpr, QueryFilter, Set, Value};
+use mongodb::{bson::{self, doc}, Collection};
+use orion::{ constants::{CREATE_NEW_GAME_RECORD, CREATE_USER_BET, ERROR_EVENT, GAME_AWAITING_BETS_STATUS, GAME_CLOCK_EVENT, GAME_IN_PROGRESS_STATUS, GAME_OVER_EVENT, GAME_OVER_STATUS, GAME_STATE_UPDATE_EVENT, GAME_RESULT_EVENT, GAME_SPECTATORS_KEY, GAME_TRANSIENT_ACTION_EVENT, GAME_TURN_TIMER, GAME_TURN_TIMER_DATA, MONGO_GAMES_MODEL, MONGO_USERS_MODEL, MONGO_USER_TURNS_MODEL, PLAYER_DISCONNECTED, PLAYER_HEARTBEAT_DATA, PLAYER_HEARTBEAT_KEY, PLAYER_PRIVATE_STATE_EVENT, USER_GAME_DELETION, USER_GAME_EVENTS, MONGO_GAME_MOVES_MODEL, USER_SCORE_UPDATE}, events::{kafka_event::{CreateNewGamePayloadEvent, GameBetEvent, GameOverEvent, KafkaGeneralEvent, UserGameBetEvent, UserGameDeletetionEvent}, ws_events::{ErrorMessagePayload, GameClockPayload, GameResultPayload, GameStateUpdatePayload, GameTransientActionPayload, PlayerPlacementPayload, PlayerPrivateStatePayload}}, engines::{registry::GameEngineRegistry, scribble::ScribbleEngine, DynGameEngine, EngineAction, GameResult, PrivateState, StateStorage}, models::{game_bet_events::GameBetStatus, game_model::Game, game_move_model::GameMoveRecord, user_game_event::UserGameMove, user_game_relation_model::UserGameRelation, user_turn_model::UserTurnMapping}, ratings::category::rating_category};
+use rdkafka::{consumer::StreamConsumer, producer::FutureProducer, Message};
+use redis::{aio::MultiplexedConnection, AsyncCommands, RedisResult};
+use sea_orm::{ActiveValue, ColIdx, Database, DatabaseConnection, IntoSimpleExpr, Set, Value};
 use tokio::{spawn, task::JoinHandle};
 use tracing::{info, warn};
-use ton::models::{self, game, game_bets, users};
+use ton::models::{self, game, game_bets};
 use chrono::Utc;
 use uuid::Uuid;
 use sea_orm::ActiveModelTrait;
-use sea_orm::ColumnTrait;
 pub mod kafka;
 pub mod conf;
 pub mod api;
 pub mod context;
 pub mod mongo_pool;
-pub mod fen_update;
 pub mod logging_tracing;
+pub mod reaper;
+pub mod archive;
+pub mod ratings;
+pub mod leaderboards;
 
 
 
@@ -46,6 +49,9 @@ async fn main()  {
     
     
     let context = ContextImpl::new_dyn_context(mongo_db_client,  redis_connection , connection);
+
+    let reaper_handle = reaper::start(context.clone(), config.reaper.clone(), kafka::producer::create_new_kafka_producer(&config.kafka).unwrap(), build_engine_registry(&config));
+    let leaderboard_handle = leaderboards::start(context.clone(), config.leaderboard.clone());
     
     let user_and_game_handles = init_user_and_game_kafka_consumer(
         context,
@@ -53,7 +59,7 @@ async fn main()  {
         consumers
     );
 
-    start_web_server(&config.server, vec![user_and_game_handles])
+    start_web_server(&config.server, vec![user_and_game_handles, reaper_handle, leaderboard_handle])
     .await;
 
 
@@ -128,11 +134,14 @@ fn init_user_and_game_kafka_consumer(
     let mut kafka_joins: Vec<JoinHandle<()>> = vec![];
 
     for (key_topic , value) in kafka_consumers.into_iter() {
+       // Each listener gets its own transactional producer so transactions never overlap
+       let producer = kafka::producer::create_new_kafka_producer(&config.kafka).unwrap();
        let kf_join =  listen(
             context.clone(),
             config,
             value,
-            key_topic
+            key_topic,
+            producer
         );
 
         kafka_joins.push(kf_join);
@@ -155,12 +164,14 @@ pub fn listen(
     config: &Configuration,
     stream_consumer: StreamConsumer,
     key_topic: String,
+    producer: FutureProducer,
 ) -> JoinHandle<()> {
     let topic = key_topic.clone();
+    let engine_registry = build_engine_registry(config);
 
     // Start listener
     tokio::spawn(async move {
-        do_listen( context, &stream_consumer, topic ).await;
+        do_listen( context, &stream_consumer, topic, producer, engine_registry ).await;
     })
 }
 
@@ -168,12 +179,16 @@ pub async fn do_listen(
     context: DynContext,
     stream_consumer: &StreamConsumer,
     topic_name: String,
+    producer: FutureProducer,
+    // Every game type is handled by its engine, picked using Game.game_type
+    engine_registry: GameEngineRegistry,
 ) {
 
     let mongo_db = context.get_mongo_db_client().database("user_game_events_db");
     let user_collection = mongo_db.collection::<UserGameRelation>("users");
     let game_collection = mongo_db.collection::<Game>("games");
     let user_turn_collection = mongo_db.collection::<UserTurnMapping>("user_turns");
+    let game_move_collection = mongo_db.collection::<GameMoveRecord>(MONGO_GAME_MOVES_MODEL);
 
     let postgres_conn = context.get_postgres_db_client();
 
@@ -205,6 +220,22 @@ pub async fn do_listen(
                     }
                     
 
+                },
+                GAME_OVER_EVENT => {
+                    let game_over_event: GameOverEvent = match serde_json::from_str(&payload) {
+                        Ok(game_over_event) => game_over_event,
+                        Err(e) => {
+                            warn!("Error while parsing GameOverEvent payload: {:?}" , e);
+                            continue;
+                        }
+                    };
+
+                    // Finished games are settled when their last move is applied, only abandoned games are closed here
+                    if game_over_event.is_game_valid {
+                        continue;
+                    }
+
+                    close_abandoned_game(&producer, &mut redis_conn, &postgres_conn, &mongo_db, &engine_registry, &game_over_event).await;
                 },
                 USER_GAME_DELETION => {
                     let user_game_deletion_event_res = serde_json::from_str(&payload);
@@ -213,77 +244,145 @@ pub async fn do_listen(
                     let _ = user_collection.delete_many(doc! { "game_id": user_game_deletion_event.game_id.clone()}, None).await;
                     let _ = game_collection.delete_many(doc! { "id": user_game_deletion_event.game_id.clone()}, None).await;
                     let _ = user_turn_collection.delete_many(doc! { "game_id": user_game_deletion_event.game_id.clone()}, None).await;
-                    let _: RedisResult<()> = redis_conn.del(CHESS_STATE_REDIS_KEY.to_owned() + &user_game_deletion_event.game_id).await;
+                    for game_type in engine_registry.game_types() {
+                        let engine = engine_registry.get(&game_type).unwrap();
+                        let _: RedisResult<()> = redis_conn.del(engine.state_key_prefix().to_owned() + &user_game_deletion_event.game_id).await;
+                    }
+                    let _: RedisResult<()> = redis_conn.del(GAME_TURN_TIMER.to_owned() + &user_game_deletion_event.game_id).await;
+                    let _: RedisResult<()> = redis_conn.del(GAME_TURN_TIMER_DATA.to_owned() + &user_game_deletion_event.game_id).await;
+                    let _: RedisResult<()> = redis_conn.del(GAME_SPECTATORS_KEY.to_owned() + &user_game_deletion_event.game_id).await;
                   }
                 },
                 USER_SCORE_UPDATE => {
-                    let user_score_update_event_payload = serde_json::from_str(&payload);
-
-                    if user_score_update_event_payload.is_ok() {
-                        let user_score_model_res: UserScoreUpdateEvent = user_score_update_event_payload.unwrap();
-
-                        let _ = users::Entity::update_many().col_expr(users::Column::Score, Expr::cust_with_values("GREATEST(0, score + $1)", [user_score_model_res.score]))
-                                .filter(users::Column::Id.eq(Uuid::from_str(&user_score_model_res.user_id).unwrap()))
-                                .exec(&postgres_conn)
-                                .await;
-                    }
-
+                    // Ratings only change through settle_game_result, scores sent by anyone else are dropped
+                    warn!("Ignoring score update received outside of a game result: {}" , payload);
                 },
                 USER_GAME_EVENTS => {
-                    let user_game_event_payload: UserGameMove = serde_json::from_str(&payload).unwrap();
-                    let mut state_key = CHESS_STATE_REDIS_KEY.to_owned();
-                    state_key.push_str(&user_game_event_payload.game_id);
-                    // Instead of getting current state from mongo keep it in redis or in elixir process
-                    let rsp: RedisResult<String>  = redis_conn.get(state_key.clone()).await;
-
-
-                   if rsp.is_ok() {
-                    let game_model = rsp.unwrap();
-                    println!("Game state is: {:?}" , game_model);
-                    let _rsp = if user_game_event_payload.move_type == "normal" {
-                        let gm_ev: ChessNormalEvent = serde_json::from_str(&user_game_event_payload.user_move).unwrap();
-            
-                        let old_position: CellPosition = serde_json::from_str(&gm_ev.initial_cell).unwrap();
-                        let new_position: CellPosition = serde_json::from_str(&gm_ev.target_cell).unwrap();
-                        let piece: Vec<char> = gm_ev.piece.chars().collect();
-                
-                        let updated_fen = fen_update::update_fen_with_timing(&game_model, *piece.get(0).unwrap() , &get_chess_position(&old_position) , &get_chess_position(&new_position) , None );
+                    let user_game_event_payload_res = serde_json::from_str(&payload);
 
-                        if updated_fen.is_some() {
-                            let updated_fen_rsp = updated_fen.unwrap();
-                           // println!("Updated Fen is: {:?}" , updated_fen.unwrap());
-                            
+                    if user_game_event_payload_res.is_err() {
+                        warn!("Error while parsing UserGameMove payload: {:?}" , user_game_event_payload_res.err());
+                        continue;
+                    }
 
-                             let redis_res: RedisResult<()> =    redis_conn.set(state_key.clone() , updated_fen_rsp.fen).await;
+                    let user_game_event_payload: UserGameMove = user_game_event_payload_res.unwrap();
 
-                          
-                          
-                        
-                        }  else {
-                            println!("UPDATE RESULT IS NONE");
-                            println!("CURRENT FEN IS: {:?}" , &game_model)
+                    let game_model = match game_collection.find_one(doc! { "id": user_game_event_payload.game_id.clone() }, None).await {
+                        Ok(Some(game)) => game,
+                        _ => {
+                            warn!("Game not found for game_id={}" , user_game_event_payload.game_id);
+                            send_move_rejected_event(&producer, &game_collection, &user_game_event_payload, "Game not found", None).await;
+                            continue;
                         }
+                    };
 
-                    } else {
-                        let gm_ev: ChessPromotionEvent = serde_json::from_str(&user_game_event_payload.user_move).unwrap();
-                        let old_position: CellPosition = serde_json::from_str(&gm_ev.initial_cell).unwrap();
-                        let new_position: CellPosition = serde_json::from_str(&gm_ev.target_cell).unwrap();
-                        let piece: Vec<char> = gm_ev.piece.chars().collect();
-                        let promoted_to: Vec<char> = gm_ev.promoted_to.chars().collect();
-                        let updated_fen = fen_update::update_fen_with_timing(&game_model, *piece.get(0).unwrap() , &get_chess_position(&old_position) , &get_chess_position(&new_position) , Some(*promoted_to.get(0).unwrap()) );
+                    // The state of a staked rematch is only dealt once both players bet again
+                    if game_model.description == GAME_AWAITING_BETS_STATUS {
+                        send_move_rejected_event(&producer, &game_collection, &user_game_event_payload, "Waiting for players to place their bets", None).await;
+                        continue;
+                    }
 
-                        if updated_fen.is_some() {
-                            let updated_fen_rsp = updated_fen.unwrap();
-                           // println!("Updated Fen is: {:?}" , updated_fen.unwrap());
+                    // Abandoned games keep their engine state, so finished games are closed here rather than by the engine
+                    if game_model.description == GAME_OVER_STATUS {
+                        send_move_rejected_event(&producer, &game_collection, &user_game_event_payload, "Game is over", None).await;
+                        continue;
+                    }
 
-                           let redis_res: RedisResult<()> =    redis_conn.set(state_key.clone(), updated_fen_rsp.fen).await;
-                        } 
+                    let engine_res = engine_registry.get(&game_model.game_type);
+                    if engine_res.is_err() {
+                        let reason = engine_res.err().unwrap().to_string();
+                        warn!("{} for game_id={}" , reason , user_game_event_payload.game_id);
+                        send_move_rejected_event(&producer, &game_collection, &user_game_event_payload, &reason, None).await;
+                        continue;
+                    }
+                    let engine = engine_res.unwrap();
+
+                    let rsp = get_game_state(engine, &mut redis_conn, &game_model, &user_game_event_payload.game_id).await;
+
+                    let current_state = match rsp {
+                        Ok(Some(current_state)) => current_state,
+                        Ok(None) => {
+                            // First action of the game, the engine deals / sets up the initial state
+                            let initial_state_res = init_game_state(engine, &user_turn_collection, &user_game_event_payload.game_id).await;
+                            if initial_state_res.is_err() {
+                                let reason = initial_state_res.err().unwrap();
+                                warn!("Could not create initial state for game_id={}: {}" , user_game_event_payload.game_id , reason);
+                                send_move_rejected_event(&producer, &game_collection, &user_game_event_payload, &reason, None).await;
+                                continue;
+                            }
+
+                            let initial_state = initial_state_res.unwrap();
+                            let save_res = save_game_state(engine, &mut redis_conn, &game_collection, &user_game_event_payload.game_id, initial_state.clone()).await;
+                            if save_res.is_err() {
+                                warn!("Error while saving initial state for game_id={}" , user_game_event_payload.game_id);
+                                send_move_rejected_event(&producer, &game_collection, &user_game_event_payload, "Game state could not be saved", None).await;
+                                continue;
+                            }
+
+                            let public_state = engine.public_state(&initial_state).ok();
+                            record_game_move(&game_move_collection, &user_game_event_payload.game_id, &game_model.session_id.clone().unwrap_or_default(), game_model.state_index, None, public_state.clone()).await;
+                            send_game_state_events(&producer, &user_game_event_payload.game_id, public_state, engine.private_states(&initial_state).unwrap_or_default(), game_model.state_index).await;
+
+                            initial_state
+                        },
+                        Err(_) => {
+                            warn!("Receieved error while fetching game state for game_id={}" , user_game_event_payload.game_id);
+                            send_move_rejected_event(&producer, &game_collection, &user_game_event_payload, "Game state not found", None).await;
+                            continue;
+                        }
+                    };
 
+                    let engine_action = match timer_tick_action(&user_game_event_payload, Utc::now().timestamp_millis()) {
+                        Some(tick_action) => tick_action,
+                        None => {
+                            let Some(player_type) = get_player_type(&user_collection, &user_game_event_payload).await else {
+                                warn!("No seat found for user_id={} in game_id={}" , user_game_event_payload.user_id , user_game_event_payload.game_id);
+                                send_move_rejected_event(&producer, &game_collection, &user_game_event_payload, "Player is not part of this game", None).await;
+                                continue;
+                            };
+                            EngineAction::from_user_game_move(&user_game_event_payload, player_type, Utc::now().timestamp_millis())
+                        }
                     };
 
-                   }  else {
-                    println!("Receieved error while fetching ChessState key from redis");
-                   }
+                    match engine.apply(&current_state, &engine_action) {
+                        Ok(applied_action) if applied_action.transient => {
+                            send_transient_action_event(&producer, &user_game_event_payload).await;
+                        },
+                        Ok(applied_action) => {
+                            let save_res = save_game_state(engine, &mut redis_conn, &game_collection, &user_game_event_payload.game_id, applied_action.state).await;
+
+                            if save_res.is_err() {
+                                warn!("Error while saving updated state for game_id={}" , user_game_event_payload.game_id);
+                                send_move_rejected_event(&producer, &game_collection, &user_game_event_payload, "Move could not be saved", engine.player_view(&current_state, &user_game_event_payload.user_id).ok()).await;
+                            } else {
+                                let _ = game_collection.update_one(
+                                    doc! { "id": user_game_event_payload.game_id.clone() },
+                                    doc! { "$inc": { "state_index": 1 }, "$set": { "updated_at": bson::DateTime::now() } },
+                                    None
+                                ).await;
+
+                                record_game_move(&game_move_collection, &user_game_event_payload.game_id, &game_model.session_id.clone().unwrap_or_default(), game_model.state_index + 1, Some(&user_game_event_payload), Some(applied_action.public_state.clone())).await;
+                                send_game_state_events(&producer, &user_game_event_payload.game_id, Some(applied_action.public_state), applied_action.private_states, game_model.state_index + 1).await;
+                                set_turn_timer(&mut redis_conn, &user_game_event_payload.game_id, applied_action.deadline).await;
+                                if let Some(deadline) = applied_action.deadline {
+                                    send_clock_event(&producer, &user_game_event_payload.game_id, deadline, game_model.state_index + 1).await;
+                                }
+                                if !applied_action.eliminated.is_empty() {
+                                    eliminate_players(&user_turn_collection, &user_game_event_payload.game_id, &applied_action.eliminated).await;
+                                }
+
+                                if let Some(result) = applied_action.result {
+                                    info!("Game over for game_id={} winner={:?} reason={}" , user_game_event_payload.game_id , result.winner , result.reason);
+                                    settle_game_result(&producer, &mut redis_conn, &mongo_db, &postgres_conn, engine, &game_model, &result).await;
+                                    clear_player_heartbeats(&mut redis_conn, &user_turn_collection, &user_game_event_payload.game_id).await;
+                                }
+                            }
+                        },
+                        Err(e) => {
+                            info!("Move rejected for game_id={} user_id={} reason={}" , user_game_event_payload.game_id , user_game_event_payload.user_id , e);
+                            send_move_rejected_event(&producer, &game_collection, &user_game_event_payload, &e.to_string(), engine.player_view(&current_state, &user_game_event_payload.user_id).ok()).await;
+                        }
+                    }
                    
                 }
 
@@ -302,28 +401,602 @@ pub async fn do_listen(
 }
 
 
+// Current state of a game, read from redis or from the game document depending on the engine. None if the game has not started yet
+async fn get_game_state(engine: &dyn DynGameEngine, redis_conn: &mut MultiplexedConnection, game_model: &Game, game_id: &str) -> Result<Option<String>, String> {
+    match engine.state_storage() {
+        StateStorage::Mongo => Ok(game_model.game_state.clone()),
+        StateStorage::Redis => {
+            let rsp: RedisResult<Option<String>> = redis_conn.get(engine.state_key_prefix().to_owned() + game_id).await;
+            rsp.map_err(|e| e.to_string())
+        }
+    }
+}
+
+
+async fn save_game_state(engine: &dyn DynGameEngine, redis_conn: &mut MultiplexedConnection, game_collection: &Collection<Game>, game_id: &str, state: String) -> Result<(), String> {
+    match engine.state_storage() {
+        StateStorage::Mongo => {
+            game_collection.update_one(doc! { "id": game_id }, doc! { "$set": { "game_state": state } }, None).await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        },
+        StateStorage::Redis => {
+            let redis_res: RedisResult<()> = redis_conn.set(engine.state_key_prefix().to_owned() + game_id, state).await;
+            redis_res.map_err(|e| e.to_string())
+        }
+    }
+}
+
+
+// Builds the first state of a game from its turn mapping. The seed is stored inside the state so the game can be replayed
+async fn init_game_state(engine: &dyn DynGameEngine, user_turn_collection: &Collection<UserTurnMapping>, game_id: &str) -> Result<String, String> {
+    let turn_mapping = match user_turn_collection.find_one(doc! { "game_id": game_id }, None).await {
+        Ok(Some(turn_mapping)) => turn_mapping,
+        _ => return Err("Game state not found".to_string()),
+    };
+
+    // Eliminated and disconnected players are not dealt into the game
+    let seed = Uuid::new_v4().as_u64_pair().0;
+    engine.initial_state(&turn_mapping.with_active_players(), seed).map_err(|e| e.to_string())
+}
+
+
+// Rates every player from their placement, archives the session and tells the game room and nebula how the game ended
+async fn settle_game_result(producer: &FutureProducer, redis_conn: &mut MultiplexedConnection, mongo_db: &mongodb::Database, postgres_conn: &DatabaseConnection, engine: &dyn DynGameEngine, game_model: &Game, result: &GameResult) {
+    let game_collection = mongo_db.collection::<Game>(MONGO_GAMES_MODEL);
+    let user_collection = mongo_db.collection::<UserGameRelation>(MONGO_USERS_MODEL);
+    let game_id = game_model.id.to_string();
+    let game_id = game_id.as_str();
+
+    // Lets the players ask for a rematch
+    let _ = game_collection.update_one(
+        doc! { "id": game_id },
+        doc! { "$set": { "description": GAME_OVER_STATUS, "rematch_requests": [], "updated_at": bson::DateTime::now() } },
+        None
+    ).await;
+
+    let placements = resolve_placements(&user_collection, game_id, result).await;
+
+    let session_id = game_model.session_id.clone().unwrap_or_default();
+    let category = rating_category(&game_model.game_type, game_model.time_control.as_deref());
+    let score_changes = if game_model.is_casual { HashMap::new() } else { ratings::rate_game(redis_conn, postgres_conn, game_model.id, &session_id, &game_model.game_type, category, &placements).await };
+
+    let mut placement_payloads = vec![];
+    let mut archived_placements = vec![];
+    for (user_id, place) in &placements {
+        let score_change = score_changes.get(user_id).copied().unwrap_or_default();
+
+        placement_payloads.push(PlayerPlacementPayload {
+            user_id: user_id.to_string(),
+            place: *place,
+            score_change,
+        });
+        archived_placements.push((*user_id, *place, score_change));
+    }
+
+    // Nobody won if every player shares first place
+    let mut winner_ids: Vec<String> = placements.iter().filter(|(_, place)| *place == 1).map(|(user_id, _)| user_id.to_string()).collect();
+    if winner_ids.len() == placements.len() {
+        winner_ids.clear();
+    }
+
+    let outcome = GameOutcome {
+        result: if winner_ids.is_empty() { RESULT_DRAW } else { RESULT_WIN }.to_string(),
+        termination_reason: result.reason.clone(),
+        placements: archived_placements,
+    };
+    archive::archive_game(postgres_conn, mongo_db, Some(engine), game_model, &outcome).await;
+
+    let payload = GameResultPayload {
+        game_id: game_id.to_string(),
+        winner_ids,
+        placements: placement_payloads,
+        reason: result.reason.clone(),
+    };
+
+    let mut kafka_events = vec![KafkaGeneralEvent {
+        topic: "game".to_string(),
+        payload: serde_json::to_string(&payload).unwrap(),
+        key: GAME_RESULT_EVENT.to_string(),
+    }];
+
+    // Nebula settles the bets of the session. It already refunded them if a player abandoned the game along the way
+    let user_turn_collection = mongo_db.collection::<UserTurnMapping>(MONGO_USER_TURNS_MODEL);
+    let abandoned = match user_turn_collection.find_one(doc! { "game_id": game_id }, None).await {
+        Ok(Some(turn_mapping)) => turn_mapping.turn_mappings.iter().any(|turn| turn.status == PLAYER_DISCONNECTED),
+        _ => false,
+    };
+    if !abandoned {
+        let game_over_event = GameOverEvent {
+            game_id: game_id.to_string(),
+            session_id,
+            winner_id: if payload.winner_ids.len() == 1 { payload.winner_ids[0].clone() } else { "".to_string() },
+            winner_ids: payload.winner_ids.clone(),
+            is_game_valid: true,
+            absent_player_id: "".to_string(),
+        };
+
+        kafka_events.push(KafkaGeneralEvent {
+            topic: GAME_OVER_EVENT.to_string(),
+            payload: serde_json::to_string(&game_over_event).unwrap(),
+            key: "game_finished".to_string(),
+        });
+    }
+
+    if let Err(e) = kafka::producer::send_kafka_events(producer, kafka_events).await {
+        warn!("Error while sending game result for game_id={}: {:?}" , game_id , e);
+    }
+}
+
+
+// The game is invalidated by nova when a player stayed away longer than the grace period. Nebula refunds the bets,
+// the player is marked disconnected here and the game goes on without them or is ended so the others stop waiting
+async fn close_abandoned_game(producer: &FutureProducer, redis_conn: &mut MultiplexedConnection, postgres_conn: &DatabaseConnection, mongo_db: &mongodb::Database, engine_registry: &GameEngineRegistry, game_over_event: &GameOverEvent) {
+    let game_collection = mongo_db.collection::<Game>(MONGO_GAMES_MODEL);
+    let user_turn_collection = mongo_db.collection::<UserTurnMapping>(MONGO_USER_TURNS_MODEL);
+    let game_id = game_over_event.game_id.as_str();
+
+    let game_model = match game_collection.find_one(doc! { "id": game_id, "description": GAME_IN_PROGRESS_STATUS, "session_id": game_over_event.session_id.clone() }, None).await {
+        Ok(Some(game_model)) => game_model,
+        _ => {
+            info!("Game game_id={} session_id={} is no longer in progress, abandonment ignored" , game_id , game_over_event.session_id);
+            return;
+        }
+    };
+
+    info!("Player user_id={} abandoned game_id={}" , game_over_event.absent_player_id , game_id);
+
+    if disconnect_player(producer, redis_conn, postgres_conn, mongo_db, engine_registry, &game_model, &game_over_event.absent_player_id).await {
+        return;
+    }
+
+    let close_res = game_collection.find_one_and_update(
+        doc! { "id": game_id, "description": GAME_IN_PROGRESS_STATUS, "session_id": game_over_event.session_id.clone() },
+        doc! { "$set": { "description": GAME_OVER_STATUS, "rematch_requests": [], "updated_at": bson::DateTime::now() } },
+        None
+    ).await;
+    let game_model = match close_res {
+        Ok(Some(game_model)) => game_model,
+        _ => {
+            info!("Game game_id={} session_id={} is no longer in progress, abandonment ignored" , game_id , game_over_event.session_id);
+            return;
+        }
+    };
+
+    set_turn_timer(redis_conn, game_id, None).await;
+    clear_player_heartbeats(redis_conn, &user_turn_collection, game_id).await;
+
+    archive::archive_game(postgres_conn, mongo_db, engine_registry.get(&game_model.game_type).ok(), &game_model, &GameOutcome::unranked("abandoned")).await;
+
+    let payload = GameResultPayload {
+        game_id: game_id.to_string(),
+        winner_ids: vec![],
+        placements: vec![],
+        reason: "abandoned".to_string(),
+    };
+
+    let kafka_event = KafkaGeneralEvent {
+        topic: "game".to_string(),
+        payload: serde_json::to_string(&payload).unwrap(),
+        key: GAME_RESULT_EVENT.to_string(),
+    };
+
+    if let Err(e) = kafka::producer::send_kafka_events(producer, vec![kafka_event]).await {
+        warn!("Error while sending abandoned game result for game_id={}: {:?}" , game_id , e);
+    }
+}
+
+
+// Marks the player disconnected. Games with enough players left go on without them if their engine allows it,
+// returns false if the game has to be closed instead
+async fn disconnect_player(producer: &FutureProducer, redis_conn: &mut MultiplexedConnection, postgres_conn: &DatabaseConnection, mongo_db: &mongodb::Database, engine_registry: &GameEngineRegistry, game_model: &Game, absent_player_id: &str) -> bool {
+    let game_collection = mongo_db.collection::<Game>(MONGO_GAMES_MODEL);
+    let user_turn_collection = mongo_db.collection::<UserTurnMapping>(MONGO_USER_TURNS_MODEL);
+    let game_move_collection = mongo_db.collection::<GameMoveRecord>(MONGO_GAME_MOVES_MODEL);
+    let game_id = game_model.id.to_string();
+    let game_id = game_id.as_str();
+
+    let Ok(Some(mut turn_mapping)) = user_turn_collection.find_one(doc! { "game_id": game_id }, None).await else {
+        return false;
+    };
+    turn_mapping.disconnect(absent_player_id);
+    if let Ok(turn_mappings) = bson::to_bson(&turn_mapping.turn_mappings) {
+        let _ = user_turn_collection.update_one(doc! { "game_id": game_id }, doc! { "$set": { "turn_mappings": turn_mappings } }, None).await;
+    }
+
+    let Ok(engine) = engine_registry.get(&game_model.game_type) else {
+        return false;
+    };
+
+    let active_players = turn_mapping.active_turns().len();
+    if active_players < 2 {
+        return false;
+    }
+
+    let current_state = match get_game_state(engine, redis_conn, game_model, game_id).await {
+        Ok(Some(current_state)) => current_state,
+        // Nothing was dealt yet, init_game_state leaves the disconnected player out
+        Ok(None) => return active_players >= engine.min_players(),
+        Err(_) => return false,
+    };
+
+    let applied_action = match engine.remove_player(&current_state, absent_player_id, Utc::now().timestamp_millis()) {
+        Ok(Some(applied_action)) => applied_action,
+        Ok(None) => return false,
+        Err(e) => {
+            warn!("Could not remove user_id={} from game_id={}: {}" , absent_player_id , game_id , e);
+            return false;
+        }
+    };
+
+    if save_game_state(engine, redis_conn, &game_collection, game_id, applied_action.state).await.is_err() {
+        warn!("Error while saving state without user_id={} for game_id={}" , absent_player_id , game_id);
+        return false;
+    }
+
+    info!("Game game_id={} goes on without user_id={}" , game_id , absent_player_id);
+
+    let _ = game_collection.update_one(
+        doc! { "id": game_id },
+        doc! { "$inc": { "state_index": 1 }, "$set": { "updated_at": bson::DateTime::now() } },
+        None
+    ).await;
+
+    let leave_move = UserGameMove {
+        user_id: absent_player_id.to_string(),
+        game_id: game_id.to_string(),
+        move_type: "leave".to_string(),
+        user_move: "".to_string(),
+    };
+    let key_id = game_id.to_owned() + "_" + absent_player_id;
+    let _: RedisResult<()> = redis_conn.del(PLAYER_HEARTBEAT_DATA.to_owned() + &key_id).await;
+
+    let session_id = game_model.session_id.clone().unwrap_or_default();
+    record_game_move(&game_move_collection, game_id, &session_id, game_model.state_index + 1, Some(&leave_move), Some(applied_action.public_state.clone())).await;
+    send_game_state_events(producer, game_id, Some(applied_action.public_state), applied_action.private_states, game_model.state_index + 1).await;
+    set_turn_timer(redis_conn, game_id, applied_action.deadline).await;
+    if let Some(deadline) = applied_action.deadline {
+        send_clock_event(producer, game_id, deadline, game_model.state_index + 1).await;
+    }
+    if !applied_action.eliminated.is_empty() {
+        eliminate_players(&user_turn_collection, game_id, &applied_action.eliminated).await;
+    }
+
+    if let Some(result) = applied_action.result {
+        info!("Game over for game_id={} winner={:?} reason={}" , game_id , result.winner , result.reason);
+        settle_game_result(producer, redis_conn, mongo_db, postgres_conn, engine, game_model, &result).await;
+        clear_player_heartbeats(redis_conn, &user_turn_collection, game_id).await;
+    }
+
+    true
+}
+
+
+// Knocked out players stay in the room but take no more turns. Players who already left keep their status
+async fn eliminate_players(user_turn_collection: &Collection<UserTurnMapping>, game_id: &str, user_ids: &[String]) {
+    let Ok(Some(mut turn_mapping)) = user_turn_collection.find_one(doc! { "game_id": game_id }, None).await else {
+        warn!("No turn mapping found for game_id={}, eliminated players are not recorded" , game_id);
+        return;
+    };
+
+    for user_id in user_ids {
+        if turn_mapping.turn_mappings.iter().any(|turn| turn.user_id == *user_id && turn.is_active()) {
+            turn_mapping.eliminate(user_id);
+        }
+    }
+
+    if let Ok(turn_mappings) = bson::to_bson(&turn_mapping.turn_mappings) {
+        let _ = user_turn_collection.update_one(doc! { "game_id": game_id }, doc! { "$set": { "turn_mappings": turn_mappings } }, None).await;
+    }
+}
+
+
+// Without this a player who dropped just before the end would still invalidate the finished game
+async fn clear_player_heartbeats(redis_conn: &mut MultiplexedConnection, user_turn_collection: &Collection<UserTurnMapping>, game_id: &str) {
+    let Ok(Some(turn_mapping)) = user_turn_collection.find_one(doc! { "game_id": game_id }, None).await else {
+        return;
+    };
+
+    for turn in &turn_mapping.turn_mappings {
+        let key_id = game_id.to_owned() + "_" + &turn.user_id;
+        let _: RedisResult<()> = redis_conn.del(PLAYER_HEARTBEAT_KEY.to_owned() + &key_id).await;
+        let _: RedisResult<()> = redis_conn.del(PLAYER_HEARTBEAT_DATA.to_owned() + &key_id).await;
+    }
+}
+
+
+// Engines key players by user_id, or by player_type for seat based games like chess
+async fn resolve_placements(user_collection: &Collection<UserGameRelation>, game_id: &str, result: &GameResult) -> Vec<(Uuid, u32)> {
+    let mut placements = vec![];
+
+    for placement in &result.placements {
+        if let Ok(user_id) = Uuid::from_str(&placement.player) {
+            placements.push((user_id, placement.place));
+            continue;
+        }
+
+        match user_collection.find_one(doc! { "game_id": game_id, "player_type": placement.player.clone() }, None).await {
+            Ok(Some(user_game_relation)) => placements.push((user_game_relation.user_id, placement.place)),
+            _ => warn!("No player with player_type={} found for game_id={}" , placement.player , game_id),
+        }
+    }
+
+    placements
+}
+
+
+// Public state goes to the game room, hidden information is sent to each player on their own user topic
+async fn send_game_state_events(producer: &FutureProducer, game_id: &str, public_state: Option<String>, private_states: Vec<PrivateState>, state_index: i64) {
+    let mut kafka_events: Vec<KafkaGeneralEvent> = vec![];
+
+    if let Some(public_state) = public_state {
+        let payload = GameStateUpdatePayload {
+            game_id: game_id.to_string(),
+            state: public_state,
+            state_index,
+        };
+
+        kafka_events.push(KafkaGeneralEvent {
+            topic: "game".to_string(),
+            payload: serde_json::to_string(&payload).unwrap(),
+            key: GAME_STATE_UPDATE_EVENT.to_string(),
+        });
+    }
+
+    for private_state in private_states {
+        let payload = PlayerPrivateStatePayload {
+            game_id: game_id.to_string(),
+            user_who_we_are_sending_event: private_state.user_id,
+            state: private_state.state,
+            state_index,
+        };
+
+        kafka_events.push(KafkaGeneralEvent {
+            topic: "user".to_string(),
+            payload: serde_json::to_string(&payload).unwrap(),
+            key: PLAYER_PRIVATE_STATE_EVENT.to_string(),
+        });
+    }
+
+    if let Err(e) = kafka::producer::send_kafka_events(producer, kafka_events).await {
+        warn!("Error while sending game state events for game_id={}: {:?}" , game_id , e);
+    }
+}
+
+
+// Move history used by the spectator feed to resume after a reconnect
+async fn record_game_move(game_move_collection: &Collection<GameMoveRecord>, game_id: &str, session_id: &str, state_index: i64, user_game_move: Option<&UserGameMove>, public_state: Option<String>) {
+    let game_move = GameMoveRecord {
+        game_id: game_id.to_string(),
+        session_id: session_id.to_string(),
+        state_index,
+        user_id: user_game_move.map(|user_game_move| user_game_move.user_id.clone()).unwrap_or_default(),
+        move_type: user_game_move.map(|user_game_move| user_game_move.move_type.clone()).unwrap_or_else(|| "start".to_string()),
+        user_move: user_game_move.map(|user_game_move| user_game_move.user_move.clone()).unwrap_or_default(),
+        public_state: public_state.unwrap_or_default(),
+        evaluation: None,
+        created_at: bson::DateTime::now(),
+    };
+
+    if let Err(e) = game_move_collection.insert_one(game_move, None).await {
+        warn!("Error while recording move {} for game_id={}: {:?}" , state_index , game_id , e);
+    }
+}
+
+
+async fn send_clock_event(producer: &FutureProducer, game_id: &str, deadline: i64, state_index: i64) {
+    let payload = GameClockPayload {
+        game_id: game_id.to_string(),
+        deadline,
+        state_index,
+    };
+
+    let kafka_event = KafkaGeneralEvent {
+        topic: "game".to_string(),
+        payload: serde_json::to_string(&payload).unwrap(),
+        key: GAME_CLOCK_EVENT.to_string(),
+    };
+
+    if let Err(e) = kafka::producer::send_kafka_events(producer, vec![kafka_event]).await {
+        warn!("Error while sending clock event for game_id={}: {:?}" , game_id , e);
+    }
+}
+
+
+async fn send_transient_action_event(producer: &FutureProducer, user_game_move: &UserGameMove) {
+    let payload = GameTransientActionPayload {
+        game_id: user_game_move.game_id.clone(),
+        user_id: user_game_move.user_id.clone(),
+        action_type: user_game_move.move_type.clone(),
+        payload: user_game_move.user_move.clone(),
+    };
+
+    let kafka_event = KafkaGeneralEvent {
+        topic: "game".to_string(),
+        payload: serde_json::to_string(&payload).unwrap(),
+        key: GAME_TRANSIENT_ACTION_EVENT.to_string(),
+    };
+
+    if let Err(e) = kafka::producer::send_kafka_events(producer, vec![kafka_event]).await {
+        warn!("Error while sending transient action for game_id={}: {:?}" , user_game_move.game_id , e);
+    }
+}
+
+
+// Move stored with the turn timer. It does not come from a player, so it has no user_id
+fn turn_timer_tick(game_id: &str) -> UserGameMove {
+    UserGameMove {
+        user_id: "".to_string(),
+        game_id: game_id.to_string(),
+        move_type: "tick".to_string(),
+        user_move: "".to_string(),
+    }
+}
+
+// Ticks have no seat to look up, the engine checks on its own that the deadline has passed
+fn timer_tick_action(user_game_move: &UserGameMove, received_at: i64) -> Option<EngineAction> {
+    if user_game_move.move_type != "tick" || !user_game_move.user_id.is_empty() {
+        return None
+    }
+
+    Some(EngineAction::from_user_game_move(user_game_move, "".to_string(), received_at))
+}
+
+
+// Timed games get a redis key expiring at the deadline. Nova sends the "tick" move stored in the data key when it expires
+async fn set_turn_timer(redis_conn: &mut MultiplexedConnection, game_id: &str, deadline: Option<i64>) {
+    let timer_key = GAME_TURN_TIMER.to_owned() + game_id;
+    let timer_data_key = GAME_TURN_TIMER_DATA.to_owned() + game_id;
+
+    let Some(deadline) = deadline else {
+        let _: RedisResult<()> = redis_conn.del(timer_key).await;
+        let _: RedisResult<()> = redis_conn.del(timer_data_key).await;
+        return;
+    };
+
+    let tick_move = turn_timer_tick(game_id);
+    let expires_in_ms = (deadline - Utc::now().timestamp_millis()).max(1) as u64;
+
+    // Data key outlives the timer key so nova can still read it on expiry
+    let _: RedisResult<()> = redis_conn.set_ex(timer_data_key, serde_json::to_string(&tick_move).unwrap(), expires_in_ms / 1000 + 60).await;
+    let _: RedisResult<()> = redis_conn.pset_ex(timer_key, deadline, expires_in_ms).await;
+}
+
+
+// Engines registered with the settings from the config file
+fn build_engine_registry(config: &Configuration) -> GameEngineRegistry {
+    let mut engine_registry = GameEngineRegistry::with_default_engines();
+
+    if let Some(scribble_config) = &config.scribble {
+        let mut scribble_engine = match &scribble_config.word_list_path {
+            Some(word_list_path) => match std::fs::read_to_string(word_list_path) {
+                Ok(word_list) => ScribbleEngine::with_words(word_list.lines().map(|word| word.to_string()).collect()),
+                Err(e) => {
+                    warn!("Could not read scribble word list {}: {:?}, using the default list" , word_list_path , e);
+                    ScribbleEngine::default()
+                }
+            },
+            None => ScribbleEngine::default(),
+        };
+
+        if let Some(rounds) = scribble_config.rounds {
+            scribble_engine.rounds = rounds;
+        }
+        if let Some(draw_time_seconds) = scribble_config.draw_time_seconds {
+            scribble_engine.draw_time_ms = draw_time_seconds * 1000;
+        }
+
+        engine_registry.register(scribble_engine);
+    }
+
+    engine_registry
+}
+
+
+// Colour / seat of the user in the game. None if the relation is missing or could not be read, the move is rejected then
+async fn get_player_type(user_collection: &Collection<UserGameRelation>, user_game_move: &UserGameMove) -> Option<String> {
+    let user_id = Uuid::from_str(&user_game_move.user_id).ok()?;
+
+    // Lobbies created by messier store user_id as a string, older documents as a binary uuid
+    let filter = doc! { "game_id": user_game_move.game_id.clone(), "user_id": { "$in": [user_game_move.user_id.clone(), bson::Binary::from_uuid(bson::Uuid::from_bytes(user_id.into_bytes()))] } };
+
+    match user_collection.find_one(filter, None).await {
+        Ok(Some(user_game_relation)) => Some(user_game_relation.player_type),
+        _ => None,
+    }
+}
+
+
+// Tells the user who submitted the move that it was dropped, along with the authoritative state so the client can resync
+async fn send_move_rejected_event(
+    producer: &FutureProducer,
+    game_collection: &Collection<Game>,
+    user_game_move: &UserGameMove,
+    reason: &str,
+    current_state: Option<String>,
+) {
+    // Ticks sent by nova have no user to report back to
+    if user_game_move.user_id.is_empty() {
+        return;
+    }
+
+    let state_index = match game_collection.find_one(doc! { "id": user_game_move.game_id.clone() }, None).await {
+        Ok(Some(game)) => Some(game.state_index),
+        _ => None,
+    };
+
+    let error_payload = ErrorMessagePayload {
+        game_id: user_game_move.game_id.clone(),
+        error_message: reason.to_string(),
+        user_who_we_are_sending_event: user_game_move.user_id.clone(),
+        current_state,
+        state_index,
+    };
+
+    let kafka_event = KafkaGeneralEvent {
+        topic: "user".to_string(),
+        payload: serde_json::to_string(&error_payload).unwrap(),
+        key: ERROR_EVENT.to_string(),
+    };
+
+    if let Err(e) = kafka::producer::send_kafka_events(producer, vec![kafka_event]).await {
+        warn!("Error while sending move rejected event for game_id={}: {:?}" , user_game_move.game_id , e);
+    }
+}
+
+
 fn hash_user_wallet_key(wallet_key: &String) -> String {
     
     let hash = crypter::encrypt(b"walletsecretsalt" , wallet_key).expect("failed to encrypt");
     String::from_utf8(hash).unwrap()
 }
 
-pub fn get_chess_position(cell: &CellPosition) -> String {
-   format!("{}{}" , get_file_letter(cell.x) , (8-cell.y).to_string())
-} 
 
-pub fn get_file_letter(num: i64) -> String {
+#[cfg(test)]
+mod tests {
+    use orion::{engines::registry::GameEngineRegistry, models::{scribble_model::ScribbleState, user_turn_model::{TurnModel, UserTurnMapping}}};
+
+    use super::*;
 
-    match num {
-        0 => "a".to_owned(),
-        1 => "b".to_owned(),
-        2 => "c".to_owned(),
-        3 => "d".to_owned(),
-        4 => "e".to_owned(),
-        5 => "f".to_owned(),
-        6 => "g".to_owned(),
-        7 => "h".to_owned(),
-        _ => "a".to_owned()
+    fn phase(raw_state: &str) -> String {
+        serde_json::from_str::<ScribbleState>(raw_state).unwrap().phase
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn turn_timer_tick_reaches_the_engine() {
+        let registry = GameEngineRegistry::with_default_engines();
+        let engine = registry.get("scribble").unwrap();
+        let turn_mapping = UserTurnMapping {
+            host_id: "a".to_string(),
+            game_id: "scribble-game".to_string(),
+            turn_mappings: ["a", "b", "c"].iter().enumerate().map(|(seat, user_id)| TurnModel {
+                count_id: seat as i64,
+                user_id: user_id.to_string(),
+                username: user_id.to_string(),
+                status: "".to_string(),
+            }).collect(),
+        };
+        let state = engine.initial_state(&turn_mapping, 1).unwrap();
+
+        let tick = turn_timer_tick("scribble-game");
+        let started = engine.apply(&state, &timer_tick_action(&tick, 1_000).unwrap()).unwrap();
+        assert_eq!(started.deadline, Some(16_000));
+
+        // Once the drawer ran out of time to choose, the tick starts the drawing with the first word
+        let drawing = engine.apply(&started.state, &timer_tick_action(&tick, 16_000).unwrap()).unwrap();
+        assert_eq!(phase(&drawing.state), "drawing");
+        assert_eq!(drawing.deadline, Some(96_000));
+    }
+
+    #[test]
+    fn only_timer_ticks_skip_the_seat_lookup() {
+        let tick = turn_timer_tick("game");
+        assert!(timer_tick_action(&tick, 0).is_some_and(|action| action.player_type.is_empty() && action.action_type == "tick"));
+
+        let player_tick = UserGameMove { user_id: "a".to_string(), ..turn_timer_tick("game") };
+        assert!(timer_tick_action(&player_tick, 0).is_none());
+
+        let anonymous_move = UserGameMove { move_type: "move".to_string(), ..turn_timer_tick("game") };
+        assert!(timer_tick_action(&anonymous_move, 0).is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use orion::ratings::glicko2::Glicko2Rating;
+use chrono::Utc;
+use sea_orm::{prelude::Expr, sea_query::OnConflict, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set, TransactionTrait};
+use redis::aio::MultiplexedConnection;
+use ton::models::{rating_history, user_ratings};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::leaderboards::{self, LeaderboardUpdate};
+
+
+// The game is one Glicko-2 rating period in which every player met every other player, a better place is a win.
+// Only the players' ratings in the game type and category of the game change.
+// Returns how much the rounded rating of each player changed, empty if the ratings could not be updated.
+// Every change is added to the rating history in the same transaction and to the leaderboards after it commits
+pub async fn rate_game(redis_conn: &mut MultiplexedConnection, postgres_conn: &DatabaseConnection, game_id: Uuid, session_id: &str, game_type: &str, category: &str, placements: &[(Uuid, u32)]) -> HashMap<Uuid, i32> {
+    let mut score_changes = HashMap::new();
+
+    let tx = match postgres_conn.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            warn!("Error while starting rating update: {:?}" , e);
+            return score_changes;
+        }
+    };
+
+    // First game in the category starts from the default rating
+    let now = Utc::now().naive_utc();
+    let default_rating = Glicko2Rating::default();
+    let new_ratings = placements.iter().map(|(user_id, _)| user_ratings::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(*user_id),
+        game_type: Set(game_type.to_string()),
+        category: Set(category.to_string()),
+        rating: Set(default_rating.rating),
+        rating_deviation: Set(default_rating.deviation),
+        rating_volatility: Set(default_rating.volatility),
+        games_played: Set(0),
+        created_at: Set(now),
+        updated_at: Set(now),
+    });
+    let on_conflict = OnConflict::columns([user_ratings::Column::UserId, user_ratings::Column::GameType, user_ratings::Column::Category]).do_nothing().to_owned();
+    if let Err(e) = user_ratings::Entity::insert_many(new_ratings).on_conflict(on_conflict).exec_without_returning(&tx).await {
+        warn!("Error while creating player ratings: {:?}" , e);
+        return score_changes;
+    }
+
+    // Rows stay locked until commit so two games ending together for the same player do not overwrite each other
+    let user_ids: Vec<Uuid> = placements.iter().map(|(user_id, _)| *user_id).collect();
+    let player_ratings = user_ratings::Entity::find()
+        .filter(user_ratings::Column::UserId.is_in(user_ids))
+        .filter(user_ratings::Column::GameType.eq(game_type))
+        .filter(user_ratings::Column::Category.eq(category))
+        .lock_exclusive()
+        .all(&tx)
+        .await;
+    let player_ratings = match player_ratings {
+        Ok(player_ratings) => player_ratings,
+        Err(e) => {
+            warn!("Error while fetching player ratings: {:?}" , e);
+            return score_changes;
+        }
+    };
+    let games_played: HashMap<Uuid, i32> = player_ratings.iter().map(|player_rating| (player_rating.user_id, player_rating.games_played)).collect();
+    let ratings: HashMap<Uuid, Glicko2Rating> = player_ratings.into_iter().map(|player_rating| (player_rating.user_id, Glicko2Rating {
+        rating: player_rating.rating,
+        deviation: player_rating.rating_deviation,
+        volatility: player_rating.rating_volatility,
+    })).collect();
+
+    let mut updated_ratings = vec![];
+    for (user_id, place) in placements {
+        let Some(current_rating) = ratings.get(user_id) else {
+            continue;
+        };
+
+        let results: Vec<(Glicko2Rating, f64)> = placements.iter()
+            .filter(|(opponent_id, _)| opponent_id != user_id)
+            .filter_map(|(opponent_id, opponent_place)| {
+                let score = match place.cmp(opponent_place) {
+                    Ordering::Less => 1.0,
+                    Ordering::Equal => 0.5,
+                    Ordering::Greater => 0.0,
+                };
+                ratings.get(opponent_id).map(|opponent_rating| (*opponent_rating, score))
+            })
+            .collect();
+
+        if !results.is_empty() {
+            let opponent_id = if results.len() == 1 { placements.iter().map(|(opponent_id, _)| *opponent_id).find(|opponent_id| opponent_id != user_id) } else { None };
+            let opponent_rating = results.iter().map(|(opponent_rating, _)| opponent_rating.rating).sum::<f64>() / results.len() as f64;
+            updated_ratings.push((*user_id, *current_rating, current_rating.update(&results), opponent_id, opponent_rating));
+        }
+    }
+
+    let mut leaderboard_updates = vec![];
+    for (user_id, current_rating, updated_rating, opponent_id, opponent_rating) in updated_ratings {
+        let update_res = user_ratings::Entity::update_many()
+            .col_expr(user_ratings::Column::Rating, Expr::value(updated_rating.rating))
+            .col_expr(user_ratings::Column::RatingDeviation, Expr::value(updated_rating.deviation))
+            .col_expr(user_ratings::Column::RatingVolatility, Expr::value(updated_rating.volatility))
+            .col_expr(user_ratings::Column::GamesPlayed, Expr::col(user_ratings::Column::GamesPlayed).add(1))
+            .col_expr(user_ratings::Column::UpdatedAt, Expr::value(now))
+            .filter(user_ratings::Column::UserId.eq(user_id))
+            .filter(user_ratings::Column::GameType.eq(game_type))
+            .filter(user_ratings::Column::Category.eq(category))
+            .exec(&tx)
+            .await;
+
+        if let Err(e) = update_res {
+            warn!("Error while updating rating of user_id={}: {:?}" , user_id , e);
+            return HashMap::new();
+        }
+
+        let rating_change = rating_history::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            game_id: Set(game_id),
+            session_id: Set(session_id.to_string()),
+            game_type: Set(game_type.to_string()),
+            category: Set(category.to_string()),
+            rating_before: Set(current_rating.rating),
+            rating_after: Set(updated_rating.rating),
+            deviation_before: Set(current_rating.deviation),
+            deviation_after: Set(updated_rating.deviation),
+            opponent_id: Set(opponent_id),
+            opponent_rating: Set(opponent_rating),
+            created_at: Set(now),
+        };
+
+        if let Err(e) = rating_change.insert(&tx).await {
+            warn!("Error while saving rating history of user_id={}: {:?}" , user_id , e);
+            return HashMap::new();
+        }
+
+        score_changes.insert(user_id, updated_rating.rating.round() as i32 - current_rating.rating.round() as i32);
+        leaderboard_updates.push(LeaderboardUpdate {
+            user_id,
+            rating: updated_rating.rating,
+            rating_change: updated_rating.rating - current_rating.rating,
+            games_played: games_played.get(&user_id).copied().unwrap_or_default() + 1,
+        });
+    }
+
+    if let Err(e) = tx.commit().await {
+        warn!("Error while saving player ratings: {:?}" , e);
+        return HashMap::new();
+    }
+
+    leaderboards::record_rating_changes(redis_conn, postgres_conn, game_type, category, &leaderboard_updates).await;
+
+    score_changes
+}
@@ -0,0 +1,233 @@
+use std::{str::FromStr, time::Duration};
+
+use chrono::Utc;
+use futures_util::TryStreamExt;
+use mongodb::{bson::{self, doc, Bson, Document}, Database};
+use orion::{constants::{GAME_AWAITING_BETS_STATUS, GAME_IN_PROGRESS_STATUS, GAME_LOBBY_STATUS, GAME_OVER_EVENT, GAME_OVER_STATUS, GAME_REAPER_LOCK_KEY, GAME_RESULT_EVENT, LOBBY_JOIN_CODE_KEY, MONGO_DB_NAME, MONGO_GAMES_MODEL, MONGO_USERS_MODEL, MONGO_USER_TURNS_MODEL, PLAYER_HEARTBEAT_DATA, PLAYER_HEARTBEAT_KEY, USER_GAME_DELETION}, events::{kafka_event::{GameOverEvent, KafkaGeneralEvent, UserGameDeletetionEvent}, ws_events::GameResultPayload}, engines::registry::GameEngineRegistry, models::{game_bet_events::GameBetStatus, game_model::Game, user_turn_model::UserTurnMapping}};
+use rdkafka::producer::FutureProducer;
+use redis::{aio::MultiplexedConnection, AsyncCommands, ExistenceCheck, RedisResult, SetExpiry, SetOptions};
+use sea_orm::DatabaseConnection;
+use tokio::{task::JoinHandle, time::interval};
+use ton::models::game_bets;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{archive::{self, GameOutcome}, conf::config_types::ReaperConfiguration, context::context::DynContext, kafka};
+
+
+// Games whose USER_GAME_DELETION never came (crashed clients, abandoned lobbies) are closed and removed here
+pub fn start(context: DynContext, config: ReaperConfiguration, producer: FutureProducer, engine_registry: GameEngineRegistry) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut sweep_interval = interval(Duration::from_secs(config.interval_secs));
+
+        loop {
+            sweep_interval.tick().await;
+            sweep(&context, &config, &producer, &engine_registry).await;
+        }
+    })
+}
+
+
+async fn sweep(context: &DynContext, config: &ReaperConfiguration, producer: &FutureProducer, engine_registry: &GameEngineRegistry) {
+    let mut redis_conn = context.get_redis_db_client();
+
+    // Only one cerotis instance sweeps per interval
+    let options = SetOptions::default().conditional_set(ExistenceCheck::NX).with_expiration(SetExpiry::EX(config.interval_secs));
+    let locked: RedisResult<Option<String>> = redis_conn.set_options(GAME_REAPER_LOCK_KEY, "locked", options).await;
+    if !matches!(locked, Ok(Some(_))) {
+        return;
+    }
+
+    let mongo_db = context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let game_collection = mongo_db.collection::<Game>(MONGO_GAMES_MODEL);
+    let postgres_conn = context.get_postgres_db_client();
+
+    let ttls = [
+        (GAME_LOBBY_STATUS, config.lobby_ttl_secs),
+        (GAME_IN_PROGRESS_STATUS, config.in_progress_ttl_secs),
+        (GAME_AWAITING_BETS_STATUS, config.in_progress_ttl_secs),
+        (GAME_OVER_STATUS, config.game_over_ttl_secs),
+    ];
+
+    for (status, ttl_secs) in ttls {
+        let stale_before = bson::DateTime::from_millis(Utc::now().timestamp_millis() - ttl_secs as i64 * 1000);
+        let filter = doc! { "description": status, "updated_at": { "$lt": stale_before } };
+
+        let games: Vec<Game> = match game_collection.find(filter, None).await {
+            Ok(cursor) => cursor.try_collect().await.unwrap_or_default(),
+            Err(e) => {
+                warn!("Error while fetching stale games with status={}: {:?}" , status , e);
+                continue;
+            }
+        };
+
+        for game in &games {
+            reap_game(producer, &mut redis_conn, &postgres_conn, &mongo_db, engine_registry, game, stale_before).await;
+        }
+    }
+
+    delete_orphaned_documents(&mongo_db).await;
+}
+
+
+// Unfinished games are ended and archived first, as an invalid game when bets are open so nebula refunds them.
+// The deletion event then removes the documents and the redis state like a destroyed lobby
+async fn reap_game(
+    producer: &FutureProducer,
+    redis_conn: &mut MultiplexedConnection,
+    postgres_conn: &DatabaseConnection,
+    mongo_db: &Database,
+    engine_registry: &GameEngineRegistry,
+    game: &Game,
+    stale_before: bson::DateTime,
+) {
+    let game_collection = mongo_db.collection::<Game>(MONGO_GAMES_MODEL);
+    let user_turn_collection = mongo_db.collection::<UserTurnMapping>(MONGO_USER_TURNS_MODEL);
+    let game_id = game.id.to_string();
+    let is_unfinished = game.description == GAME_IN_PROGRESS_STATUS || game.description == GAME_AWAITING_BETS_STATUS;
+    let mut kafka_events = vec![];
+
+    if is_unfinished {
+        let turn_mapping = user_turn_collection.find_one(doc! { "game_id": game_id.clone() }, None).await.ok().flatten();
+
+        // A connected player keeps the game alive even if nobody moved for a while
+        if has_connected_player(redis_conn, turn_mapping.as_ref(), &game_id).await {
+            return;
+        }
+
+        let close_res = game_collection.update_one(
+            doc! { "id": game_id.clone(), "description": game.description.clone(), "updated_at": { "$lt": stale_before } },
+            doc! { "$set": { "description": GAME_OVER_STATUS, "rematch_requests": [], "updated_at": bson::DateTime::now() } },
+            None
+        ).await;
+        match close_res {
+            Ok(update_result) if update_result.modified_count == 1 => {},
+            _ => return,
+        }
+
+        if let Some(session_id) = &game.session_id {
+            if has_open_bets(postgres_conn, &game_id, session_id).await {
+                let game_over_event = GameOverEvent {
+                    game_id: game_id.clone(),
+                    session_id: session_id.clone(),
+                    winner_id: "".to_string(),
+                    winner_ids: vec![],
+                    is_game_valid: false,
+                    absent_player_id: "".to_string(),
+                };
+
+                kafka_events.push(KafkaGeneralEvent {
+                    topic: GAME_OVER_EVENT.to_string(),
+                    payload: serde_json::to_string(&game_over_event).unwrap(),
+                    key: "stale_game_reaped".to_string(),
+                });
+            }
+        }
+
+        let game_result = GameResultPayload {
+            game_id: game_id.clone(),
+            winner_ids: vec![],
+            placements: vec![],
+            reason: "expired".to_string(),
+        };
+
+        kafka_events.push(KafkaGeneralEvent {
+            topic: "game".to_string(),
+            payload: serde_json::to_string(&game_result).unwrap(),
+            key: GAME_RESULT_EVENT.to_string(),
+        });
+
+        if let Some(turn_mapping) = &turn_mapping {
+            for turn in &turn_mapping.turn_mappings {
+                let key_id = game_id.clone() + "_" + &turn.user_id;
+                let _: RedisResult<()> = redis_conn.del(PLAYER_HEARTBEAT_KEY.to_owned() + &key_id).await;
+                let _: RedisResult<()> = redis_conn.del(PLAYER_HEARTBEAT_DATA.to_owned() + &key_id).await;
+            }
+        }
+
+        archive::archive_game(postgres_conn, mongo_db, engine_registry.get(&game.game_type).ok(), game, &GameOutcome::unranked("expired")).await;
+    }
+
+    let deletion_event = UserGameDeletetionEvent {
+        user_id: game.host_id.clone().unwrap_or_default(),
+        game_id: game_id.clone(),
+    };
+
+    kafka_events.push(KafkaGeneralEvent {
+        topic: USER_GAME_DELETION.to_string(),
+        payload: serde_json::to_string(&deletion_event).unwrap(),
+        key: USER_GAME_DELETION.to_string(),
+    });
+
+    if let Err(e) = kafka::producer::send_kafka_events(producer, kafka_events).await {
+        warn!("Error while sending reaper events for game_id={}: {:?}" , game_id , e);
+
+        // Leave the game as it was so the next sweep picks it up again
+        if is_unfinished {
+            let _ = game_collection.update_one(doc! { "id": game_id.clone() }, doc! { "$set": { "description": game.description.clone(), "updated_at": game.updated_at } }, None).await;
+        }
+        return;
+    }
+
+    if let Some(join_code) = &game.join_code {
+        let _: RedisResult<()> = redis_conn.del(LOBBY_JOIN_CODE_KEY.to_owned() + join_code).await;
+    }
+
+    info!("Reaped stale game game_id={} with status={}" , game_id , game.description);
+}
+
+async fn has_connected_player(redis_conn: &mut MultiplexedConnection, turn_mapping: Option<&UserTurnMapping>, game_id: &str) -> bool {
+    let Some(turn_mapping) = turn_mapping else {
+        return false;
+    };
+
+    for turn in &turn_mapping.turn_mappings {
+        let connected: bool = redis_conn.exists(PLAYER_HEARTBEAT_KEY.to_owned() + game_id + "_" + &turn.user_id).await.unwrap_or_default();
+        if connected {
+            return true;
+        }
+    }
+
+    false
+}
+
+// When the bets cannot be checked the game is treated as staked, an invalid game without bets settles nothing
+async fn has_open_bets(postgres_conn: &DatabaseConnection, game_id: &str, session_id: &str) -> bool {
+    let Ok(game_uuid) = Uuid::from_str(game_id) else {
+        return false;
+    };
+
+    game_bets::Entity::find_by_game_id_and_session_id_with_progress(game_uuid, session_id.to_string(), GameBetStatus::InProgress.to_string())
+        .all(postgres_conn)
+        .await
+        .map(|bets| !bets.is_empty())
+        .unwrap_or(true)
+}
+
+
+// Player and turn documents left behind by games that were removed without them
+async fn delete_orphaned_documents(mongo_db: &Database) {
+    let game_collection = mongo_db.collection::<Document>(MONGO_GAMES_MODEL);
+
+    for collection_name in [MONGO_USERS_MODEL, MONGO_USER_TURNS_MODEL] {
+        let collection = mongo_db.collection::<Document>(collection_name);
+
+        // Game ids are stored as strings, anything else is left alone
+        let Ok(game_ids) = collection.distinct("game_id", doc! { "game_id": { "$type": "string" } }, None).await else {
+            continue;
+        };
+        let Ok(existing_game_ids) = game_collection.distinct("id", doc! { "id": { "$in": game_ids.clone() } }, None).await else {
+            continue;
+        };
+
+        let orphaned_game_ids: Vec<Bson> = game_ids.into_iter().filter(|game_id| !existing_game_ids.contains(game_id)).collect();
+        if orphaned_game_ids.is_empty() {
+            continue;
+        }
+
+        match collection.delete_many(doc! { "game_id": { "$in": orphaned_game_ids } }, None).await {
+            Ok(delete_result) => info!("Deleted {} orphaned documents from {}" , delete_result.deleted_count , collection_name),
+            Err(e) => warn!("Error while deleting orphaned documents from {}: {:?}" , collection_name , e),
+        }
+    }
+}
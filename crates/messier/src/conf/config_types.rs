@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -122,6 +124,15 @@ pub struct EmailConfiguration {
 }
 
 
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct LobbyConfiguration {
+    // Seats per game type, clamped to what the engine supports
+    #[serde(default)]
+    pub max_players: HashMap<String, usize>,
+}
+
+
 
 // Impl Methods for structs
 impl TopicConfiguration {
@@ -3,7 +3,7 @@ use std::{env, sync::atomic::AtomicU16};
 use config::{Config, ConfigError, File};
 use serde::Deserialize;
 use std::sync::atomic::Ordering::SeqCst;
-use super::config_types::{EmailConfiguration, KafkaConfiguration, LoggingConfiguration, MongoDatabaseConfiguration, PostgresDatabaseUrl, RedisDBUrl, ServerConfiguration};
+use super::config_types::{EmailConfiguration, KafkaConfiguration, LobbyConfiguration, LoggingConfiguration, MongoDatabaseConfiguration, PostgresDatabaseUrl, RedisDBUrl, ServerConfiguration};
 
 
 pub static SERVER_PORT: AtomicU16 = AtomicU16::new(0);
@@ -17,6 +17,7 @@ pub struct Configuration {
     pub logging: LoggingConfiguration,
     pub redis_url: RedisDBUrl,
     pub email_config: EmailConfiguration,
+    pub lobby: Option<LobbyConfiguration>,
 }
 
 impl Configuration {
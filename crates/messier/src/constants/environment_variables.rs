@@ -11,6 +11,12 @@ lazy_static!{
 
 
 pub const SMTP_HOST: &str = "smtp-relay.sendinblue.com";
+pub const GAME_INVITE_EXPIRY_MINUTES: i64 = 10;
+pub const LOBBY_JOIN_CODE_LENGTH: usize = 6;
+// Codes are removed when the lobby starts or is destroyed, the expiry only covers lobbies that are never closed
+pub const LOBBY_JOIN_CODE_EXPIRY_SECS: u64 = 24 * 60 * 60;
+pub const JOIN_CODE_MAX_FAILED_ATTEMPTS: i64 = 10;
+pub const JOIN_CODE_ATTEMPTS_WINDOW_SECS: i64 = 10 * 60;
 
 fn set_token() -> String{
     dotenv().ok();
@@ -0,0 +1,1581 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::errors::Error;
+use crate::errors;
+use crate::constants::environment_variables::{GAME_INVITE_EXPIRY_MINUTES, JOIN_CODE_ATTEMPTS_WINDOW_SECS, JOIN_CODE_MAX_FAILED_ATTEMPTS, LOBBY_JOIN_CODE_EXPIRY_SECS, LOBBY_JOIN_CODE_LENGTH};
+use crate::event_producer::game_events_producer::send_game_events;
+use crate::event_producer::user_events_producer::send_event_for_user_topic;
+use crate::state::AppDBState;
+use crate::utils::generate_random_string::{generate_join_code, generate_random_string};
+use crate::utils::jwt::Claims;
+use axum::extract::State;
+use axum::Extension;
+use axum::Json;
+use bson::{doc, DateTime, Document};
+use chrono::{Duration, Utc};
+use errors::Result as APIResult;
+use futures::TryStreamExt;
+use mongodb::{options::{FindOneAndUpdateOptions, FindOneOptions, FindOptions, ReturnDocument}, Collection, Database};
+use orion::constants::{CHAT_MUTED_USERS_KEY, CHAT_PLAYERS_CHANNEL, CHAT_SPECTATORS_CHANNEL, CREATE_NEW_GAME_RECORD, GAME_AWAITING_BETS_STATUS, GAME_INVITE_ACCEPTED, GAME_INVITE_DECLINED, GAME_INVITE_EVENT, GAME_INVITE_PENDING, GAME_INVITE_RESPONSE_EVENT, GAME_IN_PROGRESS_STATUS, GAME_LOBBY_STATUS, GAME_OVER_STATUS, GAME_REMATCH_DECLINED_EVENT, GAME_REMATCH_REQUEST_EVENT, GAME_SPECTATORS_KEY, GAME_START_EVENT, GAME_TURN_TIMER, GAME_TURN_TIMER_DATA, JOIN_CODE_ATTEMPTS_KEY, LOBBY_JOIN_CODE_KEY, MONGO_DB_NAME, MONGO_GAMES_MODEL, MONGO_GAME_MESSAGES_MODEL, MONGO_GAME_MOVES_MODEL, MONGO_USERS_MODEL, MONGO_USER_TURNS_MODEL, PLAYER_DISCONNECTED, PLAYER_NOT_READY, PLAYER_READY, USER_GAME_DELETION, USER_JOINED_ROOM, USER_LEFT_ROOM, USER_STATUS_EVENT};
+use orion::engines::{EngineAction, StateStorage};
+use orion::events::kafka_event::{CreateNewGamePayloadEvent, KafkaGeneralEvent, UserGameDeletetionEvent, UserGameInviteKafkaEvent, UserGameInviteResponseKafkaEvent};
+use orion::events::ws_events::{GameRematchPayload, GameStartPayload, JoinedRoomPayload, LeavedRoomPayload, UpdateUserStatusPayload};
+use orion::models::game_bet_events::GameBetStatus;
+use orion::models::game_message_model::GameMessageRecord;
+use orion::models::game_model::Game;
+use orion::models::game_move_model::GameMoveRecord;
+use orion::ratings::category::{rating_category, TimeControl};
+use orion::models::user_game_relation_model::UserGameRelation;
+use orion::models::user_turn_model::UserTurnMapping;
+use redis::{aio::MultiplexedConnection, AsyncCommands, ExistenceCheck, RedisResult, SetExpiry, SetOptions};
+use sea_orm::{prelude::Expr, ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde::Serialize;
+use serde_json::json;
+use serde_json::Value;
+use ton::models::{game::Entity as GameRecords, game_bets::Entity as GameBets, game_history::Entity as GameHistory, game_history_players::Entity as GameHistoryPlayers, game_invites::{self, Entity as GameInvites}, users::{self, Entity as Users}, users_friends::Entity as UsersFriends};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::payloads::{CreateLobbyPayload, DestroyLobbyPayload, GameClockResponseModel, GameMessageResponseModel, GameSpectatorResponseModel, GameStakeResponseModel, GetGameCurrentStatePayload, GetGameDetailsPayload, GetGameMessagesPayload, GetGameReplayPayload, GetGameSpectatorsPayload, GetLobbyPlayersPayload, GetUserTurnMappingsPayload, JoinLobbyByCodePayload, JoinLobbyPayload, LeaveLobbyPayload, LobbyPlayerResponseModel, RematchPayload, ReplayPlayerResponseModel, ReplayPlyResponseModel, RespondToGameInvitePayload, SendGameInvitePayload, StartGamePayload, UpdatePlayerStatusPayload, UpdateSpectatorSettingsPayload};
+
+
+// Latest chat messages returned for a channel
+const GAME_MESSAGES_LIMIT: i64 = 100;
+
+
+pub async fn create_lobby(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<CreateLobbyPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_type.is_empty() || payload.game_name.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let user_id = Uuid::from_str(&claims.user_id).map_err(|_| Error::InvalidUserToken)?;
+    let username = get_username(&state, &claims.user_id).await?;
+    let (_, max_players) = state.engine_registry.player_limits(&payload.game_type).map_err(|_| Error::CreateLobbyError)?;
+
+    if !valid_spectator_settings(payload.spectator_limit, payload.spectator_delay_secs) {
+        return Err(Error::InvalidSpectatorSettings)
+    }
+
+    let time_control = payload.time_control.clone().filter(|time_control| !time_control.is_empty());
+    if time_control.as_deref().is_some_and(|time_control| TimeControl::parse(time_control).is_none()) {
+        return Err(Error::InvalidTimeControl)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let game_id = Uuid::new_v4();
+
+    let password = payload.password.clone().filter(|password| !password.is_empty());
+    let join_code = if payload.with_join_code || password.is_some() {
+        Some(reserve_join_code(&state, &game_id.to_string()).await?)
+    } else {
+        None
+    };
+
+    let new_game = Game {
+        id: game_id,
+        user_count: 1,
+        host_id: Some(claims.user_id.clone()),
+        name: payload.game_name.clone(),
+        game_type: payload.game_type.to_lowercase(),
+        is_staked: false,
+        chess_state: "".to_string(),
+        is_match: false,
+        state_index: 0,
+        description: GAME_LOBBY_STATUS.to_string(),
+        staked_money_state: None,
+        poker_state: None,
+        game_state: None,
+        session_id: None,
+        rematch_requests: vec![],
+        spectator_limit: payload.spectator_limit,
+        spectator_delay_secs: payload.spectator_delay_secs,
+        join_code: join_code.clone(),
+        join_password: password.as_deref().map(hash_lobby_password),
+        is_casual: payload.is_casual,
+        time_control,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+
+    let mut turn_mapping = UserTurnMapping {
+        host_id: claims.user_id.clone(),
+        game_id: game_id.to_string(),
+        turn_mappings: vec![],
+    };
+    turn_mapping.assign_seat(&claims.user_id, &username, max_players);
+
+    let host_relation = UserGameRelation {
+        user_id,
+        username,
+        game_id: game_id.to_string(),
+        player_type: "".to_string(),
+        player_status: PLAYER_NOT_READY.to_string(),
+    };
+
+    let game_res = mongo_db.collection::<Document>(MONGO_GAMES_MODEL).insert_one(with_string_id(&new_game, "id", &game_id), None).await;
+    if game_res.is_err() {
+        warn!("Error while creating game for lobby: {:?}", game_res.err());
+        release_join_code(&mut state.context.get_redis_db_client(), &new_game).await;
+        return Err(Error::CreateLobbyError)
+    }
+
+    let turn_res = mongo_db.collection::<UserTurnMapping>(MONGO_USER_TURNS_MODEL).insert_one(turn_mapping, None).await;
+    let relation_res = mongo_db.collection::<Document>(MONGO_USERS_MODEL).insert_one(with_string_id(&host_relation, "user_id", &user_id), None).await;
+    if turn_res.is_err() || relation_res.is_err() {
+        return Err(Error::CreateLobbyError)
+    }
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "game_id": game_id.to_string(),
+        "max_players": max_players,
+        "join_code": join_code,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn join_lobby(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<JoinLobbyPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, _, _) = lobby_collections(&mongo_db);
+
+    // Invited players join through respond_to_game_invite, anyone else needs the lobby password
+    let game = get_game(&game_collection, &payload.game_id).await?;
+    if !lobby_password_matches(&game, payload.password.as_deref()) {
+        return Err(Error::InvalidLobbyPassword)
+    }
+
+    let username = get_username(&state, &claims.user_id).await?;
+    let seat = add_lobby_player(&state, &game, &claims.user_id, &username).await?;
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "seat": seat,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn join_lobby_by_code(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<JoinLobbyByCodePayload>,
+) -> APIResult<Json<Value>> {
+    if payload.join_code.trim().is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mut redis_conn = state.context.get_redis_db_client();
+    if join_code_attempts_exceeded(&mut redis_conn, &claims.user_id).await {
+        return Err(Error::TooManyJoinCodeAttempts)
+    }
+
+    let join_code = payload.join_code.trim().to_uppercase();
+    let game_id: Option<String> = redis_conn.get(LOBBY_JOIN_CODE_KEY.to_owned() + &join_code).await.map_err(|_| Error::RedisGetKeyError)?;
+    let Some(game_id) = game_id else {
+        record_failed_join_code_attempt(&mut redis_conn, &claims.user_id).await;
+        return Err(Error::InvalidJoinCode)
+    };
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, _, _) = lobby_collections(&mongo_db);
+
+    let game = get_game(&game_collection, &game_id).await?;
+    if !lobby_password_matches(&game, payload.password.as_deref()) {
+        record_failed_join_code_attempt(&mut redis_conn, &claims.user_id).await;
+        return Err(Error::InvalidLobbyPassword)
+    }
+
+    let username = get_username(&state, &claims.user_id).await?;
+    let seat = add_lobby_player(&state, &game, &claims.user_id, &username).await?;
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "game_id": game_id,
+        "seat": seat,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn leave_lobby(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<LeaveLobbyPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, user_collection, user_turn_collection) = lobby_collections(&mongo_db);
+
+    let game = get_game(&game_collection, &payload.game_id).await?;
+    if game.description != GAME_LOBBY_STATUS {
+        return Err(Error::ErrorWhileLeavingLobby)
+    }
+
+    let removed_relation = match user_collection.find_one_and_delete(doc! { "game_id": payload.game_id.clone(), "user_id": claims.user_id.clone() }, None).await {
+        Ok(Some(user_game_relation)) => user_game_relation,
+        Ok(None) => return Err(Error::RemoveFromLobbyError),
+        Err(_) => return Err(Error::ErrorWhileLeavingLobby),
+    };
+
+    let pull_res = user_turn_collection.update_one(
+        doc! { "game_id": payload.game_id.clone() },
+        doc! { "$pull": { "turn_mappings": { "user_id": claims.user_id.clone() } } },
+        None
+    ).await;
+    if pull_res.is_err() {
+        return Err(Error::ErrorWhileLeavingLobby)
+    }
+
+    let turn_mapping = get_turn_mapping(&user_turn_collection, &payload.game_id).await?;
+    let mut kafka_events = vec![];
+
+    match turn_mapping.active_turns().first() {
+        // Last player left, the lobby goes away with them
+        None => {
+            kafka_events.push(deletion_event(&claims.user_id, &payload.game_id));
+            release_join_code(&mut state.context.get_redis_db_client(), &game).await;
+        },
+        Some(next_host) => {
+            let mut game_update = doc! { "updated_at": DateTime::now() };
+
+            if game.host_id.as_deref() == Some(claims.user_id.as_str()) {
+                game_update.insert("host_id", next_host.user_id.clone());
+                let _ = user_turn_collection.update_one(doc! { "game_id": payload.game_id.clone() }, doc! { "$set": { "host_id": next_host.user_id.clone() } }, None).await;
+            }
+
+            let _ = game_collection.update_one(
+                doc! { "id": payload.game_id.clone() },
+                doc! { "$inc": { "user_count": -1 }, "$set": game_update },
+                None
+            ).await;
+        }
+    }
+
+    let left_payload = LeavedRoomPayload {
+        user_id: claims.user_id.clone(),
+        username: removed_relation.username,
+        game_id: payload.game_id.clone(),
+        player_type: removed_relation.player_type,
+    };
+    kafka_events.insert(0, game_event(USER_LEFT_ROOM, &left_payload));
+    send_lobby_events(&state, kafka_events).await;
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		}
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn update_player_status(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<UpdatePlayerStatusPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() || payload.status.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    if payload.status != PLAYER_READY && payload.status != PLAYER_NOT_READY {
+        return Err(Error::InvalidStatusSendAsPayload)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, user_collection, _) = lobby_collections(&mongo_db);
+
+    let game = get_game(&game_collection, &payload.game_id).await?;
+    if game.description != GAME_LOBBY_STATUS {
+        return Err(Error::ErrorWhileUpdatingPlayerStatus)
+    }
+
+    let user_game_relation = match user_collection.find_one_and_update(
+        doc! { "game_id": payload.game_id.clone(), "user_id": claims.user_id.clone() },
+        doc! { "$set": { "player_status": payload.status.clone() } },
+        None
+    ).await {
+        Ok(Some(user_game_relation)) => user_game_relation,
+        _ => return Err(Error::ErrorWhileUpdatingPlayerStatus),
+    };
+
+    let status_payload = UpdateUserStatusPayload {
+        user_id: claims.user_id.clone(),
+        username: user_game_relation.username,
+        game_id: payload.game_id.clone(),
+        status: payload.status.clone(),
+    };
+    send_lobby_events(&state, vec![game_event(USER_STATUS_EVENT, &status_payload)]).await;
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		}
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn start_game(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<StartGamePayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, user_collection, user_turn_collection) = lobby_collections(&mongo_db);
+
+    let game = get_game(&game_collection, &payload.game_id).await?;
+    if game.host_id.as_deref() != Some(claims.user_id.as_str()) {
+        return Err(Error::NotGameHost)
+    }
+    if game.description != GAME_LOBBY_STATUS {
+        return Err(Error::GameCannotBeStarted)
+    }
+
+    let engine = state.engine_registry.get(&game.game_type).map_err(|_| Error::GameCannotBeStarted)?;
+    let players = get_lobby_relations(&user_collection, &payload.game_id).await?;
+
+    if players.len() < engine.min_players() {
+        return Err(Error::GameCannotBeStarted)
+    }
+
+    if players.iter().any(|player| player.player_status != PLAYER_READY) {
+        return Err(Error::NotAllPlayersHaveReadyStatus)
+    }
+
+    // Seat based games (chess, checkers) know their players by colour, seat 0 gets the first one
+    let turn_mapping = get_turn_mapping(&user_turn_collection, &payload.game_id).await?;
+    let player_types = engine.player_types();
+    for (index, turn) in turn_mapping.active_turns().iter().enumerate() {
+        let player_type = player_types.get(index).copied().unwrap_or("player");
+        let _ = user_collection.update_one(
+            doc! { "game_id": payload.game_id.clone(), "user_id": turn.user_id.clone() },
+            doc! { "$set": { "player_type": player_type } },
+            None
+        ).await;
+    }
+
+    // Only the first start request moves the game out of the lobby
+    let session_id = Uuid::new_v4().to_string();
+    let status_res = game_collection.update_one(
+        doc! { "id": payload.game_id.clone(), "description": GAME_LOBBY_STATUS },
+        doc! { "$set": { "description": GAME_IN_PROGRESS_STATUS, "session_id": session_id.clone(), "updated_at": DateTime::now() }, "$unset": { "join_code": "" } },
+        None
+    ).await;
+    match status_res {
+        Ok(update_result) if update_result.modified_count == 1 => {},
+        _ => return Err(Error::GameCannotBeStarted),
+    }
+
+    let new_game_record = CreateNewGamePayloadEvent {
+        game_id: payload.game_id.clone(),
+        session_id: session_id.clone(),
+    };
+    let game_start_payload = GameStartPayload {
+        admin_id: game.host_id.clone().unwrap_or_default(),
+        game_name: game.name.clone(),
+        game_id: payload.game_id.clone(),
+        session_id: session_id.clone(),
+    };
+
+    let kafka_events = vec![
+        KafkaGeneralEvent {
+            topic: CREATE_NEW_GAME_RECORD.to_string(),
+            payload: serde_json::to_string(&new_game_record).unwrap(),
+            key: CREATE_NEW_GAME_RECORD.to_string(),
+        },
+        game_event(GAME_START_EVENT, &game_start_payload),
+    ];
+
+    if send_game_events(&state.producer, kafka_events).await.is_err() {
+        // Put the lobby back so the host can try again
+        let _ = game_collection.update_one(doc! { "id": payload.game_id.clone() }, doc! { "$set": { "description": GAME_LOBBY_STATUS, "join_code": game.join_code.clone() } }, None).await;
+        return Err(Error::ErrorWhileChangingGameStatus)
+    }
+
+    release_join_code(&mut state.context.get_redis_db_client(), &game).await;
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "session_id": session_id,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn destroy_lobby(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<DestroyLobbyPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, _, _) = lobby_collections(&mongo_db);
+
+    let game = get_game(&game_collection, &payload.game_id).await?;
+    if game.host_id.as_deref() != Some(claims.user_id.as_str()) {
+        return Err(Error::NotGameHost)
+    }
+    if game.description != GAME_LOBBY_STATUS {
+        return Err(Error::DeleteLobbyError)
+    }
+
+    // cerotis removes the game, users and turns documents when it gets the deletion event
+    let kafka_events = vec![deletion_event(&claims.user_id, &payload.game_id)];
+    if send_game_events(&state.producer, kafka_events).await.is_err() {
+        return Err(Error::DeleteLobbyError)
+    }
+
+    release_join_code(&mut state.context.get_redis_db_client(), &game).await;
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		}
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn get_lobby_players(
+    State(state): State<AppDBState>,
+	Json(payload): Json<GetLobbyPlayersPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, user_collection, user_turn_collection) = lobby_collections(&mongo_db);
+
+    let game = get_game(&game_collection, &payload.game_id).await?;
+    if !payload.host_user_id.is_empty() && game.host_id.as_deref() != Some(payload.host_user_id.as_str()) {
+        return Err(Error::GameLobbyDeletedOrRequestIsInvalid)
+    }
+
+    let (_, max_players) = state.engine_registry.player_limits(&game.game_type).map_err(|_| Error::ErrorWhileRetrievingLobbyUsers)?;
+    let turn_mapping = get_turn_mapping(&user_turn_collection, &payload.game_id).await?;
+    let relations = get_lobby_relations(&user_collection, &payload.game_id).await?;
+
+    let players = lobby_players(relations, &turn_mapping);
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "host_id": game.host_id,
+        "game_type": game.game_type,
+        "status": game.description,
+        "max_players": max_players,
+        "players": players,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn send_game_invite(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<SendGameInvitePayload>,
+) -> APIResult<Json<Value>> {
+    if payload.friend_id.is_empty() || payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let user_id = Uuid::from_str(&claims.user_id).map_err(|_| Error::InvalidUserToken)?;
+    let friend_id = Uuid::from_str(&payload.friend_id).map_err(|_| Error::MissingParamsError)?;
+    let game_id = Uuid::from_str(&payload.game_id).map_err(|_| Error::MissingParamsError)?;
+
+    let friend_relation = UsersFriends::find_by_user_and_friend_id(&user_id, &friend_id).one(&state.conn).await.map_err(|_| Error::ErrorWhileFetchingUserFriends)?;
+    if friend_relation.is_none() {
+        return Err(Error::UserIsNotAFriend)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, _, user_turn_collection) = lobby_collections(&mongo_db);
+
+    let game = get_game(&game_collection, &payload.game_id).await?;
+    if game.description != GAME_LOBBY_STATUS {
+        return Err(Error::GameInviteSendError)
+    }
+
+    // Only players already in the lobby can invite
+    let turn_mapping = get_turn_mapping(&user_turn_collection, &payload.game_id).await?;
+    if !turn_mapping.turn_mappings.iter().any(|turn| turn.user_id == claims.user_id) {
+        return Err(Error::GameInviteSendError)
+    }
+
+    let now = Utc::now().naive_utc();
+    let pending_invite = GameInvites::find_by_game_id_received_id_and_status(&game_id, &friend_id, GAME_INVITE_PENDING.to_string(), now).one(&state.conn).await.map_err(|_| Error::GameInviteSendError)?;
+    if let Some(pending_invite) = pending_invite {
+        let body = Json(json!({
+            "result": {
+                "success": true
+            },
+            "invite_id": pending_invite.id,
+        }));
+
+        return Ok(body)
+    }
+
+    let username = get_username(&state, &claims.user_id).await?;
+    let invite_id = Uuid::new_v4();
+    let expires_at = now + Duration::minutes(GAME_INVITE_EXPIRY_MINUTES);
+    let new_invite = game_invites::ActiveModel {
+        id: Set(invite_id),
+        game_id: Set(game_id),
+        user_sent_id: Set(user_id),
+        user_sent_username: Set(username.clone()),
+        user_recieved_id: Set(friend_id),
+        status: Set(GAME_INVITE_PENDING.to_string()),
+        expires_at: Set(expires_at),
+        created_at: Set(now),
+    };
+
+    if new_invite.insert(&state.conn).await.is_err() {
+        return Err(Error::GameInviteSendError)
+    }
+
+    let kafka_event = UserGameInviteKafkaEvent {
+        user_who_send_request_id: claims.user_id.clone(),
+        user_who_send_request_username: username,
+        user_who_we_are_sending_event: payload.friend_id.clone(),
+        game_id: payload.game_id.clone(),
+        game_name: game.name.clone(),
+        game_type: game.game_type.clone(),
+        invite_id: invite_id.to_string(),
+        expires_at: expires_at.and_utc().timestamp_millis(),
+    };
+
+    if send_event_for_user_topic(&state.producer, &state.context, GAME_INVITE_EVENT.to_string(), serde_json::to_string(&kafka_event).unwrap()).await.is_err() {
+        let _ = GameInvites::delete_by_id(invite_id).exec(&state.conn).await;
+        return Err(Error::GameInviteSendError)
+    }
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "invite_id": invite_id,
+        "expires_at": kafka_event.expires_at,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn respond_to_game_invite(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<RespondToGameInvitePayload>,
+) -> APIResult<Json<Value>> {
+    if payload.invite_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let invite_id = Uuid::from_str(&payload.invite_id).map_err(|_| Error::MissingParamsError)?;
+    let invite = GameInvites::find_by_id(&invite_id).one(&state.conn).await.map_err(|_| Error::GameInviteNotFound)?.ok_or(Error::GameInviteNotFound)?;
+
+    if invite.user_recieved_id.to_string() != claims.user_id || invite.status != GAME_INVITE_PENDING {
+        return Err(Error::GameInviteNotFound)
+    }
+
+    if invite.expires_at <= Utc::now().naive_utc() {
+        return Err(Error::GameInviteExpired)
+    }
+
+    let username = get_username(&state, &claims.user_id).await?;
+
+    // The invite stays pending if the lobby cannot be joined (full, already started) so the error reaches the invitee
+    let mut seat = None;
+    if payload.accept {
+        let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+        let (game_collection, _, _) = lobby_collections(&mongo_db);
+        let game = get_game(&game_collection, &invite.game_id.to_string()).await?;
+
+        seat = Some(add_lobby_player(&state, &game, &claims.user_id, &username).await?);
+    }
+
+    let status = if payload.accept { GAME_INVITE_ACCEPTED } else { GAME_INVITE_DECLINED };
+    let update_res = GameInvites::update_many()
+        .col_expr(game_invites::Column::Status, Expr::value(status))
+        .filter(game_invites::Column::Id.eq(invite_id))
+        .filter(game_invites::Column::Status.eq(GAME_INVITE_PENDING))
+        .exec(&state.conn)
+        .await;
+
+    match update_res {
+        // Answered twice at the same time, the first answer already notified the inviter
+        Ok(update_result) if update_result.rows_affected == 0 => {},
+        Ok(_) => {
+            let kafka_event = UserGameInviteResponseKafkaEvent {
+                invite_id: payload.invite_id.clone(),
+                game_id: invite.game_id.to_string(),
+                user_who_responded_id: claims.user_id.clone(),
+                user_who_responded_username: username,
+                user_who_we_are_sending_event: invite.user_sent_id.to_string(),
+                accepted: payload.accept,
+            };
+
+            if let Err(e) = send_event_for_user_topic(&state.producer, &state.context, GAME_INVITE_RESPONSE_EVENT.to_string(), serde_json::to_string(&kafka_event).unwrap()).await {
+                warn!("Error while notifying inviter for invite_id={}: {:?}", payload.invite_id, e);
+            }
+        },
+        Err(_) => return Err(Error::GameInviteSendError),
+    }
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "game_id": invite.game_id,
+        "seat": seat,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn get_game_invites(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+) -> APIResult<Json<Value>> {
+    let user_id = Uuid::from_str(&claims.user_id).map_err(|_| Error::InvalidUserToken)?;
+
+    let invites = GameInvites::find_by_received_id_and_status(&user_id, GAME_INVITE_PENDING.to_string(), Utc::now().naive_utc())
+        .all(&state.conn)
+        .await
+        .map_err(|_| Error::GameInviteNotFound)?;
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "invites": invites,
+	}));
+
+    Ok(body)
+}
+
+// Everything a client needs to rebuild its view of a game after a reload. Players get their own view of the state,
+// everyone else the public one
+pub async fn get_game_current_state(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<GetGameCurrentStatePayload>,
+) -> APIResult<Json<Value>> {
+    let game_id = payload.game_id.to_string();
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, user_collection, user_turn_collection) = lobby_collections(&mongo_db);
+
+    let game = get_game(&game_collection, &game_id).await?;
+    let engine = state.engine_registry.get(&game.game_type).map_err(|_| Error::ErrorWhileFetchingGameDetails)?;
+    let turn_mapping = get_turn_mapping(&user_turn_collection, &game_id).await?;
+    let relations = get_lobby_relations(&user_collection, &game_id).await?;
+
+    let players = lobby_players(relations, &turn_mapping);
+    let is_spectator = !players.iter().any(|player| player.user_id == claims.user_id);
+
+    let mut redis_conn = state.context.get_redis_db_client();
+    let delay_secs = game.broadcast_delay_secs();
+
+    let (current_state, state_index) = if is_spectator && delay_secs > 0 {
+        // Spectators of staked games only get what the delayed broadcast has already shown them
+        match get_delayed_move(&mongo_db, &game_id, delay_secs).await {
+            Some(game_move) => (Some(game_move.public_state), game_move.state_index),
+            None => (None, game.state_index),
+        }
+    } else {
+        let raw_state: Option<String> = match engine.state_storage() {
+            StateStorage::Mongo => game.game_state.clone(),
+            StateStorage::Redis => redis_conn.get(engine.state_key_prefix().to_owned() + &game_id).await.map_err(|_| Error::RedisGetKeyError)?,
+        };
+
+        // No state until the first move of the session deals it
+        match raw_state {
+            Some(raw_state) => (Some(engine.player_view(&raw_state, &claims.user_id).map_err(|_| Error::ErrorWhileFetchingGameDetails)?), game.state_index),
+            None => (None, game.state_index),
+        }
+    };
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "game": game_details(&game_id, &game),
+        "state_index": state_index,
+        "current_state": current_state,
+        "is_spectator": is_spectator,
+        "players": players,
+        "turn_mappings": turn_mapping.turn_mappings,
+        "clock": get_game_clock(&mut redis_conn, &game_id).await,
+        "stake": get_game_stake(&state, &game_id, &game).await,
+        "rematch_requests": game.rematch_requests,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn get_user_turn_mappings(
+    State(state): State<AppDBState>,
+	Json(payload): Json<GetUserTurnMappingsPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (_, _, user_turn_collection) = lobby_collections(&mongo_db);
+
+    let turn_mapping = get_turn_mapping(&user_turn_collection, &payload.game_id).await?;
+    let active_turns: Vec<String> = turn_mapping.active_turns().iter().map(|turn| turn.user_id.clone()).collect();
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "host_id": turn_mapping.host_id,
+        "turn_mappings": turn_mapping.turn_mappings,
+        "active_turns": active_turns,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn get_game_details(
+    State(state): State<AppDBState>,
+	Json(payload): Json<GetGameDetailsPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, user_collection, user_turn_collection) = lobby_collections(&mongo_db);
+
+    let game = get_game(&game_collection, &payload.game_id).await?;
+    let turn_mapping = get_turn_mapping(&user_turn_collection, &payload.game_id).await?;
+    let relations = get_lobby_relations(&user_collection, &payload.game_id).await?;
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "game": game_details(&payload.game_id, &game),
+        "players": lobby_players(relations, &turn_mapping),
+        "stake": get_game_stake(&state, &payload.game_id, &game).await,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn update_spectator_settings(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<UpdateSpectatorSettingsPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    if !valid_spectator_settings(payload.spectator_limit, payload.spectator_delay_secs) {
+        return Err(Error::InvalidSpectatorSettings)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, _, _) = lobby_collections(&mongo_db);
+
+    let game = get_game(&game_collection, &payload.game_id).await?;
+    if game.host_id.as_deref() != Some(claims.user_id.as_str()) {
+        return Err(Error::GameLobbyDeletedOrRequestIsInvalid)
+    }
+
+    // Spectators already watching keep the delay they joined with, so it is fixed once the game starts
+    if game.description != GAME_LOBBY_STATUS {
+        return Err(Error::InvalidSpectatorSettings)
+    }
+
+    let update_res = game_collection.update_one(
+        doc! { "id": payload.game_id.clone() },
+        doc! { "$set": { "spectator_limit": payload.spectator_limit, "spectator_delay_secs": payload.spectator_delay_secs, "updated_at": DateTime::now() } },
+        None
+    ).await;
+    if update_res.is_err() {
+        return Err(Error::ErrorWhileChangingGameStatus)
+    }
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		}
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn get_game_spectators(
+    State(state): State<AppDBState>,
+	Json(payload): Json<GetGameSpectatorsPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, _, _) = lobby_collections(&mongo_db);
+    let game = get_game(&game_collection, &payload.game_id).await?;
+
+    let mut redis_conn = state.context.get_redis_db_client();
+    let spectator_connections: HashMap<String, i64> = redis_conn.hgetall(GAME_SPECTATORS_KEY.to_owned() + &payload.game_id).await.map_err(|_| Error::RedisGetKeyError)?;
+
+    // Feed viewers are not logged in, they are only counted
+    let spectator_ids: Vec<Uuid> = spectator_connections.keys().filter_map(|spectator_id| Uuid::from_str(spectator_id).ok()).collect();
+    let spectators: Vec<GameSpectatorResponseModel> = Users::find()
+        .filter(users::Column::Id.is_in(spectator_ids))
+        .all(&state.conn)
+        .await
+        .map_err(|_| Error::ErrorWhileFetchingGameDetails)?
+        .into_iter()
+        .map(|user| GameSpectatorResponseModel { user_id: user.id.to_string(), username: user.username })
+        .collect();
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "spectator_count": spectator_connections.len(),
+        "spectator_limit": game.spectator_limit,
+        "broadcast_delay_secs": game.broadcast_delay_secs(),
+        "spectators": spectators,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn get_game_messages(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<GetGameMessagesPayload>,
+) -> APIResult<Json<Value>> {
+    let channel = if payload.channel.is_empty() { CHAT_PLAYERS_CHANNEL } else { payload.channel.as_str() };
+    if payload.game_id.is_empty() || (channel != CHAT_PLAYERS_CHANNEL && channel != CHAT_SPECTATORS_CHANNEL) {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, user_collection, _) = lobby_collections(&mongo_db);
+
+    let game = get_game(&game_collection, &payload.game_id).await?;
+    let relations = get_lobby_relations(&user_collection, &payload.game_id).await?;
+    let is_spectator = !relations.iter().any(|relation| relation.user_id.to_string() == claims.user_id);
+
+    // Players only get to read the spectator chat once the game is over
+    if channel == CHAT_SPECTATORS_CHANNEL && !is_spectator && game.description != GAME_OVER_STATUS {
+        return Err(Error::ChatChannelNotAllowed)
+    }
+
+    let mut filter = doc! {
+        "game_id": payload.game_id.clone(),
+        "session_id": game.session_id.clone().unwrap_or_default(),
+        "channel": channel,
+        "deleted": false,
+    };
+
+    let delay_secs = game.broadcast_delay_secs();
+    if is_spectator && channel == CHAT_PLAYERS_CHANNEL && delay_secs > 0 {
+        filter.insert("created_at", doc! { "$lte": DateTime::from_millis(DateTime::now().timestamp_millis() - delay_secs as i64 * 1000) });
+    }
+
+    let mut redis_conn = state.context.get_redis_db_client();
+    let muted_users: Vec<String> = redis_conn.smembers(CHAT_MUTED_USERS_KEY.to_owned() + &claims.user_id).await.unwrap_or_default();
+    if !muted_users.is_empty() {
+        filter.insert("user_id", doc! { "$nin": muted_users });
+    }
+
+    let options = FindOptions::builder().sort(doc! { "created_at": -1 }).limit(GAME_MESSAGES_LIMIT).build();
+    let cursor = mongo_db.collection::<GameMessageRecord>(MONGO_GAME_MESSAGES_MODEL).find(filter, options).await.map_err(|_| Error::ErrorWhileFetchingGameDetails)?;
+    let records: Vec<GameMessageRecord> = cursor.try_collect().await.map_err(|_| Error::ErrorWhileFetchingGameDetails)?;
+
+    // Oldest first, like they were received
+    let messages: Vec<GameMessageResponseModel> = records.into_iter().rev().map(|record| GameMessageResponseModel {
+        message_id: record.id,
+        user_id: record.user_id,
+        username: record.username,
+        message: record.message,
+        channel: record.channel,
+        created_at: record.created_at.timestamp_millis(),
+    }).collect();
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "messages": messages,
+	}));
+
+    Ok(body)
+}
+
+
+
+// Moves of a finished session, one entry per ply. Only players can watch private lobbies again
+pub async fn get_game_replay(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<GetGameReplayPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() || payload.session_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let game_uuid = Uuid::from_str(&payload.game_id).map_err(|_| Error::GameReplayNotFound)?;
+
+    // Sessions are archived when they end, so games still being played have no replay
+    let history = GameHistory::find_by_game_id_and_session_id(&game_uuid, payload.session_id.clone())
+        .one(&state.conn)
+        .await
+        .map_err(|_| Error::ErrorWhileFetchingGameDetails)?
+        .ok_or(Error::GameReplayNotFound)?;
+
+    let players = GameHistoryPlayers::find_by_game_history_id(&history.id)
+        .all(&state.conn)
+        .await
+        .map_err(|_| Error::ErrorWhileFetchingGameDetails)?;
+
+    if history.is_private && !players.iter().any(|player| player.user_id.to_string() == claims.user_id) {
+        return Err(Error::GameReplayNotAllowed)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let options = FindOptions::builder().sort(doc! { "state_index": 1 }).build();
+    let cursor = mongo_db.collection::<GameMoveRecord>(MONGO_GAME_MOVES_MODEL)
+        .find(doc! { "game_id": payload.game_id.clone(), "session_id": payload.session_id.clone() }, options)
+        .await
+        .map_err(|_| Error::ErrorWhileFetchingGameDetails)?;
+    let game_moves: Vec<GameMoveRecord> = cursor.try_collect().await.map_err(|_| Error::ErrorWhileFetchingGameDetails)?;
+
+    if payload.ply.is_some_and(|ply| ply >= game_moves.len()) {
+        return Err(Error::InvalidReplayPly)
+    }
+
+    let engine = state.engine_registry.get(&history.game_type).ok();
+    let plies: Vec<ReplayPlyResponseModel> = game_moves.iter().enumerate()
+        .filter(|(ply, _)| payload.ply.is_none_or(|requested_ply| requested_ply == *ply))
+        .map(|(ply, game_move)| {
+            let previous_move = ply.checked_sub(1).map(|previous_ply| &game_moves[previous_ply]);
+
+            ReplayPlyResponseModel {
+                ply,
+                user_id: game_move.user_id.clone(),
+                move_type: game_move.move_type.clone(),
+                user_move: game_move.user_move.clone(),
+                notation: previous_move.zip(engine).and_then(|(previous_move, engine)| engine.move_notation(&previous_move.public_state, &EngineAction::from_game_move_record(game_move))),
+                state: game_move.public_state.clone(),
+                created_at: game_move.created_at.timestamp_millis(),
+                elapsed_ms: previous_move.map(|previous_move| game_move.created_at.timestamp_millis() - previous_move.created_at.timestamp_millis()).unwrap_or_default(),
+                evaluation: game_move.evaluation,
+            }
+        })
+        .collect();
+
+    let players: Vec<ReplayPlayerResponseModel> = players.into_iter().map(|player| ReplayPlayerResponseModel {
+        user_id: player.user_id.to_string(),
+        username: player.username,
+        player_type: player.player_type,
+        seat: player.seat,
+        place: player.place,
+        score_change: player.score_change,
+        is_winner: player.is_winner,
+    }).collect();
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "game_id": payload.game_id,
+        "session_id": payload.session_id,
+        "game_type": history.game_type,
+        "game_name": history.game_name,
+        "game_result": history.result,
+        "termination_reason": history.termination_reason,
+        "move_count": history.move_count,
+        "ply_count": game_moves.len(),
+        "pgn": history.pgn,
+        "started_at": history.started_at.and_utc().timestamp_millis(),
+        "ended_at": history.ended_at.and_utc().timestamp_millis(),
+        "players": players,
+        "plies": plies,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn request_rematch(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<RematchPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, _, user_turn_collection) = lobby_collections(&mongo_db);
+
+    let (turn_mapping, username) = get_rematch_player(&user_turn_collection, &payload.game_id, &claims.user_id).await?;
+
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+    let game = match game_collection.find_one_and_update(
+        doc! { "id": payload.game_id.clone(), "description": GAME_OVER_STATUS },
+        doc! { "$addToSet": { "rematch_requests": claims.user_id.clone() }, "$set": { "updated_at": DateTime::now() } },
+        options
+    ).await {
+        Ok(Some(game)) => game,
+        Ok(None) => return Err(Error::RematchNotAllowed),
+        Err(_) => return Err(Error::ErrorWhileFetchingGame),
+    };
+
+    let rematch_payload = GameRematchPayload {
+        game_id: payload.game_id.clone(),
+        user_id: claims.user_id.clone(),
+        username,
+        requested_by: game.rematch_requests.clone(),
+    };
+    send_lobby_events(&state, vec![game_event(GAME_REMATCH_REQUEST_EVENT, &rematch_payload)]).await;
+
+    // The last player to ask accepts the rematch, players who left are not waited for
+    if !turn_mapping.seated_turns().iter().all(|turn| game.rematch_requests.contains(&turn.user_id)) {
+        let body = Json(json!({
+            "result": {
+                "success": true
+            },
+            "rematch_started": false,
+        }));
+
+        return Ok(body)
+    }
+
+    let session_id = begin_rematch(&state, &mongo_db, game, turn_mapping).await?;
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "rematch_started": true,
+        "session_id": session_id,
+	}));
+
+    Ok(body)
+}
+
+
+pub async fn decline_rematch(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<RematchPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, _, user_turn_collection) = lobby_collections(&mongo_db);
+
+    let (_, username) = get_rematch_player(&user_turn_collection, &payload.game_id, &claims.user_id).await?;
+
+    let decline_res = game_collection.update_one(
+        doc! { "id": payload.game_id.clone(), "description": GAME_OVER_STATUS },
+        doc! { "$set": { "rematch_requests": [], "updated_at": DateTime::now() } },
+        None
+    ).await;
+    match decline_res {
+        Ok(update_result) if update_result.matched_count == 1 => {},
+        Ok(_) => return Err(Error::RematchNotAllowed),
+        Err(_) => return Err(Error::ErrorWhileChangingGameStatus),
+    }
+
+    let rematch_payload = GameRematchPayload {
+        game_id: payload.game_id.clone(),
+        user_id: claims.user_id.clone(),
+        username,
+        requested_by: vec![],
+    };
+    send_lobby_events(&state, vec![game_event(GAME_REMATCH_DECLINED_EVENT, &rematch_payload)]).await;
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		}
+	}));
+
+    Ok(body)
+}
+
+
+// Starts a staked rematch once every player has a bet on the new session
+pub async fn start_rematch(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+	Json(payload): Json<RematchPayload>,
+) -> APIResult<Json<Value>> {
+    if payload.game_id.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, _, user_turn_collection) = lobby_collections(&mongo_db);
+
+    let (turn_mapping, _) = get_rematch_player(&user_turn_collection, &payload.game_id, &claims.user_id).await?;
+
+    let game = get_game(&game_collection, &payload.game_id).await?;
+    let session_id = match (game.description.as_str(), game.session_id.clone()) {
+        (GAME_AWAITING_BETS_STATUS, Some(session_id)) => session_id,
+        _ => return Err(Error::RematchNotAllowed),
+    };
+
+    let game_id = Uuid::from_str(&payload.game_id).map_err(|_| Error::MissingParamsError)?;
+    let player_bets = GameBets::find_by_game_id_and_session_id_with_progress(game_id, session_id.clone(), GameBetStatus::InProgress.to_string())
+        .all(&state.conn)
+        .await
+        .map_err(|_| Error::ErrorWhileFetchingGame)?;
+
+    let every_player_bet = turn_mapping.turn_mappings.iter().all(|turn| {
+        player_bets.iter().any(|bet| bet.is_player && bet.user_id.to_string() == turn.user_id)
+    });
+    if !every_player_bet {
+        return Err(Error::RematchBetsMissing)
+    }
+
+    let status_res = game_collection.update_one(
+        doc! { "id": payload.game_id.clone(), "description": GAME_AWAITING_BETS_STATUS, "session_id": session_id.clone() },
+        doc! { "$set": { "description": GAME_IN_PROGRESS_STATUS, "updated_at": DateTime::now() } },
+        None
+    ).await;
+    match status_res {
+        Ok(update_result) if update_result.modified_count == 1 => {},
+        _ => return Err(Error::GameCannotBeStarted),
+    }
+
+    let game_start_payload = GameStartPayload {
+        admin_id: game.host_id.clone().unwrap_or_default(),
+        game_name: game.name.clone(),
+        game_id: payload.game_id.clone(),
+        session_id: session_id.clone(),
+    };
+
+    if send_game_events(&state.producer, vec![game_event(GAME_START_EVENT, &game_start_payload)]).await.is_err() {
+        let _ = game_collection.update_one(doc! { "id": payload.game_id.clone() }, doc! { "$set": { "description": GAME_AWAITING_BETS_STATUS } }, None).await;
+        return Err(Error::ErrorWhileChangingGameStatus)
+    }
+
+    let body = Json(json!({
+		"result": {
+			"success": true
+		},
+        "session_id": session_id,
+	}));
+
+    Ok(body)
+}
+
+
+fn lobby_collections(mongo_db: &Database) -> (Collection<Game>, Collection<UserGameRelation>, Collection<UserTurnMapping>) {
+    (
+        mongo_db.collection::<Game>(MONGO_GAMES_MODEL),
+        mongo_db.collection::<UserGameRelation>(MONGO_USERS_MODEL),
+        mongo_db.collection::<UserTurnMapping>(MONGO_USER_TURNS_MODEL),
+    )
+}
+
+async fn get_game(game_collection: &Collection<Game>, game_id: &str) -> APIResult<Game> {
+    match game_collection.find_one(doc! { "id": game_id }, None).await {
+        Ok(Some(game)) => Ok(game),
+        Ok(None) => Err(Error::GameNotFound),
+        Err(_) => Err(Error::ErrorWhileFetchingGame),
+    }
+}
+
+async fn get_turn_mapping(user_turn_collection: &Collection<UserTurnMapping>, game_id: &str) -> APIResult<UserTurnMapping> {
+    match user_turn_collection.find_one(doc! { "game_id": game_id }, None).await {
+        Ok(Some(turn_mapping)) => Ok(turn_mapping),
+        Ok(None) => Err(Error::NoMappingFound),
+        Err(_) => Err(Error::ErrorWhileFetchingUserTurns),
+    }
+}
+
+async fn get_lobby_relations(user_collection: &Collection<UserGameRelation>, game_id: &str) -> APIResult<Vec<UserGameRelation>> {
+    let cursor = user_collection.find(doc! { "game_id": game_id }, None).await.map_err(|_| Error::ErrorWhileRetrievingLobbyUsers)?;
+    cursor.try_collect().await.map_err(|_| Error::ErrorWhileRetrievingLobbyUsers)
+}
+
+// Players in seat order, spectators are not part of the lobby relations
+fn lobby_players(relations: Vec<UserGameRelation>, turn_mapping: &UserTurnMapping) -> Vec<LobbyPlayerResponseModel> {
+    let mut players: Vec<LobbyPlayerResponseModel> = relations.into_iter().map(|relation| {
+        let user_id = relation.user_id.to_string();
+        let seat = turn_mapping.turn_mappings.iter().find(|turn| turn.user_id == user_id).map(|turn| turn.count_id);
+
+        LobbyPlayerResponseModel {
+            user_id,
+            username: relation.username,
+            player_type: relation.player_type,
+            player_status: relation.player_status,
+            seat,
+        }
+    }).collect();
+    players.sort_by_key(|player| player.seat.unwrap_or(i64::MAX));
+
+    players
+}
+
+fn game_details(game_id: &str, game: &Game) -> Value {
+    json!({
+        "id": game_id,
+        "name": game.name,
+        "game_type": game.game_type,
+        "status": game.description,
+        "host_id": game.host_id,
+        "is_staked": game.is_staked,
+        "session_id": game.session_id,
+        "is_private": game.join_password.is_some(),
+        "is_rated": !game.is_casual,
+        "time_control": game.time_control,
+        "rating_category": rating_category(&game.game_type, game.time_control.as_deref()),
+    })
+}
+
+// The turn timer cerotis sets for timed games holds the deadline of the current turn
+async fn get_game_clock(redis_conn: &mut MultiplexedConnection, game_id: &str) -> Option<GameClockResponseModel> {
+    let deadline: Option<i64> = redis_conn.get(GAME_TURN_TIMER.to_owned() + game_id).await.ok().flatten();
+
+    deadline.map(|deadline| GameClockResponseModel {
+        deadline,
+        remaining_ms: (deadline - Utc::now().timestamp_millis()).max(0),
+    })
+}
+
+// Stake window of the current session, kept by nebula in the game table
+async fn get_game_stake(state: &AppDBState, game_id: &str, game: &Game) -> Option<GameStakeResponseModel> {
+    let session_id = game.session_id.clone()?;
+    let game_uuid = Uuid::from_str(game_id).ok()?;
+
+    let game_record = GameRecords::find_by_game_id_and_session_id(game_uuid, session_id.clone()).one(&state.conn).await.ok().flatten()?;
+
+    Some(GameStakeResponseModel {
+        session_id,
+        is_stake_allowed: game_record.is_stake_allowed,
+    })
+}
+
+// A few tries in case the code is already taken by another lobby
+async fn reserve_join_code(state: &AppDBState, game_id: &str) -> APIResult<String> {
+    let mut redis_conn = state.context.get_redis_db_client();
+
+    for _ in 0..5 {
+        let join_code = generate_join_code(LOBBY_JOIN_CODE_LENGTH);
+        let options = SetOptions::default().conditional_set(ExistenceCheck::NX).with_expiration(SetExpiry::EX(LOBBY_JOIN_CODE_EXPIRY_SECS));
+
+        let reserved: Option<String> = redis_conn.set_options(LOBBY_JOIN_CODE_KEY.to_owned() + &join_code, game_id, options).await.map_err(|_| Error::FailedToSetRedisKeyWithOptions)?;
+        if reserved.is_some() {
+            return Ok(join_code)
+        }
+    }
+
+    Err(Error::CreateLobbyError)
+}
+
+async fn release_join_code(redis_conn: &mut MultiplexedConnection, game: &Game) {
+    if let Some(join_code) = &game.join_code {
+        let _: RedisResult<()> = redis_conn.del(LOBBY_JOIN_CODE_KEY.to_owned() + join_code).await;
+    }
+}
+
+// Only failed lookups count, so players sharing a lobby are not slowed down
+async fn join_code_attempts_exceeded(redis_conn: &mut MultiplexedConnection, user_id: &str) -> bool {
+    let attempts: Option<i64> = redis_conn.get(JOIN_CODE_ATTEMPTS_KEY.to_owned() + user_id).await.ok().flatten();
+    attempts.unwrap_or_default() >= JOIN_CODE_MAX_FAILED_ATTEMPTS
+}
+
+async fn record_failed_join_code_attempt(redis_conn: &mut MultiplexedConnection, user_id: &str) {
+    let key = JOIN_CODE_ATTEMPTS_KEY.to_owned() + user_id;
+    let attempts: RedisResult<i64> = redis_conn.incr(&key, 1).await;
+
+    if let Ok(1) = attempts {
+        let _: RedisResult<()> = redis_conn.expire(&key, JOIN_CODE_ATTEMPTS_WINDOW_SECS).await;
+    }
+}
+
+fn hash_lobby_password(password: &str) -> String {
+    let salt = generate_random_string(16);
+    argon2::hash_encoded(password.as_bytes(), salt.as_bytes(), &argon2::Config::default()).unwrap()
+}
+
+fn lobby_password_matches(game: &Game, password: Option<&str>) -> bool {
+    match &game.join_password {
+        Some(join_password) => argon2::verify_encoded(join_password, password.unwrap_or_default().as_bytes()).unwrap_or(false),
+        None => true,
+    }
+}
+
+// The claims only carry the user id, the username shown in the lobby comes from the users table
+async fn get_username(state: &AppDBState, user_id: &str) -> APIResult<String> {
+    let user_id = Uuid::from_str(user_id).map_err(|_| Error::InvalidUserToken)?;
+
+    match Users::find_by_id(user_id).one(&state.conn).await {
+        Ok(Some(user)) => Ok(user.username),
+        _ => Err(Error::NoUserEntityFoundForToken),
+    }
+}
+
+// Takes a free seat in the lobby for the player, or returns the seat they already have
+async fn add_lobby_player(state: &AppDBState, game: &Game, player_id: &str, username: &str) -> APIResult<i64> {
+    let user_id = Uuid::from_str(player_id).map_err(|_| Error::MissingParamsError)?;
+    let game_id = game.id.to_string();
+    let mongo_db = state.context.get_mongo_db_client().database(MONGO_DB_NAME);
+    let (game_collection, user_collection, user_turn_collection) = lobby_collections(&mongo_db);
+
+    if game.description != GAME_LOBBY_STATUS {
+        return Err(Error::JoinLobbyError)
+    }
+
+    let (_, max_players) = state.engine_registry.player_limits(&game.game_type).map_err(|_| Error::JoinLobbyError)?;
+    let mut turn_mapping = get_turn_mapping(&user_turn_collection, &game_id).await?;
+
+    if let Some(turn) = turn_mapping.turn_mappings.iter().find(|turn| turn.user_id == player_id) {
+        return Ok(turn.count_id)
+    }
+
+    let seat = turn_mapping.assign_seat(player_id, username, max_players).ok_or(Error::LobbyIsFull)?;
+    let new_turn = turn_mapping.turn_mappings.last().cloned().unwrap();
+
+    // The seat is only taken if nobody else got it (or filled the lobby) since the mapping was read
+    let mut filter = doc! {
+        "game_id": game_id.clone(),
+        "turn_mappings.user_id": { "$ne": player_id.to_string() },
+        "turn_mappings.count_id": { "$ne": seat },
+    };
+    filter.insert(format!("turn_mappings.{}", max_players - 1), doc! { "$exists": false });
+
+    let push_res = user_turn_collection.update_one(filter, doc! { "$push": { "turn_mappings": bson::to_bson(&new_turn).unwrap() } }, None).await;
+    match push_res {
+        Ok(update_result) if update_result.matched_count == 1 => {},
+        Ok(_) => return Err(Error::LobbyIsFull),
+        Err(_) => return Err(Error::JoinLobbyError),
+    }
+
+    let new_relation = UserGameRelation {
+        user_id,
+        username: username.to_string(),
+        game_id: game_id.clone(),
+        player_type: "".to_string(),
+        player_status: PLAYER_NOT_READY.to_string(),
+    };
+
+    let relation_res = user_collection.clone_with_type::<Document>().insert_one(with_string_id(&new_relation, "user_id", &user_id), None).await;
+    if relation_res.is_err() {
+        return Err(Error::JoinLobbyError)
+    }
+
+    let _ = game_collection.update_one(
+        doc! { "id": game_id.clone() },
+        doc! { "$inc": { "user_count": 1 }, "$set": { "updated_at": DateTime::now() } },
+        None
+    ).await;
+
+    let joined_payload = JoinedRoomPayload {
+        user_id: player_id.to_string(),
+        username: username.to_string(),
+        game_id: game_id.clone(),
+    };
+    send_lobby_events(state, vec![game_event(USER_JOINED_ROOM, &joined_payload)]).await;
+
+    Ok(seat)
+}
+
+fn valid_spectator_settings(spectator_limit: Option<i64>, spectator_delay_secs: Option<i64>) -> bool {
+    spectator_limit.unwrap_or_default() >= 0 && spectator_delay_secs.unwrap_or_default() >= 0
+}
+
+// Latest move recorded before the broadcast delay
+async fn get_delayed_move(mongo_db: &Database, game_id: &str, delay_secs: u64) -> Option<GameMoveRecord> {
+    let visible_until = DateTime::from_millis(DateTime::now().timestamp_millis() - delay_secs as i64 * 1000);
+    let options = FindOneOptions::builder().sort(doc! { "state_index": -1 }).build();
+
+    mongo_db.collection::<GameMoveRecord>(MONGO_GAME_MOVES_MODEL)
+        .find_one(doc! { "game_id": game_id, "created_at": { "$lte": visible_until } }, options)
+        .await
+        .ok()
+        .flatten()
+}
+
+// Turn mapping of the game and the username of the player, only players who still have a seat have a say in the rematch
+async fn get_rematch_player(user_turn_collection: &Collection<UserTurnMapping>, game_id: &str, user_id: &str) -> APIResult<(UserTurnMapping, String)> {
+    let turn_mapping = get_turn_mapping(user_turn_collection, game_id).await?;
+    let username = turn_mapping.seated_turns().iter()
+        .find(|turn| turn.user_id == user_id)
+        .map(|turn| turn.username.clone())
+        .ok_or(Error::RematchNotAllowed)?;
+
+    Ok((turn_mapping, username))
+}
+
+// Deals a new session on the same game with the seats rotated. Staked games wait for new bets before starting
+async fn begin_rematch(state: &AppDBState, mongo_db: &Database, game: Game, mut turn_mapping: UserTurnMapping) -> APIResult<String> {
+    let (game_collection, user_collection, user_turn_collection) = lobby_collections(mongo_db);
+    let game_id = turn_mapping.game_id.clone();
+    let engine = state.engine_registry.get(&game.game_type).map_err(|_| Error::GameCannotBeStarted)?;
+    let session_id = Uuid::new_v4().to_string();
+
+    // Players who left are not dealt into the new session
+    turn_mapping.turn_mappings.retain(|turn| turn.status != PLAYER_DISCONNECTED);
+    if turn_mapping.turn_mappings.len() < engine.min_players() {
+        return Err(Error::RematchNotAllowed)
+    }
+
+    // Moves are rejected while awaiting bets, so the game is parked there until the new state is ready.
+    // state_index moves on so the first move of the rematch does not reuse the last index of the previous game
+    let status_res = game_collection.update_one(
+        doc! { "id": game_id.clone(), "description": GAME_OVER_STATUS },
+        doc! {
+            "$set": { "description": GAME_AWAITING_BETS_STATUS, "session_id": session_id.clone(), "rematch_requests": [], "game_state": null, "updated_at": DateTime::now() },
+            "$inc": { "state_index": 1 }
+        },
+        None
+    ).await;
+    match status_res {
+        Ok(update_result) if update_result.modified_count == 1 => {},
+        _ => return Err(Error::RematchNotAllowed),
+    }
+
+    turn_mapping.rotate_seats();
+    let turn_mappings = bson::to_bson(&turn_mapping.turn_mappings).map_err(|_| Error::ErrorWhileFetchingUserTurns)?;
+    let _ = user_turn_collection.update_one(doc! { "game_id": game_id.clone() }, doc! { "$set": { "turn_mappings": turn_mappings } }, None).await;
+
+    let player_types = engine.player_types();
+    for (index, turn) in turn_mapping.active_turns().iter().enumerate() {
+        let player_type = player_types.get(index).copied().unwrap_or("player");
+        let _ = user_collection.update_one(
+            doc! { "game_id": game_id.clone(), "user_id": turn.user_id.clone() },
+            doc! { "$set": { "player_type": player_type } },
+            None
+        ).await;
+    }
+
+    let mut redis_conn = state.context.get_redis_db_client();
+    let _: RedisResult<()> = redis_conn.del(engine.state_key_prefix().to_owned() + &game_id).await;
+    let _: RedisResult<()> = redis_conn.del(GAME_TURN_TIMER.to_owned() + &game_id).await;
+    let _: RedisResult<()> = redis_conn.del(GAME_TURN_TIMER_DATA.to_owned() + &game_id).await;
+
+    let new_game_record = CreateNewGamePayloadEvent {
+        game_id: game_id.clone(),
+        session_id: session_id.clone(),
+    };
+    let mut kafka_events = vec![
+        KafkaGeneralEvent {
+            topic: CREATE_NEW_GAME_RECORD.to_string(),
+            payload: serde_json::to_string(&new_game_record).unwrap(),
+            key: CREATE_NEW_GAME_RECORD.to_string(),
+        },
+    ];
+
+    if !game.is_staked {
+        let _ = game_collection.update_one(doc! { "id": game_id.clone() }, doc! { "$set": { "description": GAME_IN_PROGRESS_STATUS } }, None).await;
+
+        let game_start_payload = GameStartPayload {
+            admin_id: game.host_id.clone().unwrap_or_default(),
+            game_name: game.name.clone(),
+            game_id: game_id.clone(),
+            session_id: session_id.clone(),
+        };
+        kafka_events.push(game_event(GAME_START_EVENT, &game_start_payload));
+    }
+
+    if send_game_events(&state.producer, kafka_events).await.is_err() {
+        // Back to the finished game so the players can ask again
+        let _ = game_collection.update_one(doc! { "id": game_id.clone() }, doc! { "$set": { "description": GAME_OVER_STATUS } }, None).await;
+        return Err(Error::ErrorWhileChangingGameStatus)
+    }
+
+    Ok(session_id)
+}
+
+// Ids are stored as strings like the rest of the game documents so the lookups by string keep working
+fn with_string_id<T: Serialize>(model: &T, field: &str, id: &Uuid) -> Document {
+    let mut document = bson::to_document(model).unwrap();
+    document.insert(field, id.to_string());
+    document
+}
+
+fn game_event<T: Serialize>(event_name: &str, payload: &T) -> KafkaGeneralEvent {
+    KafkaGeneralEvent {
+        topic: "game".to_string(),
+        payload: serde_json::to_string(payload).unwrap(),
+        key: event_name.to_string(),
+    }
+}
+
+fn deletion_event(user_id: &str, game_id: &str) -> KafkaGeneralEvent {
+    let deletion_payload = UserGameDeletetionEvent {
+        user_id: user_id.to_string(),
+        game_id: game_id.to_string(),
+    };
+
+    KafkaGeneralEvent {
+        topic: USER_GAME_DELETION.to_string(),
+        payload: serde_json::to_string(&deletion_payload).unwrap(),
+        key: USER_GAME_DELETION.to_string(),
+    }
+}
+
+// Room notifications are best effort, the lobby change is already saved
+async fn send_lobby_events(state: &AppDBState, kafka_events: Vec<KafkaGeneralEvent>) {
+    if let Err(e) = send_game_events(&state.producer, kafka_events).await {
+        warn!("Error while sending lobby events: {:?}", e);
+    }
+}
@@ -1,3 +1,4 @@
 pub mod user_auth_controller;
 pub mod user_logic_controller;
+pub mod game_logic_controller;
 pub mod payloads;
\ No newline at end of file
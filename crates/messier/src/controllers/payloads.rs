@@ -5,24 +5,43 @@ use uuid::Uuid;
 // Game Payloads
 #[derive(Clone, Debug, Deserialize)]
 pub struct CreateLobbyPayload {
-    pub user_id: String,
-    pub username: String,
     pub game_type: String,
     pub game_name: String,
+    #[serde(default)]
+    pub spectator_limit: Option<i64>,
+    #[serde(default)]
+    pub spectator_delay_secs: Option<i64>,
+    // A password always comes with a join code
+    #[serde(default)]
+    pub with_join_code: bool,
+    #[serde(default)]
+    pub password: Option<String>,
+    // Casual games leave the ratings untouched
+    #[serde(default)]
+    pub is_casual: bool,
+    // "base+increment" in seconds, leave empty for games without a clock
+    #[serde(default)]
+    pub time_control: Option<String>,
 }
 
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct JoinLobbyPayload {
-    pub user_id: String,
-    pub username: String,
     pub game_id: String,
     pub game_name: String,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct JoinLobbyByCodePayload {
+    pub join_code: String,
+    #[serde(default)]
+    pub password: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct LeaveLobbyPayload {
-    pub user_id: String,
     pub game_id: String,
     pub game_name: String,
 }
@@ -33,7 +52,6 @@ pub struct LeaveLobbyPayload {
 #[derive(Clone, Debug, Deserialize)]
 pub struct UpdatePlayerStatusPayload {
     pub game_id: String,
-    pub user_id: String,
     pub game_name: String,
     pub status: String,
 }
@@ -87,6 +105,120 @@ pub struct GetLobbyPlayersPayload {
 
 
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct SendGameInvitePayload {
+    pub friend_id: String,
+    pub game_id: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RespondToGameInvitePayload {
+    pub invite_id: String,
+    pub accept: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct UpdateSpectatorSettingsPayload {
+    pub game_id: String,
+    pub spectator_limit: Option<i64>,
+    pub spectator_delay_secs: Option<i64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GetGameSpectatorsPayload {
+    pub game_id: String,
+}
+
+// channel defaults to the players chat
+#[derive(Clone, Debug, Deserialize)]
+pub struct GetGameMessagesPayload {
+    pub game_id: String,
+    #[serde(default)]
+    pub channel: String,
+}
+
+// ply 0 is the initial position, ply n the position after n moves. Without it every ply is returned
+#[derive(Clone, Debug, Deserialize)]
+pub struct GetGameReplayPayload {
+    pub game_id: String,
+    pub session_id: String,
+    #[serde(default)]
+    pub ply: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RematchPayload {
+    pub game_id: String,
+}
+
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LobbyPlayerResponseModel {
+    pub user_id: String,
+    pub username: String,
+    pub player_type: String,
+    pub player_status: String,
+    pub seat: Option<i64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameSpectatorResponseModel {
+    pub user_id: String,
+    pub username: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameMessageResponseModel {
+    pub message_id: String,
+    pub user_id: String,
+    pub username: String,
+    pub message: String,
+    pub channel: String,
+    pub created_at: i64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReplayPlayerResponseModel {
+    pub user_id: String,
+    pub username: String,
+    pub player_type: String,
+    pub seat: i64,
+    pub place: Option<i32>,
+    pub score_change: i32,
+    pub is_winner: bool,
+}
+
+// Position reached at a ply and the move that led to it (empty for ply 0)
+#[derive(Clone, Debug, Serialize)]
+pub struct ReplayPlyResponseModel {
+    pub ply: usize,
+    pub user_id: String,
+    pub move_type: String,
+    pub user_move: String,
+    // SAN for chess, None for games without a standard notation
+    pub notation: Option<String>,
+    pub state: String,
+    pub created_at: i64,
+    // Time the player took for the move
+    pub elapsed_ms: i64,
+    pub evaluation: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameClockResponseModel {
+    // Time (ms since epoch) at which the current turn runs out
+    pub deadline: i64,
+    pub remaining_ms: i64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameStakeResponseModel {
+    pub session_id: String,
+    pub is_stake_allowed: bool,
+}
+
+
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct BrodcastGamePayload {
     pub game_id: String,
@@ -123,6 +255,9 @@ pub struct RegistrationPayload {
     pub email: String,
 	pub username: String,
 	pub password: String,
+	// ISO 3166-1 alpha-2 code, optional
+	#[serde(default)]
+	pub country: Option<String>,
 }
 
 #[derive( Clone, Debug, Deserialize)]
@@ -146,7 +281,6 @@ pub struct ResponseUser {
    pub first_name: String,
    pub last_name: String,
    pub email: String,
-   pub score: i32,
    pub verified: bool,
 }
 
@@ -212,6 +346,94 @@ pub struct GetUsersOngoingGamesPayload {
     pub user_id: String,
 }
 
+// max_points downsamples long histories, every change is returned without it
+#[derive(Clone, Debug, Deserialize)]
+pub struct GetUserRatingHistoryPayload {
+    pub user_id: String,
+    pub game_type: String,
+    // Defaults to the category of games without a clock
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub max_points: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GetUserRatingsPayload {
+    pub user_id: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GetLeaderboardPayload {
+    pub game_type: String,
+    // Defaults to the category of games without a clock
+    #[serde(default)]
+    pub category: Option<String>,
+    // global, friends, country, weekly or monthly
+    pub scope: String,
+    // Country leaderboard to show, defaults to the country of the caller
+    #[serde(default)]
+    pub country: Option<String>,
+    // Shows the weekly or monthly leaderboard of the period before the current one
+    #[serde(default)]
+    pub previous: bool,
+    #[serde(default)]
+    pub page: u64,
+    #[serde(default)]
+    pub page_size: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GetLeaderboardRankPayload {
+    pub game_type: String,
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ChangeUserCountryPayload {
+    // ISO 3166-1 alpha-2 code, empty to leave the country leaderboards
+    #[serde(default)]
+    pub country: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LeaderboardEntryResponseModel {
+    pub rank: u64,
+    pub user_id: String,
+    pub username: String,
+    // Rating, or rating points gained on weekly and monthly leaderboards
+    pub score: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LeaderboardRankResponseModel {
+    pub rank: u64,
+    pub score: f64,
+    pub total: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct UserRatingResponseModel {
+    pub game_type: String,
+    pub category: String,
+    pub rating: f64,
+    pub rating_deviation: f64,
+    pub games_played: i32,
+    pub is_provisional: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RatingHistoryPointResponseModel {
+    pub rating: f64,
+    pub deviation: f64,
+    pub rating_change: f64,
+    pub game_id: String,
+    pub session_id: String,
+    pub opponent_id: Option<String>,
+    pub created_at: i64,
+}
+
 
 
 #[derive( Clone, Debug, Deserialize , Serialize)]
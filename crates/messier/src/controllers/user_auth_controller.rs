@@ -16,6 +16,7 @@ use chrono::Utc;
 use lazy_regex::Regex;
 use redis::aio::MultiplexedConnection;
 use redis::{AsyncCommands, Commands, Connection, RedisResult, SetOptions};
+use orion::ratings::leaderboard::is_valid_country;
 use ton::models::users::{self , Entity as Users};
 use errors::Result;
 use sea_orm::ActiveModelTrait;
@@ -69,7 +70,6 @@ pub async fn verify_token(
             first_name: user_model.first_name,
             last_name: user_model.last_name,
             username: user_model.username,
-            score: user_model.score,
             verified: user_model.verified,
             email: user_model.email
         },
@@ -137,7 +137,6 @@ pub async fn login_user(
             first_name: user_found.first_name,
             last_name: user_found.last_name,
             username: user_found.username,
-            score: user_found.score,
             verified: user_found.verified,
             email: user_found.email
         },
@@ -187,10 +186,10 @@ pub async fn register_user(
         username: Set(payload.username),
         email: Set(payload.email),
         verified: Set(false),
-        score: Set(0),
         created_at: Set(Utc::now().naive_utc()),
         updated_at: Set(Utc::now().naive_utc()),
-        is_online: Set(false)
+        is_online: Set(false),
+        country: Set(payload.country.map(|country| country.to_uppercase()))
      };
 
      let _result = new_user.insert(&state.conn).await.unwrap();
@@ -208,7 +207,6 @@ pub async fn register_user(
             first_name: recieved_user.first_name,
             last_name: recieved_user.last_name,
             username: recieved_user.username,
-            score: recieved_user.score,
             verified: recieved_user.verified,
             email: recieved_user.email
         },
@@ -317,10 +315,10 @@ pub async fn verify_user(
         password: Set(user_recieved.password),
         created_at: Set(user_recieved.created_at),
         updated_at: Set(user_recieved.updated_at),
-        score: Set(user_recieved.score),
         email: Set(user_recieved.email),
         verified: Set(user_recieved.verified),
-        is_online: Set(false)
+        is_online: Set(false),
+        country: Set(user_recieved.country)
     };
 
     let rsp = user_active_model.save(&state.conn).await;
@@ -360,6 +358,10 @@ fn validate_user_payload(payload: &RegistrationPayload) -> bool {
         return false
     }
 
+    if payload.country.as_ref().is_some_and(|country| !is_valid_country(&country.to_uppercase())) {
+        return false
+    }
+
     return true
 }
 
@@ -11,10 +11,13 @@ use orion::constants::{MONGO_DB_NAME, MONGO_GAMES_MODEL, MONGO_USERS_MODEL};
 use orion::models::game_model::Game;
 use orion::{constants::FRIEND_REQUEST_EVENT, models::user_game_relation_model::UserGameRelation};
 use orion::events::kafka_event::UserFriendRequestKafkaEvent;
-use ton::models::{self, users, users_wallet_keys};
+use orion::ratings::{category::{is_valid_category, rating_category, PROVISIONAL_GAMES}, glicko2::Glicko2Rating, leaderboard::{country_leaderboard_key, is_valid_country, leaderboard_key, period_leaderboard_key, LeaderboardPeriod, LEADERBOARD_COUNTRY, LEADERBOARD_FRIENDS, LEADERBOARD_GLOBAL, LEADERBOARD_MONTHLY, LEADERBOARD_WEEKLY}};
+use ton::models::{self, rating_history::Entity as RatingHistory, user_ratings::Entity as UserRatings, users, users_wallet_keys};
 use crate::state::AppDBState;
+use crate::utils::jwt::Claims;
 use models::{users_friends_requests::{self , Entity as UsersFriendsRequests}, users_friends::{self, Entity as UsersFriends}, users::{Entity as Users}};
 use axum::extract::State;
+use axum::Extension;
 use axum::Json;
 use errors::Result as APIResult;
 use sea_orm::{ActiveModelTrait, Condition, DbBackend, IntoActiveModel, JoinType, QueryFilter, QuerySelect, RelationTrait, Statement, TryIntoModel};
@@ -24,8 +27,15 @@ use serde_json::json;
 use serde_json::Value;
 use uuid::Uuid;
 use sea_orm::ColumnTrait;
+use chrono::{DateTime, Utc};
+use redis::{aio::MultiplexedConnection, AsyncCommands, RedisResult};
+use tracing::warn;
 
-use super::payloads::{AcceptOrRejectRequestPayload, AddWalletAddressPayload, ChangeUserPasswordPayload, ChangeUserUsernamePayload, DeleteWalletAddressPayload, GetFriendsRequestPayload, GetOnlineFriendsPayload, GetOnlineFriendsResponseModel, GetUserWalletPayload, GetUsersOngoingGamesPayload, GetUsersOngoingGamesResponseModel, SendRequestPayload};
+use super::payloads::{AcceptOrRejectRequestPayload, AddWalletAddressPayload, ChangeUserCountryPayload, ChangeUserPasswordPayload, ChangeUserUsernamePayload, DeleteWalletAddressPayload, GetFriendsRequestPayload, GetLeaderboardPayload, GetLeaderboardRankPayload, GetOnlineFriendsPayload, GetOnlineFriendsResponseModel, GetUserRatingHistoryPayload, GetUserRatingsPayload, GetUserWalletPayload, GetUsersOngoingGamesPayload, GetUsersOngoingGamesResponseModel, LeaderboardEntryResponseModel, LeaderboardRankResponseModel, RatingHistoryPointResponseModel, SendRequestPayload, UserRatingResponseModel};
+
+
+const DEFAULT_LEADERBOARD_PAGE_SIZE: u64 = 50;
+const MAX_LEADERBOARD_PAGE_SIZE: u64 = 100;
 
 pub async fn send_request(
     state: State<AppDBState>,
@@ -379,7 +389,7 @@ pub async fn get_user_online_friends(
         Statement::from_sql_and_values(DbBackend::Postgres, 
             
             r#"SELECT "u2"."id", "u2"."first_name", "u2"."last_name", "u2"."email", "u2"."password", "u2"."username",
-            "u2"."verified", "u2"."score", "u2"."is_online", "u2"."created_at", "u2"."updated_at" FROM "users" "u1" JOIN "users_friends" "uf" ON "u1"."id" = "uf"."user_id" 
+            "u2"."verified", "u2"."is_online", "u2"."country", "u2"."created_at", "u2"."updated_at" FROM "users" "u1" JOIN "users_friends" "uf" ON "u1"."id" = "uf"."user_id" 
             JOIN "users" "u2" ON "uf"."friend_id" = "u2"."id" WHERE "u1"."id"=$1 AND "u2"."is_online"=$2"#
             , [Uuid::parse_str(&payload.user_id).unwrap().into() , true.into()])
     )
@@ -518,4 +528,362 @@ fn hash_password(password: &String) -> String {
     let config = Config::default();
     let hash = argon2::hash_encoded(password.as_bytes(), b"secretsalt", &config).unwrap();
     hash
-}
\ No newline at end of file
+}
+
+
+
+// Rating over time in one game type and category for the rating graph. Peak and lowest are taken from the full history,
+// not the downsampled points
+pub async fn get_user_rating_history(
+    State(state): State<AppDBState>,
+    Json(payload): Json<GetUserRatingHistoryPayload>,
+) -> APIResult<Json<Value>> {
+    let user_id = Uuid::from_str(&payload.user_id).map_err(|_| Error::MissingParamsError)?;
+    if payload.max_points == Some(0) {
+        return Err(Error::MissingParamsError)
+    }
+
+    let (game_type, category) = resolve_rating_category(&payload.game_type, payload.category.as_deref())?;
+
+    Users::find_by_id(user_id)
+        .one(&state.conn)
+        .await
+        .map_err(|_| Error::RatingHistoryFetchError)?
+        .ok_or(Error::EntityNotFound)?;
+
+    // Players who have not finished a rated game in the category yet have the default rating
+    let (current_rating, games_played) = match UserRatings::find_by_category(&user_id, &game_type, &category).one(&state.conn).await {
+        Ok(Some(user_rating)) => (Glicko2Rating { rating: user_rating.rating, deviation: user_rating.rating_deviation, volatility: user_rating.rating_volatility }, user_rating.games_played),
+        Ok(None) => (Glicko2Rating::default(), 0),
+        Err(_) => return Err(Error::RatingHistoryFetchError),
+    };
+
+    let rating_changes = RatingHistory::find_by_category(&user_id, &game_type, &category)
+        .all(&state.conn)
+        .await
+        .map_err(|_| Error::RatingHistoryFetchError)?;
+
+    let ratings = rating_changes.first().map(|rating_change| rating_change.rating_before).into_iter()
+        .chain(rating_changes.iter().map(|rating_change| rating_change.rating_after));
+    let peak_rating = ratings.clone().fold(current_rating.rating, f64::max);
+    let lowest_rating = ratings.fold(current_rating.rating, f64::min);
+    let games_rated = rating_changes.len();
+
+    let points: Vec<RatingHistoryPointResponseModel> = downsample(rating_changes, payload.max_points).into_iter().map(|rating_change| RatingHistoryPointResponseModel {
+        rating: rating_change.rating_after,
+        deviation: rating_change.deviation_after,
+        rating_change: rating_change.rating_after - rating_change.rating_before,
+        game_id: rating_change.game_id.to_string(),
+        session_id: rating_change.session_id,
+        opponent_id: rating_change.opponent_id.map(|opponent_id| opponent_id.to_string()),
+        created_at: rating_change.created_at.and_utc().timestamp_millis(),
+    }).collect();
+
+    let body = Json(json!({
+        "result": {
+            "success": true
+        },
+        "game_type": game_type,
+        "category": category,
+        "rating": current_rating.rating,
+        "rating_deviation": current_rating.deviation,
+        "is_provisional": games_played < PROVISIONAL_GAMES,
+        "peak_rating": peak_rating,
+        "lowest_rating": lowest_rating,
+        "games_rated": games_rated,
+        "points": points,
+    }));
+
+    Ok(body)
+}
+
+// Every rating of a player for the profile, most played first. Categories without a rated game are left out
+pub async fn get_user_ratings(
+    State(state): State<AppDBState>,
+    Json(payload): Json<GetUserRatingsPayload>,
+) -> APIResult<Json<Value>> {
+    let user_id = Uuid::from_str(&payload.user_id).map_err(|_| Error::MissingParamsError)?;
+
+    let mut user_ratings = UserRatings::find_by_user_id(&user_id)
+        .all(&state.conn)
+        .await
+        .map_err(|_| Error::UserRatingsFetchError)?;
+    user_ratings.sort_by_key(|user_rating| std::cmp::Reverse(user_rating.games_played));
+
+    let ratings: Vec<UserRatingResponseModel> = user_ratings.into_iter().map(|user_rating| UserRatingResponseModel {
+        is_provisional: user_rating.games_played < PROVISIONAL_GAMES,
+        game_type: user_rating.game_type,
+        category: user_rating.category,
+        rating: user_rating.rating,
+        rating_deviation: user_rating.rating_deviation,
+        games_played: user_rating.games_played,
+    }).collect();
+
+    let body = Json(json!({
+        "result": {
+            "success": true
+        },
+        "ratings": ratings,
+    }));
+
+    Ok(body)
+}
+
+// Splits the history into max_points equal buckets and keeps the last change of each, so the latest rating is always shown
+fn downsample<T>(points: Vec<T>, max_points: Option<usize>) -> Vec<T> {
+    let Some(max_points) = max_points.filter(|max_points| points.len() > *max_points) else {
+        return points
+    };
+
+    let point_count = points.len();
+    points.into_iter().enumerate()
+        .filter(|(index, _)| (index + 1) * max_points / point_count != index * max_points / point_count)
+        .map(|(_, point)| point)
+        .collect()
+}
+
+
+
+
+// One page of a leaderboard of a game type and category, with the rank of the caller on it
+pub async fn get_leaderboard(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<GetLeaderboardPayload>,
+) -> APIResult<Json<Value>> {
+    let user_id = Uuid::from_str(&claims.user_id).map_err(|_| Error::MissingParamsError)?;
+    let (game_type, category) = resolve_rating_category(&payload.game_type, payload.category.as_deref())?;
+    let page_size = payload.page_size.unwrap_or(DEFAULT_LEADERBOARD_PAGE_SIZE).clamp(1, MAX_LEADERBOARD_PAGE_SIZE);
+    let start = payload.page * page_size;
+    let mut redis_conn = state.context.get_redis_db_client();
+
+    let (members, total, my_rank) = if payload.scope == LEADERBOARD_FRIENDS {
+        let friends_leaderboard = get_friends_leaderboard(&state, &mut redis_conn, &user_id, &game_type, &category).await?;
+        let total = friends_leaderboard.len() as u64;
+        let my_rank = friends_leaderboard.iter().position(|(member, _)| *member == claims.user_id)
+            .map(|index| LeaderboardRankResponseModel { rank: index as u64 + 1, score: friends_leaderboard[index].1, total });
+        let members = friends_leaderboard.into_iter().skip(start as usize).take(page_size as usize).collect();
+        (members, total, my_rank)
+    } else {
+        let country = match payload.country.as_deref().filter(|country| !country.is_empty()) {
+            Some(country) => Some(country.to_uppercase()),
+            None if payload.scope == LEADERBOARD_COUNTRY => get_user_country(&state, &user_id).await?,
+            None => None,
+        };
+        let at = Utc::now();
+        let key = leaderboard_scope_key(&payload.scope, &game_type, &category, country.as_deref(), payload.previous, at)?;
+
+        let members: Vec<(String, f64)> = redis_conn.zrevrange_withscores(&key, start as isize, (start + page_size - 1) as isize).await.map_err(|_| Error::LeaderboardFetchError)?;
+        let total: u64 = redis_conn.zcard(&key).await.map_err(|_| Error::LeaderboardFetchError)?;
+        let my_rank = get_rank_on_leaderboard(&mut redis_conn, &key, &claims.user_id, total).await?;
+        (members, total, my_rank)
+    };
+
+    let entries = with_usernames(&state, members, start).await?;
+
+    let body = Json(json!({
+        "result": {
+            "success": true
+        },
+        "game_type": game_type,
+        "category": category,
+        "scope": payload.scope,
+        "page": payload.page,
+        "page_size": page_size,
+        "total": total,
+        "entries": entries,
+        "my_rank": my_rank,
+    }));
+
+    Ok(body)
+}
+
+// Rank of the caller on every leaderboard of a game type and category, null where they are not ranked
+pub async fn get_leaderboard_rank(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<GetLeaderboardRankPayload>,
+) -> APIResult<Json<Value>> {
+    let user_id = Uuid::from_str(&claims.user_id).map_err(|_| Error::MissingParamsError)?;
+    let (game_type, category) = resolve_rating_category(&payload.game_type, payload.category.as_deref())?;
+    let country = get_user_country(&state, &user_id).await?;
+    let mut redis_conn = state.context.get_redis_db_client();
+    let at = Utc::now();
+
+    let mut ranks = serde_json::Map::new();
+    for scope in [LEADERBOARD_GLOBAL, LEADERBOARD_COUNTRY, LEADERBOARD_WEEKLY, LEADERBOARD_MONTHLY] {
+        let rank = match leaderboard_scope_key(scope, &game_type, &category, country.as_deref(), false, at) {
+            Ok(key) => {
+                let total: u64 = redis_conn.zcard(&key).await.map_err(|_| Error::LeaderboardFetchError)?;
+                get_rank_on_leaderboard(&mut redis_conn, &key, &claims.user_id, total).await?
+            },
+            // Players without a country are on no country leaderboard
+            Err(_) => None,
+        };
+        ranks.insert(scope.to_string(), json!(rank));
+    }
+
+    let friends_leaderboard = get_friends_leaderboard(&state, &mut redis_conn, &user_id, &game_type, &category).await?;
+    let friends_rank = friends_leaderboard.iter().position(|(member, _)| *member == claims.user_id)
+        .map(|index| LeaderboardRankResponseModel { rank: index as u64 + 1, score: friends_leaderboard[index].1, total: friends_leaderboard.len() as u64 });
+    ranks.insert(LEADERBOARD_FRIENDS.to_string(), json!(friends_rank));
+
+    let body = Json(json!({
+        "result": {
+            "success": true
+        },
+        "game_type": game_type,
+        "category": category,
+        "country": country,
+        "ranks": ranks,
+    }));
+
+    Ok(body)
+}
+
+// Moves the caller to the country leaderboards of the new country right away, without waiting for the next rated game
+pub async fn change_user_country(
+    State(state): State<AppDBState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<ChangeUserCountryPayload>,
+) -> APIResult<Json<Value>> {
+    let user_id = Uuid::from_str(&claims.user_id).map_err(|_| Error::MissingParamsError)?;
+    let country = payload.country.filter(|country| !country.is_empty()).map(|country| country.to_uppercase());
+    if country.as_deref().is_some_and(|country| !is_valid_country(country)) {
+        return Err(Error::InvalidCountry)
+    }
+
+    let user = Users::find_by_id(user_id)
+        .one(&state.conn)
+        .await
+        .map_err(|_| Error::CountryChangeError)?
+        .ok_or(Error::EntityNotFound)?;
+    let previous_country = user.country.clone();
+
+    let mut user_model = user.into_active_model();
+    user_model.country = Set(country.clone());
+    user_model.update(&state.conn).await.map_err(|_| Error::CountryChangeError)?;
+
+    let user_ratings = UserRatings::find_by_user_id(&user_id)
+        .all(&state.conn)
+        .await
+        .map_err(|_| Error::CountryChangeError)?;
+
+    let mut pipe = redis::pipe();
+    for user_rating in user_ratings.iter().filter(|user_rating| user_rating.games_played >= PROVISIONAL_GAMES) {
+        if let Some(previous_country) = &previous_country {
+            pipe.zrem(country_leaderboard_key(&user_rating.game_type, &user_rating.category, previous_country), &claims.user_id).ignore();
+        }
+        if let Some(country) = &country {
+            pipe.zadd(country_leaderboard_key(&user_rating.game_type, &user_rating.category, country), &claims.user_id, user_rating.rating).ignore();
+        }
+    }
+
+    // The nightly rebuild moves the player if this fails
+    let leaderboard_res: RedisResult<()> = pipe.query_async(&mut state.context.get_redis_db_client()).await;
+    if let Err(e) = leaderboard_res {
+        warn!("Error while moving user_id={} to the leaderboards of country={:?}: {:?}" , claims.user_id , country , e);
+    }
+
+    let body = Json(json!({
+        "result": {
+            "success": true
+        },
+        "country": country,
+    }));
+
+    Ok(body)
+}
+
+// Lowercases the game type and checks the category, defaulting to the one of games without a clock
+fn resolve_rating_category(game_type: &str, category: Option<&str>) -> APIResult<(String, String)> {
+    let game_type = game_type.to_lowercase();
+    if game_type.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let category = category.map(str::to_string).unwrap_or_else(|| rating_category(&game_type, None).to_string());
+    if !is_valid_category(&game_type, &category) {
+        return Err(Error::InvalidRatingCategory)
+    }
+
+    Ok((game_type, category))
+}
+
+fn leaderboard_scope_key(scope: &str, game_type: &str, category: &str, country: Option<&str>, previous: bool, at: DateTime<Utc>) -> APIResult<String> {
+    match scope {
+        LEADERBOARD_GLOBAL => Ok(leaderboard_key(game_type, category)),
+        LEADERBOARD_COUNTRY => {
+            let country = country.filter(|country| is_valid_country(country)).ok_or(Error::InvalidCountry)?;
+            Ok(country_leaderboard_key(game_type, category, country))
+        },
+        scope => {
+            let period = LeaderboardPeriod::from_scope(scope).ok_or(Error::InvalidLeaderboardScope)?;
+            Ok(period_leaderboard_key(game_type, category, period, if previous { period.previous(at) } else { at }))
+        },
+    }
+}
+
+async fn get_rank_on_leaderboard(redis_conn: &mut MultiplexedConnection, key: &str, user_id: &str, total: u64) -> APIResult<Option<LeaderboardRankResponseModel>> {
+    let rank: Option<u64> = redis_conn.zrevrank(key, user_id).await.map_err(|_| Error::LeaderboardFetchError)?;
+    let score: Option<f64> = redis_conn.zscore(key, user_id).await.map_err(|_| Error::LeaderboardFetchError)?;
+
+    Ok(rank.zip(score).map(|(rank, score)| LeaderboardRankResponseModel { rank: rank + 1, score, total }))
+}
+
+// The caller and their friends by rating, built from the global leaderboard on every request
+async fn get_friends_leaderboard(state: &AppDBState, redis_conn: &mut MultiplexedConnection, user_id: &Uuid, game_type: &str, category: &str) -> APIResult<Vec<(String, f64)>> {
+    let mut member_ids: Vec<String> = UsersFriends::find()
+        .filter(users_friends::Column::UserId.eq(*user_id))
+        .all(&state.conn)
+        .await
+        .map_err(|_| Error::ErrorWhileFetchingUserFriends)?
+        .into_iter()
+        .map(|friend| friend.friend_id.to_string())
+        .collect();
+    member_ids.push(user_id.to_string());
+
+    let scores: Vec<Option<f64>> = redis::cmd("ZMSCORE")
+        .arg(leaderboard_key(game_type, category))
+        .arg(&member_ids)
+        .query_async(redis_conn)
+        .await
+        .map_err(|_| Error::LeaderboardFetchError)?;
+
+    let mut members: Vec<(String, f64)> = member_ids.into_iter().zip(scores)
+        .filter_map(|(member, score)| score.map(|score| (member, score)))
+        .collect();
+    members.sort_by(|(first_member, first_score), (second_member, second_score)| second_score.total_cmp(first_score).then_with(|| first_member.cmp(second_member)));
+
+    Ok(members)
+}
+
+async fn get_user_country(state: &AppDBState, user_id: &Uuid) -> APIResult<Option<String>> {
+    let user = Users::find_by_id(*user_id)
+        .one(&state.conn)
+        .await
+        .map_err(|_| Error::LeaderboardFetchError)?
+        .ok_or(Error::EntityNotFound)?;
+
+    Ok(user.country)
+}
+
+// Ranks follow the position on the leaderboard, start is the position of the first member
+async fn with_usernames(state: &AppDBState, members: Vec<(String, f64)>, start: u64) -> APIResult<Vec<LeaderboardEntryResponseModel>> {
+    let user_ids: Vec<Uuid> = members.iter().filter_map(|(member, _)| Uuid::from_str(member).ok()).collect();
+    let usernames: HashMap<String, String> = Users::find()
+        .filter(users::Column::Id.is_in(user_ids))
+        .all(&state.conn)
+        .await
+        .map_err(|_| Error::LeaderboardFetchError)?
+        .into_iter()
+        .map(|user| (user.id.to_string(), user.username))
+        .collect();
+
+    Ok(members.into_iter().enumerate().map(|(index, (user_id, score))| LeaderboardEntryResponseModel {
+        rank: start + index as u64 + 1,
+        username: usernames.get(&user_id).cloned().unwrap_or_default(),
+        user_id,
+        score,
+    }).collect())
+}
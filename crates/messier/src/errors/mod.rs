@@ -71,6 +71,28 @@ pub enum Error {
 	RedisGetKeyError,
 	LobbyFull,
 	GameInviteSendError,
+	GameInviteNotFound,
+	GameInviteExpired,
+	UserIsNotAFriend,
+	RematchNotAllowed,
+	RematchBetsMissing,
+	InvalidSpectatorSettings,
+	ChatChannelNotAllowed,
+	InvalidJoinCode,
+	InvalidLobbyPassword,
+	TooManyJoinCodeAttempts,
+	GameReplayNotFound,
+	GameReplayNotAllowed,
+	InvalidReplayPly,
+	RatingHistoryFetchError,
+	InvalidTimeControl,
+	InvalidRatingCategory,
+	UserRatingsFetchError,
+	InvalidLeaderboardScope,
+	InvalidCountry,
+	LeaderboardFetchError,
+	CountryChangeError,
+	NotGameHost,
 	WalletAddressSaveError,
 	ErrorWhileMakingRelation,
 	SpectateGameJoinError,
@@ -216,6 +238,35 @@ impl Error {
 
 			//Game Invite Error
 			Self::GameInviteSendError => (StatusCode::BAD_REQUEST, ClientError::GAME_INVITE_SEND_ERROR),
+			Self::GameInviteNotFound => (StatusCode::BAD_REQUEST, ClientError::GAME_INVITE_NOT_FOUND),
+			Self::GameInviteExpired => (StatusCode::BAD_REQUEST, ClientError::GAME_INVITE_EXPIRED),
+			Self::UserIsNotAFriend => (StatusCode::FORBIDDEN, ClientError::USER_IS_NOT_A_FRIEND),
+
+			//Rematch Error
+			Self::RematchNotAllowed => (StatusCode::BAD_REQUEST, ClientError::REMATCH_NOT_ALLOWED),
+			Self::RematchBetsMissing => (StatusCode::BAD_REQUEST, ClientError::REMATCH_BETS_MISSING),
+
+			Self::InvalidSpectatorSettings => (StatusCode::BAD_REQUEST, ClientError::INVALID_SPECTATOR_SETTINGS),
+
+			Self::ChatChannelNotAllowed => (StatusCode::FORBIDDEN, ClientError::CHAT_CHANNEL_NOT_ALLOWED),
+
+			Self::InvalidJoinCode => (StatusCode::BAD_REQUEST, ClientError::INVALID_JOIN_CODE),
+			Self::InvalidLobbyPassword => (StatusCode::FORBIDDEN, ClientError::INVALID_LOBBY_PASSWORD),
+			Self::TooManyJoinCodeAttempts => (StatusCode::TOO_MANY_REQUESTS, ClientError::TOO_MANY_JOIN_CODE_ATTEMPTS),
+
+			Self::GameReplayNotFound => (StatusCode::BAD_REQUEST, ClientError::GAME_REPLAY_NOT_FOUND),
+			Self::GameReplayNotAllowed => (StatusCode::FORBIDDEN, ClientError::GAME_REPLAY_NOT_ALLOWED),
+			Self::InvalidReplayPly => (StatusCode::BAD_REQUEST, ClientError::INVALID_REPLAY_PLY),
+
+			Self::RatingHistoryFetchError => (StatusCode::BAD_REQUEST, ClientError::RATING_HISTORY_FETCH_ERROR),
+			Self::InvalidTimeControl => (StatusCode::BAD_REQUEST, ClientError::INVALID_TIME_CONTROL),
+			Self::InvalidRatingCategory => (StatusCode::BAD_REQUEST, ClientError::INVALID_RATING_CATEGORY),
+			Self::UserRatingsFetchError => (StatusCode::BAD_REQUEST, ClientError::USER_RATINGS_FETCH_ERROR),
+			Self::InvalidLeaderboardScope => (StatusCode::BAD_REQUEST, ClientError::INVALID_LEADERBOARD_SCOPE),
+			Self::InvalidCountry => (StatusCode::BAD_REQUEST, ClientError::INVALID_COUNTRY),
+			Self::LeaderboardFetchError => (StatusCode::BAD_REQUEST, ClientError::LEADERBOARD_FETCH_ERROR),
+			Self::CountryChangeError => (StatusCode::BAD_REQUEST, ClientError::COUNTRY_CHANGE_ERROR),
+			Self::NotGameHost => (StatusCode::FORBIDDEN, ClientError::NOT_GAME_HOST),
 
 			// -- Auth.
 			Self::AuthFailNoAuthTokenCookie
@@ -288,6 +339,28 @@ pub enum ClientError {
 	USERNAME_CONTAINS_INVALID_CHARACTER,
 	SAME_PASSWORD_AS_PREVIOUS_ONE,
 	GAME_INVITE_SEND_ERROR,
+	GAME_INVITE_NOT_FOUND,
+	GAME_INVITE_EXPIRED,
+	USER_IS_NOT_A_FRIEND,
+	REMATCH_NOT_ALLOWED,
+	REMATCH_BETS_MISSING,
+	INVALID_SPECTATOR_SETTINGS,
+	CHAT_CHANNEL_NOT_ALLOWED,
+	INVALID_JOIN_CODE,
+	INVALID_LOBBY_PASSWORD,
+	TOO_MANY_JOIN_CODE_ATTEMPTS,
+	GAME_REPLAY_NOT_FOUND,
+	GAME_REPLAY_NOT_ALLOWED,
+	INVALID_REPLAY_PLY,
+	RATING_HISTORY_FETCH_ERROR,
+	INVALID_TIME_CONTROL,
+	INVALID_RATING_CATEGORY,
+	USER_RATINGS_FETCH_ERROR,
+	INVALID_LEADERBOARD_SCOPE,
+	INVALID_COUNTRY,
+	LEADERBOARD_FETCH_ERROR,
+	COUNTRY_CHANGE_ERROR,
+	NOT_GAME_HOST,
 	ERROR_WHILE_MAKING_RELATION,
 	ERROR_WHILE_RETRIEVING_PLAYERS_STATUS,
 	USERNAME_NOT_FOUND,
@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+use futures_util::future;
+use orion::events::kafka_event::KafkaGeneralEvent;
+use rdkafka::{error::KafkaError, producer::{FutureProducer, FutureRecord, Producer}, util::Timeout};
+
+
+// Lobby events are sent in one transaction, none of them is published if one fails
+pub async fn send_game_events(producer: &FutureProducer, kafka_events: Vec<KafkaGeneralEvent>) -> Result<(), KafkaError> {
+    if kafka_events.is_empty() {
+        return Ok(())
+    }
+
+    producer.begin_transaction()?;
+
+    let kafka_result = future::try_join_all(kafka_events.iter().map(|event| async move {
+        producer
+        .send(
+            FutureRecord::to(&event.topic)
+                    .payload(&event.payload)
+                    .key(&event.key),
+            Duration::from_secs(3),
+        )
+        .await
+    })).await;
+
+    if let Err(e) = kafka_result {
+        let _ = producer.abort_transaction(Timeout::from(Duration::from_secs(5)));
+        return Err(e.0)
+    }
+
+    producer.commit_transaction(Timeout::from(Duration::from_secs(5)))
+}
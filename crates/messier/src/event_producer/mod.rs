@@ -1 +1,2 @@
-pub mod user_events_producer;
\ No newline at end of file
+pub mod user_events_producer;
+pub mod game_events_producer;
\ No newline at end of file
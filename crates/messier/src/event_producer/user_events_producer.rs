@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use orion::{constants::{FRIEND_REQUEST_EVENT, GAME_INVITE_EVENT}, events::kafka_event::KafkaGeneralEvent};
+use orion::{constants::{FRIEND_REQUEST_EVENT, GAME_INVITE_EVENT, GAME_INVITE_RESPONSE_EVENT}, events::kafka_event::KafkaGeneralEvent};
 use rdkafka::{error::KafkaError, producer::{FutureProducer, FutureRecord, Producer}, util::Timeout};
 use futures_util::future;
 use crate::context::context::DynContext;
@@ -17,6 +17,12 @@ pub async fn send_event_for_user_topic(
         FRIEND_REQUEST_EVENT => {
             create_friend_request_event(context , payload).await
         },
+        GAME_INVITE_EVENT => {
+            create_game_invite_event(payload)
+        },
+        GAME_INVITE_RESPONSE_EVENT => {
+            create_game_invite_response_event(payload)
+        },
 
         _ => {vec![]}
 
@@ -68,4 +74,22 @@ pub async fn create_friend_request_event(context: &DynContext, payload: String)
     };
     let results_resp: Vec<KafkaGeneralEvent> = vec![kafka_general_event];
     return results_resp
-}
\ No newline at end of file
+}
+
+
+pub fn create_game_invite_event(payload: String) -> Vec<KafkaGeneralEvent> {
+    vec![KafkaGeneralEvent {
+        topic: "user".to_string(),
+        payload,
+        key: GAME_INVITE_EVENT.to_string(),
+    }]
+}
+
+
+pub fn create_game_invite_response_event(payload: String) -> Vec<KafkaGeneralEvent> {
+    vec![KafkaGeneralEvent {
+        topic: "user".to_string(),
+        payload,
+        key: GAME_INVITE_RESPONSE_EVENT.to_string(),
+    }]
+}
@@ -8,7 +8,8 @@ use tower::ServiceBuilder;
 use tower_cookies::CookieManagerLayer;
 use tower_http::cors::CorsLayer;
 use tracing::info;
-use crate::{conf::configuration, context::context::ContextImpl, state::AppDBState};
+use orion::engines::registry::GameEngineRegistry;
+use crate::{conf::configuration::{self, Configuration}, context::context::ContextImpl, state::AppDBState};
 
 
 pub mod errors;
@@ -32,6 +33,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>>  {
 
   logging_tracing::init(&config)?;
 
+  let engine_registry = Arc::new(build_engine_registry(&config));
+
     //Connect with database
     let connection = match Database::connect(config.postgres_url.url).await {
         Ok(connection) => connection,
@@ -53,16 +56,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>>  {
 
   
   let kafka_producer = kafka::init_producer::create_new_kafka_producer(&config.kafka).unwrap();
-  let state = AppDBState {conn: connection , from_email: config.email_config.from_email , smtp_key: config.email_config.smtp_key, context: context, producer: kafka_producer };
+  let state = AppDBState {conn: connection , from_email: config.email_config.from_email , smtp_key: config.email_config.smtp_key, context: context, producer: kafka_producer, engine_registry };
 
     let user_auth_routes = routes::user_auth_routes::create_user_routes() ;
     let user_logic_routes = routes::user_logic_routes::create_user_logic_routes();
-   // let game_routes = routes::game_logic_routes::create_game_routes();
+    let game_routes = routes::game_logic_routes::create_game_routes();
     let routes_all = Router::new()
                           .route( "/api/v1/health", get(health))
                             .nest( "/api/v1/auth", user_auth_routes)
                             .nest("/api/v1/user", user_logic_routes)
-                         //   .nest( "/api/v1/game", game_routes)
+                            .nest( "/api/v1/game", game_routes)
                             .layer(ServiceBuilder::new()
                                     .layer(CookieManagerLayer::new())
                                     .layer(CorsLayer::permissive()))
@@ -84,3 +87,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>>  {
 pub async fn health() -> impl IntoResponse {
   axum::Json(json!({ "Messier status" : "UP" }))
 }
+
+
+// Engines with the lobby sizes from the config file
+fn build_engine_registry(config: &Configuration) -> GameEngineRegistry {
+  let mut engine_registry = GameEngineRegistry::with_default_engines();
+
+  if let Some(lobby_config) = &config.lobby {
+    for (game_type, max_players) in &lobby_config.max_players {
+      if engine_registry.set_max_players(game_type, *max_players).is_err() {
+        tracing::warn!("Unknown game type {} in lobby config", game_type);
+      }
+    }
+  }
+
+  engine_registry
+}
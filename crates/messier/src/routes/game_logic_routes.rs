@@ -0,0 +1,32 @@
+use axum::{middleware, routing::{get, post, put}, Router};
+
+use crate::{controllers, state::AppDBState, utils};
+
+
+
+pub fn create_game_routes() -> Router<AppDBState> {
+    Router::new()
+    .route("/create_lobby", post(controllers::game_logic_controller::create_lobby))
+    .route("/join_lobby", post(controllers::game_logic_controller::join_lobby))
+    .route("/join_lobby_by_code", post(controllers::game_logic_controller::join_lobby_by_code))
+    .route("/leave_lobby", post(controllers::game_logic_controller::leave_lobby))
+    .route("/update_player_status", put(controllers::game_logic_controller::update_player_status))
+    .route("/start_game", post(controllers::game_logic_controller::start_game))
+    .route("/destroy_lobby", post(controllers::game_logic_controller::destroy_lobby))
+    .route("/get_lobby_players", get(controllers::game_logic_controller::get_lobby_players))
+    .route("/send_game_invite", post(controllers::game_logic_controller::send_game_invite))
+    .route("/respond_to_game_invite", put(controllers::game_logic_controller::respond_to_game_invite))
+    .route("/get_game_invites", get(controllers::game_logic_controller::get_game_invites))
+    .route("/get_game_current_state", get(controllers::game_logic_controller::get_game_current_state))
+    .route("/get_user_turn_mappings", get(controllers::game_logic_controller::get_user_turn_mappings))
+    .route("/get_game_details", get(controllers::game_logic_controller::get_game_details))
+    .route("/update_spectator_settings", put(controllers::game_logic_controller::update_spectator_settings))
+    .route("/get_game_spectators", get(controllers::game_logic_controller::get_game_spectators))
+    .route("/get_game_messages", get(controllers::game_logic_controller::get_game_messages))
+    .route("/get_game_replay", get(controllers::game_logic_controller::get_game_replay))
+    .route("/request_rematch", post(controllers::game_logic_controller::request_rematch))
+    .route("/decline_rematch", post(controllers::game_logic_controller::decline_rematch))
+    .route("/start_rematch", post(controllers::game_logic_controller::start_rematch))
+    .route_layer(middleware::from_fn(utils::middleware::guard))
+
+}
@@ -1,2 +1,3 @@
 pub mod user_auth_routes;
-pub mod user_logic_routes;
\ No newline at end of file
+pub mod user_logic_routes;
+pub mod game_logic_routes;
\ No newline at end of file
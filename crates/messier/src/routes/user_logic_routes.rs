@@ -16,6 +16,11 @@ pub fn create_user_logic_routes() -> Router<AppDBState> {
     .route("/change_user_password", put(controllers::user_logic_controller::change_user_password))
     .route("/change_user_username", put(controllers::user_logic_controller::change_user_username))
     .route("/get_ongoing_games_for_user", get(controllers::user_logic_controller::get_ongoing_games_for_user))
+    .route("/get_user_rating_history", get(controllers::user_logic_controller::get_user_rating_history))
+    .route("/get_user_ratings", get(controllers::user_logic_controller::get_user_ratings))
+    .route("/get_leaderboard", get(controllers::user_logic_controller::get_leaderboard))
+    .route("/get_leaderboard_rank", get(controllers::user_logic_controller::get_leaderboard_rank))
+    .route("/change_user_country", put(controllers::user_logic_controller::change_user_country))
     .route_layer(middleware::from_fn(utils::middleware::guard))
 
 }
\ No newline at end of file
@@ -1,3 +1,6 @@
+use std::sync::Arc;
+
+use orion::engines::registry::GameEngineRegistry;
 use rdkafka::producer::FutureProducer;
 use sea_orm::DatabaseConnection;
 
@@ -9,6 +12,8 @@ pub struct AppDBState {
     pub from_email: String,
     pub smtp_key: String,
     pub producer: FutureProducer,
-    pub context: DynContext
+    pub context: DynContext,
+    // Lobby capacity and seat names for every game type
+    pub engine_registry: Arc<GameEngineRegistry>,
 }
 
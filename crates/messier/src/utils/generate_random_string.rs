@@ -19,4 +19,16 @@ pub fn generate_random_string(len: usize) -> String {
 
     let code_str: String = code.into_iter().collect();
     code_str
-}
\ No newline at end of file
+}
+
+
+// No 0/O or 1/I, the code is read out and typed in by people
+const JOIN_CODE_CHARACTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+pub fn generate_join_code(len: usize) -> String {
+    let mut rng = rand::thread_rng();
+
+    (0..len)
+        .map(|_| JOIN_CODE_CHARACTERS[rng.gen_range(0..JOIN_CODE_CHARACTERS.len())] as char)
+        .collect()
+}
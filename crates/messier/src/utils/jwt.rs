@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use crate::constants; 
 
 
-#[derive(Serialize,Deserialize)]
+#[derive(Serialize,Deserialize,Clone)]
 pub struct Claims{
     pub exp: usize,
     pub iat: usize,
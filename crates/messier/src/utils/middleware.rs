@@ -7,13 +7,16 @@ use super::{api_error::APIError, jwt::decode_jwt};
 
 
 
-pub async fn guard(req: Request, next: Next) -> Result<Response,APIError> {
+pub async fn guard(mut req: Request, next: Next) -> Result<Response,APIError> {
 
     let token = req.headers().get("Authorization")
     .ok_or(APIError { message: "No Auth token found".to_owned(), status_code: StatusCode::BAD_REQUEST, error_code: Some(40)  })?.to_owned();
     let token_value: Vec<&str> = token.to_str().unwrap().split_whitespace().collect();
-    let _claim = decode_jwt(token_value[1].to_string())
+    let claim = decode_jwt(token_value[1].to_string())
     .map_err(|_err| APIError { message: "Unauthorized".to_owned(), status_code: StatusCode::UNAUTHORIZED, error_code: Some(41)  })?.claims;
 
+    // Handlers that need to know who is calling read the claims from the request extensions
+    req.extensions_mut().insert(claim);
+
     Ok(next.run(req).await)
 } 
\ No newline at end of file
@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GameInvites::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GameInvites::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GameInvites::GameId).uuid().not_null())
+                    .col(ColumnDef::new(GameInvites::UserSentId).uuid().not_null())
+                    .col(ColumnDef::new(GameInvites::UserSentUsername).text().not_null())
+                    .col(ColumnDef::new(GameInvites::UserRecievedId).uuid().not_null())
+                    .col(ColumnDef::new(GameInvites::Status).string().not_null())
+                    .col(ColumnDef::new(GameInvites::ExpiresAt).timestamp().not_null())
+                    .col(ColumnDef::new(GameInvites::CreatedAt).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk-game-invites-user-sent-id")
+                        .from(GameInvites::Table, GameInvites::UserSentId)
+                        .to(Users::Table, Users::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk-game-invites-user-recieved-id")
+                        .from(GameInvites::Table, GameInvites::UserRecievedId)
+                        .to(Users::Table, Users::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GameInvites::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GameInvites {
+    Table,
+    Id,
+    GameId,
+    UserSentId,
+    UserSentUsername,
+    UserRecievedId,
+    Status,
+    ExpiresAt,
+    CreatedAt,
+}
+
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,139 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GameHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GameHistory::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GameHistory::GameId).uuid().not_null())
+                    .col(ColumnDef::new(GameHistory::SessionId).string().not_null())
+                    .col(ColumnDef::new(GameHistory::GameType).string().not_null())
+                    .col(ColumnDef::new(GameHistory::GameName).text().not_null())
+                    .col(ColumnDef::new(GameHistory::Result).string().not_null())
+                    .col(ColumnDef::new(GameHistory::TerminationReason).string().not_null())
+                    .col(ColumnDef::new(GameHistory::TimeControl).string())
+                    .col(ColumnDef::new(GameHistory::MoveCount).integer().not_null())
+                    .col(ColumnDef::new(GameHistory::FinalState).text())
+                    .col(ColumnDef::new(GameHistory::Pgn).text())
+                    .col(ColumnDef::new(GameHistory::StartedAt).timestamp().not_null())
+                    .col(ColumnDef::new(GameHistory::EndedAt).timestamp().not_null())
+                    .index(
+                        Index::create()
+                        .name("idx-game-history-game-id-session-id")
+                        .col(GameHistory::GameId)
+                        .col(GameHistory::SessionId)
+                        .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(GameHistoryPlayers::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GameHistoryPlayers::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GameHistoryPlayers::GameHistoryId).uuid().not_null())
+                    .col(ColumnDef::new(GameHistoryPlayers::UserId).uuid().not_null())
+                    .col(ColumnDef::new(GameHistoryPlayers::Username).text().not_null())
+                    .col(ColumnDef::new(GameHistoryPlayers::PlayerType).string().not_null())
+                    .col(ColumnDef::new(GameHistoryPlayers::Seat).big_integer().not_null())
+                    .col(ColumnDef::new(GameHistoryPlayers::Place).integer())
+                    .col(ColumnDef::new(GameHistoryPlayers::ScoreChange).integer().not_null())
+                    .col(ColumnDef::new(GameHistoryPlayers::IsWinner).boolean().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk-game-history-players-game-history-id")
+                        .from(GameHistoryPlayers::Table, GameHistoryPlayers::GameHistoryId)
+                        .to(GameHistory::Table, GameHistory::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk-game-history-players-user-id")
+                        .from(GameHistoryPlayers::Table, GameHistoryPlayers::UserId)
+                        .to(Users::Table, Users::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-game-history-players-user-id")
+                    .table(GameHistoryPlayers::Table)
+                    .col(GameHistoryPlayers::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GameHistoryPlayers::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(GameHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GameHistory {
+    Table,
+    Id,
+    GameId,
+    SessionId,
+    GameType,
+    GameName,
+    Result,
+    TerminationReason,
+    TimeControl,
+    MoveCount,
+    FinalState,
+    Pgn,
+    StartedAt,
+    EndedAt,
+}
+
+
+#[derive(DeriveIden)]
+enum GameHistoryPlayers {
+    Table,
+    Id,
+    GameHistoryId,
+    UserId,
+    Username,
+    PlayerType,
+    Seat,
+    Place,
+    ScoreChange,
+    IsWinner,
+}
+
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GameHistory::Table)
+                    .add_column(
+                        ColumnDef::new(GameHistory::IsPrivate)
+                            .boolean()
+                            .not_null().default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table(GameHistory::Table).drop_column(GameHistory::IsPrivate).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GameHistory {
+    Table,
+    IsPrivate,
+}
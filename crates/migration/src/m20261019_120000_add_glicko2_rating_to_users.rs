@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(ColumnDef::new(Users::Rating).double().not_null().default(1500.0))
+                    .add_column(ColumnDef::new(Users::RatingDeviation).double().not_null().default(350.0))
+                    .add_column(ColumnDef::new(Users::RatingVolatility).double().not_null().default(0.06))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Old scores started at 0 and moved by at most 10 points a game, they are carried over on top of the default
+        // rating. rating holds the legacy rating from now on, score only mirrors it rounded for older clients until both
+        // are moved to user_ratings
+        manager
+            .get_connection()
+            .execute_unprepared(r#"UPDATE "users" SET "rating" = 1500 + "score", "score" = 1500 + "score""#)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(r#"UPDATE "users" SET "score" = GREATEST(0, "score" - 1500)"#)
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::Rating)
+                    .drop_column(Users::RatingDeviation)
+                    .drop_column(Users::RatingVolatility)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Rating,
+    RatingDeviation,
+    RatingVolatility,
+}
@@ -0,0 +1,86 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RatingHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RatingHistory::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(RatingHistory::UserId).uuid().not_null())
+                    .col(ColumnDef::new(RatingHistory::GameId).uuid().not_null())
+                    .col(ColumnDef::new(RatingHistory::SessionId).string().not_null())
+                    .col(ColumnDef::new(RatingHistory::RatingBefore).double().not_null())
+                    .col(ColumnDef::new(RatingHistory::RatingAfter).double().not_null())
+                    .col(ColumnDef::new(RatingHistory::DeviationBefore).double().not_null())
+                    .col(ColumnDef::new(RatingHistory::DeviationAfter).double().not_null())
+                    .col(ColumnDef::new(RatingHistory::OpponentId).uuid())
+                    .col(ColumnDef::new(RatingHistory::OpponentRating).double().not_null())
+                    .col(ColumnDef::new(RatingHistory::CreatedAt).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk-rating-history-user-id")
+                        .from(RatingHistory::Table, RatingHistory::UserId)
+                        .to(Users::Table, Users::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk-rating-history-opponent-id")
+                        .from(RatingHistory::Table, RatingHistory::OpponentId)
+                        .to(Users::Table, Users::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-rating-history-user-id-created-at")
+                    .table(RatingHistory::Table)
+                    .col(RatingHistory::UserId)
+                    .col(RatingHistory::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RatingHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RatingHistory {
+    Table,
+    Id,
+    UserId,
+    GameId,
+    SessionId,
+    RatingBefore,
+    RatingAfter,
+    DeviationBefore,
+    DeviationAfter,
+    OpponentId,
+    OpponentRating,
+    CreatedAt,
+}
+
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
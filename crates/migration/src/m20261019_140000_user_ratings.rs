@@ -0,0 +1,202 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserRatings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UserRatings::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(UserRatings::UserId).uuid().not_null())
+                    .col(ColumnDef::new(UserRatings::GameType).string().not_null())
+                    .col(ColumnDef::new(UserRatings::Category).string().not_null())
+                    .col(ColumnDef::new(UserRatings::Rating).double().not_null().default(1500.0))
+                    .col(ColumnDef::new(UserRatings::RatingDeviation).double().not_null().default(350.0))
+                    .col(ColumnDef::new(UserRatings::RatingVolatility).double().not_null().default(0.06))
+                    .col(ColumnDef::new(UserRatings::GamesPlayed).integer().not_null().default(0))
+                    .col(ColumnDef::new(UserRatings::CreatedAt).timestamp().not_null())
+                    .col(ColumnDef::new(UserRatings::UpdatedAt).timestamp().not_null())
+                    .index(
+                        Index::create()
+                        .name("idx-user-ratings-user-id-game-type-category")
+                        .col(UserRatings::UserId)
+                        .col(UserRatings::GameType)
+                        .col(UserRatings::Category)
+                        .unique(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                        .name("fk-user-ratings-user-id")
+                        .from(UserRatings::Table, UserRatings::UserId)
+                        .to(Users::Table, Users::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-user-ratings-game-type-category-rating")
+                    .table(UserRatings::Table)
+                    .col(UserRatings::GameType)
+                    .col(UserRatings::Category)
+                    .col(UserRatings::Rating)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RatingHistory::Table)
+                    .add_column(ColumnDef::new(RatingHistory::GameType).string().not_null().default(""))
+                    .add_column(ColumnDef::new(RatingHistory::Category).string().not_null().default(""))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every archived game so far was played without a clock, chess ones count as correspondence
+        let connection = manager.get_connection();
+        connection
+            .execute_unprepared(
+                r#"UPDATE "rating_history" SET "game_type" = "game_history"."game_type",
+                    "category" = CASE WHEN "game_history"."game_type" = 'chess' THEN 'correspondence' ELSE 'standard' END
+                FROM "game_history"
+                WHERE "game_history"."game_id" = "rating_history"."game_id" AND "game_history"."session_id" = "rating_history"."session_id""#,
+            )
+            .await?;
+
+        // There was a single rating until now, it is carried over to every game type the player has finished a ranked game of
+        connection
+            .execute_unprepared(
+                r#"INSERT INTO "user_ratings" ("id", "user_id", "game_type", "category", "rating", "rating_deviation", "rating_volatility", "games_played", "created_at", "updated_at")
+                SELECT gen_random_uuid(), "users"."id", "played"."game_type", "played"."category", "users"."rating", "users"."rating_deviation",
+                    "users"."rating_volatility", "played"."games_played", NOW(), NOW()
+                FROM "users"
+                JOIN (
+                    SELECT "game_history_players"."user_id", "game_history"."game_type",
+                        CASE WHEN "game_history"."game_type" = 'chess' THEN 'correspondence' ELSE 'standard' END AS "category",
+                        COUNT(*) AS "games_played"
+                    FROM "game_history_players"
+                    JOIN "game_history" ON "game_history"."id" = "game_history_players"."game_history_id"
+                    WHERE "game_history_players"."place" IS NOT NULL
+                    GROUP BY 1, 2, 3
+                ) AS "played" ON "played"."user_id" = "users"."id""#,
+            )
+            .await?;
+
+        // Everyone else keeps it as their chess correspondence rating, the category of the unclocked games it was earned in
+        connection
+            .execute_unprepared(
+                r#"INSERT INTO "user_ratings" ("id", "user_id", "game_type", "category", "rating", "rating_deviation", "rating_volatility", "games_played", "created_at", "updated_at")
+                SELECT gen_random_uuid(), "users"."id", 'chess', 'correspondence', "users"."rating", "users"."rating_deviation", "users"."rating_volatility", 0, NOW(), NOW()
+                FROM "users"
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM "user_ratings"
+                    WHERE "user_ratings"."user_id" = "users"."id" AND "user_ratings"."game_type" = 'chess' AND "user_ratings"."category" = 'correspondence'
+                )"#,
+            )
+            .await?;
+
+        // score only mirrored the single rating, there is nothing left for it to show
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::Rating)
+                    .drop_column(Users::RatingDeviation)
+                    .drop_column(Users::RatingVolatility)
+                    .drop_column(Users::Score)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(ColumnDef::new(Users::Rating).double().not_null().default(1500.0))
+                    .add_column(ColumnDef::new(Users::RatingDeviation).double().not_null().default(350.0))
+                    .add_column(ColumnDef::new(Users::RatingVolatility).double().not_null().default(0.06))
+                    .add_column(ColumnDef::new(Users::Score).integer().not_null().default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Players get back the rating they have played the most games with
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"UPDATE "users" SET "rating" = "best"."rating", "rating_deviation" = "best"."rating_deviation", "rating_volatility" = "best"."rating_volatility",
+                    "score" = ROUND("best"."rating")
+                FROM (
+                    SELECT DISTINCT ON ("user_id") "user_id", "rating", "rating_deviation", "rating_volatility"
+                    FROM "user_ratings"
+                    ORDER BY "user_id", "games_played" DESC
+                ) AS "best"
+                WHERE "best"."user_id" = "users"."id""#,
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RatingHistory::Table)
+                    .drop_column(RatingHistory::GameType)
+                    .drop_column(RatingHistory::Category)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(UserRatings::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserRatings {
+    Table,
+    Id,
+    UserId,
+    GameType,
+    Category,
+    Rating,
+    RatingDeviation,
+    RatingVolatility,
+    GamesPlayed,
+    CreatedAt,
+    UpdatedAt,
+}
+
+
+#[derive(DeriveIden)]
+enum RatingHistory {
+    Table,
+    GameType,
+    Category,
+}
+
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+    Rating,
+    RatingDeviation,
+    RatingVolatility,
+    Score,
+}
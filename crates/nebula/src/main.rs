@@ -5,11 +5,11 @@ use chrono::Utc;
 use conf::{config_types::ServerConfiguration, configuration::Configuration};
 use context::context::{ContextImpl, DynContext};
 use kafka::producer;
-use orion::{constants::{CREATE_USER_BET, GAME_BET_SETTLED, GAME_BET_SETTLED_ERROR, GAME_OVER_EVENT, GAME_STAKE_TIME_OVER, GAME_STAKE_TIME_OVER_DATA, GENERATE_GAME_BET_EVENTS, SETTLE_BET_KEY, SETTLE_BET_KEY_DATA, STAKE_TIME_OVER, STAKE_TIME_OVER_RESULT, START_GAME_SETTLE_EVENT}, events::kafka_event::{GameBetEvent, GameBetSettleKafkaPayload, GameOverEvent, GameSettleBetErrorRedisPayload, GameStakeTimeOverEventResult, GameStakeTimeRedisPayload, GameUserBetSettleEvent, GenerateGameBetSettleEvents, UserGameBetEvent}, models::game_bet_events::GameBetStatus};
+use orion::{constants::{CREATE_USER_BET, GAME_BET_POOL_EVENT, GAME_BET_SETTLED, GAME_BET_SETTLED_ERROR, GAME_OVER_EVENT, GAME_STAKE_TIME_OVER, GAME_STAKE_TIME_OVER_DATA, GENERATE_GAME_BET_EVENTS, SETTLE_BET_KEY, SETTLE_BET_KEY_DATA, STAKE_TIME_OVER, STAKE_TIME_OVER_RESULT, START_GAME_SETTLE_EVENT}, events::{kafka_event::{GameBetEvent, GameBetSettleKafkaPayload, GameOverEvent, GameSettleBetErrorRedisPayload, GameStakeTimeOverEventResult, GameStakeTimeRedisPayload, GameUserBetSettleEvent, GenerateGameBetSettleEvents, UserGameBetEvent, game_winner_ids}, ws_events::GameBetPoolPayload}, models::game_bet_events::GameBetStatus};
 use rdkafka::{consumer::StreamConsumer, error::KafkaError, message::ToBytes, producer::{FutureProducer, FutureRecord, Producer}, util::Timeout, Message};
 use redis::{AsyncCommands, RedisResult, SetOptions, ToRedisArgs};
 use reqwest::Client;
-use sea_orm::{ColumnTrait, DbErr};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr};
 use sea_orm::{prelude::Expr, Condition};
 use sea_orm::{Database, EntityTrait, QueryFilter, QuerySelect, Set, TransactionTrait};
 use serde_json::json;
@@ -235,11 +235,14 @@ pub async fn do_listen(
 
 
 
-                            let mut game_bets = if !game_bet_res_model.winner_id.is_empty() {
-                                game_bets::Entity::find_by_game_id_and_session_id_with_progress_with_winner_id(Uuid::from_str(&game_bet_res_model.game_id).unwrap(),
-                                game_bet_res_model.session_id.clone() , GameBetStatus::InProgress.to_string() , Uuid::parse_str(&game_bet_res_model.winner_id).unwrap()).limit(2000).all(&postgres_conn).await
+                            let winner_ids = game_winner_ids(&game_bet_res_model.winner_id, &game_bet_res_model.winner_ids);
+
+                            // Every bet left on an invalid game is compensated, the absent player's stake was settled on game over
+                            let mut game_bets = if game_bet_res_model.is_game_valid && !winner_ids.is_empty() {
+                                game_bets::Entity::find_by_game_id_and_session_id_with_progress_with_winner_ids(Uuid::from_str(&game_bet_res_model.game_id).unwrap(),
+                                game_bet_res_model.session_id.clone() , GameBetStatus::InProgress.to_string() , winner_ids.iter().map(|winner_id| Uuid::parse_str(winner_id).unwrap()).collect()).limit(2000).all(&postgres_conn).await
                             } else {
-                                //stalemate case
+                                //stalemate or invalid game case
                                 game_bets::Entity::find_by_game_id_and_session_id_with_progress(Uuid::from_str(&game_bet_res_model.game_id).unwrap(),
                                 game_bet_res_model.session_id.clone() , GameBetStatus::InProgress.to_string()).limit(2000).all(&postgres_conn).await
                             };
@@ -260,6 +263,7 @@ pub async fn do_listen(
                                         game_id: bet.game_id.to_string().clone(),
                                         session_id: bet.session_id.clone(),
                                         winner_id: game_bet_res_model.winner_id.clone(),
+                                        winner_ids: winner_ids.clone(),
                                         user_id: bet.user_id.to_string().clone(),
                                         user_betting_on: bet.user_id_betting_on.to_string().clone(),
                                         record_id: bet.id.to_string(),
@@ -290,6 +294,7 @@ pub async fn do_listen(
                                     game_id: game_bet_res_model.game_id.clone(),
                                     session_id: game_bet_res_model.session_id.clone(),
                                     winner_id: game_bet_res_model.winner_id.clone(),
+                                    winner_ids: winner_ids.clone(),
                                     is_game_valid: game_bet_res_model.is_game_valid,
                                 };
     
@@ -310,6 +315,7 @@ pub async fn do_listen(
                                 game_id: game_bet_res_model.game_id.clone(),
                                 session_id: game_bet_res_model.session_id.clone(),
                                 winner_id: game_bet_res_model.winner_id.clone(),
+                                winner_ids: game_bet_res_model.winner_ids.clone(),
                                 is_game_valid: game_bet_res_model.is_game_valid.clone(),
                             };
 
@@ -343,10 +349,11 @@ pub async fn do_listen(
 
                         let mut game_bets = if game_over_event_model.is_game_valid {
                             // there are two possible cases
-                            // winner_id not empty -> which means the game was completed with a winner
-                            if !game_over_event_model.winner_id.is_empty() {
-                                game_bets::Entity::find_by_game_id_and_session_id_with_progress_not_equal_to_winner_id(Uuid::from_str(&game_over_event_model.game_id).unwrap(),
-                                game_over_event_model.session_id.clone() , GameBetStatus::InProgress.to_string() , Uuid::parse_str(&game_over_event_model.winner_id).unwrap()).all(&postgres_conn).await
+                            // winner_ids not empty -> which means the game was completed with one or more winners
+                            let winner_ids = game_winner_ids(&game_over_event_model.winner_id, &game_over_event_model.winner_ids);
+                            if !winner_ids.is_empty() {
+                                game_bets::Entity::find_by_game_id_and_session_id_with_progress_not_in_winner_ids(Uuid::from_str(&game_over_event_model.game_id).unwrap(),
+                                game_over_event_model.session_id.clone() , GameBetStatus::InProgress.to_string() , winner_ids.iter().map(|winner_id| Uuid::parse_str(winner_id).unwrap()).collect()).all(&postgres_conn).await
                             } else {
                                 //  if winner_id is empty it means their was a stalemate
                                 // in this cases game is valid but all bets become invalid as there was no clear winner
@@ -356,10 +363,12 @@ pub async fn do_listen(
                   
                         } else {
                             // If not valid only player with issue must be settle rest should be compensated
-                           // This func will return Player who is not the winner
-                           // in this case it means it will return the player because of whom the game became invalid
-                            game_bets::Entity::find_invalid_user_by_game_id_and_session_id_for_invalid_game(Uuid::from_str(&game_over_event_model.game_id).unwrap(),
-                        game_over_event_model.session_id.clone()  , Uuid::parse_str(&game_over_event_model.winner_id).unwrap()).all(&postgres_conn).await
+                           // This func will return the player because of whom the game became invalid
+                            match Uuid::parse_str(&game_over_event_model.absent_player_id) {
+                                Ok(absent_player_id) => game_bets::Entity::find_invalid_user_by_game_id_and_session_id_for_invalid_game(Uuid::from_str(&game_over_event_model.game_id).unwrap(),
+                                game_over_event_model.session_id.clone() , absent_player_id).all(&postgres_conn).await,
+                                Err(_) => Ok(vec![]),
+                            }
                         };
                     if game_bets.is_err() {
                         error!("Error while fetching GameBets")
@@ -441,6 +450,7 @@ pub async fn do_listen(
                             game_id: game_over_event_model.game_id.clone(),
                             session_id: game_over_event_model.session_id.clone(),
                             winner_id: game_over_event_model.winner_id.clone(),
+                            winner_ids: game_over_event_model.winner_ids.clone(),
                             is_game_valid: game_over_event_model.is_game_valid.clone()
                         };
 
@@ -516,6 +526,7 @@ pub async fn do_listen(
                                 game_id: game_over_event_model.game_id.clone(),
                                 session_id: game_over_event_model.session_id.clone(),
                                 winner_id: game_over_event_model.winner_id.clone(),
+                                winner_ids: game_over_event_model.winner_ids.clone(),
                                 is_game_valid: game_over_event_model.is_game_valid.clone(),
                             };
 
@@ -535,6 +546,8 @@ pub async fn do_listen(
 
                     if user_game_bet_payload.is_ok() {
                         let user_game_bet_model: UserGameBetEvent = user_game_bet_payload.unwrap();
+                        let (game_id , session_id) = (user_game_bet_model.game_id.clone() , user_game_bet_model.session_id.clone());
+
                         if user_game_bet_model.event_type == GameBetEvent::CREATE {
                             let new_bet = game_bets::ActiveModel {
                                 id: Set(Uuid::new_v4()),
@@ -578,6 +591,8 @@ pub async fn do_listen(
                                 }
                             }
                         }
+
+                        publish_game_bet_pool_event(&producer , &postgres_conn , game_id , session_id).await;
                     } else {
                         println!("Error WHile parsing UserGameBetEvent");
                         println!("{:?}" , user_game_bet_payload.err().unwrap());
@@ -690,6 +705,59 @@ pub fn decrypt_user_wallet(encrypted_wallet_key: String) -> String {
 }
 
 
+// Spectator feeds show how much is bet on each player while the session is running
+pub async fn publish_game_bet_pool_event(producer: &FutureProducer , postgres_conn: &DatabaseConnection , game_id: String , session_id: String) {
+    let Ok(game_uuid) = Uuid::from_str(&game_id) else {
+        return;
+    };
+
+    let bets = match game_bets::Entity::find_by_game_id_and_session_id_with_progress(game_uuid, session_id.clone(), GameBetStatus::InProgress.to_string()).all(postgres_conn).await {
+        Ok(bets) => bets,
+        Err(e) => {
+            warn!("Error while fetching bet pool for game_id={} session_id={}: {:?}" , game_id , session_id , e);
+            return;
+        }
+    };
+
+    let mut pools: HashMap<String, f64> = HashMap::new();
+    for bet in &bets {
+        *pools.entry(bet.user_id_betting_on.to_string()).or_default() += bet.bet_amount;
+    }
+
+    let payload = GameBetPoolPayload {
+        game_id,
+        session_id,
+        total_amount: pools.values().sum(),
+        pools,
+    };
+
+    if let Err(e) = producer.begin_transaction() {
+        warn!("Error while starting the bet pool transaction for game_id={}: {:?}" , payload.game_id , e);
+        return;
+    }
+
+    let delivery_result = producer
+        .send(
+            FutureRecord::to("game")
+                    .payload(&serde_json::to_string(&payload).unwrap())
+                    .key(GAME_BET_POOL_EVENT),
+            Duration::from_secs(2),
+        )
+        .await;
+
+    if let Err(e) = delivery_result {
+        warn!("Error while publishing bet pool for game_id={}: {:?}" , payload.game_id , e.0);
+        let _ = producer.abort_transaction(Timeout::from(Duration::from_secs(1)));
+        return;
+    }
+
+    if let Err(e) = producer.commit_transaction(Timeout::from(Duration::from_secs(1))) {
+        warn!("Error while committing bet pool for game_id={}: {:?}" , payload.game_id , e);
+        let _ = producer.abort_transaction(Timeout::from(Duration::from_secs(1)));
+    }
+}
+
+
 pub async fn publish_game_bet_events_for_settlement(producer: &FutureProducer , kafka_events: Vec<GameBetSettleKafkaPayload>) -> Result<(), KafkaError> {
 
 
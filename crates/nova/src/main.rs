@@ -4,7 +4,7 @@ use axum::{response::IntoResponse, routing::get, Router};
 use conf::config_types::{KafkaConfiguration, ServerConfiguration};
 use context::context::ContextImpl;
 use futures::{future, StreamExt};
-use orion::{constants::{EXECUTOR_GAME_OVER_EVENT, EXECUTOR_GAME_STAKE_TIME_OVER_EVENT, GAME_OVER_STATUS_KEY, GAME_STAKE_TIME_OVER, GAME_STAKE_TIME_OVER_DATA, GENERATE_GAME_BET_EVENTS, SETTLE_BET_KEY, SETTLE_BET_KEY_DATA}, events::kafka_event::GenerateGameBetSettleEvents};
+use orion::{constants::{EXECUTOR_GAME_OVER_EVENT, EXECUTOR_GAME_STAKE_TIME_OVER_EVENT, GAME_OVER_STATUS_KEY, GAME_STAKE_TIME_OVER, GAME_STAKE_TIME_OVER_DATA, GAME_OVER_EVENT, GAME_TURN_TIMER, GAME_TURN_TIMER_DATA, GENERATE_GAME_BET_EVENTS, PLAYER_HEARTBEAT_DATA, PLAYER_HEARTBEAT_KEY, SETTLE_BET_KEY, SETTLE_BET_KEY_DATA, USER_GAME_EVENTS}, events::kafka_event::GenerateGameBetSettleEvents};
 use rdkafka::{error::KafkaError, producer::{FutureProducer, FutureRecord, Producer}, util::Timeout};
 use redis::{aio::{MultiplexedConnection, PubSub}, AsyncCommands, RedisResult};
 use serde_json::json;
@@ -152,6 +152,8 @@ pub async fn start_listening_to_key_events(
 
  let kafka_producer_for_settle_events = kafka::producer::create_new_kafka_producer(kafka_config).unwrap();
  let kafka_producer_for_game_over_events = kafka::producer::create_new_kafka_producer(kafka_config).unwrap();
+ let kafka_producer_for_turn_timer_events = kafka::producer::create_new_kafka_producer(kafka_config).unwrap();
+ let kafka_producer_for_abandoned_game_events = kafka::producer::create_new_kafka_producer(kafka_config).unwrap();
 
 
     // Start listener
@@ -181,6 +183,28 @@ pub async fn start_listening_to_key_events(
                             
                         let _ = publish_game_stake_time_over_event(&kafka_producer_for_game_over_events, vec![redis_payload_val]).await;
                         }
+                       } else if expired_key_channel.contains(GAME_TURN_TIMER) {
+                        // Keyspace events are also sent when the timer is set, only the expiry should tick the game
+                        let key_event: String = new_message.get_payload().unwrap_or_default();
+
+                        if key_event == "expired" {
+                            let redis_payload = get_redis_payload_for_key(redis_conn.clone() , GAME_TURN_TIMER , expired_key_channel).await;
+
+                            if let Some(redis_payload_val) = redis_payload {
+                            let _ = publish_game_turn_tick_event(&kafka_producer_for_turn_timer_events, vec![redis_payload_val]).await;
+                            }
+                        }
+                       } else if expired_key_channel.contains(PLAYER_HEARTBEAT_KEY) {
+                        // The heartbeat is refreshed every few seconds, only its expiry means the player is gone
+                        let key_event: String = new_message.get_payload().unwrap_or_default();
+
+                        if key_event == "expired" {
+                            let redis_payload = get_redis_payload_for_key(redis_conn.clone() , PLAYER_HEARTBEAT_KEY , expired_key_channel).await;
+
+                            if let Some(redis_payload_val) = redis_payload {
+                            let _ = publish_player_abandoned_game_event(&kafka_producer_for_abandoned_game_events, vec![redis_payload_val]).await;
+                            }
+                        }
                        }
 
 
@@ -212,6 +236,10 @@ pub async fn get_redis_payload_for_key(mut redis_conn: MultiplexedConnection , k
 
     let key = if key_type.eq(SETTLE_BET_KEY) {
             SETTLE_BET_KEY_DATA.to_string() + &key_id
+    } else if key_type.eq(GAME_TURN_TIMER) {
+        GAME_TURN_TIMER_DATA.to_string() + &key_id
+    } else if key_type.eq(PLAYER_HEARTBEAT_KEY) {
+        PLAYER_HEARTBEAT_DATA.to_string() + &key_id
     } else {
         GAME_STAKE_TIME_OVER_DATA.to_string() + &key_id
     };
@@ -299,4 +327,78 @@ pub async fn publish_game_stake_time_over_event(producer: &FutureProducer , kafk
 
     Ok(())
 
-}
\ No newline at end of file
+}
+
+
+// Sends the "tick" UserGameMove stored by cerotis so the game engine can end the timed out turn
+pub async fn publish_game_turn_tick_event(producer: &FutureProducer , kafka_events: Vec<String>) -> Result<(), KafkaError> {
+    tracing::info!("Publishing {} turn tick events to {}", kafka_events.len(), USER_GAME_EVENTS);
+
+    producer.begin_transaction().unwrap();
+
+
+    let kafka_result = future::try_join_all(kafka_events.iter().map(|event| async move {
+
+        producer
+        .send(
+            FutureRecord::to(USER_GAME_EVENTS)
+                    .payload(event)
+                    .key("game_turn_tick"),
+            Duration::from_secs(2),
+        )
+        .await
+
+    })
+
+    ).await;
+
+    match kafka_result {
+        Ok(_) => (),
+        Err(e) => {
+            let _ = producer.abort_transaction(Timeout::from(Duration::from_secs(1)));
+            return Err(e.0)
+        },
+    }
+
+    producer.commit_transaction(Timeout::from(Duration::from_secs(1))).unwrap(); 
+
+    Ok(())
+
+}
+
+
+// Sends the invalid GameOverEvent stored by the gateway for the player who has been gone longer than the grace period
+pub async fn publish_player_abandoned_game_event(producer: &FutureProducer , kafka_events: Vec<String>) -> Result<(), KafkaError> {
+    tracing::info!("Publishing {} abandoned game events to {}", kafka_events.len(), GAME_OVER_EVENT);
+
+    producer.begin_transaction().unwrap();
+
+
+    let kafka_result = future::try_join_all(kafka_events.iter().map(|event| async move {
+
+        producer
+        .send(
+            FutureRecord::to(GAME_OVER_EVENT)
+                    .payload(event)
+                    .key("player_abandoned_game"),
+            Duration::from_secs(2),
+        )
+        .await
+
+    })
+
+    ).await;
+
+    match kafka_result {
+        Ok(_) => (),
+        Err(e) => {
+            let _ = producer.abort_transaction(Timeout::from(Duration::from_secs(1)));
+            return Err(e.0)
+        },
+    }
+
+    producer.commit_transaction(Timeout::from(Duration::from_secs(1))).unwrap(); 
+
+    Ok(())
+
+}
@@ -3,6 +3,8 @@
 pub const USER_ONLINE_EVENT: &str = "user-online-event";
 pub const FRIEND_REQUEST_EVENT: &str = "friend-request-event";
 pub const GAME_INVITE_EVENT: &str = "game-invite-event";
+pub const GAME_INVITE_RESPONSE_EVENT: &str = "game-invite-response-event";
+pub const MATCH_FOUND_EVENT: &str = "match-found-event";
 pub const USER_JOINED_ROOM: &str = "user-joined-room";
 pub const USER_LEFT_ROOM: &str = "user-left-room";
 
@@ -13,6 +15,26 @@ pub const ERROR_EVENT: &str = "error-event";
 //Game Events
 pub const USER_GAME_MOVE: &str = "user-game-move";
 pub const GAME_GENERAL_EVENT: &str = "game-general-event";
+pub const PLAYER_PRIVATE_STATE_EVENT: &str = "player-private-state-event";
+pub const GAME_STATE_UPDATE_EVENT: &str = "game-state-update-event";
+pub const GAME_TRANSIENT_ACTION_EVENT: &str = "game-transient-action-event";
+pub const GAME_RESULT_EVENT: &str = "game-result-event";
+pub const GAME_START_EVENT: &str = "game-start-event";
+pub const GAME_MESSAGE_EVENT: &str = "game-message-event";
+pub const GAME_CLOCK_EVENT: &str = "game-clock-event";
+pub const GAME_BET_POOL_EVENT: &str = "game-bet-pool-event";
+pub const GAME_REMATCH_REQUEST_EVENT: &str = "game-rematch-request-event";
+pub const GAME_REMATCH_DECLINED_EVENT: &str = "game-rematch-declined-event";
+pub const GAME_SPECTATOR_COUNT_EVENT: &str = "game-spectator-count-event";
+pub const GAME_MESSAGE_DELETED_EVENT: &str = "game-message-deleted-event";
+
+//Websocket gateway events sent by clients
+pub const JOIN_GAME_ROOM: &str = "join-game-room";
+pub const LEAVE_GAME_ROOM: &str = "leave-game-room";
+pub const CHAT_MUTE_USER: &str = "chat-mute-user";
+pub const CHAT_UNMUTE_USER: &str = "chat-unmute-user";
+pub const CHAT_DELETE_MESSAGE: &str = "chat-delete-message";
+pub const CHAT_TIMEOUT_USER: &str = "chat-timeout-user";
 //Redis Key
 pub const REDIS_USER_GAME_KEY: &str = "-user-game-id";
 pub const REDIS_USER_PLAYER_KEY: &str = "-user-player-type";
@@ -23,6 +45,8 @@ pub const MONGO_DB_NAME: &str = "user_game_events_db";
 pub const MONGO_USERS_MODEL: &str = "users";
 pub const MONGO_GAMES_MODEL: &str = "games";
 pub const MONGO_USER_TURNS_MODEL: &str = "user_turns";
+pub const MONGO_GAME_MOVES_MODEL: &str = "game_moves";
+pub const MONGO_GAME_MESSAGES_MODEL: &str = "game_messages";
 
 
 //Game Bet Related Kafka Topics
@@ -48,9 +72,72 @@ pub const GAME_BET_SETTLED_ERROR: &str = "game_bet_settled_error";
 //Redis Keys
 pub const SETTLE_BET_KEY: &str = "GameSettle_";
 pub const CHESS_STATE_REDIS_KEY: &str = "ChessState_";
+pub const CHECKERS_STATE_REDIS_KEY: &str = "CheckersState_";
+pub const POKER_STATE_REDIS_KEY: &str = "PokerState_";
+pub const SCRIBBLE_STATE_REDIS_KEY: &str = "ScribbleState_";
+// Monopoly state lives in the games collection, the prefix only identifies the engine
+pub const MONOPOLY_STATE_KEY: &str = "MonopolyState_";
+pub const GAME_TURN_TIMER: &str = "GameTurnTimer_";
 pub const GAME_OVER_STATUS_KEY: &str = "GameOver_";
 pub const GAME_STAKE_TIME_OVER: &str = "GameStakeTimeOver_";
+// Refreshed by the gateway while a player is connected, expires once the player has been gone for the grace period
+pub const PLAYER_HEARTBEAT_KEY: &str = "PlayerHeartbeat_";
 
 // Redis keys for data
 pub const SETTLE_BET_KEY_DATA: &str = "GameSettleData_";
-pub const GAME_STAKE_TIME_OVER_DATA: &str = "GameStakeTimeOverData_";
\ No newline at end of file
+pub const GAME_STAKE_TIME_OVER_DATA: &str = "GameStakeTimeOverData_";
+pub const GAME_TURN_TIMER_DATA: &str = "GameTurnTimerData_";
+pub const PLAYER_HEARTBEAT_DATA: &str = "PlayerHeartbeatData_";
+
+// Websocket gateway room registry. Set of gateway instances with members in a room, and the channel of each instance
+pub const WS_ROOM_KEY: &str = "WsRoom_";
+pub const WS_GATEWAY_CHANNEL: &str = "WsGateway_";
+// Hash of the users watching a game, user_id -> open connections
+pub const GAME_SPECTATORS_KEY: &str = "GameSpectators_";
+// Spectators of staked games see every event this many seconds late unless the game sets its own delay
+pub const DEFAULT_SPECTATOR_DELAY_SECS: i64 = 30;
+// Held by the cerotis instance sweeping stale games
+pub const GAME_REAPER_LOCK_KEY: &str = "GameReaperLock";
+// Lobby join codes, code -> game_id, and the failed code lookups of each user in the current window
+pub const LOBBY_JOIN_CODE_KEY: &str = "LobbyJoinCode_";
+pub const JOIN_CODE_ATTEMPTS_KEY: &str = "JoinCodeAttempts_";
+// Chat moderation. Messages sent in the current rate limit window, users timed out by a moderator, and the users each user muted
+pub const CHAT_RATE_LIMIT_KEY: &str = "ChatRateLimit_";
+pub const CHAT_TIMEOUT_KEY: &str = "ChatTimeout_";
+pub const CHAT_MUTED_USERS_KEY: &str = "ChatMutedUsers_";
+// Matchmaking. Sorted set of the queued users of a pool by rating, the queue entry of each user, the set of pools
+// with queued users, the lock of the matcher instance pairing a pool and the recent opponents of each user
+pub const MATCHMAKING_QUEUE_KEY: &str = "MatchmakingQueue_";
+pub const MATCHMAKING_ENTRY_KEY: &str = "MatchmakingEntry_";
+pub const MATCHMAKING_POOLS_KEY: &str = "MatchmakingPools";
+pub const MATCHMAKING_LOCK_KEY: &str = "MatchmakingLock_";
+pub const MATCHMAKING_RECENT_OPPONENTS_KEY: &str = "MatchmakingRecentOpponents_";
+// Leaderboards. Sorted sets of user_id by rating, or by rating gained for weekly and monthly ones, and the lock of
+// the cerotis instance rebuilding them from Postgres for a day
+pub const LEADERBOARD_KEY: &str = "Leaderboard_";
+pub const LEADERBOARD_REBUILD_LOCK_KEY: &str = "LeaderboardRebuildLock_";
+
+// Player status in UserTurnMapping
+pub const PLAYER_ACTIVE: &str = "active";
+pub const PLAYER_ELIMINATED: &str = "eliminated";
+pub const PLAYER_DISCONNECTED: &str = "disconnected";
+
+// Game.description values
+pub const GAME_LOBBY_STATUS: &str = "LOBBY";
+pub const GAME_IN_PROGRESS_STATUS: &str = "IN_PROGRESS";
+pub const GAME_OVER_STATUS: &str = "GAME_OVER";
+// Staked game or rematch waiting for the players to bet
+pub const GAME_AWAITING_BETS_STATUS: &str = "AWAITING_BETS";
+
+// UserGameRelation.player_status values while in the lobby
+pub const PLAYER_READY: &str = "ready";
+pub const PLAYER_NOT_READY: &str = "not-ready";
+
+// Chat channels of a game. Spectators have their own so they cannot coach the players
+pub const CHAT_PLAYERS_CHANNEL: &str = "players";
+pub const CHAT_SPECTATORS_CHANNEL: &str = "spectators";
+
+// Game invite status
+pub const GAME_INVITE_PENDING: &str = "pending";
+pub const GAME_INVITE_ACCEPTED: &str = "accepted";
+pub const GAME_INVITE_DECLINED: &str = "declined";
@@ -0,0 +1,226 @@
+use crate::{constants::CHECKERS_STATE_REDIS_KEY, models::{checkers_events::CheckersMoveEvent, checkers_model::CheckersState, user_turn_model::UserTurnMapping}};
+
+use super::{EngineAction, EngineError, GameEngine, GameResult};
+
+pub mod pdn;
+pub mod rules;
+
+use rules::{CheckersMove, CheckersPosition, Variant, BLACK, WHITE};
+
+
+// Draughts played like chess: two players, colour taken from player_type, state kept in redis under CheckersState_<game_id>
+pub struct CheckersEngine {
+    pub variant: Variant,
+}
+
+impl CheckersEngine {
+    pub fn english() -> Self {
+        CheckersEngine { variant: Variant::English }
+    }
+
+    pub fn international() -> Self {
+        CheckersEngine { variant: Variant::International }
+    }
+
+    fn parse_move(&self, state: &CheckersState, action: &EngineAction) -> Result<(CheckersPosition, CheckersMove), EngineError> {
+        if action.action_type != "move" {
+            return Err(EngineError::InvalidAction(format!("Unknown checkers action {}", action.action_type)))
+        }
+
+        let move_event: CheckersMoveEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid move payload".to_string()))?;
+        let position = position(state)?;
+
+        let checkers_move = position.legal_moves().into_iter().find(|legal_move| legal_move.path == move_event.path).ok_or_else(|| {
+            let notation = move_event.path.iter().map(|square| square.to_string()).collect::<Vec<String>>().join("-");
+            EngineError::IllegalAction(format!("Move {} is not valid for the current position", notation))
+        })?;
+
+        Ok((position, checkers_move))
+    }
+}
+
+impl GameEngine for CheckersEngine {
+    type State = CheckersState;
+
+    fn game_type(&self) -> &'static str {
+        match self.variant {
+            Variant::English => "checkers",
+            Variant::International => "international_draughts",
+        }
+    }
+
+    fn state_key_prefix(&self) -> &'static str {
+        CHECKERS_STATE_REDIS_KEY
+    }
+
+    fn player_types(&self) -> Vec<&'static str> {
+        vec!["white", "black"]
+    }
+
+    fn initial_state(&self, turn_mapping: &UserTurnMapping, _seed: u64) -> Result<Self::State, EngineError> {
+        if turn_mapping.turn_mappings.len() != 2 {
+            return Err(EngineError::InvalidPlayerCount { min: 2, max: 2, found: turn_mapping.turn_mappings.len() })
+        }
+
+        let fen = CheckersPosition::starting(self.variant).to_fen();
+        Ok(CheckersState {
+            variant: self.variant.name().to_string(),
+            initial_fen: fen.clone(),
+            fen: fen.clone(),
+            repetition_fens: vec![fen],
+            ..Default::default()
+        })
+    }
+
+    fn validate_action(&self, state: &Self::State, action: &EngineAction) -> Result<(), EngineError> {
+        // Same as chess, a user without a colour from UserGameRelation cannot move
+        let player_color = match action.player_type.as_str() {
+            "white" => WHITE,
+            "black" => BLACK,
+            other => return Err(EngineError::InvalidAction(format!("Unknown player type {:?}", other))),
+        };
+
+        if player_color != position(state)?.side_to_move {
+            return Err(EngineError::NotPlayersTurn)
+        }
+
+        self.parse_move(state, action).map(|_| ())
+    }
+
+    fn apply_action(&self, state: &Self::State, action: &EngineAction) -> Result<Self::State, EngineError> {
+        let (position, checkers_move) = self.parse_move(state, action)?;
+        Ok(record_move(state, &position, &checkers_move))
+    }
+
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        self.result(state).is_some()
+    }
+
+    fn result(&self, state: &Self::State) -> Option<GameResult> {
+        let position = position(state).ok()?;
+
+        // A side without pieces or without a legal move loses
+        if position.legal_moves().is_empty() {
+            let (winner, loser) = if position.side_to_move == WHITE { ("black", "white") } else { ("white", "black") };
+            return Some(GameResult::win(winner, loser, "no_moves"))
+        }
+
+        if state.repetition_fens.iter().filter(|fen| **fen == state.fen).count() >= 3 {
+            return Some(GameResult::draw(&["white", "black"], "threefold_repetition"))
+        }
+
+        if state.quiet_plies >= position.variant.move_limit_plies() {
+            return Some(GameResult::draw(&["white", "black"], "move_limit"))
+        }
+
+        None
+    }
+
+    fn serialize_state(&self, state: &Self::State) -> Result<String, EngineError> {
+        serde_json::to_string(state).map_err(|err| EngineError::InvalidState(err.to_string()))
+    }
+
+    fn deserialize_state(&self, raw_state: &str) -> Result<Self::State, EngineError> {
+        serde_json::from_str(raw_state).map_err(|err| EngineError::InvalidState(err.to_string()))
+    }
+}
+
+
+pub fn position(state: &CheckersState) -> Result<CheckersPosition, EngineError> {
+    let variant = Variant::from_name(&state.variant).ok_or(EngineError::InvalidState(format!("Unknown checkers variant {}", state.variant)))?;
+    CheckersPosition::from_fen(variant, &state.fen).ok_or(EngineError::InvalidState(format!("Invalid FEN {}", state.fen)))
+}
+
+// Captures and man moves can never be undone, so the repetition history and the move limit start again after them
+fn record_move(state: &CheckersState, position: &CheckersPosition, checkers_move: &CheckersMove) -> CheckersState {
+    let mut state = state.clone();
+    let fen = position.make_move(checkers_move).to_fen();
+
+    state.moves.push(checkers_move.to_notation());
+    if checkers_move.is_capture() || position.is_man_move(checkers_move) {
+        state.repetition_fens = vec![fen.clone()];
+        state.quiet_plies = 0;
+    } else {
+        state.repetition_fens.push(fen.clone());
+        state.quiet_plies += 1;
+    }
+    state.fen = fen;
+
+    state
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::models::user_turn_model::TurnModel;
+
+    use super::*;
+
+    fn turn_mapping() -> UserTurnMapping {
+        UserTurnMapping {
+            host_id: "a".to_string(),
+            game_id: "checkers-test".to_string(),
+            turn_mappings: ["a", "b"].iter().enumerate().map(|(seat, user_id)| TurnModel {
+                count_id: seat as i64,
+                user_id: user_id.to_string(),
+                username: user_id.to_string(),
+                status: "".to_string(),
+            }).collect(),
+        }
+    }
+
+    fn move_action(player_type: &str, path: &str) -> EngineAction {
+        EngineAction { user_id: player_type.to_string(), player_type: player_type.to_string(), action_type: "move".to_string(), payload: format!(r#"{{"path":{}}}"#, path), received_at: 0 }
+    }
+
+    fn state_at(variant: Variant, fen: &str) -> CheckersState {
+        CheckersState { variant: variant.name().to_string(), initial_fen: fen.to_string(), fen: fen.to_string(), repetition_fens: vec![fen.to_string()], ..Default::default() }
+    }
+
+    #[test]
+    fn turns_follow_the_colour_of_the_player() {
+        let engine = CheckersEngine::international();
+        let state = engine.initial_state(&turn_mapping(), 0).unwrap();
+
+        assert!(matches!(engine.validate_action(&state, &move_action("black", "[17,21]")), Err(EngineError::NotPlayersTurn)));
+        assert!(matches!(engine.validate_action(&state, &move_action("", "[31,26]")), Err(EngineError::InvalidAction(_))));
+        assert!(matches!(engine.validate_action(&state, &move_action("white", "[31,25]")), Err(EngineError::IllegalAction(_))));
+        assert!(engine.validate_action(&state, &move_action("white", "[31,26]")).is_ok());
+
+        let state = engine.apply_action(&state, &move_action("white", "[31,26]")).unwrap();
+        assert_eq!(state.moves, vec!["31-26"]);
+        assert_eq!(position(&state).unwrap().side_to_move, BLACK);
+    }
+
+    #[test]
+    fn side_without_moves_loses() {
+        let engine = CheckersEngine::international();
+        let state = engine.apply_action(&state_at(Variant::International, "W:W28:B33"), &move_action("white", "[28,39]")).unwrap();
+
+        assert_eq!(state.fen, "B:W39:B");
+        let result = engine.result(&state).unwrap();
+        assert_eq!(result.winner.as_deref(), Some("white"));
+        assert_eq!(result.reason, "no_moves");
+    }
+
+    #[test]
+    fn king_moves_count_towards_the_draw() {
+        let engine = CheckersEngine::international();
+        let mut state = state_at(Variant::International, "W:WK46:BK5");
+        state.quiet_plies = 49;
+        assert!(!engine.is_terminal(&state));
+
+        let state = engine.apply_action(&state, &move_action("white", "[46,41]")).unwrap();
+        assert_eq!(engine.result(&state).unwrap().reason, "move_limit");
+
+        // The same position coming back a third time is a draw as well
+        let engine = CheckersEngine::english();
+        let mut state = state_at(Variant::English, "W:WK29:BK4");
+        for (player_type, path) in [("white", "[29,25]"), ("black", "[4,8]"), ("white", "[25,29]"), ("black", "[8,4]")].iter().cycle().take(8) {
+            assert!(!engine.is_terminal(&state));
+            state = engine.apply_action(&state, &move_action(player_type, path)).unwrap();
+        }
+        assert_eq!(engine.result(&state).unwrap().reason, "threefold_repetition");
+        assert_eq!(state.quiet_plies, 8);
+    }
+}
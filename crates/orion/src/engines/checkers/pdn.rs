@@ -0,0 +1,201 @@
+use crate::{engines::{EngineError, GameResult}, models::checkers_model::CheckersState};
+
+use super::{position, record_move, rules::{opposite, CheckersPosition, Variant, WHITE}};
+
+const RESULT_TOKENS: [&str; 7] = ["*", "1-0", "0-1", "1/2-1/2", "2-0", "0-2", "1-1"];
+
+
+// PDN GameType tag values
+fn game_type_tag(variant: Variant) -> &'static str {
+    match variant {
+        Variant::English => "21",
+        Variant::International => "20",
+    }
+}
+
+// PDN results are written from the point of view of the side that moves first in the variant
+fn result_text(variant: Variant, result: Option<&GameResult>) -> String {
+    let (win, draw, loss) = match variant {
+        Variant::English => ("1-0", "1/2-1/2", "0-1"),
+        Variant::International => ("2-0", "1-1", "0-2"),
+    };
+    let first_color = if variant.first_to_move() == WHITE { "white" } else { "black" };
+
+    match result {
+        None => "*".to_string(),
+        Some(GameResult { winner: None, .. }) => draw.to_string(),
+        Some(GameResult { winner: Some(winner), .. }) => if winner == first_color { win.to_string() } else { loss.to_string() },
+    }
+}
+
+// Full PDN document of a game: tags, numbered move text and the result
+pub fn export_pdn(state: &CheckersState, result: Option<&GameResult>) -> Result<String, EngineError> {
+    let variant = position(state)?.variant;
+    let result = result_text(variant, result);
+
+    let mut pdn = format!("[GameType \"{}\"]\n[Result \"{}\"]\n", game_type_tag(variant), result);
+    if state.initial_fen != CheckersPosition::starting(variant).to_fen() {
+        pdn.push_str(&format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", state.initial_fen));
+    }
+    pdn.push('\n');
+
+    let initial_side = CheckersPosition::from_fen(variant, &state.initial_fen).map(|position| position.side_to_move).unwrap_or(variant.first_to_move());
+    let mut side = initial_side;
+    let mut move_number = 1;
+    let mut move_text: Vec<String> = vec![];
+
+    for (index, notation) in state.moves.iter().enumerate() {
+        if side == variant.first_to_move() {
+            move_text.push(format!("{}.", move_number));
+        } else if index == 0 {
+            move_text.push(format!("{}...", move_number));
+        }
+        move_text.push(notation.clone());
+
+        if side != variant.first_to_move() {
+            move_number += 1;
+        }
+        side = opposite(side);
+    }
+    move_text.push(result);
+
+    pdn.push_str(&move_text.join(" "));
+    pdn.push('\n');
+    Ok(pdn)
+}
+
+
+// Loads a single PDN game. Moves are replayed through the rules so an illegal game is rejected.
+// Captures may be written with every landing square ("22x15x8") or only the first and last one ("22x8")
+pub fn import_pdn(pdn: &str) -> Result<CheckersState, EngineError> {
+    let mut variant = Variant::English;
+    let mut fen: Option<String> = None;
+    let mut move_text = String::new();
+
+    for line in pdn.lines().map(|line| line.trim()) {
+        if let Some(tag) = line.strip_prefix('[').and_then(|tag| tag.strip_suffix(']')) {
+            let Some((name, value)) = tag.split_once(' ') else { continue };
+            let value = value.trim().trim_matches('"');
+
+            match name {
+                "GameType" => {
+                    variant = match value.split(',').next().unwrap_or("") {
+                        "20" => Variant::International,
+                        "21" => Variant::English,
+                        other => return Err(EngineError::InvalidState(format!("Unsupported PDN game type {}", other))),
+                    };
+                },
+                "FEN" => fen = Some(value.to_string()),
+                _ => {},
+            }
+        } else {
+            move_text.push_str(line);
+            move_text.push(' ');
+        }
+    }
+
+    let initial_position = match &fen {
+        Some(fen) => CheckersPosition::from_fen(variant, fen).ok_or(EngineError::InvalidState(format!("Invalid FEN {}", fen)))?,
+        None => CheckersPosition::starting(variant),
+    };
+    let initial_fen = initial_position.to_fen();
+
+    let mut state = CheckersState {
+        variant: variant.name().to_string(),
+        initial_fen: initial_fen.clone(),
+        fen: initial_fen.clone(),
+        repetition_fens: vec![initial_fen],
+        ..Default::default()
+    };
+
+    for token in strip_comments(&move_text).split_whitespace() {
+        // Move numbers can be glued to the move ("1.11-15") or stand alone ("1." / "1...")
+        let token = token.rsplit('.').next().unwrap_or("");
+        let token = if token.is_empty() || RESULT_TOKENS.contains(&token) { continue } else { token };
+
+        let current_position = position(&state)?;
+        let squares: Vec<usize> = token.split(['-', 'x']).map(|square| square.parse::<usize>()).collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| EngineError::InvalidAction(format!("Invalid PDN move {}", token)))?;
+
+        let legal_moves = current_position.legal_moves();
+        let mut candidates: Vec<_> = legal_moves.iter().filter(|legal_move| legal_move.path == squares).collect();
+        if candidates.is_empty() && squares.len() == 2 {
+            candidates = legal_moves.iter().filter(|legal_move| legal_move.from_square() == squares[0] && legal_move.to_square() == squares[1]).collect();
+        }
+
+        match candidates.as_slice() {
+            [checkers_move] => state = record_move(&state, &current_position, checkers_move),
+            [] => return Err(EngineError::IllegalAction(format!("PDN move {} is not valid", token))),
+            _ => return Err(EngineError::InvalidAction(format!("PDN move {} is ambiguous", token))),
+        }
+    }
+
+    Ok(state)
+}
+
+fn strip_comments(text: &str) -> String {
+    let mut depth = 0;
+    text.chars().filter(|c| {
+        match c {
+            '{' | '(' => depth += 1,
+            '}' | ')' => depth -= 1,
+            _ => return depth == 0,
+        }
+        false
+    }).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::engines::{checkers::CheckersEngine, GameEngine};
+
+    use super::*;
+
+    #[test]
+    fn imports_and_exports_an_english_game() {
+        let pdn = "[Event \"Club night\"]\n[GameType \"21\"]\n\n1. 11-15 23-19 {book} 2.8-11 22-17 (2... 9-13) *\n";
+        let state = import_pdn(pdn).unwrap();
+
+        assert_eq!(state.variant, "english");
+        assert_eq!(state.moves, vec!["11-15", "23-19", "8-11", "22-17"]);
+        assert_eq!(state.fen, "B:W17,19,21,24,25,26,27,28,29,30,31,32:B1,2,3,4,5,6,7,9,10,11,12,15");
+
+        let exported = export_pdn(&state, None).unwrap();
+        assert_eq!(exported, "[GameType \"21\"]\n[Result \"*\"]\n\n1. 11-15 23-19 2. 8-11 22-17 *\n");
+        assert_eq!(import_pdn(&exported).unwrap().fen, state.fen);
+    }
+
+    #[test]
+    fn imports_international_captures_written_from_first_to_last_square() {
+        let pdn = "[GameType \"20\"]\n[FEN \"W:W28:B12,22,33\"]\n1. 28x8 *";
+        let state = import_pdn(pdn).unwrap();
+
+        assert_eq!(state.variant, "international");
+        assert_eq!(state.initial_fen, "W:W28:B12,22,33");
+        assert_eq!(state.moves, vec!["28x17x8"]);
+        assert_eq!(state.fen, "B:W8:B33");
+    }
+
+    #[test]
+    fn exports_a_set_up_position_with_the_result() {
+        let state = import_pdn("[GameType \"20\"]\n[FEN \"B:W28:B33\"]\n1... 33x22").unwrap();
+        let engine = CheckersEngine::international();
+        let result = engine.result(&state).unwrap();
+
+        assert_eq!(result.winner.as_deref(), Some("black"));
+        assert_eq!(
+            export_pdn(&state, Some(&result)).unwrap(),
+            "[GameType \"20\"]\n[Result \"0-2\"]\n[SetUp \"1\"]\n[FEN \"B:W28:B33\"]\n\n1... 33x22 0-2\n",
+        );
+    }
+
+    #[test]
+    fn rejects_illegal_games() {
+        assert!(matches!(import_pdn("[GameType \"21\"]\n1. 11-14 *"), Err(EngineError::IllegalAction(_))));
+        assert!(matches!(import_pdn("[GameType \"21\"]\n1. 11-15 11-15 *"), Err(EngineError::IllegalAction(_))));
+        assert!(matches!(import_pdn("[GameType \"21\"]\n1. 11to15 *"), Err(EngineError::InvalidAction(_))));
+        assert!(matches!(import_pdn("[GameType \"25\"]\n1. 11-15 *"), Err(EngineError::InvalidState(_))));
+        assert!(matches!(import_pdn("[GameType \"20\"]\n[FEN \"W:W51:B1\"]\n*"), Err(EngineError::InvalidState(_))));
+    }
+}
@@ -0,0 +1,482 @@
+pub const BLACK: char = 'b';
+pub const WHITE: char = 'w';
+const EMPTY: char = '.';
+
+const DIRECTIONS: [(i32, i32); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    // 8x8, black moves first, men capture forward only, kings move one square
+    English,
+    // 10x10, white moves first, men capture backwards, flying kings, the longest capture is mandatory
+    International,
+}
+
+impl Variant {
+    pub fn from_name(name: &str) -> Option<Variant> {
+        match name {
+            "english" => Some(Variant::English),
+            "international" => Some(Variant::International),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::English => "english",
+            Variant::International => "international",
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            Variant::English => 8,
+            Variant::International => 10,
+        }
+    }
+
+    pub fn first_to_move(&self) -> char {
+        match self {
+            Variant::English => BLACK,
+            Variant::International => WHITE,
+        }
+    }
+
+    // Number of rows filled with men at the start, for each side
+    fn starting_rows(&self) -> usize {
+        match self {
+            Variant::English => 3,
+            Variant::International => 4,
+        }
+    }
+
+    fn is_international(&self) -> bool {
+        *self == Variant::International
+    }
+
+    // Plies without a capture or a man move after which the game is drawn (40 moves each / 25 moves each)
+    pub fn move_limit_plies(&self) -> u32 {
+        match self {
+            Variant::English => 80,
+            Variant::International => 50,
+        }
+    }
+}
+
+
+// A move as the list of squares the piece stops on (PDN numbering, starting at 1) and the squares of the captured pieces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckersMove {
+    pub path: Vec<usize>,
+    pub captures: Vec<usize>,
+}
+
+impl CheckersMove {
+    pub fn from_square(&self) -> usize {
+        self.path[0]
+    }
+
+    pub fn to_square(&self) -> usize {
+        self.path[self.path.len() - 1]
+    }
+
+    pub fn is_capture(&self) -> bool {
+        !self.captures.is_empty()
+    }
+
+    // PDN move text: "11-15" for a normal move, "22x15x8" for a capture with every landing square
+    pub fn to_notation(&self) -> String {
+        let separator = if self.is_capture() { "x" } else { "-" };
+        self.path.iter().map(|square| square.to_string()).collect::<Vec<String>>().join(separator)
+    }
+}
+
+
+pub fn color_of(piece: char) -> Option<char> {
+    match piece {
+        'b' | 'B' => Some(BLACK),
+        'w' | 'W' => Some(WHITE),
+        _ => None,
+    }
+}
+
+pub fn opposite(color: char) -> char {
+    if color == WHITE { BLACK } else { WHITE }
+}
+
+fn is_king(piece: char) -> bool {
+    piece.is_ascii_uppercase()
+}
+
+
+// Board is a size x size grid with row 0 at black's side. Only the dark squares (PDN squares) are used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckersPosition {
+    pub variant: Variant,
+    pub board: Vec<char>,
+    pub side_to_move: char,
+}
+
+impl CheckersPosition {
+    pub fn starting(variant: Variant) -> CheckersPosition {
+        let size = variant.size();
+        let squares_per_row = size / 2;
+        let total_squares = size * squares_per_row;
+        let rows = variant.starting_rows();
+
+        let mut position = CheckersPosition { variant, board: vec![EMPTY; size * size], side_to_move: variant.first_to_move() };
+        for square in 1..=(rows * squares_per_row) {
+            position.set(square, 'b');
+        }
+        for square in (total_squares - rows * squares_per_row + 1)..=total_squares {
+            position.set(square, 'w');
+        }
+        position
+    }
+
+    pub fn square_count(&self) -> usize {
+        self.variant.size() * self.variant.size() / 2
+    }
+
+    // PDN numbering: square 1 is the first dark square of black's back row, counting left to right, row by row
+    pub fn coords(&self, square: usize) -> Option<(usize, usize)> {
+        if square == 0 || square > self.square_count() {
+            return None;
+        }
+
+        let squares_per_row = self.variant.size() / 2;
+        let row = (square - 1) / squares_per_row;
+        let index = (square - 1) % squares_per_row;
+        let col = if row.is_multiple_of(2) { index * 2 + 1 } else { index * 2 };
+        Some((row, col))
+    }
+
+    pub fn square_at(&self, row: usize, col: usize) -> usize {
+        row * (self.variant.size() / 2) + col / 2 + 1
+    }
+
+    pub fn piece_at(&self, square: usize) -> char {
+        match self.coords(square) {
+            Some((row, col)) => self.board[row * self.variant.size() + col],
+            None => EMPTY,
+        }
+    }
+
+    fn set(&mut self, square: usize, piece: char) {
+        if let Some((row, col)) = self.coords(square) {
+            let size = self.variant.size();
+            self.board[row * size + col] = piece;
+        }
+    }
+
+    fn grid(&self, row: i32, col: i32) -> Option<char> {
+        let size = self.variant.size() as i32;
+        if row < 0 || col < 0 || row >= size || col >= size {
+            return None;
+        }
+        Some(self.board[(row * size + col) as usize])
+    }
+
+    fn forward(color: char) -> i32 {
+        if color == BLACK { 1 } else { -1 }
+    }
+
+    fn promotion_row(&self, color: char) -> usize {
+        if color == BLACK { self.variant.size() - 1 } else { 0 }
+    }
+
+    // PDN FEN, e.g. "B:W21,22,23,K5:B1,2,3"
+    pub fn from_fen(variant: Variant, fen: &str) -> Option<CheckersPosition> {
+        let mut parts = fen.trim().trim_end_matches('.').split(':');
+        let side_to_move = match parts.next()?.trim() {
+            "W" => WHITE,
+            "B" => BLACK,
+            _ => return None,
+        };
+
+        let mut position = CheckersPosition { variant, board: vec![EMPTY; variant.size() * variant.size()], side_to_move };
+
+        for part in parts {
+            let part = part.trim();
+            let color = match part.chars().next()? {
+                'W' => WHITE,
+                'B' => BLACK,
+                _ => return None,
+            };
+
+            for token in part[1..].split(',').map(|token| token.trim()).filter(|token| !token.is_empty()) {
+                let (king, squares) = match token.strip_prefix('K') {
+                    Some(squares) => (true, squares),
+                    None => (false, token),
+                };
+
+                let (first, last) = match squares.split_once('-') {
+                    Some((first, last)) => (first.parse::<usize>().ok()?, last.parse::<usize>().ok()?),
+                    None => {
+                        let square = squares.parse::<usize>().ok()?;
+                        (square, square)
+                    },
+                };
+
+                for square in first..=last {
+                    position.coords(square)?;
+                    let piece = if king { color.to_ascii_uppercase() } else { color };
+                    position.set(square, piece);
+                }
+            }
+        }
+
+        Some(position)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let side = |color: char| -> String {
+            (1..=self.square_count())
+                .filter(|square| color_of(self.piece_at(*square)) == Some(color))
+                .map(|square| if is_king(self.piece_at(square)) { format!("K{}", square) } else { square.to_string() })
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+
+        let turn = if self.side_to_move == WHITE { "W" } else { "B" };
+        format!("{}:W{}:B{}", turn, side(WHITE), side(BLACK))
+    }
+
+    // Captures are mandatory. In international draughts the sequence taking the most pieces has to be played
+    pub fn legal_moves(&self) -> Vec<CheckersMove> {
+        let pieces: Vec<usize> = (1..=self.square_count()).filter(|square| color_of(self.piece_at(*square)) == Some(self.side_to_move)).collect();
+
+        let mut captures: Vec<CheckersMove> = pieces.iter().flat_map(|square| self.captures_from(*square)).collect();
+        if !captures.is_empty() {
+            if self.variant.is_international() {
+                let most_captures = captures.iter().map(|capture| capture.captures.len()).max().unwrap_or(0);
+                captures.retain(|capture| capture.captures.len() == most_captures);
+            }
+            return captures;
+        }
+
+        pieces.iter().flat_map(|square| self.simple_moves_from(*square)).collect()
+    }
+
+    pub fn is_legal(&self, checkers_move: &CheckersMove) -> bool {
+        self.legal_moves().iter().any(|legal_move| legal_move.path == checkers_move.path)
+    }
+
+    fn simple_moves_from(&self, square: usize) -> Vec<CheckersMove> {
+        let piece = self.piece_at(square);
+        let color = color_of(piece).unwrap_or(WHITE);
+        let Some((row, col)) = self.coords(square) else { return vec![] };
+        let flying = is_king(piece) && self.variant.is_international();
+
+        let mut moves = vec![];
+        for (dr, dc) in DIRECTIONS {
+            if !is_king(piece) && dr != Self::forward(color) {
+                continue;
+            }
+
+            let (mut r, mut c) = (row as i32 + dr, col as i32 + dc);
+            while self.grid(r, c) == Some(EMPTY) {
+                moves.push(CheckersMove { path: vec![square, self.square_at(r as usize, c as usize)], captures: vec![] });
+                if !flying {
+                    break;
+                }
+                r += dr;
+                c += dc;
+            }
+        }
+        moves
+    }
+
+    fn captures_from(&self, square: usize) -> Vec<CheckersMove> {
+        let piece = self.piece_at(square);
+        let Some((row, col)) = self.coords(square) else { return vec![] };
+
+        // The moving piece is lifted so it does not block its own path when a sequence passes its starting square
+        let mut lifted = self.clone();
+        lifted.set(square, EMPTY);
+
+        let mut results = vec![];
+        lifted.capture_sequences(piece, (row as i32, col as i32), vec![square], vec![], &mut results);
+        results
+    }
+
+    // Depth first search over capture sequences. Captured pieces stay on the board until the move is over,
+    // so they cannot be jumped twice and block the way
+    fn capture_sequences(&self, piece: char, from: (i32, i32), path: Vec<usize>, captured: Vec<usize>, results: &mut Vec<CheckersMove>) {
+        let color = color_of(piece).unwrap_or(WHITE);
+        let flying = is_king(piece) && self.variant.is_international();
+        let mut extended = false;
+
+        for (dr, dc) in DIRECTIONS {
+            if !is_king(piece) && !self.variant.is_international() && dr != Self::forward(color) {
+                continue;
+            }
+
+            let (mut r, mut c) = (from.0 + dr, from.1 + dc);
+            if flying {
+                while self.grid(r, c) == Some(EMPTY) {
+                    r += dr;
+                    c += dc;
+                }
+            }
+
+            let Some(target) = self.grid(r, c) else { continue };
+            let target_square = self.square_at(r as usize, c as usize);
+            if color_of(target) != Some(opposite(color)) || captured.contains(&target_square) {
+                continue;
+            }
+
+            let (mut land_r, mut land_c) = (r + dr, c + dc);
+            while self.grid(land_r, land_c) == Some(EMPTY) {
+                let landing = self.square_at(land_r as usize, land_c as usize);
+                let mut next_path = path.clone();
+                next_path.push(landing);
+                let mut next_captured = captured.clone();
+                next_captured.push(target_square);
+                extended = true;
+
+                // In english checkers a man reaching the last row is crowned and the move ends
+                let crowned = !is_king(piece) && land_r as usize == self.promotion_row(color);
+                if crowned && !self.variant.is_international() {
+                    results.push(CheckersMove { path: next_path, captures: next_captured });
+                } else {
+                    self.capture_sequences(piece, (land_r, land_c), next_path, next_captured, results);
+                }
+
+                if !flying {
+                    break;
+                }
+                land_r += dr;
+                land_c += dc;
+            }
+        }
+
+        if !extended && !captured.is_empty() {
+            results.push(CheckersMove { path, captures: captured });
+        }
+    }
+
+    // Men are only crowned when the move ends on the last row
+    pub fn make_move(&self, checkers_move: &CheckersMove) -> CheckersPosition {
+        let mut position = self.clone();
+        let piece = self.piece_at(checkers_move.from_square());
+        let color = color_of(piece).unwrap_or(WHITE);
+
+        position.set(checkers_move.from_square(), EMPTY);
+        for captured in &checkers_move.captures {
+            position.set(*captured, EMPTY);
+        }
+
+        let crowned = position.coords(checkers_move.to_square()).map(|(row, _)| row == self.promotion_row(color)).unwrap_or(false);
+        position.set(checkers_move.to_square(), if crowned { piece.to_ascii_uppercase() } else { piece });
+        position.side_to_move = opposite(color);
+        position
+    }
+
+    pub fn is_man_move(&self, checkers_move: &CheckersMove) -> bool {
+        !is_king(self.piece_at(checkers_move.from_square()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(position: &CheckersPosition) -> Vec<Vec<usize>> {
+        let mut paths: Vec<Vec<usize>> = position.legal_moves().into_iter().map(|legal_move| legal_move.path).collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn starting_positions() {
+        let english = CheckersPosition::starting(Variant::English);
+        assert_eq!(english.to_fen(), "B:W21,22,23,24,25,26,27,28,29,30,31,32:B1,2,3,4,5,6,7,8,9,10,11,12");
+        assert_eq!(english.legal_moves().len(), 7);
+
+        let international = CheckersPosition::starting(Variant::International);
+        assert_eq!(international.side_to_move, WHITE);
+        assert_eq!(international.legal_moves().len(), 9);
+        assert_eq!(international.piece_at(20), 'b');
+        assert_eq!(international.piece_at(31), 'w');
+        assert_eq!(international.piece_at(25), EMPTY);
+    }
+
+    #[test]
+    fn square_numbering_follows_pdn() {
+        let position = CheckersPosition::starting(Variant::International);
+        assert_eq!(position.coords(1), Some((0, 1)));
+        assert_eq!(position.coords(6), Some((1, 0)));
+        assert_eq!(position.coords(28), Some((5, 4)));
+        assert_eq!(position.coords(50), Some((9, 8)));
+        assert_eq!(position.coords(51), None);
+        assert_eq!(position.square_at(5, 4), 28);
+    }
+
+    #[test]
+    fn fen_round_trips_with_kings_and_ranges() {
+        let position = CheckersPosition::from_fen(Variant::English, "W:W21-23,K5:B1,K30.").unwrap();
+        assert_eq!(position.side_to_move, WHITE);
+        assert_eq!(position.piece_at(22), 'w');
+        assert_eq!(position.piece_at(5), 'W');
+        assert_eq!(position.piece_at(30), 'B');
+        assert_eq!(position.to_fen(), "W:WK5,21,22,23:B1,K30");
+
+        assert!(CheckersPosition::from_fen(Variant::English, "X:W21:B1").is_none());
+        assert!(CheckersPosition::from_fen(Variant::English, "W:W33:B1").is_none());
+    }
+
+    #[test]
+    fn only_international_men_capture_backwards() {
+        let international = CheckersPosition::from_fen(Variant::International, "W:W28:B33").unwrap();
+        assert_eq!(international.legal_moves(), vec![CheckersMove { path: vec![28, 39], captures: vec![33] }]);
+
+        let english = CheckersPosition::from_fen(Variant::English, "W:W18:B22").unwrap();
+        assert_eq!(paths(&english), vec![vec![18, 14], vec![18, 15]]);
+    }
+
+    #[test]
+    fn international_capture_taking_the_most_pieces_is_mandatory() {
+        let position = CheckersPosition::from_fen(Variant::International, "W:W28:B12,22,33").unwrap();
+        let legal_moves = position.legal_moves();
+
+        assert_eq!(legal_moves, vec![CheckersMove { path: vec![28, 17, 8], captures: vec![22, 12] }]);
+        assert_eq!(legal_moves[0].to_notation(), "28x17x8");
+        assert_eq!(position.make_move(&legal_moves[0]).to_fen(), "B:W8:B33");
+    }
+
+    #[test]
+    fn international_kings_fly() {
+        let position = CheckersPosition::from_fen(Variant::International, "W:WK46:B5").unwrap();
+        assert_eq!(paths(&position), vec![vec![46, 10], vec![46, 14], vec![46, 19], vec![46, 23], vec![46, 28], vec![46, 32], vec![46, 37], vec![46, 41]]);
+
+        // Any empty square behind the captured piece can be the landing square
+        let position = CheckersPosition::from_fen(Variant::International, "W:WK46:B28").unwrap();
+        assert_eq!(paths(&position), vec![vec![46, 5], vec![46, 10], vec![46, 14], vec![46, 19], vec![46, 23]]);
+        assert!(position.legal_moves().iter().all(|legal_move| legal_move.captures == vec![28]));
+
+        let english = CheckersPosition::from_fen(Variant::English, "B:WK29:B1").unwrap();
+        let english = CheckersPosition { side_to_move: WHITE, ..english };
+        assert_eq!(paths(&english), vec![vec![29, 25]]);
+    }
+
+    #[test]
+    fn men_are_crowned_on_the_last_row() {
+        let position = CheckersPosition::from_fen(Variant::International, "W:W6:B45").unwrap();
+        let crowning = CheckersMove { path: vec![6, 1], captures: vec![] };
+        assert!(position.is_legal(&crowning));
+        assert_eq!(position.make_move(&crowning).to_fen(), "B:WK1:B45");
+    }
+
+    #[test]
+    fn english_men_jump_forward_until_crowned() {
+        let position = CheckersPosition::from_fen(Variant::English, "W:W23:B11,19").unwrap();
+        assert_eq!(position.legal_moves(), vec![CheckersMove { path: vec![23, 16, 7], captures: vec![19, 11] }]);
+
+        // The new king on 3 could jump 8 as well, the move stops at the crowning instead
+        let position = CheckersPosition::from_fen(Variant::English, "W:W10:B7,8").unwrap();
+        assert_eq!(position.legal_moves(), vec![CheckersMove { path: vec![10, 3], captures: vec![7] }]);
+    }
+}
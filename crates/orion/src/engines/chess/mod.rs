@@ -0,0 +1,157 @@
+use crate::{constants::CHESS_STATE_REDIS_KEY, models::{chess_events::{CellPosition, ChessNormalEvent, ChessPromotionEvent}, user_turn_model::UserTurnMapping}};
+
+use super::{EngineAction, EngineError, GameEngine, GameResult};
+
+pub mod pgn;
+pub mod rules;
+
+use rules::{ChessMove, ChessPosition, Square, BLACK, WHITE};
+
+
+// Standard chess. State is kept in redis as a plain FEN string under ChessState_<game_id>
+pub struct ChessEngine;
+
+impl ChessEngine {
+    fn parse_move(&self, action: &EngineAction) -> Result<(ChessMove, char), EngineError> {
+        let (initial_cell, target_cell, piece, promoted_to) = if action.action_type == "normal" {
+            let gm_ev: ChessNormalEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid move payload".to_string()))?;
+            (gm_ev.initial_cell, gm_ev.target_cell, gm_ev.piece, None)
+        } else {
+            let gm_ev: ChessPromotionEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid promotion payload".to_string()))?;
+            (gm_ev.initial_cell, gm_ev.target_cell, gm_ev.piece, Some(gm_ev.promoted_to))
+        };
+
+        let from = parse_cell(&initial_cell)?;
+        let to = parse_cell(&target_cell)?;
+        let piece = piece.chars().next().ok_or(EngineError::InvalidAction("Missing piece".to_string()))?;
+        let promotion = match promoted_to {
+            Some(promoted) => {
+                let promoted = promoted.chars().next().ok_or(EngineError::InvalidAction("Missing promotion piece".to_string()))?;
+                if !"qrbnQRBN".contains(promoted) {
+                    return Err(EngineError::InvalidAction(format!("Cannot promote to {}", promoted)))
+                }
+                Some(promoted)
+            },
+            None => None,
+        };
+
+        Ok((ChessMove { from, to, promotion }, piece))
+    }
+}
+
+impl GameEngine for ChessEngine {
+    type State = ChessPosition;
+
+    fn game_type(&self) -> &'static str {
+        "chess"
+    }
+
+    fn state_key_prefix(&self) -> &'static str {
+        CHESS_STATE_REDIS_KEY
+    }
+
+    fn player_types(&self) -> Vec<&'static str> {
+        vec!["white", "black"]
+    }
+
+    fn initial_state(&self, turn_mapping: &UserTurnMapping, _seed: u64) -> Result<Self::State, EngineError> {
+        if turn_mapping.turn_mappings.len() != 2 {
+            return Err(EngineError::InvalidPlayerCount { min: 2, max: 2, found: turn_mapping.turn_mappings.len() })
+        }
+
+        self.deserialize_state(ChessPosition::STARTING_FEN)
+    }
+
+    fn validate_action(&self, state: &Self::State, action: &EngineAction) -> Result<(), EngineError> {
+        // player_type is the colour stored in UserGameRelation, anyone without one cannot move
+        let player_color = match action.player_type.as_str() {
+            "white" => WHITE,
+            "black" => BLACK,
+            other => return Err(EngineError::InvalidAction(format!("Unknown player type {:?}", other))),
+        };
+
+        if player_color != state.active_color {
+            return Err(EngineError::NotPlayersTurn)
+        }
+
+        let (chess_move, piece) = self.parse_move(action)?;
+        let board_piece = state.piece_at(chess_move.from);
+
+        if rules::color_of(board_piece) != Some(state.active_color) {
+            return Err(EngineError::IllegalAction(format!("No piece of the side to move on {}", chess_move.from.to_algebraic())))
+        }
+
+        if !board_piece.eq_ignore_ascii_case(&piece) {
+            return Err(EngineError::IllegalAction(format!("Piece {} is not on {}", piece, chess_move.from.to_algebraic())))
+        }
+
+        // Normalise the promotion piece to the mover's colour before comparing with generated moves
+        let chess_move = ChessMove {
+            promotion: chess_move.promotion.map(|promoted| if state.active_color == WHITE { promoted.to_ascii_uppercase() } else { promoted.to_ascii_lowercase() }),
+            ..chess_move
+        };
+
+        if !state.is_legal(&chess_move) {
+            return Err(EngineError::IllegalAction(format!("Move {}{} is not valid for the current position", chess_move.from.to_algebraic(), chess_move.to.to_algebraic())))
+        }
+
+        Ok(())
+    }
+
+    fn apply_action(&self, state: &Self::State, action: &EngineAction) -> Result<Self::State, EngineError> {
+        let (chess_move, _) = self.parse_move(action)?;
+        Ok(state.make_move(&chess_move))
+    }
+
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        self.result(state).is_some()
+    }
+
+    fn result(&self, state: &Self::State) -> Option<GameResult> {
+        if state.legal_moves().is_empty() {
+            if state.in_check(state.active_color) {
+                let winner = if state.active_color == WHITE { "black" } else { "white" };
+                let loser = if state.active_color == WHITE { "white" } else { "black" };
+                return Some(GameResult::win(winner, loser, "checkmate"))
+            }
+
+            return Some(GameResult::draw(&["white", "black"], "stalemate"))
+        }
+
+        if state.is_insufficient_material() {
+            return Some(GameResult::draw(&["white", "black"], "insufficient_material"))
+        }
+
+        // 75 move rule ends the game without any claim
+        if state.halfmove_clock >= 150 {
+            return Some(GameResult::draw(&["white", "black"], "seventy_five_move_rule"))
+        }
+
+        None
+    }
+
+    fn serialize_state(&self, state: &Self::State) -> Result<String, EngineError> {
+        Ok(state.to_fen())
+    }
+
+    fn deserialize_state(&self, raw_state: &str) -> Result<Self::State, EngineError> {
+        ChessPosition::from_fen(raw_state).ok_or(EngineError::InvalidState(format!("Invalid FEN {}", raw_state)))
+    }
+
+    fn move_notation(&self, state: &Self::State, action: &EngineAction) -> Option<String> {
+        let (chess_move, _) = self.parse_move(action).ok()?;
+        Some(state.to_san(&chess_move))
+    }
+}
+
+
+// Cells sent by the client are CellPosition json where x is the file and y = 0 is the eighth rank
+fn parse_cell(cell: &str) -> Result<Square, EngineError> {
+    let position: CellPosition = serde_json::from_str(cell).map_err(|_| EngineError::InvalidAction("Invalid cell".to_string()))?;
+
+    if !(0..8).contains(&position.x) || !(0..8).contains(&position.y) {
+        return Err(EngineError::InvalidAction(format!("Cell ({}, {}) is outside the board", position.x, position.y)))
+    }
+
+    Ok(Square { rank: (7 - position.y) as usize, file: position.x as usize })
+}
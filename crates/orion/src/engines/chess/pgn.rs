@@ -0,0 +1,59 @@
+use super::rules::{ChessPosition, WHITE};
+
+
+// Export format keeps movetext lines under 80 characters
+const PGN_LINE_LENGTH: usize = 79;
+
+
+// PGN of a game given its moves in SAN. tags are written in the given order and should start with the seven tag roster,
+// result is the game termination marker (1-0, 0-1, 1/2-1/2 or *)
+pub fn write_pgn(tags: &[(&str, String)], initial_fen: &str, moves: &[String], result: &str) -> String {
+    let mut pgn = String::new();
+
+    for (name, value) in tags {
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+    }
+
+    if initial_fen != ChessPosition::STARTING_FEN {
+        pgn.push_str("[SetUp \"1\"]\n");
+        pgn.push_str(&format!("[FEN \"{}\"]\n", initial_fen));
+    }
+    pgn.push('\n');
+
+    let (mut move_number, mut white_to_move) = ChessPosition::from_fen(initial_fen)
+        .map(|position| (position.fullmove_number, position.active_color == WHITE))
+        .unwrap_or((1, true));
+
+    let mut tokens = vec![];
+    for (index, san) in moves.iter().enumerate() {
+        if white_to_move {
+            tokens.push(format!("{}.", move_number));
+        } else if index == 0 {
+            tokens.push(format!("{}...", move_number));
+        }
+        tokens.push(san.clone());
+
+        if !white_to_move {
+            move_number += 1;
+        }
+        white_to_move = !white_to_move;
+    }
+    tokens.push(result.to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > PGN_LINE_LENGTH {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push('\n');
+
+    pgn
+}
@@ -0,0 +1,719 @@
+// Board is indexed as board[rank][file] where rank 0 is the first rank (white side) and ' ' is an empty square
+
+pub const WHITE: char = 'w';
+pub const BLACK: char = 'b';
+
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const KING_OFFSETS: [(i32, i32); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+const ROOK_DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Square {
+    pub rank: usize,
+    pub file: usize,
+}
+
+impl Square {
+    pub fn from_algebraic(notation: &str) -> Option<Square> {
+        let mut chars = notation.chars();
+        let file_char = chars.next()?;
+        let rank_char = chars.next()?;
+
+        if chars.next().is_some() || !('a'..='h').contains(&file_char) || !('1'..='8').contains(&rank_char) {
+            return None;
+        }
+
+        Some(Square { rank: (rank_char as u8 - b'1') as usize, file: (file_char as u8 - b'a') as usize })
+    }
+
+    pub fn to_algebraic(&self) -> String {
+        format!("{}{}", (b'a' + self.file as u8) as char, (b'1' + self.rank as u8) as char)
+    }
+
+    fn offset(&self, rank_delta: i32, file_delta: i32) -> Option<Square> {
+        let rank = self.rank as i32 + rank_delta;
+        let file = self.file as i32 + file_delta;
+
+        if !(0..8).contains(&rank) || !(0..8).contains(&file) {
+            return None;
+        }
+
+        Some(Square { rank: rank as usize, file: file as usize })
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChessMove {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<char>,
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChessPosition {
+    pub board: [[char; 8]; 8],
+    pub active_color: char,
+    pub castling_rights: String,
+    pub en_passant: String,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
+
+
+pub fn color_of(piece: char) -> Option<char> {
+    if piece == ' ' {
+        None
+    } else if piece.is_ascii_uppercase() {
+        Some(WHITE)
+    } else {
+        Some(BLACK)
+    }
+}
+
+pub fn opposite(color: char) -> char {
+    if color == WHITE { BLACK } else { WHITE }
+}
+
+fn piece_for(color: char, piece: char) -> char {
+    if color == WHITE { piece.to_ascii_uppercase() } else { piece.to_ascii_lowercase() }
+}
+
+
+impl ChessPosition {
+    pub const STARTING_FEN: &'static str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    pub fn from_fen(fen: &str) -> Option<ChessPosition> {
+        let parts: Vec<&str> = fen.split_whitespace().collect();
+        if parts.len() != 6 {
+            return None;
+        }
+
+        let active_color = parts[1].chars().next()?;
+        if active_color != WHITE && active_color != BLACK {
+            return None;
+        }
+
+        Some(ChessPosition {
+            board: parse_fen_board(parts[0])?,
+            active_color,
+            castling_rights: parts[2].to_string(),
+            en_passant: parts[3].to_string(),
+            halfmove_clock: parts[4].parse().ok()?,
+            fullmove_number: parts[5].parse().ok()?,
+        })
+    }
+
+    pub fn to_fen(&self) -> String {
+        format!("{} {} {} {} {} {}",
+            board_to_fen(&self.board),
+            self.active_color,
+            self.castling_rights,
+            self.en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+
+    pub fn piece_at(&self, square: Square) -> char {
+        self.board[square.rank][square.file]
+    }
+
+    pub fn king_square(&self, color: char) -> Option<Square> {
+        let king = piece_for(color, 'k');
+        for rank in 0..8 {
+            for file in 0..8 {
+                if self.board[rank][file] == king {
+                    return Some(Square { rank, file });
+                }
+            }
+        }
+        None
+    }
+
+    pub fn in_check(&self, color: char) -> bool {
+        match self.king_square(color) {
+            Some(square) => self.is_square_attacked(square, opposite(color)),
+            None => false,
+        }
+    }
+
+    pub fn is_square_attacked(&self, square: Square, by_color: char) -> bool {
+        // Pawns attack diagonally forward, so look one rank behind the square from the attacker's point of view
+        let pawn_rank_delta = if by_color == WHITE { -1 } else { 1 };
+        for file_delta in [-1, 1] {
+            if let Some(from) = square.offset(pawn_rank_delta, file_delta) {
+                if self.piece_at(from) == piece_for(by_color, 'p') {
+                    return true;
+                }
+            }
+        }
+
+        for (rank_delta, file_delta) in KNIGHT_OFFSETS {
+            if let Some(from) = square.offset(rank_delta, file_delta) {
+                if self.piece_at(from) == piece_for(by_color, 'n') {
+                    return true;
+                }
+            }
+        }
+
+        for (rank_delta, file_delta) in KING_OFFSETS {
+            if let Some(from) = square.offset(rank_delta, file_delta) {
+                if self.piece_at(from) == piece_for(by_color, 'k') {
+                    return true;
+                }
+            }
+        }
+
+        self.is_attacked_by_slider(square, by_color, &ROOK_DIRECTIONS, 'r')
+            || self.is_attacked_by_slider(square, by_color, &BISHOP_DIRECTIONS, 'b')
+    }
+
+    fn is_attacked_by_slider(&self, square: Square, by_color: char, directions: &[(i32, i32)], slider: char) -> bool {
+        for (rank_delta, file_delta) in directions {
+            let mut current = square;
+            while let Some(next) = current.offset(*rank_delta, *file_delta) {
+                let piece = self.piece_at(next);
+                if piece != ' ' {
+                    if piece == piece_for(by_color, slider) || piece == piece_for(by_color, 'q') {
+                        return true;
+                    }
+                    break;
+                }
+                current = next;
+            }
+        }
+        false
+    }
+
+    pub fn legal_moves(&self) -> Vec<ChessMove> {
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|chess_move| !self.make_move(chess_move).in_check(self.active_color))
+            .collect()
+    }
+
+    pub fn is_legal(&self, chess_move: &ChessMove) -> bool {
+        self.legal_moves().contains(chess_move)
+    }
+
+    fn pseudo_legal_moves(&self) -> Vec<ChessMove> {
+        let mut moves = vec![];
+        let color = self.active_color;
+
+        for rank in 0..8 {
+            for file in 0..8 {
+                let piece = self.board[rank][file];
+                if color_of(piece) != Some(color) {
+                    continue;
+                }
+
+                let from = Square { rank, file };
+                match piece.to_ascii_lowercase() {
+                    'p' => self.pawn_moves(from, &mut moves),
+                    'n' => self.step_moves(from, &KNIGHT_OFFSETS, &mut moves),
+                    'b' => self.slide_moves(from, &BISHOP_DIRECTIONS, &mut moves),
+                    'r' => self.slide_moves(from, &ROOK_DIRECTIONS, &mut moves),
+                    'q' => {
+                        self.slide_moves(from, &BISHOP_DIRECTIONS, &mut moves);
+                        self.slide_moves(from, &ROOK_DIRECTIONS, &mut moves);
+                    },
+                    'k' => {
+                        self.step_moves(from, &KING_OFFSETS, &mut moves);
+                        self.castling_moves(from, &mut moves);
+                    },
+                    _ => {}
+                }
+            }
+        }
+
+        moves
+    }
+
+    fn pawn_moves(&self, from: Square, moves: &mut Vec<ChessMove>) {
+        let color = self.active_color;
+        let direction = if color == WHITE { 1 } else { -1 };
+        let start_rank = if color == WHITE { 1 } else { 6 };
+        let promotion_rank = if color == WHITE { 7 } else { 0 };
+
+        let push = |to: Square, moves: &mut Vec<ChessMove>| {
+            if to.rank == promotion_rank {
+                for promotion in ['q', 'r', 'b', 'n'] {
+                    moves.push(ChessMove { from, to, promotion: Some(piece_for(color, promotion)) });
+                }
+            } else {
+                moves.push(ChessMove { from, to, promotion: None });
+            }
+        };
+
+        if let Some(one_step) = from.offset(direction, 0) {
+            if self.piece_at(one_step) == ' ' {
+                push(one_step, moves);
+
+                if from.rank == start_rank {
+                    if let Some(two_step) = from.offset(2 * direction, 0) {
+                        if self.piece_at(two_step) == ' ' {
+                            push(two_step, moves);
+                        }
+                    }
+                }
+            }
+        }
+
+        let en_passant = Square::from_algebraic(&self.en_passant);
+        for file_delta in [-1, 1] {
+            if let Some(target) = from.offset(direction, file_delta) {
+                let target_piece = self.piece_at(target);
+                if color_of(target_piece) == Some(opposite(color)) || Some(target) == en_passant {
+                    push(target, moves);
+                }
+            }
+        }
+    }
+
+    fn step_moves(&self, from: Square, offsets: &[(i32, i32)], moves: &mut Vec<ChessMove>) {
+        for (rank_delta, file_delta) in offsets {
+            if let Some(to) = from.offset(*rank_delta, *file_delta) {
+                if color_of(self.piece_at(to)) != Some(self.active_color) {
+                    moves.push(ChessMove { from, to, promotion: None });
+                }
+            }
+        }
+    }
+
+    fn slide_moves(&self, from: Square, directions: &[(i32, i32)], moves: &mut Vec<ChessMove>) {
+        for (rank_delta, file_delta) in directions {
+            let mut current = from;
+            while let Some(to) = current.offset(*rank_delta, *file_delta) {
+                let target_piece = self.piece_at(to);
+                if color_of(target_piece) == Some(self.active_color) {
+                    break;
+                }
+                moves.push(ChessMove { from, to, promotion: None });
+                if target_piece != ' ' {
+                    break;
+                }
+                current = to;
+            }
+        }
+    }
+
+    fn castling_moves(&self, from: Square, moves: &mut Vec<ChessMove>) {
+        let color = self.active_color;
+        let back_rank = if color == WHITE { 0 } else { 7 };
+        if from != (Square { rank: back_rank, file: 4 }) || self.in_check(color) {
+            return;
+        }
+
+        let (king_side, queen_side) = if color == WHITE { ('K', 'Q') } else { ('k', 'q') };
+        let enemy = opposite(color);
+        let rook = piece_for(color, 'r');
+
+        if self.castling_rights.contains(king_side)
+            && self.board[back_rank][7] == rook
+            && self.board[back_rank][5] == ' '
+            && self.board[back_rank][6] == ' '
+            && !self.is_square_attacked(Square { rank: back_rank, file: 5 }, enemy)
+            && !self.is_square_attacked(Square { rank: back_rank, file: 6 }, enemy) {
+            moves.push(ChessMove { from, to: Square { rank: back_rank, file: 6 }, promotion: None });
+        }
+
+        if self.castling_rights.contains(queen_side)
+            && self.board[back_rank][0] == rook
+            && self.board[back_rank][1] == ' '
+            && self.board[back_rank][2] == ' '
+            && self.board[back_rank][3] == ' '
+            && !self.is_square_attacked(Square { rank: back_rank, file: 3 }, enemy)
+            && !self.is_square_attacked(Square { rank: back_rank, file: 2 }, enemy) {
+            moves.push(ChessMove { from, to: Square { rank: back_rank, file: 2 }, promotion: None });
+        }
+    }
+
+    // Applies a move without checking legality
+    pub fn make_move(&self, chess_move: &ChessMove) -> ChessPosition {
+        let mut next = self.clone();
+        let from = chess_move.from;
+        let to = chess_move.to;
+        let piece = self.piece_at(from);
+        let captured = self.piece_at(to);
+        let is_pawn = piece.eq_ignore_ascii_case(&'p');
+
+        // En passant removes the pawn that is beside the moving pawn
+        if is_pawn && from.file != to.file && captured == ' ' {
+            next.board[from.rank][to.file] = ' ';
+        }
+
+        // Castling also moves the rook
+        if piece.eq_ignore_ascii_case(&'k') && (to.file as i32 - from.file as i32).abs() == 2 {
+            let (rook_from, rook_to) = if to.file == 6 { (7, 5) } else { (0, 3) };
+            next.board[from.rank][rook_to] = next.board[from.rank][rook_from];
+            next.board[from.rank][rook_from] = ' ';
+        }
+
+        next.board[from.rank][from.file] = ' ';
+        next.board[to.rank][to.file] = match chess_move.promotion {
+            Some(promoted) => piece_for(self.active_color, promoted),
+            None => piece,
+        };
+
+        // Castling rights are lost when the king moves or a rook leaves / is captured on its original square
+        let mut rights = next.castling_rights.replace('-', "");
+        if piece == 'K' {
+            rights = rights.replace(['K', 'Q'], "");
+        } else if piece == 'k' {
+            rights = rights.replace(['k', 'q'], "");
+        }
+        for square in [from, to] {
+            match (square.rank, square.file) {
+                (0, 0) => rights = rights.replace('Q', ""),
+                (0, 7) => rights = rights.replace('K', ""),
+                (7, 0) => rights = rights.replace('q', ""),
+                (7, 7) => rights = rights.replace('k', ""),
+                _ => {}
+            }
+        }
+        next.castling_rights = if rights.is_empty() { "-".to_string() } else { rights };
+
+        next.en_passant = if is_pawn && (to.rank as i32 - from.rank as i32).abs() == 2 {
+            Square { rank: (from.rank + to.rank) / 2, file: from.file }.to_algebraic()
+        } else {
+            "-".to_string()
+        };
+
+        next.halfmove_clock = if is_pawn || captured != ' ' { 0 } else { self.halfmove_clock + 1 };
+        if self.active_color == BLACK {
+            next.fullmove_number += 1;
+        }
+        next.active_color = opposite(self.active_color);
+
+        next
+    }
+
+    // Standard algebraic notation of a legal move, e.g. Nbd7, exd6, O-O or e8=Q#
+    pub fn to_san(&self, chess_move: &ChessMove) -> String {
+        let from = chess_move.from;
+        let to = chess_move.to;
+        let piece = self.piece_at(from);
+        let kind = piece.to_ascii_uppercase();
+        let is_capture = self.piece_at(to) != ' ' || (kind == 'P' && from.file != to.file);
+        let file_char = (b'a' + from.file as u8) as char;
+
+        let mut san = String::new();
+        if kind == 'K' && (to.file as i32 - from.file as i32).abs() == 2 {
+            san.push_str(if to.file == 6 { "O-O" } else { "O-O-O" });
+        } else if kind == 'P' {
+            if is_capture {
+                san.push(file_char);
+                san.push('x');
+            }
+            san.push_str(&to.to_algebraic());
+            if let Some(promoted) = chess_move.promotion {
+                san.push('=');
+                san.push(promoted.to_ascii_uppercase());
+            }
+        } else {
+            san.push(kind);
+
+            // Only as much of the origin square as is needed to tell apart pieces of the same kind reaching the target
+            let rivals: Vec<Square> = self.legal_moves().iter()
+                .filter(|other| other.to == to && other.from != from && self.piece_at(other.from) == piece)
+                .map(|other| other.from)
+                .collect();
+            if !rivals.is_empty() {
+                if rivals.iter().all(|rival| rival.file != from.file) {
+                    san.push(file_char);
+                } else if rivals.iter().all(|rival| rival.rank != from.rank) {
+                    san.push((b'1' + from.rank as u8) as char);
+                } else {
+                    san.push_str(&from.to_algebraic());
+                }
+            }
+
+            if is_capture {
+                san.push('x');
+            }
+            san.push_str(&to.to_algebraic());
+        }
+
+        let next = self.make_move(chess_move);
+        if next.in_check(next.active_color) {
+            san.push(if next.legal_moves().is_empty() { '#' } else { '+' });
+        }
+
+        san
+    }
+
+    // Legal move written in SAN, check marks and annotations are optional. Illegal or ambiguous moves give None
+    pub fn parse_san(&self, san: &str) -> Option<ChessMove> {
+        let strip = |notation: &str| notation.trim_end_matches(['+', '#', '!', '?']).to_string();
+        let wanted = strip(san);
+
+        self.legal_moves().into_iter().find(|chess_move| strip(&self.to_san(chess_move)) == wanted)
+    }
+
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut minor_pieces = vec![];
+
+        for rank in 0..8 {
+            for file in 0..8 {
+                match self.board[rank][file].to_ascii_lowercase() {
+                    ' ' | 'k' => {},
+                    'b' | 'n' => minor_pieces.push((self.board[rank][file], (rank + file) % 2)),
+                    _ => return false,
+                }
+            }
+        }
+
+        match minor_pieces.len() {
+            0 | 1 => true,
+            // King and bishop vs king and bishop with both bishops on the same colour
+            2 => minor_pieces.iter().all(|(piece, _)| piece.eq_ignore_ascii_case(&'b'))
+                && color_of(minor_pieces[0].0) != color_of(minor_pieces[1].0)
+                && minor_pieces[0].1 == minor_pieces[1].1,
+            _ => false,
+        }
+    }
+}
+
+
+fn parse_fen_board(fen_board: &str) -> Option<[[char; 8]; 8]> {
+    let ranks: Vec<&str> = fen_board.split('/').collect();
+    if ranks.len() != 8 {
+        return None;
+    }
+
+    let mut board = [[' '; 8]; 8];
+
+    for (rank_idx, rank) in ranks.iter().enumerate() {
+        let mut file_idx = 0;
+
+        for c in rank.chars() {
+            if file_idx >= 8 {
+                return None;
+            }
+
+            if let Some(empty_squares) = c.to_digit(10) {
+                file_idx += empty_squares as usize;
+            } else if "pnbrqkPNBRQK".contains(c) {
+                board[7 - rank_idx][file_idx] = c;
+                file_idx += 1;
+            } else {
+                return None;
+            }
+        }
+
+        if file_idx != 8 {
+            return None;
+        }
+    }
+
+    Some(board)
+}
+
+fn board_to_fen(board: &[[char; 8]; 8]) -> String {
+    let mut fen = String::new();
+
+    for rank in (0..8).rev() {
+        let mut empty_count = 0;
+
+        for &piece in board[rank].iter() {
+            if piece == ' ' {
+                empty_count += 1;
+            } else {
+                if empty_count > 0 {
+                    fen.push_str(&empty_count.to_string());
+                    empty_count = 0;
+                }
+                fen.push(piece);
+            }
+        }
+
+        if empty_count > 0 {
+            fen.push_str(&empty_count.to_string());
+        }
+
+        if rank > 0 {
+            fen.push('/');
+        }
+    }
+
+    fen
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(fen: &str) -> ChessPosition {
+        ChessPosition::from_fen(fen).unwrap()
+    }
+
+    fn square(notation: &str) -> Square {
+        Square::from_algebraic(notation).unwrap()
+    }
+
+    fn chess_move(from: &str, to: &str) -> ChessMove {
+        ChessMove { from: square(from), to: square(to), promotion: None }
+    }
+
+    fn moves_from(position: &ChessPosition, from: &str) -> Vec<String> {
+        let mut targets: Vec<String> = position.legal_moves().iter()
+            .filter(|legal_move| legal_move.from == square(from))
+            .map(|legal_move| legal_move.to.to_algebraic())
+            .collect();
+        targets.sort();
+        targets
+    }
+
+    #[test]
+    fn starting_position_round_trips_and_has_twenty_moves() {
+        let start = position(ChessPosition::STARTING_FEN);
+        assert_eq!(start.to_fen(), ChessPosition::STARTING_FEN);
+        assert_eq!(start.legal_moves().len(), 20);
+
+        assert!(ChessPosition::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1").is_none());
+        assert!(ChessPosition::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1").is_none());
+    }
+
+    #[test]
+    fn castling_moves_the_rook_and_drops_the_rights() {
+        let start = position("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert!(start.is_legal(&chess_move("e1", "g1")));
+        assert!(start.is_legal(&chess_move("e1", "c1")));
+
+        let castled = start.make_move(&chess_move("e1", "g1"));
+        assert_eq!(castled.to_fen(), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+
+        // A rook leaving its square or being captured there only drops its own side
+        let rook_moved = start.make_move(&chess_move("a1", "a2"));
+        assert_eq!(rook_moved.castling_rights, "Kkq");
+        let rook_captured = start.make_move(&chess_move("a1", "a8"));
+        assert_eq!(rook_captured.to_fen(), "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1");
+
+        let no_rights = position("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1");
+        assert!(!no_rights.is_legal(&chess_move("e1", "g1")));
+        assert!(!no_rights.is_legal(&chess_move("e1", "c1")));
+    }
+
+    #[test]
+    fn cannot_castle_out_of_or_through_check() {
+        let in_check = position("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+        assert!(!in_check.is_legal(&chess_move("e1", "g1")));
+        assert!(!in_check.is_legal(&chess_move("e1", "c1")));
+
+        let f1_attacked = position("4kr2/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+        assert!(!f1_attacked.is_legal(&chess_move("e1", "g1")));
+        assert!(f1_attacked.is_legal(&chess_move("e1", "c1")));
+
+        // Only the squares the king crosses matter, the rook may pass an attacked b1
+        let b1_attacked = position("1r2k3/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+        assert!(b1_attacked.is_legal(&chess_move("e1", "c1")));
+    }
+
+    #[test]
+    fn en_passant_is_only_available_right_after_the_double_step() {
+        let start = position("4k3/3p4/8/4P3/8/8/8/4K3 b - - 0 1");
+        let double_step = start.make_move(&chess_move("d7", "d5"));
+        assert_eq!(double_step.to_fen(), "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2");
+        assert_eq!(moves_from(&double_step, "e5"), vec!["d6", "e6"]);
+
+        let captured = double_step.make_move(&chess_move("e5", "d6"));
+        assert_eq!(captured.to_fen(), "4k3/8/3P4/8/8/8/8/4K3 b - - 0 2");
+        assert_eq!(double_step.to_san(&chess_move("e5", "d6")), "exd6");
+
+        // One ply later the capture is gone
+        let waited = double_step
+            .make_move(&chess_move("e1", "e2"))
+            .make_move(&chess_move("e8", "f7"));
+        assert_eq!(waited.en_passant, "-");
+        assert_eq!(moves_from(&waited, "e5"), vec!["e6"]);
+    }
+
+    #[test]
+    fn pawns_promote_to_any_piece_on_the_last_rank() {
+        let start = position("1r6/P3k3/8/8/8/8/8/4K3 w - - 0 1");
+        let promotions: Vec<ChessMove> = start.legal_moves().into_iter().filter(|legal_move| legal_move.from == square("a7")).collect();
+        assert_eq!(promotions.len(), 8);
+        assert!(promotions.iter().all(|promotion| promotion.promotion.is_some()));
+
+        let queen = ChessMove { promotion: Some('Q'), ..chess_move("a7", "a8") };
+        assert_eq!(start.to_san(&queen), "a8=Q");
+        assert_eq!(start.make_move(&queen).piece_at(square("a8")), 'Q');
+
+        // The promotion piece takes the colour of the side to move
+        let knight = ChessMove { promotion: Some('n'), ..chess_move("a7", "b8") };
+        assert_eq!(start.make_move(&knight).piece_at(square("b8")), 'N');
+        assert_eq!(start.to_san(&ChessMove { promotion: Some('N'), ..chess_move("a7", "b8") }), "axb8=N");
+    }
+
+    #[test]
+    fn pinned_pieces_only_move_along_the_pin() {
+        let bishop_pinned = position("4r1k1/8/8/8/8/8/4B3/4K3 w - - 0 1");
+        assert!(moves_from(&bishop_pinned, "e2").is_empty());
+
+        let rook_pinned = position("4r1k1/8/8/8/8/8/4R3/4K3 w - - 0 1");
+        assert_eq!(moves_from(&rook_pinned, "e2"), vec!["e3", "e4", "e5", "e6", "e7", "e8"]);
+    }
+
+    #[test]
+    fn checkmate_and_stalemate_leave_no_legal_moves() {
+        let mut game = position(ChessPosition::STARTING_FEN);
+        let mut notation = vec![];
+        for (from, to) in [("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")] {
+            let next_move = chess_move(from, to);
+            assert!(game.is_legal(&next_move));
+            notation.push(game.to_san(&next_move));
+            game = game.make_move(&next_move);
+        }
+        assert_eq!(notation, vec!["f3", "e5", "g4", "Qh4#"]);
+        assert!(game.legal_moves().is_empty());
+        assert!(game.in_check(WHITE));
+
+        let stalemate = position("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+        assert!(stalemate.legal_moves().is_empty());
+        assert!(!stalemate.in_check(BLACK));
+    }
+
+    #[test]
+    fn san_disambiguates_and_parses_back() {
+        let knights = position("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1");
+        assert_eq!(knights.to_san(&chess_move("b1", "d2")), "Nbd2");
+        assert_eq!(knights.parse_san("Nbd2"), Some(chess_move("b1", "d2")));
+        assert_eq!(knights.parse_san("Nfd2"), Some(chess_move("f3", "d2")));
+        assert_eq!(knights.parse_san("Nd2"), None);
+
+        let rooks = position("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1");
+        assert_eq!(rooks.to_san(&chess_move("a1", "a3")), "R1a3");
+        assert_eq!(rooks.parse_san("R5a3"), Some(chess_move("a5", "a3")));
+        assert_eq!(rooks.to_san(&chess_move("a5", "a8")), "Ra8+");
+
+        let start = position(ChessPosition::STARTING_FEN);
+        assert_eq!(start.parse_san("Nf3"), Some(chess_move("g1", "f3")));
+        assert_eq!(start.parse_san("e4!"), Some(chess_move("e2", "e4")));
+        assert_eq!(start.parse_san("e5"), None);
+
+        let castling = position("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(castling.parse_san("O-O"), Some(chess_move("e1", "g1")));
+        assert_eq!(castling.parse_san("O-O-O"), Some(chess_move("e1", "c1")));
+
+        let mate = position("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2");
+        assert_eq!(mate.parse_san("Qh4"), Some(chess_move("d8", "h4")));
+        assert_eq!(mate.parse_san("Qh4#"), Some(chess_move("d8", "h4")));
+    }
+
+    #[test]
+    fn insufficient_material() {
+        assert!(position("4k3/8/8/8/8/8/8/4K3 w - - 0 1").is_insufficient_material());
+        assert!(position("4k3/8/8/8/8/8/8/4KN2 w - - 0 1").is_insufficient_material());
+        // Bishops on the same colour cannot mate, on opposite colours they can
+        assert!(position("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1").is_insufficient_material());
+        assert!(!position("2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1").is_insufficient_material());
+        assert!(!position("4k3/8/8/8/8/8/8/3NKN2 w - - 0 1").is_insufficient_material());
+        assert!(!position("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").is_insufficient_material());
+        assert!(!position("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").is_insufficient_material());
+    }
+}
@@ -0,0 +1,377 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{game_move_model::GameMoveRecord, user_game_event::UserGameMove, user_turn_model::UserTurnMapping};
+
+pub mod checkers;
+pub mod chess;
+pub mod monopoly;
+pub mod poker;
+pub mod registry;
+pub mod rng;
+pub mod scribble;
+
+
+// A single player action handed to an engine. Built by cerotis from the UserGameMove kafka event
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EngineAction {
+    pub user_id: String,
+    // player_type from UserGameRelation (colour / seat). Only empty for replayed moves, which are not validated
+    pub player_type: String,
+    pub action_type: String,
+    pub payload: String,
+    // Milliseconds since epoch when the action was received by the server
+    pub received_at: i64,
+}
+
+impl EngineAction {
+    pub fn from_user_game_move(user_game_move: &UserGameMove, player_type: String, received_at: i64) -> Self {
+        EngineAction {
+            user_id: user_game_move.user_id.clone(),
+            player_type,
+            action_type: user_game_move.move_type.clone(),
+            payload: user_game_move.user_move.clone(),
+            received_at,
+        }
+    }
+
+    // Action of a recorded move, used to replay a finished game. The player_type is not recorded
+    pub fn from_game_move_record(game_move: &GameMoveRecord) -> Self {
+        EngineAction {
+            user_id: game_move.user_id.clone(),
+            player_type: String::new(),
+            action_type: game_move.move_type.clone(),
+            payload: game_move.user_move.clone(),
+            received_at: game_move.created_at.timestamp_millis(),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct GameResult {
+    // Player key of the winner (user_id, or player_type for seat based engines like chess). None means draw
+    pub winner: Option<String>,
+    pub reason: String,
+    // Final place of every player (same player keys as winner), 1 is the best. Tied players share a place
+    #[serde(default)]
+    pub placements: Vec<Placement>,
+}
+
+impl GameResult {
+    pub fn win(winner: &str, loser: &str, reason: &str) -> Self {
+        GameResult::from_eliminations(vec![winner.to_string()], &[vec![loser.to_string()]], reason)
+    }
+
+    pub fn draw(players: &[&str], reason: &str) -> Self {
+        GameResult::from_eliminations(players.iter().map(|player| player.to_string()).collect(), &[], reason)
+    }
+
+    // Players still in the game share first place, the others are ranked by when they were knocked out (latest first).
+    // eliminated holds one group per elimination, earliest first, players of the same group share a place
+    pub fn from_eliminations(survivors: Vec<String>, eliminated: &[Vec<String>], reason: &str) -> Self {
+        let mut placements: Vec<Placement> = survivors.iter().map(|player| Placement { player: player.clone(), place: 1 }).collect();
+        let mut next_place = survivors.len() as u32 + 1;
+
+        for group in eliminated.iter().rev() {
+            placements.extend(group.iter().map(|player| Placement { player: player.clone(), place: next_place }));
+            next_place += group.len() as u32;
+        }
+
+        GameResult {
+            winner: if survivors.len() == 1 { survivors.first().cloned() } else { None },
+            reason: reason.to_string(),
+            placements,
+        }
+    }
+
+    // Everyone sharing first place, used for multi winner bet settlement
+    pub fn winners(&self) -> Vec<String> {
+        self.placements.iter().filter(|placement| placement.place == 1).map(|placement| placement.player.clone()).collect()
+    }
+}
+
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct Placement {
+    pub player: String,
+    pub place: u32,
+}
+
+
+// Where cerotis keeps the serialised state of a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateStorage {
+    // Redis key made of state_key_prefix + game id
+    Redis,
+    // game_state field of the game document in the mongo games collection
+    Mongo,
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineError {
+    UnknownGameType(String),
+    InvalidState(String),
+    InvalidAction(String),
+    IllegalAction(String),
+    NotPlayersTurn,
+    GameAlreadyOver,
+    InvalidPlayerCount { min: usize, max: usize, found: usize },
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EngineError::UnknownGameType(game_type) => write!(f, "No engine registered for game type {}", game_type),
+            EngineError::InvalidState(reason) => write!(f, "Invalid game state: {}", reason),
+            EngineError::InvalidAction(reason) => write!(f, "Invalid action: {}", reason),
+            EngineError::IllegalAction(reason) => write!(f, "Illegal action: {}", reason),
+            EngineError::NotPlayersTurn => write!(f, "It is not this player's turn"),
+            EngineError::GameAlreadyOver => write!(f, "The game is already over"),
+            EngineError::InvalidPlayerCount { min, max, found } => write!(f, "Game needs between {} and {} players, found {}", min, max, found),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+
+// Rules of a single game type. Engines are pure: all randomness must come from the seed given to initial_state
+pub trait GameEngine: Send + Sync {
+    type State;
+
+    // Value of Game.game_type handled by this engine
+    fn game_type(&self) -> &'static str;
+
+    // Redis key prefix under which the serialised state is stored (the game id is appended)
+    fn state_key_prefix(&self) -> &'static str;
+
+    // Lobby capacity for the game type
+    fn min_players(&self) -> usize {
+        2
+    }
+
+    fn max_players(&self) -> usize {
+        2
+    }
+
+    // player_type given to each seat when the game starts (seat 0 first). Engines keyed by user_id leave it empty
+    fn player_types(&self) -> Vec<&'static str> {
+        vec![]
+    }
+
+    // Long running games with a lot of state can keep it in mongo instead
+    fn state_storage(&self) -> StateStorage {
+        StateStorage::Redis
+    }
+
+    fn initial_state(&self, turn_mapping: &UserTurnMapping, seed: u64) -> Result<Self::State, EngineError>;
+
+    fn validate_action(&self, state: &Self::State, action: &EngineAction) -> Result<(), EngineError>;
+
+    // Callers are expected to run validate_action first
+    fn apply_action(&self, state: &Self::State, action: &EngineAction) -> Result<Self::State, EngineError>;
+
+    fn is_terminal(&self, state: &Self::State) -> bool;
+
+    fn result(&self, state: &Self::State) -> Option<GameResult>;
+
+    fn serialize_state(&self, state: &Self::State) -> Result<String, EngineError>;
+
+    fn deserialize_state(&self, raw_state: &str) -> Result<Self::State, EngineError>;
+
+    // State that can be shown to everyone in the room. Engines with hidden information must strip it here
+    fn public_state(&self, state: &Self::State) -> Result<String, EngineError> {
+        self.serialize_state(state)
+    }
+
+    // Views containing hidden information (hole cards, secret words ...), each one is sent only to its owner
+    fn private_states(&self, _state: &Self::State) -> Result<Vec<PrivateState>, EngineError> {
+        Ok(vec![])
+    }
+
+    // Transient actions (e.g. drawing strokes) are validated and forwarded to the room but never change the stored state
+    fn is_transient_action(&self, _action: &EngineAction) -> bool {
+        false
+    }
+
+    // Time (ms since epoch) at which a "tick" action has to be sent to move a timed game forward
+    fn next_deadline(&self, _state: &Self::State) -> Option<i64> {
+        None
+    }
+
+    // Standard notation of an action played from the given state (SAN for chess), used for archived game records
+    fn move_notation(&self, _state: &Self::State, _action: &EngineAction) -> Option<String> {
+        None
+    }
+
+    // Takes a player who left for good out of the game. None if the game cannot go on without them, it is abandoned then
+    fn remove_player(&self, _state: &Self::State, _user_id: &str, _now: i64) -> Option<Self::State> {
+        None
+    }
+
+    // Players knocked out so far (busted, bankrupt ...), they take no more turns
+    fn eliminated_players(&self, _state: &Self::State) -> Vec<String> {
+        vec![]
+    }
+}
+
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PrivateState {
+    pub user_id: String,
+    pub state: String,
+}
+
+
+#[derive(Debug, Clone)]
+pub struct AppliedAction {
+    // Full state to be stored, never sent to clients as is
+    pub state: String,
+    pub public_state: String,
+    pub private_states: Vec<PrivateState>,
+    pub result: Option<GameResult>,
+    pub deadline: Option<i64>,
+    // True if the action was only validated, state is then the unchanged input state
+    pub transient: bool,
+    // Players knocked out by this action
+    pub eliminated: Vec<String>,
+}
+
+
+// Object safe view of a GameEngine working on serialised states, used by the registry
+pub trait DynGameEngine: Send + Sync {
+    fn game_type(&self) -> &'static str;
+    fn state_key_prefix(&self) -> &'static str;
+    fn min_players(&self) -> usize;
+    fn max_players(&self) -> usize;
+    fn player_types(&self) -> Vec<&'static str>;
+    fn state_storage(&self) -> StateStorage;
+    fn initial_state(&self, turn_mapping: &UserTurnMapping, seed: u64) -> Result<String, EngineError>;
+    fn apply(&self, raw_state: &str, action: &EngineAction) -> Result<AppliedAction, EngineError>;
+    fn result(&self, raw_state: &str) -> Result<Option<GameResult>, EngineError>;
+    fn public_state(&self, raw_state: &str) -> Result<String, EngineError>;
+    fn private_states(&self, raw_state: &str) -> Result<Vec<PrivateState>, EngineError>;
+    // What a single user is allowed to see: their private view if they have one, the public state otherwise
+    fn player_view(&self, raw_state: &str, user_id: &str) -> Result<String, EngineError>;
+    fn move_notation(&self, raw_state: &str, action: &EngineAction) -> Option<String>;
+    // None if the game cannot go on without the player
+    fn remove_player(&self, raw_state: &str, user_id: &str, now: i64) -> Result<Option<AppliedAction>, EngineError>;
+}
+
+impl<E> DynGameEngine for E where E: GameEngine {
+    fn game_type(&self) -> &'static str {
+        GameEngine::game_type(self)
+    }
+
+    fn state_key_prefix(&self) -> &'static str {
+        GameEngine::state_key_prefix(self)
+    }
+
+    fn min_players(&self) -> usize {
+        GameEngine::min_players(self)
+    }
+
+    fn max_players(&self) -> usize {
+        GameEngine::max_players(self)
+    }
+
+    fn player_types(&self) -> Vec<&'static str> {
+        GameEngine::player_types(self)
+    }
+
+    fn state_storage(&self) -> StateStorage {
+        GameEngine::state_storage(self)
+    }
+
+    fn initial_state(&self, turn_mapping: &UserTurnMapping, seed: u64) -> Result<String, EngineError> {
+        let state = GameEngine::initial_state(self, turn_mapping, seed)?;
+        self.serialize_state(&state)
+    }
+
+    fn apply(&self, raw_state: &str, action: &EngineAction) -> Result<AppliedAction, EngineError> {
+        let state = self.deserialize_state(raw_state)?;
+
+        if self.is_terminal(&state) {
+            return Err(EngineError::GameAlreadyOver)
+        }
+
+        self.validate_action(&state, action)?;
+
+        if self.is_transient_action(action) {
+            return Ok(AppliedAction {
+                state: raw_state.to_string(),
+                public_state: GameEngine::public_state(self, &state)?,
+                private_states: vec![],
+                result: None,
+                deadline: self.next_deadline(&state),
+                transient: true,
+                eliminated: vec![],
+            })
+        }
+
+        let new_state = self.apply_action(&state, action)?;
+        applied_action(self, &state, &new_state)
+    }
+
+    fn result(&self, raw_state: &str) -> Result<Option<GameResult>, EngineError> {
+        let state = self.deserialize_state(raw_state)?;
+        Ok(GameEngine::result(self, &state))
+    }
+
+    fn public_state(&self, raw_state: &str) -> Result<String, EngineError> {
+        let state = self.deserialize_state(raw_state)?;
+        GameEngine::public_state(self, &state)
+    }
+
+    fn private_states(&self, raw_state: &str) -> Result<Vec<PrivateState>, EngineError> {
+        let state = self.deserialize_state(raw_state)?;
+        GameEngine::private_states(self, &state)
+    }
+
+    fn player_view(&self, raw_state: &str, user_id: &str) -> Result<String, EngineError> {
+        let state = self.deserialize_state(raw_state)?;
+        let private_state = GameEngine::private_states(self, &state)?.into_iter().find(|private_state| private_state.user_id == user_id);
+
+        match private_state {
+            Some(private_state) => Ok(private_state.state),
+            None => GameEngine::public_state(self, &state),
+        }
+    }
+
+    fn move_notation(&self, raw_state: &str, action: &EngineAction) -> Option<String> {
+        let state = self.deserialize_state(raw_state).ok()?;
+        GameEngine::move_notation(self, &state, action)
+    }
+
+    fn remove_player(&self, raw_state: &str, user_id: &str, now: i64) -> Result<Option<AppliedAction>, EngineError> {
+        let state = self.deserialize_state(raw_state)?;
+
+        if self.is_terminal(&state) {
+            return Err(EngineError::GameAlreadyOver)
+        }
+
+        match GameEngine::remove_player(self, &state, user_id, now) {
+            Some(new_state) => applied_action(self, &state, &new_state).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+
+fn applied_action<E>(engine: &E, state: &E::State, new_state: &E::State) -> Result<AppliedAction, EngineError> where E: GameEngine {
+    let result = if engine.is_terminal(new_state) { GameEngine::result(engine, new_state) } else { None };
+    let eliminated_before = engine.eliminated_players(state);
+
+    Ok(AppliedAction {
+        state: engine.serialize_state(new_state)?,
+        public_state: GameEngine::public_state(engine, new_state)?,
+        private_states: GameEngine::private_states(engine, new_state)?,
+        result,
+        deadline: engine.next_deadline(new_state),
+        transient: false,
+        eliminated: engine.eliminated_players(new_state).into_iter().filter(|player| !eliminated_before.contains(player)).collect(),
+    })
+}
@@ -0,0 +1,119 @@
+// Standard (US) Monopoly board, indexed by square number starting at GO
+pub const BOARD_SIZE: usize = 40;
+pub const JAIL_SQUARE: usize = 10;
+pub const RAILROAD_SQUARES: [usize; 4] = [5, 15, 25, 35];
+pub const UTILITY_SQUARES: [usize; 2] = [12, 28];
+pub const RAILROAD_PRICE: i64 = 200;
+pub const UTILITY_PRICE: i64 = 150;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SquareKind {
+    Go,
+    // rents are for 0..=4 houses and a hotel
+    Street { group: &'static str, price: i64, rents: [i64; 6], house_cost: i64 },
+    Railroad,
+    Utility,
+    Tax(i64),
+    Chance,
+    CommunityChest,
+    Jail,
+    FreeParking,
+    GoToJail,
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct Square {
+    pub name: &'static str,
+    pub kind: SquareKind,
+}
+
+const fn street(name: &'static str, group: &'static str, price: i64, rents: [i64; 6], house_cost: i64) -> Square {
+    Square { name, kind: SquareKind::Street { group, price, rents, house_cost } }
+}
+
+const fn square(name: &'static str, kind: SquareKind) -> Square {
+    Square { name, kind }
+}
+
+pub const BOARD: [Square; BOARD_SIZE] = [
+    square("Go", SquareKind::Go),
+    street("Mediterranean Avenue", "brown", 60, [2, 10, 30, 90, 160, 250], 50),
+    square("Community Chest", SquareKind::CommunityChest),
+    street("Baltic Avenue", "brown", 60, [4, 20, 60, 180, 320, 450], 50),
+    square("Income Tax", SquareKind::Tax(200)),
+    square("Reading Railroad", SquareKind::Railroad),
+    street("Oriental Avenue", "light_blue", 100, [6, 30, 90, 270, 400, 550], 50),
+    square("Chance", SquareKind::Chance),
+    street("Vermont Avenue", "light_blue", 100, [6, 30, 90, 270, 400, 550], 50),
+    street("Connecticut Avenue", "light_blue", 120, [8, 40, 100, 300, 450, 600], 50),
+    square("Jail", SquareKind::Jail),
+    street("St. Charles Place", "pink", 140, [10, 50, 150, 450, 625, 750], 100),
+    square("Electric Company", SquareKind::Utility),
+    street("States Avenue", "pink", 140, [10, 50, 150, 450, 625, 750], 100),
+    street("Virginia Avenue", "pink", 160, [12, 60, 180, 500, 700, 900], 100),
+    square("Pennsylvania Railroad", SquareKind::Railroad),
+    street("St. James Place", "orange", 180, [14, 70, 200, 550, 750, 950], 100),
+    square("Community Chest", SquareKind::CommunityChest),
+    street("Tennessee Avenue", "orange", 180, [14, 70, 200, 550, 750, 950], 100),
+    street("New York Avenue", "orange", 200, [16, 80, 220, 600, 800, 1000], 100),
+    square("Free Parking", SquareKind::FreeParking),
+    street("Kentucky Avenue", "red", 220, [18, 90, 250, 700, 875, 1050], 150),
+    square("Chance", SquareKind::Chance),
+    street("Indiana Avenue", "red", 220, [18, 90, 250, 700, 875, 1050], 150),
+    street("Illinois Avenue", "red", 240, [20, 100, 300, 750, 925, 1100], 150),
+    square("B. & O. Railroad", SquareKind::Railroad),
+    street("Atlantic Avenue", "yellow", 260, [22, 110, 330, 800, 975, 1150], 150),
+    street("Ventnor Avenue", "yellow", 260, [22, 110, 330, 800, 975, 1150], 150),
+    square("Water Works", SquareKind::Utility),
+    street("Marvin Gardens", "yellow", 280, [24, 120, 360, 850, 1025, 1200], 150),
+    square("Go To Jail", SquareKind::GoToJail),
+    street("Pacific Avenue", "green", 300, [26, 130, 390, 900, 1100, 1275], 200),
+    street("North Carolina Avenue", "green", 300, [26, 130, 390, 900, 1100, 1275], 200),
+    square("Community Chest", SquareKind::CommunityChest),
+    street("Pennsylvania Avenue", "green", 320, [28, 150, 450, 1000, 1200, 1400], 200),
+    square("Short Line", SquareKind::Railroad),
+    square("Chance", SquareKind::Chance),
+    street("Park Place", "dark_blue", 350, [35, 175, 500, 1100, 1300, 1500], 200),
+    square("Luxury Tax", SquareKind::Tax(100)),
+    street("Boardwalk", "dark_blue", 400, [50, 200, 600, 1400, 1700, 2000], 200),
+];
+
+
+// Purchase price, None for squares that cannot be owned
+pub fn price(square: usize) -> Option<i64> {
+    match BOARD.get(square)?.kind {
+        SquareKind::Street { price, .. } => Some(price),
+        SquareKind::Railroad => Some(RAILROAD_PRICE),
+        SquareKind::Utility => Some(UTILITY_PRICE),
+        _ => None,
+    }
+}
+
+pub fn is_ownable(square: usize) -> bool {
+    price(square).is_some()
+}
+
+pub fn street_group(square: usize) -> Option<&'static str> {
+    match BOARD.get(square)?.kind {
+        SquareKind::Street { group, .. } => Some(group),
+        _ => None,
+    }
+}
+
+pub fn group_squares(group: &str) -> Vec<usize> {
+    (0..BOARD_SIZE).filter(|square| street_group(*square) == Some(group)).collect()
+}
+
+pub fn house_cost(square: usize) -> Option<i64> {
+    match BOARD.get(square)?.kind {
+        SquareKind::Street { house_cost, .. } => Some(house_cost),
+        _ => None,
+    }
+}
+
+// First square of the given kind reached when moving forward from position
+pub fn next_square(position: usize, candidates: &[usize]) -> usize {
+    candidates.iter().copied().find(|square| *square > position).unwrap_or(candidates[0])
+}
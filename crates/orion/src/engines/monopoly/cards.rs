@@ -0,0 +1,77 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardEffect {
+    AdvanceTo(usize),
+    // Pays twice the normal railroad rent if owned
+    AdvanceToNearestRailroad,
+    // Pays ten times a new dice throw if owned
+    AdvanceToNearestUtility,
+    Collect(i64),
+    Pay(i64),
+    GetOutOfJailFree,
+    GoBack(usize),
+    GoToJail,
+    Repairs { per_house: i64, per_hotel: i64 },
+    PayEachPlayer(i64),
+    CollectFromEachPlayer(i64),
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct Card {
+    pub text: &'static str,
+    pub effect: CardEffect,
+}
+
+const fn card(text: &'static str, effect: CardEffect) -> Card {
+    Card { text, effect }
+}
+
+pub const CHANCE_DECK: &str = "chance";
+pub const COMMUNITY_CHEST_DECK: &str = "community_chest";
+
+pub const CHANCE_CARDS: [Card; 16] = [
+    card("Advance to Boardwalk", CardEffect::AdvanceTo(39)),
+    card("Advance to Go (Collect $200)", CardEffect::AdvanceTo(0)),
+    card("Advance to Illinois Avenue. If you pass Go, collect $200", CardEffect::AdvanceTo(24)),
+    card("Advance to St. Charles Place. If you pass Go, collect $200", CardEffect::AdvanceTo(11)),
+    card("Advance to the nearest Railroad", CardEffect::AdvanceToNearestRailroad),
+    card("Advance to the nearest Railroad", CardEffect::AdvanceToNearestRailroad),
+    card("Advance token to nearest Utility", CardEffect::AdvanceToNearestUtility),
+    card("Bank pays you dividend of $50", CardEffect::Collect(50)),
+    card("Get Out of Jail Free", CardEffect::GetOutOfJailFree),
+    card("Go Back 3 Spaces", CardEffect::GoBack(3)),
+    card("Go to Jail", CardEffect::GoToJail),
+    card("Make general repairs on all your property: $25 per house, $100 per hotel", CardEffect::Repairs { per_house: 25, per_hotel: 100 }),
+    card("Speeding fine $15", CardEffect::Pay(15)),
+    card("Take a trip to Reading Railroad. If you pass Go, collect $200", CardEffect::AdvanceTo(5)),
+    card("You have been elected Chairman of the Board. Pay each player $50", CardEffect::PayEachPlayer(50)),
+    card("Your building loan matures. Collect $150", CardEffect::Collect(150)),
+];
+
+pub const COMMUNITY_CHEST_CARDS: [Card; 16] = [
+    card("Advance to Go (Collect $200)", CardEffect::AdvanceTo(0)),
+    card("Bank error in your favor. Collect $200", CardEffect::Collect(200)),
+    card("Doctor's fee. Pay $50", CardEffect::Pay(50)),
+    card("From sale of stock you get $50", CardEffect::Collect(50)),
+    card("Get Out of Jail Free", CardEffect::GetOutOfJailFree),
+    card("Go to Jail", CardEffect::GoToJail),
+    card("Holiday fund matures. Receive $100", CardEffect::Collect(100)),
+    card("Income tax refund. Collect $20", CardEffect::Collect(20)),
+    card("It is your birthday. Collect $10 from every player", CardEffect::CollectFromEachPlayer(10)),
+    card("Life insurance matures. Collect $100", CardEffect::Collect(100)),
+    card("Pay hospital fees of $100", CardEffect::Pay(100)),
+    card("Pay school fees of $50", CardEffect::Pay(50)),
+    card("Receive $25 consultancy fee", CardEffect::Collect(25)),
+    card("You are assessed for street repair: $40 per house, $115 per hotel", CardEffect::Repairs { per_house: 40, per_hotel: 115 }),
+    card("You have won second prize in a beauty contest. Collect $10", CardEffect::Collect(10)),
+    card("You inherit $100", CardEffect::Collect(100)),
+];
+
+pub fn deck_cards(deck: &str) -> &'static [Card; 16] {
+    if deck == CHANCE_DECK { &CHANCE_CARDS } else { &COMMUNITY_CHEST_CARDS }
+}
+
+// Index of the "get out of jail free" card inside a deck, used to put a played card back
+pub fn jail_free_card(deck: &str) -> u8 {
+    deck_cards(deck).iter().position(|card| card.effect == CardEffect::GetOutOfJailFree).unwrap_or(0) as u8
+}
@@ -0,0 +1,1234 @@
+use crate::{constants::MONOPOLY_STATE_KEY, models::{monopoly_events::{MonopolyBidEvent, MonopolySquareEvent, MonopolyTradeEvent}, monopoly_model::{MonopolyAuction, MonopolyDebt, MonopolyPlayer, MonopolyProperty, MonopolyState, MonopolyTrade}, user_turn_model::UserTurnMapping}};
+
+use self::{board::{SquareKind, BOARD, BOARD_SIZE, JAIL_SQUARE, RAILROAD_SQUARES, UTILITY_SQUARES}, cards::{CardEffect, CHANCE_DECK, COMMUNITY_CHEST_DECK}};
+
+use super::{rng::DeterministicRng, EngineAction, EngineError, GameEngine, GameResult, StateStorage};
+
+pub mod board;
+pub mod cards;
+
+const MIN_PLAYERS: usize = 2;
+const MAX_PLAYERS: usize = 8;
+const MAX_LOG_ENTRIES: usize = 50;
+const HOTEL: u8 = 5;
+const MAX_JAIL_TURNS: u8 = 3;
+const TOTAL_HOUSES: u8 = 32;
+const TOTAL_HOTELS: u8 = 12;
+// Streams of the seeded rng used for the decks, dice use the roll counter as stream
+const CHANCE_STREAM: u64 = u64::MAX;
+const COMMUNITY_CHEST_STREAM: u64 = u64::MAX - 1;
+
+const ROLL_PHASE: &str = "roll";
+const BUY_PHASE: &str = "buy_decision";
+const AUCTION_PHASE: &str = "auction";
+const DEBT_PHASE: &str = "debt";
+const POST_ROLL_PHASE: &str = "post_roll";
+const GAME_OVER_PHASE: &str = "game_over";
+
+
+// How rent is worked out when a card sends a player to a railroad or utility
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RentRule {
+    Normal,
+    DoubleRailroad,
+    TenTimesDice,
+}
+
+
+// Classic Monopoly. Players move in the order of the UserTurnMapping, the last player who is not bankrupt wins.
+// Dice and decks come from the seed so a game can be replayed from its list of actions
+pub struct MonopolyEngine {
+    pub starting_cash: i64,
+    pub go_salary: i64,
+    pub jail_fine: i64,
+}
+
+impl Default for MonopolyEngine {
+    fn default() -> Self {
+        MonopolyEngine {
+            starting_cash: 1500,
+            go_salary: 200,
+            jail_fine: 50,
+        }
+    }
+}
+
+impl MonopolyEngine {
+    // Runs an action on the given state. validate_action uses it on a copy so both share the same checks
+    fn act(&self, state: &mut MonopolyState, action: &EngineAction) -> Result<(), EngineError> {
+        let actor = player_index(state, &action.user_id).ok_or(EngineError::InvalidAction("Player is not part of this game".to_string()))?;
+        if state.players[actor].bankrupt {
+            return Err(EngineError::IllegalAction("Player is bankrupt".to_string()))
+        }
+
+        match action.action_type.as_str() {
+            "roll" => {
+                require_turn(state, actor, &[ROLL_PHASE])?;
+                self.roll(state, actor);
+            },
+            "pay_jail_fine" => {
+                require_turn(state, actor, &[ROLL_PHASE])?;
+                if !state.players[actor].in_jail {
+                    return Err(EngineError::IllegalAction("Player is not in jail".to_string()))
+                }
+                if state.players[actor].cash < self.jail_fine {
+                    return Err(EngineError::IllegalAction("Not enough cash to pay the fine".to_string()))
+                }
+
+                state.players[actor].cash -= self.jail_fine;
+                leave_jail(state, actor);
+                add_log(state, format!("{} paid ${} to leave jail", action.user_id, self.jail_fine));
+            },
+            "use_jail_card" => {
+                require_turn(state, actor, &[ROLL_PHASE])?;
+                if !state.players[actor].in_jail {
+                    return Err(EngineError::IllegalAction("Player is not in jail".to_string()))
+                }
+
+                let deck = state.players[actor].jail_free_cards.pop().ok_or(EngineError::IllegalAction("Player has no get out of jail free card".to_string()))?;
+                return_jail_free_card(state, &deck);
+                leave_jail(state, actor);
+                add_log(state, format!("{} used a get out of jail free card", action.user_id));
+            },
+            "buy" => {
+                require_turn(state, actor, &[BUY_PHASE])?;
+                let square = state.pending_purchase.ok_or(EngineError::IllegalAction("Nothing to buy".to_string()))?;
+                let price = board::price(square).unwrap_or(0);
+                if state.players[actor].cash < price {
+                    return Err(EngineError::IllegalAction("Not enough cash, decline to put it up for auction".to_string()))
+                }
+
+                state.players[actor].cash -= price;
+                property_mut(state, square)?.owner = Some(action.user_id.clone());
+                state.pending_purchase = None;
+                add_log(state, format!("{} bought {} for ${}", action.user_id, BOARD[square].name, price));
+            },
+            "decline" => {
+                require_turn(state, actor, &[BUY_PHASE])?;
+                let square = state.pending_purchase.take().ok_or(EngineError::IllegalAction("Nothing to buy".to_string()))?;
+                start_auction(state, square);
+            },
+            "bid" | "pass_bid" => {
+                let auction = state.auction.as_ref().ok_or(EngineError::IllegalAction("There is no auction running".to_string()))?;
+                if state.phase != AUCTION_PHASE {
+                    return Err(EngineError::IllegalAction(format!("Cannot bid during {}", state.phase)))
+                }
+                if auction.bidders.get(auction.current_bidder) != Some(&action.user_id) {
+                    return Err(EngineError::NotPlayersTurn)
+                }
+
+                if action.action_type == "bid" {
+                    let bid_event: MonopolyBidEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid bid payload".to_string()))?;
+                    self.bid(state, actor, bid_event.amount)?;
+                } else {
+                    pass_bid(state);
+                }
+            },
+            "build" => {
+                require_turn(state, actor, &[ROLL_PHASE, POST_ROLL_PHASE])?;
+                self.build(state, actor, square_payload(action)?)?;
+            },
+            "sell_house" | "mortgage" => {
+                require_turn_or_debtor(state, actor, &[ROLL_PHASE, BUY_PHASE, POST_ROLL_PHASE])?;
+                let square = square_payload(action)?;
+                if action.action_type == "sell_house" {
+                    sell_house(state, actor, square)?;
+                } else {
+                    mortgage(state, actor, square)?;
+                }
+            },
+            "unmortgage" => {
+                require_turn(state, actor, &[ROLL_PHASE, POST_ROLL_PHASE])?;
+                unmortgage(state, actor, square_payload(action)?)?;
+            },
+            "propose_trade" => {
+                require_turn(state, actor, &[ROLL_PHASE, POST_ROLL_PHASE])?;
+                let trade_event: MonopolyTradeEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid trade payload".to_string()))?;
+                propose_trade(state, actor, trade_event)?;
+            },
+            "accept_trade" | "reject_trade" => {
+                let trade = state.pending_trade.clone().ok_or(EngineError::IllegalAction("There is no trade offer".to_string()))?;
+
+                if action.action_type == "reject_trade" {
+                    // The player who made the offer can take it back
+                    if trade.to_user != action.user_id && trade.from_user != action.user_id {
+                        return Err(EngineError::NotPlayersTurn)
+                    }
+                    state.pending_trade = None;
+                    add_log(state, format!("Trade between {} and {} was rejected", trade.from_user, trade.to_user));
+                } else {
+                    if trade.to_user != action.user_id {
+                        return Err(EngineError::NotPlayersTurn)
+                    }
+                    if state.phase != ROLL_PHASE && state.phase != POST_ROLL_PHASE {
+                        return Err(EngineError::IllegalAction(format!("Cannot trade during {}", state.phase)))
+                    }
+                    accept_trade(state, &trade)?;
+                }
+            },
+            "pay_debt" => {
+                require_debtor(state, actor)?;
+                let debt = state.debts[0].clone();
+                if state.players[actor].cash < debt.amount {
+                    return Err(EngineError::IllegalAction("Not enough cash to pay the debt".to_string()))
+                }
+
+                state.debts.remove(0);
+                transfer(state, actor, debt.creditor.as_deref(), debt.amount);
+                add_log(state, format!("{} paid a debt of ${}", action.user_id, debt.amount));
+            },
+            "declare_bankruptcy" => {
+                require_debtor(state, actor)?;
+                let debt = state.debts.remove(0);
+                self.bankrupt(state, actor, debt.creditor);
+            },
+            "end_turn" => {
+                require_turn(state, actor, &[POST_ROLL_PHASE])?;
+                next_player(state);
+            },
+            other => return Err(EngineError::InvalidAction(format!("Unknown monopoly action {}", other))),
+        }
+
+        update_phase(state);
+        Ok(())
+    }
+
+    fn roll(&self, state: &mut MonopolyState, actor: usize) {
+        let (first, second) = roll_dice(state);
+        let total = (first + second) as usize;
+        let doubles = first == second;
+
+        state.last_dice = vec![first, second];
+        state.can_roll = false;
+        add_log(state, format!("{} rolled {} and {}", state.players[actor].user_id, first, second));
+
+        if state.players[actor].in_jail {
+            if doubles {
+                leave_jail(state, actor);
+            } else {
+                state.players[actor].jail_turns += 1;
+                if state.players[actor].jail_turns < MAX_JAIL_TURNS {
+                    return;
+                }
+
+                // Third failed attempt, the fine has to be paid and the player moves anyway
+                leave_jail(state, actor);
+                charge(state, actor, None, self.jail_fine);
+            }
+
+            // Leaving jail with doubles does not give another roll
+            self.advance(state, actor, total);
+            return;
+        }
+
+        if doubles {
+            state.doubles_count += 1;
+            if state.doubles_count >= 3 {
+                send_to_jail(state, actor);
+                return;
+            }
+            state.can_roll = true;
+        }
+
+        self.advance(state, actor, total);
+    }
+
+    fn advance(&self, state: &mut MonopolyState, actor: usize, steps: usize) {
+        let target = (state.players[actor].position + steps) % BOARD_SIZE;
+        self.move_to(state, actor, target);
+        self.land(state, actor, RentRule::Normal);
+    }
+
+    // Moves forward to the target square, collecting the salary when passing GO
+    fn move_to(&self, state: &mut MonopolyState, actor: usize, target: usize) {
+        if target <= state.players[actor].position {
+            state.players[actor].cash += self.go_salary;
+        }
+        state.players[actor].position = target;
+    }
+
+    fn land(&self, state: &mut MonopolyState, actor: usize, rent_rule: RentRule) {
+        let square = state.players[actor].position;
+
+        match BOARD[square].kind {
+            SquareKind::Street { .. } | SquareKind::Railroad | SquareKind::Utility => {
+                let Some(property) = state.properties.iter().find(|property| property.square == square).cloned() else { return };
+
+                match property.owner {
+                    None => state.pending_purchase = Some(square),
+                    Some(owner) if owner != state.players[actor].user_id && !property.mortgaged => {
+                        let rent = rent(state, square, &owner, rent_rule);
+                        add_log(state, format!("{} owes ${} rent for {}", state.players[actor].user_id, rent, BOARD[square].name));
+                        charge(state, actor, Some(&owner), rent);
+                    },
+                    _ => {},
+                }
+            },
+            SquareKind::Tax(amount) => charge(state, actor, None, amount),
+            SquareKind::Chance => self.draw_card(state, actor, CHANCE_DECK),
+            SquareKind::CommunityChest => self.draw_card(state, actor, COMMUNITY_CHEST_DECK),
+            SquareKind::GoToJail => send_to_jail(state, actor),
+            SquareKind::Go | SquareKind::Jail | SquareKind::FreeParking => {},
+        }
+    }
+
+    fn draw_card(&self, state: &mut MonopolyState, actor: usize, deck: &str) {
+        let deck_cards = if deck == CHANCE_DECK { &mut state.chance_deck } else { &mut state.community_chest_deck };
+        if deck_cards.is_empty() {
+            return;
+        }
+
+        let card_index = deck_cards.remove(0);
+        let card = cards::deck_cards(deck)[card_index as usize];
+
+        // Held jail cards go back to the bottom of their deck once they are used
+        if card.effect == CardEffect::GetOutOfJailFree {
+            state.players[actor].jail_free_cards.push(deck.to_string());
+        } else {
+            deck_cards.push(card_index);
+        }
+
+        add_log(state, format!("{} drew \"{}\"", state.players[actor].user_id, card.text));
+
+        match card.effect {
+            CardEffect::AdvanceTo(target) => {
+                self.move_to(state, actor, target);
+                self.land(state, actor, RentRule::Normal);
+            },
+            CardEffect::AdvanceToNearestRailroad => {
+                let target = board::next_square(state.players[actor].position, &RAILROAD_SQUARES);
+                self.move_to(state, actor, target);
+                self.land(state, actor, RentRule::DoubleRailroad);
+            },
+            CardEffect::AdvanceToNearestUtility => {
+                let target = board::next_square(state.players[actor].position, &UTILITY_SQUARES);
+                self.move_to(state, actor, target);
+                self.land(state, actor, RentRule::TenTimesDice);
+            },
+            CardEffect::Collect(amount) => state.players[actor].cash += amount,
+            CardEffect::Pay(amount) => charge(state, actor, None, amount),
+            CardEffect::GetOutOfJailFree => {},
+            CardEffect::GoBack(steps) => {
+                state.players[actor].position = (state.players[actor].position + BOARD_SIZE - steps) % BOARD_SIZE;
+                self.land(state, actor, RentRule::Normal);
+            },
+            CardEffect::GoToJail => send_to_jail(state, actor),
+            CardEffect::Repairs { per_house, per_hotel } => {
+                let user_id = state.players[actor].user_id.clone();
+                let amount: i64 = state.properties.iter()
+                    .filter(|property| property.owner.as_deref() == Some(user_id.as_str()))
+                    .map(|property| if property.houses == HOTEL { per_hotel } else { per_house * property.houses as i64 })
+                    .sum();
+                charge(state, actor, None, amount);
+            },
+            CardEffect::PayEachPlayer(amount) => {
+                for other in active_players(state) {
+                    if other != actor {
+                        let creditor = state.players[other].user_id.clone();
+                        charge(state, actor, Some(&creditor), amount);
+                    }
+                }
+            },
+            CardEffect::CollectFromEachPlayer(amount) => {
+                let creditor = state.players[actor].user_id.clone();
+                for other in active_players(state) {
+                    if other != actor {
+                        charge(state, other, Some(&creditor), amount);
+                    }
+                }
+            },
+        }
+    }
+
+    fn bid(&self, state: &mut MonopolyState, actor: usize, amount: i64) -> Result<(), EngineError> {
+        let auction = state.auction.as_mut().ok_or(EngineError::IllegalAction("There is no auction running".to_string()))?;
+        if amount <= auction.highest_bid {
+            return Err(EngineError::IllegalAction(format!("Bid has to be higher than ${}", auction.highest_bid)))
+        }
+        if amount > state.players[actor].cash {
+            return Err(EngineError::IllegalAction("Not enough cash for this bid".to_string()))
+        }
+
+        auction.highest_bid = amount;
+        auction.highest_bidder = Some(state.players[actor].user_id.clone());
+        auction.current_bidder = (auction.current_bidder + 1) % auction.bidders.len();
+        finish_auction_if_done(state);
+        Ok(())
+    }
+
+    fn build(&self, state: &mut MonopolyState, actor: usize, square: usize) -> Result<(), EngineError> {
+        let user_id = state.players[actor].user_id.clone();
+        let group = board::street_group(square).ok_or(EngineError::IllegalAction("Houses can only be built on streets".to_string()))?;
+        let group_properties = group_properties(state, group);
+
+        if !group_properties.iter().all(|property| property.owner.as_deref() == Some(user_id.as_str())) {
+            return Err(EngineError::IllegalAction("The whole colour group has to be owned to build".to_string()))
+        }
+        if group_properties.iter().any(|property| property.mortgaged) {
+            return Err(EngineError::IllegalAction("Cannot build while a property of the group is mortgaged".to_string()))
+        }
+
+        let houses = property_mut(state, square)?.houses;
+        let fewest_houses = group_properties.iter().map(|property| property.houses).min().unwrap_or(0);
+        if houses >= HOTEL {
+            return Err(EngineError::IllegalAction("Property already has a hotel".to_string()))
+        }
+        if houses > fewest_houses {
+            return Err(EngineError::IllegalAction("Houses have to be built evenly across the group".to_string()))
+        }
+
+        let cost = board::house_cost(square).unwrap_or(0);
+        if state.players[actor].cash < cost {
+            return Err(EngineError::IllegalAction("Not enough cash to build".to_string()))
+        }
+
+        // A hotel replaces the four houses, which go back to the bank
+        if houses == HOTEL - 1 {
+            if state.hotels_left == 0 {
+                return Err(EngineError::IllegalAction("The bank has no hotels left".to_string()))
+            }
+            state.hotels_left -= 1;
+            state.houses_left += HOTEL - 1;
+        } else {
+            if state.houses_left == 0 {
+                return Err(EngineError::IllegalAction("The bank has no houses left".to_string()))
+            }
+            state.houses_left -= 1;
+        }
+
+        state.players[actor].cash -= cost;
+        property_mut(state, square)?.houses += 1;
+        add_log(state, format!("{} built on {}", user_id, BOARD[square].name));
+        Ok(())
+    }
+
+    // Bankrupt players give everything to the creditor, or back to the bank if they owe the bank
+    fn bankrupt(&self, state: &mut MonopolyState, actor: usize, creditor: Option<String>) {
+        let user_id = state.players[actor].user_id.clone();
+        let creditor_index = creditor.as_deref().and_then(|creditor| player_index(state, creditor));
+
+        // Buildings are sold back to the bank at half price first
+        for index in 0..state.properties.len() {
+            let property = &state.properties[index];
+            if property.owner.as_deref() != Some(user_id.as_str()) || property.houses == 0 {
+                continue;
+            }
+
+            let houses = property.houses;
+            let square = property.square;
+            state.players[actor].cash += board::house_cost(square).unwrap_or(0) / 2 * houses as i64;
+            if houses == HOTEL { state.hotels_left += 1 } else { state.houses_left += houses }
+            state.properties[index].houses = 0;
+        }
+
+        let cash = std::mem::take(&mut state.players[actor].cash);
+        let jail_free_cards = std::mem::take(&mut state.players[actor].jail_free_cards);
+
+        match creditor_index {
+            Some(creditor_index) => {
+                state.players[creditor_index].cash += cash;
+                state.players[creditor_index].jail_free_cards.extend(jail_free_cards);
+            },
+            None => {
+                for deck in jail_free_cards {
+                    return_jail_free_card(state, &deck);
+                }
+            },
+        }
+
+        for property in state.properties.iter_mut().filter(|property| property.owner.as_deref() == Some(user_id.as_str())) {
+            property.owner = creditor.clone();
+            if creditor.is_none() {
+                property.mortgaged = false;
+            }
+        }
+
+        state.players[actor].bankrupt = true;
+        state.bankruptcies.push(user_id.clone());
+
+        // Whatever else the player owed is lost, money owed to them goes to whoever took over their assets
+        state.debts.retain(|debt| debt.debtor != user_id);
+        for debt in state.debts.iter_mut().filter(|debt| debt.creditor.as_deref() == Some(user_id.as_str())) {
+            debt.creditor = creditor.clone();
+        }
+        if state.pending_trade.as_ref().map(|trade| trade.from_user == user_id || trade.to_user == user_id).unwrap_or(false) {
+            state.pending_trade = None;
+        }
+
+        add_log(state, format!("{} went bankrupt", user_id));
+
+        if state.current_player == actor {
+            state.pending_purchase = None;
+            state.auction = None;
+            next_player(state);
+        }
+    }
+}
+
+impl GameEngine for MonopolyEngine {
+    type State = MonopolyState;
+
+    fn game_type(&self) -> &'static str {
+        "monopoly"
+    }
+
+    fn state_key_prefix(&self) -> &'static str {
+        MONOPOLY_STATE_KEY
+    }
+
+    fn min_players(&self) -> usize {
+        MIN_PLAYERS
+    }
+
+    fn max_players(&self) -> usize {
+        MAX_PLAYERS
+    }
+
+    fn state_storage(&self) -> StateStorage {
+        StateStorage::Mongo
+    }
+
+    fn initial_state(&self, turn_mapping: &UserTurnMapping, seed: u64) -> Result<Self::State, EngineError> {
+        let player_count = turn_mapping.turn_mappings.len();
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&player_count) {
+            return Err(EngineError::InvalidPlayerCount { min: MIN_PLAYERS, max: MAX_PLAYERS, found: player_count })
+        }
+
+        let mut turns: Vec<_> = turn_mapping.turn_mappings.iter().collect();
+        turns.sort_by_key(|turn| turn.count_id);
+
+        let mut chance_deck: Vec<u8> = (0..cards::CHANCE_CARDS.len() as u8).collect();
+        let mut community_chest_deck: Vec<u8> = (0..cards::COMMUNITY_CHEST_CARDS.len() as u8).collect();
+        DeterministicRng::for_stream(seed, CHANCE_STREAM).shuffle(&mut chance_deck);
+        DeterministicRng::for_stream(seed, COMMUNITY_CHEST_STREAM).shuffle(&mut community_chest_deck);
+
+        Ok(MonopolyState {
+            game_id: turn_mapping.game_id.clone(),
+            seed,
+            players: turns.iter().map(|turn| MonopolyPlayer { user_id: turn.user_id.clone(), cash: self.starting_cash, ..Default::default() }).collect(),
+            phase: ROLL_PHASE.to_string(),
+            can_roll: true,
+            properties: (0..BOARD_SIZE).filter(|square| board::is_ownable(*square)).map(|square| MonopolyProperty { square, ..Default::default() }).collect(),
+            houses_left: TOTAL_HOUSES,
+            hotels_left: TOTAL_HOTELS,
+            chance_deck,
+            community_chest_deck,
+            ..Default::default()
+        })
+    }
+
+    fn validate_action(&self, state: &Self::State, action: &EngineAction) -> Result<(), EngineError> {
+        let mut state = state.clone();
+        self.act(&mut state, action)
+    }
+
+    fn apply_action(&self, state: &Self::State, action: &EngineAction) -> Result<Self::State, EngineError> {
+        let mut state = state.clone();
+        self.act(&mut state, action)?;
+        Ok(state)
+    }
+
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        state.phase == GAME_OVER_PHASE
+    }
+
+    fn result(&self, state: &Self::State) -> Option<GameResult> {
+        if !self.is_terminal(state) {
+            return None
+        }
+
+        let survivors: Vec<String> = state.players.iter().filter(|player| !player.bankrupt).map(|player| player.user_id.clone()).collect();
+        let bankruptcies: Vec<Vec<String>> = state.bankruptcies.iter().map(|user_id| vec![user_id.clone()]).collect();
+        Some(GameResult::from_eliminations(survivors, &bankruptcies, "bankruptcy"))
+    }
+
+    fn serialize_state(&self, state: &Self::State) -> Result<String, EngineError> {
+        serde_json::to_string(state).map_err(|err| EngineError::InvalidState(err.to_string()))
+    }
+
+    fn deserialize_state(&self, raw_state: &str) -> Result<Self::State, EngineError> {
+        serde_json::from_str(raw_state).map_err(|err| EngineError::InvalidState(err.to_string()))
+    }
+
+    // Deck order and seed would give away the next cards and dice
+    fn public_state(&self, state: &Self::State) -> Result<String, EngineError> {
+        let mut public_state = state.clone();
+        public_state.chance_deck = vec![];
+        public_state.community_chest_deck = vec![];
+        if !self.is_terminal(state) {
+            public_state.seed = 0;
+        }
+
+        self.serialize_state(&public_state)
+    }
+
+    // Leaving goes bankrupt to the bank
+    fn remove_player(&self, state: &Self::State, user_id: &str, _now: i64) -> Option<Self::State> {
+        let mut state = state.clone();
+        let Some(actor) = player_index(&state, user_id) else {
+            return Some(state)
+        };
+
+        if !state.players[actor].bankrupt {
+            leave_auction(&mut state, user_id);
+            self.bankrupt(&mut state, actor, None);
+            update_phase(&mut state);
+        }
+
+        Some(state)
+    }
+
+    fn eliminated_players(&self, state: &Self::State) -> Vec<String> {
+        state.bankruptcies.clone()
+    }
+}
+
+
+fn player_index(state: &MonopolyState, user_id: &str) -> Option<usize> {
+    state.players.iter().position(|player| player.user_id == user_id)
+}
+
+fn active_players(state: &MonopolyState) -> Vec<usize> {
+    (0..state.players.len()).filter(|index| !state.players[*index].bankrupt).collect()
+}
+
+fn property_mut(state: &mut MonopolyState, square: usize) -> Result<&mut MonopolyProperty, EngineError> {
+    state.properties.iter_mut().find(|property| property.square == square).ok_or(EngineError::InvalidAction(format!("Square {} cannot be owned", square)))
+}
+
+fn group_properties(state: &MonopolyState, group: &str) -> Vec<MonopolyProperty> {
+    let squares = board::group_squares(group);
+    state.properties.iter().filter(|property| squares.contains(&property.square)).cloned().collect()
+}
+
+fn owned_by(state: &MonopolyState, user_id: &str, squares: &[usize]) -> usize {
+    state.properties.iter().filter(|property| squares.contains(&property.square) && property.owner.as_deref() == Some(user_id)).count()
+}
+
+fn add_log(state: &mut MonopolyState, entry: String) {
+    state.log.push(entry);
+    if state.log.len() > MAX_LOG_ENTRIES {
+        let overflow = state.log.len() - MAX_LOG_ENTRIES;
+        state.log.drain(0..overflow);
+    }
+}
+
+fn square_payload(action: &EngineAction) -> Result<usize, EngineError> {
+    let square_event: MonopolySquareEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid square payload".to_string()))?;
+    Ok(square_event.square)
+}
+
+fn require_turn(state: &MonopolyState, actor: usize, phases: &[&str]) -> Result<(), EngineError> {
+    if !phases.contains(&state.phase.as_str()) {
+        return Err(EngineError::IllegalAction(format!("Cannot do that during {}", state.phase)))
+    }
+    if state.current_player != actor {
+        return Err(EngineError::NotPlayersTurn)
+    }
+    Ok(())
+}
+
+fn require_debtor(state: &MonopolyState, actor: usize) -> Result<(), EngineError> {
+    match state.debts.first() {
+        Some(debt) if state.phase == DEBT_PHASE => {
+            if debt.debtor != state.players[actor].user_id {
+                return Err(EngineError::NotPlayersTurn)
+            }
+            Ok(())
+        },
+        _ => Err(EngineError::IllegalAction("There is no debt to settle".to_string())),
+    }
+}
+
+// Selling and mortgaging is how a debtor raises money, otherwise it is only allowed on the player's own turn
+fn require_turn_or_debtor(state: &MonopolyState, actor: usize, phases: &[&str]) -> Result<(), EngineError> {
+    if state.phase == DEBT_PHASE {
+        return require_debtor(state, actor)
+    }
+    require_turn(state, actor, phases)
+}
+
+fn roll_dice(state: &mut MonopolyState) -> (u8, u8) {
+    let mut rng = DeterministicRng::for_stream(state.seed, state.roll_count);
+    state.roll_count += 1;
+    (rng.gen_range(6) as u8 + 1, rng.gen_range(6) as u8 + 1)
+}
+
+fn rent(state: &MonopolyState, square: usize, owner: &str, rent_rule: RentRule) -> i64 {
+    match BOARD[square].kind {
+        SquareKind::Street { group, rents, .. } => {
+            let houses = state.properties.iter().find(|property| property.square == square).map(|property| property.houses).unwrap_or(0);
+            let group_squares = board::group_squares(group);
+
+            // Unimproved streets pay double when the owner has the whole colour group
+            if houses == 0 && owned_by(state, owner, &group_squares) == group_squares.len() {
+                rents[0] * 2
+            } else {
+                rents[houses as usize]
+            }
+        },
+        SquareKind::Railroad => {
+            let rent = 25 << (owned_by(state, owner, &RAILROAD_SQUARES).max(1) - 1);
+            if rent_rule == RentRule::DoubleRailroad { rent * 2 } else { rent }
+        },
+        SquareKind::Utility => {
+            let dice_total: i64 = state.last_dice.iter().map(|die| *die as i64).sum();
+            let multiplier = if rent_rule == RentRule::TenTimesDice || owned_by(state, owner, &UTILITY_SQUARES) == UTILITY_SQUARES.len() { 10 } else { 4 };
+            dice_total * multiplier
+        },
+        _ => 0,
+    }
+}
+
+// Moves money from a player to the creditor (None is the bank)
+fn transfer(state: &mut MonopolyState, payer: usize, creditor: Option<&str>, amount: i64) {
+    state.players[payer].cash -= amount;
+    if let Some(creditor_index) = creditor.and_then(|creditor| player_index(state, creditor)) {
+        state.players[creditor_index].cash += amount;
+    }
+}
+
+// Payments that cannot be covered with cash become debts the player has to settle before the game goes on
+fn charge(state: &mut MonopolyState, payer: usize, creditor: Option<&str>, amount: i64) {
+    if amount <= 0 || state.players[payer].bankrupt {
+        return;
+    }
+
+    if state.players[payer].cash >= amount {
+        transfer(state, payer, creditor, amount);
+        return;
+    }
+
+    state.debts.push(MonopolyDebt {
+        debtor: state.players[payer].user_id.clone(),
+        creditor: creditor.map(|creditor| creditor.to_string()),
+        amount,
+    });
+}
+
+fn send_to_jail(state: &mut MonopolyState, actor: usize) {
+    state.players[actor].position = JAIL_SQUARE;
+    state.players[actor].in_jail = true;
+    state.players[actor].jail_turns = 0;
+    state.can_roll = false;
+    state.doubles_count = 0;
+    add_log(state, format!("{} went to jail", state.players[actor].user_id));
+}
+
+fn leave_jail(state: &mut MonopolyState, actor: usize) {
+    state.players[actor].in_jail = false;
+    state.players[actor].jail_turns = 0;
+}
+
+fn return_jail_free_card(state: &mut MonopolyState, deck: &str) {
+    let card_index = cards::jail_free_card(deck);
+    if deck == CHANCE_DECK {
+        state.chance_deck.push(card_index);
+    } else {
+        state.community_chest_deck.push(card_index);
+    }
+}
+
+// Every player still in the game can bid, starting with the one after the player who declined
+fn start_auction(state: &mut MonopolyState, square: usize) {
+    let player_count = state.players.len();
+    let bidders = (1..=player_count)
+        .map(|offset| (state.current_player + offset) % player_count)
+        .filter(|index| !state.players[*index].bankrupt)
+        .map(|index| state.players[index].user_id.clone())
+        .collect();
+
+    state.auction = Some(MonopolyAuction { square, bidders, ..Default::default() });
+    add_log(state, format!("{} is up for auction", BOARD[square].name));
+}
+
+fn pass_bid(state: &mut MonopolyState) {
+    if let Some(auction) = state.auction.as_mut() {
+        auction.bidders.remove(auction.current_bidder);
+        if auction.current_bidder >= auction.bidders.len() {
+            auction.current_bidder = 0;
+        }
+    }
+    finish_auction_if_done(state);
+}
+
+// A player leaving while it is not their turn stops bidding, their bid is withdrawn
+fn leave_auction(state: &mut MonopolyState, user_id: &str) {
+    let Some(auction) = state.auction.as_mut() else { return };
+    let Some(index) = auction.bidders.iter().position(|bidder| bidder == user_id) else { return };
+
+    auction.bidders.remove(index);
+    if index < auction.current_bidder {
+        auction.current_bidder -= 1;
+    }
+    if auction.current_bidder >= auction.bidders.len() {
+        auction.current_bidder = 0;
+    }
+    if auction.highest_bidder.as_deref() == Some(user_id) {
+        auction.highest_bidder = None;
+        auction.highest_bid = 0;
+    }
+    finish_auction_if_done(state);
+}
+
+// The auction ends once only the highest bidder is left, or everyone passed without bidding
+fn finish_auction_if_done(state: &mut MonopolyState) {
+    let Some(auction) = state.auction.clone() else { return };
+    let winner_left = auction.bidders.len() == 1 && auction.highest_bidder.as_ref() == auction.bidders.first();
+    if !winner_left && !auction.bidders.is_empty() {
+        return;
+    }
+
+    state.auction = None;
+    match auction.highest_bidder.and_then(|winner| player_index(state, &winner)) {
+        Some(winner) => {
+            state.players[winner].cash -= auction.highest_bid;
+            let user_id = state.players[winner].user_id.clone();
+            if let Some(property) = state.properties.iter_mut().find(|property| property.square == auction.square) {
+                property.owner = Some(user_id.clone());
+            }
+            add_log(state, format!("{} won {} for ${}", user_id, BOARD[auction.square].name, auction.highest_bid));
+        },
+        None => add_log(state, format!("Nobody bid on {}", BOARD[auction.square].name)),
+    }
+}
+
+fn sell_house(state: &mut MonopolyState, actor: usize, square: usize) -> Result<(), EngineError> {
+    let user_id = state.players[actor].user_id.clone();
+    let group = board::street_group(square).ok_or(EngineError::IllegalAction("Only streets have houses".to_string()))?;
+    let most_houses = group_properties(state, group).iter().map(|property| property.houses).max().unwrap_or(0);
+    let property = property_mut(state, square)?.clone();
+
+    if property.owner.as_deref() != Some(user_id.as_str()) {
+        return Err(EngineError::IllegalAction("Property is not owned by the player".to_string()))
+    }
+    if property.houses == 0 {
+        return Err(EngineError::IllegalAction("There are no houses to sell".to_string()))
+    }
+    if property.houses < most_houses {
+        return Err(EngineError::IllegalAction("Houses have to be sold evenly across the group".to_string()))
+    }
+
+    // Breaking a hotel down needs four houses from the bank
+    if property.houses == HOTEL {
+        if state.houses_left < HOTEL - 1 {
+            return Err(EngineError::IllegalAction("The bank does not have enough houses to break down the hotel".to_string()))
+        }
+        state.houses_left -= HOTEL - 1;
+        state.hotels_left += 1;
+    } else {
+        state.houses_left += 1;
+    }
+
+    property_mut(state, square)?.houses -= 1;
+    state.players[actor].cash += board::house_cost(square).unwrap_or(0) / 2;
+    add_log(state, format!("{} sold a house on {}", user_id, BOARD[square].name));
+    Ok(())
+}
+
+fn mortgage(state: &mut MonopolyState, actor: usize, square: usize) -> Result<(), EngineError> {
+    let user_id = state.players[actor].user_id.clone();
+    let group_has_houses = board::street_group(square).map(|group| group_properties(state, group).iter().any(|property| property.houses > 0)).unwrap_or(false);
+    let property = property_mut(state, square)?;
+
+    if property.owner.as_deref() != Some(user_id.as_str()) {
+        return Err(EngineError::IllegalAction("Property is not owned by the player".to_string()))
+    }
+    if property.mortgaged {
+        return Err(EngineError::IllegalAction("Property is already mortgaged".to_string()))
+    }
+    if group_has_houses {
+        return Err(EngineError::IllegalAction("Houses of the colour group have to be sold first".to_string()))
+    }
+
+    property.mortgaged = true;
+    state.players[actor].cash += board::price(square).unwrap_or(0) / 2;
+    add_log(state, format!("{} mortgaged {}", user_id, BOARD[square].name));
+    Ok(())
+}
+
+// Lifting a mortgage costs the mortgage value plus 10% interest
+fn unmortgage(state: &mut MonopolyState, actor: usize, square: usize) -> Result<(), EngineError> {
+    let user_id = state.players[actor].user_id.clone();
+    let cost = (board::price(square).unwrap_or(0) / 2 * 11 + 9) / 10;
+    let cash = state.players[actor].cash;
+    let property = property_mut(state, square)?;
+
+    if property.owner.as_deref() != Some(user_id.as_str()) {
+        return Err(EngineError::IllegalAction("Property is not owned by the player".to_string()))
+    }
+    if !property.mortgaged {
+        return Err(EngineError::IllegalAction("Property is not mortgaged".to_string()))
+    }
+    if cash < cost {
+        return Err(EngineError::IllegalAction("Not enough cash to lift the mortgage".to_string()))
+    }
+
+    property.mortgaged = false;
+    state.players[actor].cash -= cost;
+    add_log(state, format!("{} lifted the mortgage on {}", user_id, BOARD[square].name));
+    Ok(())
+}
+
+fn propose_trade(state: &mut MonopolyState, actor: usize, trade_event: MonopolyTradeEvent) -> Result<(), EngineError> {
+    let trade = MonopolyTrade {
+        from_user: state.players[actor].user_id.clone(),
+        to_user: trade_event.to_user,
+        offer_cash: trade_event.offer_cash,
+        request_cash: trade_event.request_cash,
+        offer_squares: trade_event.offer_squares,
+        request_squares: trade_event.request_squares,
+        offer_jail_free_cards: trade_event.offer_jail_free_cards,
+        request_jail_free_cards: trade_event.request_jail_free_cards,
+    };
+
+    if trade.to_user == trade.from_user {
+        return Err(EngineError::InvalidAction("Cannot trade with yourself".to_string()))
+    }
+    let is_empty = trade.offer_cash == 0 && trade.request_cash == 0 && trade.offer_squares.is_empty() && trade.request_squares.is_empty()
+        && trade.offer_jail_free_cards == 0 && trade.request_jail_free_cards == 0;
+    if is_empty {
+        return Err(EngineError::InvalidAction("Trade is empty".to_string()))
+    }
+
+    validate_trade(state, &trade)?;
+    add_log(state, format!("{} offered a trade to {}", trade.from_user, trade.to_user));
+    state.pending_trade = Some(trade);
+    Ok(())
+}
+
+// Checked again on accept since both players may have sold or mortgaged things in between
+fn validate_trade(state: &MonopolyState, trade: &MonopolyTrade) -> Result<(), EngineError> {
+    let from = player_index(state, &trade.from_user).ok_or(EngineError::InvalidAction("Unknown player in trade".to_string()))?;
+    let to = player_index(state, &trade.to_user).ok_or(EngineError::InvalidAction("Unknown player in trade".to_string()))?;
+
+    if state.players[to].bankrupt {
+        return Err(EngineError::IllegalAction("Cannot trade with a bankrupt player".to_string()))
+    }
+    if trade.offer_cash < 0 || trade.request_cash < 0 {
+        return Err(EngineError::InvalidAction("Cash in a trade cannot be negative".to_string()))
+    }
+    if state.players[from].cash < trade.offer_cash || state.players[to].cash < trade.request_cash {
+        return Err(EngineError::IllegalAction("Not enough cash for this trade".to_string()))
+    }
+    if state.players[from].jail_free_cards.len() < trade.offer_jail_free_cards as usize || state.players[to].jail_free_cards.len() < trade.request_jail_free_cards as usize {
+        return Err(EngineError::IllegalAction("Not enough get out of jail free cards for this trade".to_string()))
+    }
+
+    for (user_id, squares) in [(&trade.from_user, &trade.offer_squares), (&trade.to_user, &trade.request_squares)] {
+        for square in squares {
+            let property = state.properties.iter().find(|property| property.square == *square).ok_or(EngineError::InvalidAction(format!("Square {} cannot be owned", square)))?;
+            if property.owner.as_ref() != Some(user_id) {
+                return Err(EngineError::IllegalAction(format!("{} is not owned by {}", BOARD[*square].name, user_id)))
+            }
+
+            let group_has_houses = board::street_group(*square).map(|group| group_properties(state, group).iter().any(|property| property.houses > 0)).unwrap_or(false);
+            if group_has_houses {
+                return Err(EngineError::IllegalAction(format!("Houses of the colour group of {} have to be sold first", BOARD[*square].name)))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn accept_trade(state: &mut MonopolyState, trade: &MonopolyTrade) -> Result<(), EngineError> {
+    validate_trade(state, trade)?;
+    let from = player_index(state, &trade.from_user).unwrap_or(0);
+    let to = player_index(state, &trade.to_user).unwrap_or(0);
+
+    state.players[from].cash += trade.request_cash - trade.offer_cash;
+    state.players[to].cash += trade.offer_cash - trade.request_cash;
+
+    for property in state.properties.iter_mut() {
+        if trade.offer_squares.contains(&property.square) {
+            property.owner = Some(trade.to_user.clone());
+        } else if trade.request_squares.contains(&property.square) {
+            property.owner = Some(trade.from_user.clone());
+        }
+    }
+
+    for _ in 0..trade.offer_jail_free_cards {
+        if let Some(card) = state.players[from].jail_free_cards.pop() {
+            state.players[to].jail_free_cards.push(card);
+        }
+    }
+    for _ in 0..trade.request_jail_free_cards {
+        if let Some(card) = state.players[to].jail_free_cards.pop() {
+            state.players[from].jail_free_cards.push(card);
+        }
+    }
+
+    state.pending_trade = None;
+    add_log(state, format!("{} accepted the trade from {}", trade.to_user, trade.from_user));
+    Ok(())
+}
+
+fn next_player(state: &mut MonopolyState) {
+    let player_count = state.players.len();
+    for offset in 1..=player_count {
+        let index = (state.current_player + offset) % player_count;
+        if !state.players[index].bankrupt {
+            state.current_player = index;
+            break;
+        }
+    }
+
+    state.can_roll = true;
+    state.doubles_count = 0;
+    state.pending_trade = None;
+}
+
+fn update_phase(state: &mut MonopolyState) {
+    let phase = if active_players(state).len() <= 1 {
+        GAME_OVER_PHASE
+    } else if !state.debts.is_empty() {
+        DEBT_PHASE
+    } else if state.auction.is_some() {
+        AUCTION_PHASE
+    } else if state.pending_purchase.is_some() {
+        BUY_PHASE
+    } else if state.can_roll {
+        ROLL_PHASE
+    } else {
+        POST_ROLL_PHASE
+    };
+
+    state.phase = phase.to_string();
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::models::user_turn_model::TurnModel;
+
+    use super::*;
+
+    fn new_game(players: &[&str], seed: u64) -> (MonopolyEngine, MonopolyState) {
+        let turn_mapping = UserTurnMapping {
+            host_id: players[0].to_string(),
+            game_id: "monopoly-test".to_string(),
+            turn_mappings: players.iter().enumerate().map(|(seat, user_id)| TurnModel {
+                count_id: seat as i64,
+                user_id: user_id.to_string(),
+                username: user_id.to_string(),
+                status: "".to_string(),
+            }).collect(),
+        };
+
+        let engine = MonopolyEngine::default();
+        let state = engine.initial_state(&turn_mapping, seed).unwrap();
+        (engine, state)
+    }
+
+    fn action(user_id: &str, action_type: &str, payload: &str) -> EngineAction {
+        EngineAction { user_id: user_id.to_string(), player_type: "".to_string(), action_type: action_type.to_string(), payload: payload.to_string(), received_at: 0 }
+    }
+
+    fn give(state: &mut MonopolyState, user_id: &str, squares: &[usize]) {
+        for square in squares {
+            property_mut(state, *square).unwrap().owner = Some(user_id.to_string());
+        }
+    }
+
+    fn property(state: &MonopolyState, square: usize) -> &MonopolyProperty {
+        state.properties.iter().find(|property| property.square == square).unwrap()
+    }
+
+    #[test]
+    fn initial_state_seats_players_in_turn_order() {
+        let (_, state) = new_game(&["a", "b", "c"], 1);
+
+        assert_eq!(state.players.iter().map(|player| player.user_id.as_str()).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert!(state.players.iter().all(|player| player.cash == 1500 && player.position == 0));
+        assert_eq!(state.phase, ROLL_PHASE);
+        assert_eq!(state.properties.len(), 28);
+        assert_eq!((state.houses_left, state.hotels_left), (32, 12));
+
+        let mut chance_deck = state.chance_deck.clone();
+        chance_deck.sort_unstable();
+        assert_eq!(chance_deck, (0..16).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn rolls_are_replayed_from_the_seed() {
+        let (engine, state) = new_game(&["a", "b"], 5);
+        let first = engine.apply_action(&state, &action("a", "roll", "")).unwrap();
+        let second = engine.apply_action(&state, &action("a", "roll", "")).unwrap();
+
+        assert_eq!(first.last_dice, second.last_dice);
+        assert_eq!(first.players[0].position, second.players[0].position);
+        assert_eq!(first.roll_count, 1);
+        assert!(first.last_dice.iter().all(|die| (1..=6).contains(die)));
+
+        assert!(matches!(engine.validate_action(&state, &action("b", "roll", "")), Err(EngineError::NotPlayersTurn)));
+    }
+
+    #[test]
+    fn rent_follows_groups_houses_railroads_and_utilities() {
+        let (_, mut state) = new_game(&["a", "b"], 1);
+
+        give(&mut state, "a", &[1]);
+        assert_eq!(rent(&state, 1, "a", RentRule::Normal), 2);
+        give(&mut state, "a", &[3]);
+        assert_eq!(rent(&state, 1, "a", RentRule::Normal), 4);
+        property_mut(&mut state, 3).unwrap().houses = 2;
+        assert_eq!(rent(&state, 3, "a", RentRule::Normal), 60);
+
+        give(&mut state, "a", &[5, 15]);
+        assert_eq!(rent(&state, 5, "a", RentRule::Normal), 50);
+        assert_eq!(rent(&state, 5, "a", RentRule::DoubleRailroad), 100);
+        give(&mut state, "a", &[25, 35]);
+        assert_eq!(rent(&state, 35, "a", RentRule::Normal), 200);
+
+        state.last_dice = vec![3, 4];
+        give(&mut state, "a", &[12]);
+        assert_eq!(rent(&state, 12, "a", RentRule::Normal), 28);
+        assert_eq!(rent(&state, 12, "a", RentRule::TenTimesDice), 70);
+        give(&mut state, "a", &[28]);
+        assert_eq!(rent(&state, 12, "a", RentRule::Normal), 70);
+    }
+
+    #[test]
+    fn houses_are_built_evenly_on_complete_groups() {
+        let (engine, mut state) = new_game(&["a", "b"], 1);
+        let build_on = |square: usize| action("a", "build", &format!(r#"{{"square":{}}}"#, square));
+
+        give(&mut state, "a", &[1]);
+        assert!(matches!(engine.validate_action(&state, &build_on(1)), Err(EngineError::IllegalAction(_))));
+
+        give(&mut state, "a", &[3]);
+        let state = engine.apply_action(&state, &build_on(1)).unwrap();
+        assert_eq!(property(&state, 1).houses, 1);
+        assert_eq!(state.players[0].cash, 1450);
+        assert_eq!(state.houses_left, 31);
+
+        assert!(matches!(engine.validate_action(&state, &build_on(1)), Err(EngineError::IllegalAction(_))));
+        let state = engine.apply_action(&state, &build_on(3)).unwrap();
+        assert_eq!(property(&state, 3).houses, 1);
+    }
+
+    #[test]
+    fn lifting_a_mortgage_costs_ten_percent_interest() {
+        let (engine, mut state) = new_game(&["a", "b"], 1);
+        give(&mut state, "a", &[1]);
+
+        let state = engine.apply_action(&state, &action("a", "mortgage", r#"{"square":1}"#)).unwrap();
+        assert!(property(&state, 1).mortgaged);
+        assert_eq!(state.players[0].cash, 1530);
+
+        let state = engine.apply_action(&state, &action("a", "unmortgage", r#"{"square":1}"#)).unwrap();
+        assert!(!property(&state, 1).mortgaged);
+        assert_eq!(state.players[0].cash, 1497);
+    }
+
+    #[test]
+    fn chance_card_moves_the_player_and_goes_to_the_bottom() {
+        let (engine, mut state) = new_game(&["a", "b"], 1);
+        // Advance to Boardwalk on top of the deck
+        state.chance_deck.retain(|card| *card != 0);
+        state.chance_deck.insert(0, 0);
+        state.players[0].position = 7;
+
+        engine.land(&mut state, 0, RentRule::Normal);
+
+        assert_eq!(state.players[0].position, 39);
+        assert_eq!(state.players[0].cash, 1500);
+        assert_eq!(state.pending_purchase, Some(39));
+        assert_eq!(state.chance_deck.last(), Some(&0));
+    }
+
+    #[test]
+    fn passing_go_pays_the_salary_and_go_to_jail_does_not() {
+        let (engine, mut state) = new_game(&["a", "b"], 1);
+        state.players[0].position = 35;
+        engine.advance(&mut state, 0, 6);
+        assert_eq!(state.players[0].position, 1);
+        assert_eq!(state.players[0].cash, 1700);
+        assert_eq!(state.pending_purchase, Some(1));
+
+        let (engine, mut state) = new_game(&["a", "b"], 1);
+        state.players[0].position = 25;
+        engine.advance(&mut state, 0, 5);
+        assert_eq!(state.players[0].position, JAIL_SQUARE);
+        assert!(state.players[0].in_jail);
+        assert_eq!(state.players[0].cash, 1500);
+    }
+
+    #[test]
+    fn declined_property_goes_to_the_highest_bidder() {
+        let (engine, mut state) = new_game(&["a", "b", "c"], 1);
+        state.pending_purchase = Some(1);
+        state.can_roll = false;
+        update_phase(&mut state);
+
+        let state = engine.apply_action(&state, &action("a", "decline", "")).unwrap();
+        assert_eq!(state.phase, AUCTION_PHASE);
+        assert_eq!(state.auction.as_ref().unwrap().bidders, vec!["b", "c", "a"]);
+
+        let state = engine.apply_action(&state, &action("b", "bid", r#"{"amount":10}"#)).unwrap();
+        let state = engine.apply_action(&state, &action("c", "pass_bid", "")).unwrap();
+        assert!(matches!(engine.validate_action(&state, &action("a", "bid", r#"{"amount":10}"#)), Err(EngineError::IllegalAction(_))));
+        let state = engine.apply_action(&state, &action("a", "bid", r#"{"amount":20}"#)).unwrap();
+        let state = engine.apply_action(&state, &action("b", "pass_bid", "")).unwrap();
+
+        assert!(state.auction.is_none());
+        assert_eq!(property(&state, 1).owner.as_deref(), Some("a"));
+        assert_eq!(state.players[0].cash, 1480);
+        assert_eq!(state.phase, POST_ROLL_PHASE);
+    }
+
+    #[test]
+    fn unpaid_rent_ends_in_bankruptcy_to_the_owner() {
+        let (engine, mut state) = new_game(&["a", "b"], 1);
+        give(&mut state, "a", &[1]);
+        give(&mut state, "b", &[39]);
+        property_mut(&mut state, 1).unwrap().mortgaged = true;
+        state.players[0].cash = 20;
+        state.players[0].position = 39;
+
+        engine.land(&mut state, 0, RentRule::Normal);
+        update_phase(&mut state);
+        assert_eq!(state.phase, DEBT_PHASE);
+        assert_eq!(state.debts[0].amount, 50);
+        assert_eq!(state.players[0].cash, 20);
+
+        assert!(matches!(engine.validate_action(&state, &action("a", "pay_debt", "")), Err(EngineError::IllegalAction(_))));
+        assert!(matches!(engine.validate_action(&state, &action("b", "declare_bankruptcy", "")), Err(EngineError::NotPlayersTurn)));
+
+        let state = engine.apply_action(&state, &action("a", "declare_bankruptcy", "")).unwrap();
+        assert!(engine.is_terminal(&state));
+        assert_eq!(state.players[1].cash, 1520);
+        // The creditor takes the property over with its mortgage
+        assert_eq!(property(&state, 1).owner.as_deref(), Some("b"));
+        assert!(property(&state, 1).mortgaged);
+
+        let result = engine.result(&state).unwrap();
+        assert_eq!(result.winner.as_deref(), Some("b"));
+        assert_eq!(engine.eliminated_players(&state), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn leaving_player_withdraws_their_bid() {
+        let (engine, mut state) = new_game(&["a", "b", "c"], 1);
+        state.current_player = 2;
+        state.pending_purchase = Some(1);
+        state.can_roll = false;
+        update_phase(&mut state);
+
+        let state = engine.apply_action(&state, &action("c", "decline", "")).unwrap();
+        let state = engine.apply_action(&state, &action("a", "bid", r#"{"amount":50}"#)).unwrap();
+        let state = engine.remove_player(&state, "a", 0).unwrap();
+
+        let auction = state.auction.as_ref().unwrap();
+        assert_eq!(auction.bidders, vec!["b", "c"]);
+        assert_eq!(auction.current_bidder, 0);
+        assert_eq!((auction.highest_bid, auction.highest_bidder.clone()), (0, None));
+        assert!(state.players[0].bankrupt);
+        assert_eq!(state.phase, AUCTION_PHASE);
+
+        let state = engine.remove_player(&state, "c", 0).unwrap();
+        assert!(engine.is_terminal(&state));
+        assert_eq!(engine.result(&state).unwrap().winner.as_deref(), Some("b"));
+    }
+}
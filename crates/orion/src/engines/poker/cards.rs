@@ -0,0 +1,253 @@
+use std::cmp::Ordering;
+
+use crate::engines::rng::DeterministicRng;
+
+// Cards are kept as two character strings in the state: rank (23456789TJQKA) followed by suit (cdhs), e.g. "As", "Td"
+const RANKS: &str = "23456789TJQKA";
+const SUITS: &str = "cdhs";
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    // 2 ..= 14 where 14 is the ace
+    pub rank: u8,
+    pub suit: char,
+}
+
+impl Card {
+    pub fn parse(card: &str) -> Option<Card> {
+        let mut chars = card.chars();
+        let rank_char = chars.next()?;
+        let suit = chars.next()?;
+
+        if chars.next().is_some() || !SUITS.contains(suit) {
+            return None;
+        }
+
+        let rank = RANKS.find(rank_char)? as u8 + 2;
+        Some(Card { rank, suit })
+    }
+}
+
+
+// Full 52 card deck shuffled for a single hand. Replaying (seed, hand_number) gives back the same deck
+pub fn shuffled_deck(seed: u64, hand_number: u64) -> Vec<String> {
+    let mut deck: Vec<String> = RANKS
+        .chars()
+        .flat_map(|rank| SUITS.chars().map(move |suit| format!("{}{}", rank, suit)))
+        .collect();
+
+    DeterministicRng::for_stream(seed, hand_number).shuffle(&mut deck);
+    deck
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HandCategory {
+    HighCard,
+    Pair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+impl HandCategory {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HandCategory::HighCard => "high_card",
+            HandCategory::Pair => "pair",
+            HandCategory::TwoPair => "two_pair",
+            HandCategory::ThreeOfAKind => "three_of_a_kind",
+            HandCategory::Straight => "straight",
+            HandCategory::Flush => "flush",
+            HandCategory::FullHouse => "full_house",
+            HandCategory::FourOfAKind => "four_of_a_kind",
+            HandCategory::StraightFlush => "straight_flush",
+        }
+    }
+}
+
+
+// Category followed by the ranks that break ties, so two values compare the same way the hands do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandValue {
+    pub category: HandCategory,
+    pub tiebreakers: Vec<u8>,
+}
+
+impl Ord for HandValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.category.cmp(&other.category).then_with(|| self.tiebreakers.cmp(&other.tiebreakers))
+    }
+}
+
+impl PartialOrd for HandValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+
+// Best five card hand out of the given cards (hole cards + board)
+pub fn best_hand(cards: &[Card]) -> Option<HandValue> {
+    if cards.len() < 5 {
+        return None;
+    }
+
+    let mut best: Option<HandValue> = None;
+    let n = cards.len();
+
+    for a in 0..n {
+        for b in (a + 1)..n {
+            for c in (b + 1)..n {
+                for d in (c + 1)..n {
+                    for e in (d + 1)..n {
+                        let value = evaluate_five([cards[a], cards[b], cards[c], cards[d], cards[e]]);
+                        if best.as_ref().map(|current| value > *current).unwrap_or(true) {
+                            best = Some(value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    best
+}
+
+fn evaluate_five(cards: [Card; 5]) -> HandValue {
+    let mut ranks: Vec<u8> = cards.iter().map(|card| card.rank).collect();
+    ranks.sort_unstable_by(|a, b| b.cmp(a));
+
+    let is_flush = cards.iter().all(|card| card.suit == cards[0].suit);
+    let straight_high = straight_high_card(&ranks);
+
+    // Group ranks by count, bigger groups first and higher ranks first inside the same count
+    let mut groups: Vec<(u8, u8)> = vec![];
+    for rank in &ranks {
+        match groups.iter_mut().find(|(group_rank, _)| group_rank == rank) {
+            Some(group) => group.1 += 1,
+            None => groups.push((*rank, 1)),
+        }
+    }
+    groups.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+    let grouped_ranks: Vec<u8> = groups.iter().map(|(rank, _)| *rank).collect();
+
+    let (category, tiebreakers) = match (is_flush, straight_high, groups[0].1, groups.get(1).map(|group| group.1)) {
+        (true, Some(high), _, _) => (HandCategory::StraightFlush, vec![high]),
+        (_, _, 4, _) => (HandCategory::FourOfAKind, grouped_ranks),
+        (_, _, 3, Some(2)) => (HandCategory::FullHouse, grouped_ranks),
+        (true, None, _, _) => (HandCategory::Flush, ranks),
+        (false, Some(high), _, _) => (HandCategory::Straight, vec![high]),
+        (_, _, 3, _) => (HandCategory::ThreeOfAKind, grouped_ranks),
+        (_, _, 2, Some(2)) => (HandCategory::TwoPair, grouped_ranks),
+        (_, _, 2, _) => (HandCategory::Pair, grouped_ranks),
+        _ => (HandCategory::HighCard, ranks),
+    };
+
+    HandValue { category, tiebreakers }
+}
+
+// ranks must be sorted from high to low. The wheel (A-2-3-4-5) is a five high straight
+fn straight_high_card(ranks: &[u8]) -> Option<u8> {
+    if ranks.windows(2).all(|pair| pair[0] == pair[1] + 1) {
+        return Some(ranks[0]);
+    }
+
+    if ranks == [14, 5, 4, 3, 2] {
+        return Some(5);
+    }
+
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(cards: &str) -> HandValue {
+        let cards: Vec<Card> = cards.split_whitespace().map(|card| Card::parse(card).unwrap()).collect();
+        best_hand(&cards).unwrap()
+    }
+
+    #[test]
+    fn parses_cards() {
+        assert_eq!(Card::parse("As"), Some(Card { rank: 14, suit: 's' }));
+        assert_eq!(Card::parse("Td"), Some(Card { rank: 10, suit: 'd' }));
+        assert_eq!(Card::parse("2c"), Some(Card { rank: 2, suit: 'c' }));
+        assert_eq!(Card::parse("1c"), None);
+        assert_eq!(Card::parse("Ax"), None);
+        assert_eq!(Card::parse("Asd"), None);
+    }
+
+    #[test]
+    fn needs_five_cards() {
+        let cards: Vec<Card> = ["As", "Ks", "Qs", "Js"].iter().map(|card| Card::parse(card).unwrap()).collect();
+        assert_eq!(best_hand(&cards), None);
+    }
+
+    #[test]
+    fn evaluates_every_category() {
+        let cases = [
+            ("Ah Kd 9c 7s 3h", HandCategory::HighCard),
+            ("Ah Ad 9c 7s 3h", HandCategory::Pair),
+            ("Ah Ad 9c 9s 3h", HandCategory::TwoPair),
+            ("Ah Ad Ac 7s 3h", HandCategory::ThreeOfAKind),
+            ("9h Td Jc Qs Kh", HandCategory::Straight),
+            ("Ah Jh 9h 7h 3h", HandCategory::Flush),
+            ("Ah Ad Ac 7s 7h", HandCategory::FullHouse),
+            ("Ah Ad Ac As 3h", HandCategory::FourOfAKind),
+            ("9h Th Jh Qh Kh", HandCategory::StraightFlush),
+        ];
+
+        for (cards, category) in cases {
+            assert_eq!(hand(cards).category, category, "{}", cards);
+        }
+    }
+
+    #[test]
+    fn wheel_is_a_five_high_straight() {
+        let wheel = hand("Ah 2d 3c 4s 5h");
+        assert_eq!(wheel, HandValue { category: HandCategory::Straight, tiebreakers: vec![5] });
+        assert!(wheel < hand("2h 3d 4c 5s 6h"));
+        assert_eq!(hand("Qh Kd Ac 2s 3h").category, HandCategory::HighCard);
+    }
+
+    #[test]
+    fn ties_are_broken_by_the_grouped_ranks_then_kickers() {
+        assert!(hand("Kh Kd Kc 2s 2h") > hand("Qh Qd Qc As Ah"));
+        assert!(hand("Ah Ad 9c 9s 3h") > hand("Ah Ad 8c 8s Kh"));
+        assert!(hand("Ah Ad Kc 7s 3h") > hand("As Ac Qd Js Th"));
+        assert!(hand("Ah Jh 9h 7h 4h") > hand("As Js 9s 7s 3s"));
+        assert_eq!(hand("Ah Kd 9c 7s 3h"), hand("As Kc 9d 7h 3d"));
+    }
+
+    #[test]
+    fn picks_the_best_five_out_of_seven() {
+        // Board and hole cards together make both a flush and a straight flush
+        let value = hand("5h 6h 7h 8h 9h Ah Kh");
+        assert_eq!(value, HandValue { category: HandCategory::StraightFlush, tiebreakers: vec![9] });
+
+        let value = hand("Ks Kd 2c 7d 9h Jc Kh");
+        assert_eq!(value, HandValue { category: HandCategory::ThreeOfAKind, tiebreakers: vec![13, 11, 9] });
+    }
+
+    #[test]
+    fn deck_is_replayable_per_hand() {
+        let deck = shuffled_deck(42, 1);
+        assert_eq!(deck, shuffled_deck(42, 1));
+        assert_ne!(deck, shuffled_deck(42, 2));
+        assert_ne!(deck, shuffled_deck(43, 1));
+
+        let mut cards = deck.clone();
+        cards.sort();
+        cards.dedup();
+        assert_eq!(cards.len(), 52);
+        assert!(cards.iter().all(|card| Card::parse(card).is_some()));
+    }
+}
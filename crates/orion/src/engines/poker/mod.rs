@@ -0,0 +1,761 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{constants::POKER_STATE_REDIS_KEY, models::{game_model::{Poker, PokerHandSummary, PokerPotResult, PokerState}, poker_events::PokerBetEvent, user_turn_model::UserTurnMapping}};
+
+use super::{EngineAction, EngineError, GameEngine, GameResult, PrivateState};
+
+pub mod cards;
+
+use cards::{best_hand, shuffled_deck, Card};
+
+const MIN_PLAYERS: usize = 2;
+const MAX_PLAYERS: usize = 9;
+const GAME_OVER_STREET: &str = "game_over";
+
+
+// No-Limit Texas Hold'em. Hands are dealt one after the other until a single player has chips left.
+// State is kept as PokerState json in redis under PokerState_<game_id>
+pub struct PokerEngine {
+    pub starting_stack: f64,
+    pub small_blind: f64,
+    pub big_blind: f64,
+}
+
+impl Default for PokerEngine {
+    fn default() -> Self {
+        PokerEngine { starting_stack: 1000.0, small_blind: 5.0, big_blind: 10.0 }
+    }
+}
+
+impl GameEngine for PokerEngine {
+    type State = PokerState;
+
+    fn game_type(&self) -> &'static str {
+        "poker"
+    }
+
+    fn state_key_prefix(&self) -> &'static str {
+        POKER_STATE_REDIS_KEY
+    }
+
+    fn min_players(&self) -> usize {
+        MIN_PLAYERS
+    }
+
+    fn max_players(&self) -> usize {
+        MAX_PLAYERS
+    }
+
+    fn initial_state(&self, turn_mapping: &UserTurnMapping, seed: u64) -> Result<Self::State, EngineError> {
+        let player_count = turn_mapping.turn_mappings.len();
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&player_count) {
+            return Err(EngineError::InvalidPlayerCount { min: MIN_PLAYERS, max: MAX_PLAYERS, found: player_count })
+        }
+
+        let mut turns: Vec<_> = turn_mapping.turn_mappings.iter().collect();
+        turns.sort_by_key(|turn| turn.count_id);
+
+        let mut state = PokerState {
+            // Unrelated to the seed, the id is sent to every client
+            id: Uuid::new_v4(),
+            game_id: Uuid::parse_str(&turn_mapping.game_id).map_err(|_| EngineError::InvalidState("Invalid game id".to_string()))?,
+            seats: turns.iter().map(|turn| turn.user_id.clone()).collect(),
+            user_states: turns.iter().map(|turn| (turn.user_id.clone(), Some(Poker { money_left: self.starting_stack, ..Default::default() }))).collect(),
+            seed,
+            small_blind: self.small_blind,
+            big_blind: self.big_blind,
+            ..Default::default()
+        };
+
+        start_hand(&mut state);
+        Ok(state)
+    }
+
+    fn validate_action(&self, state: &Self::State, action: &EngineAction) -> Result<(), EngineError> {
+        if state.current_turn != action.user_id {
+            return Err(EngineError::NotPlayersTurn)
+        }
+
+        let player = get_player(state, &action.user_id).ok_or(EngineError::InvalidAction("Player is not seated at this table".to_string()))?;
+        let to_call = (state.current_bet - player.round_bet).max(0.0);
+
+        match action.action_type.as_str() {
+            "fold" => Ok(()),
+            "check" => {
+                if to_call > 0.0 {
+                    return Err(EngineError::IllegalAction(format!("Cannot check, {} to call", to_call)))
+                }
+                Ok(())
+            },
+            "call" => {
+                if to_call <= 0.0 {
+                    return Err(EngineError::IllegalAction("Nothing to call".to_string()))
+                }
+                Ok(())
+            },
+            "bet" | "raise" => {
+                if action.action_type == "bet" && state.current_bet > 0.0 {
+                    return Err(EngineError::IllegalAction("Cannot bet when there is already a bet, raise instead".to_string()))
+                }
+                if action.action_type == "raise" && state.current_bet <= 0.0 {
+                    return Err(EngineError::IllegalAction("Cannot raise when there is no bet, bet instead".to_string()))
+                }
+
+                let bet_event: PokerBetEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid bet payload".to_string()))?;
+                validate_raise_to(state, player, bet_event.amount)
+            },
+            "all_in" => {
+                if player.money_left <= 0.0 {
+                    return Err(EngineError::IllegalAction("No chips left".to_string()))
+                }
+                if player.money_left > to_call && player.has_acted {
+                    return Err(EngineError::IllegalAction("Betting was not reopened, only call or fold are allowed".to_string()))
+                }
+                Ok(())
+            },
+            other => Err(EngineError::InvalidAction(format!("Unknown poker action {}", other))),
+        }
+    }
+
+    fn apply_action(&self, state: &Self::State, action: &EngineAction) -> Result<Self::State, EngineError> {
+        let mut state = state.clone();
+        let seat = seat_of(&state, &action.user_id).ok_or(EngineError::InvalidAction("Player is not seated at this table".to_string()))?;
+        let player = get_player(&state, &action.user_id).ok_or(EngineError::InvalidAction("Player is not seated at this table".to_string()))?.clone();
+
+        match action.action_type.as_str() {
+            "fold" => {
+                get_player_mut(&mut state, &action.user_id).unwrap().folded = true;
+            },
+            "check" => {},
+            "call" => {
+                let to_call = (state.current_bet - player.round_bet).max(0.0);
+                commit_chips(&mut state, &action.user_id, to_call.min(player.money_left));
+            },
+            "bet" | "raise" => {
+                let bet_event: PokerBetEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid bet payload".to_string()))?;
+                raise_to(&mut state, &action.user_id, bet_event.amount);
+            },
+            _ => {
+                // all_in either calls or raises depending on the stack size
+                raise_to(&mut state, &action.user_id, player.round_bet + player.money_left);
+            },
+        }
+
+        get_player_mut(&mut state, &action.user_id).unwrap().has_acted = true;
+        progress(&mut state, seat);
+
+        Ok(state)
+    }
+
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        state.street == GAME_OVER_STREET
+    }
+
+    fn result(&self, state: &Self::State) -> Option<GameResult> {
+        if !self.is_terminal(state) {
+            return None
+        }
+
+        let survivors: Vec<String> = state.seats.iter().filter(|user_id| get_player(state, user_id).map(|player| player.money_left > 0.0).unwrap_or(false)).cloned().collect();
+        Some(GameResult::from_eliminations(survivors, &state.busted, "last_player_standing"))
+    }
+
+    fn serialize_state(&self, state: &Self::State) -> Result<String, EngineError> {
+        serde_json::to_string(state).map_err(|e| EngineError::InvalidState(e.to_string()))
+    }
+
+    fn deserialize_state(&self, raw_state: &str) -> Result<Self::State, EngineError> {
+        serde_json::from_str(raw_state).map_err(|e| EngineError::InvalidState(e.to_string()))
+    }
+
+    fn public_state(&self, state: &Self::State) -> Result<String, EngineError> {
+        self.serialize_state(&public_view(state))
+    }
+
+    // Every player only gets their own hole cards
+    fn private_states(&self, state: &Self::State) -> Result<Vec<PrivateState>, EngineError> {
+        let public = public_view(state);
+        let mut private_states = vec![];
+
+        for user_id in &state.seats {
+            let hole_cards = match get_player(state, user_id) {
+                Some(player) if !player.hole_cards.is_empty() => player.hole_cards.clone(),
+                _ => continue,
+            };
+
+            let mut view = public.clone();
+            if let Some(player) = get_player_mut(&mut view, user_id) {
+                player.hole_cards = hole_cards;
+            }
+
+            private_states.push(PrivateState { user_id: user_id.clone(), state: self.serialize_state(&view)? });
+        }
+
+        Ok(private_states)
+    }
+
+    // The player folds and forfeits their chips, they bust out when the hand is over
+    fn remove_player(&self, state: &Self::State, user_id: &str, _now: i64) -> Option<Self::State> {
+        let mut state = state.clone();
+        let (Some(seat), Some(player)) = (seat_of(&state, user_id), get_player_mut(&mut state, user_id)) else {
+            return Some(state)
+        };
+
+        player.folded = true;
+        player.money_left = 0.0;
+
+        let players_in_hand = state.user_states.values().flatten().filter(|player| is_in_hand(player)).count();
+        if state.current_turn == user_id || players_in_hand <= 1 {
+            progress(&mut state, seat);
+        }
+
+        Some(state)
+    }
+
+    fn eliminated_players(&self, state: &Self::State) -> Vec<String> {
+        state.busted.concat()
+    }
+}
+
+
+fn public_view(state: &PokerState) -> PokerState {
+    let mut view = state.clone();
+    view.deck = vec![];
+    // Anyone with the seed could deal every hand of the table themselves
+    view.seed = 0;
+
+    for player in view.user_states.values_mut().flatten() {
+        player.hole_cards = vec![];
+    }
+
+    view
+}
+
+fn get_player<'a>(state: &'a PokerState, user_id: &str) -> Option<&'a Poker> {
+    state.user_states.get(user_id).and_then(|player| player.as_ref())
+}
+
+fn get_player_mut<'a>(state: &'a mut PokerState, user_id: &str) -> Option<&'a mut Poker> {
+    state.user_states.get_mut(user_id).and_then(|player| player.as_mut())
+}
+
+fn seat_of(state: &PokerState, user_id: &str) -> Option<usize> {
+    state.seats.iter().position(|seat_user_id| seat_user_id == user_id)
+}
+
+// Dealt into the current hand and not folded
+fn is_in_hand(player: &Poker) -> bool {
+    !player.hole_cards.is_empty() && !player.folded
+}
+
+fn can_act(player: &Poker) -> bool {
+    is_in_hand(player) && !player.go_all_in
+}
+
+// First seat after from_seat (wrapping around the table) whose player matches the predicate
+fn next_seat<F>(state: &PokerState, from_seat: usize, predicate: F) -> Option<usize> where F: Fn(&Poker) -> bool {
+    let seat_count = state.seats.len();
+
+    (1..=seat_count)
+        .map(|offset| (from_seat + offset) % seat_count)
+        .find(|seat| get_player(state, &state.seats[*seat]).map(&predicate).unwrap_or(false))
+}
+
+fn needs_to_act(state: &PokerState, player: &Poker) -> bool {
+    can_act(player) && (!player.has_acted || player.round_bet < state.current_bet)
+}
+
+
+fn validate_raise_to(state: &PokerState, player: &Poker, amount: f64) -> Result<(), EngineError> {
+    if amount <= 0.0 || amount.fract() != 0.0 {
+        return Err(EngineError::InvalidAction("Bet amount must be a positive whole number of chips".to_string()))
+    }
+
+    if amount <= state.current_bet {
+        return Err(EngineError::IllegalAction(format!("Amount must be above the current bet of {}", state.current_bet)))
+    }
+
+    let chips_needed = amount - player.round_bet;
+    if chips_needed > player.money_left {
+        return Err(EngineError::IllegalAction(format!("Not enough chips, {} left", player.money_left)))
+    }
+
+    // Players that already acted can only raise again if a full raise reopened the betting
+    if player.has_acted {
+        return Err(EngineError::IllegalAction("Betting was not reopened, only call or fold are allowed".to_string()))
+    }
+
+    // Going all in is always allowed, even for less than a full raise
+    let is_all_in = chips_needed == player.money_left;
+    if !is_all_in && amount - state.current_bet < state.min_raise {
+        return Err(EngineError::IllegalAction(format!("Minimum raise is to {}", state.current_bet + state.min_raise)))
+    }
+
+    Ok(())
+}
+
+fn raise_to(state: &mut PokerState, user_id: &str, amount: f64) {
+    let round_bet = get_player(state, user_id).map(|player| player.round_bet).unwrap_or(0.0);
+    commit_chips(state, user_id, amount - round_bet);
+
+    if amount > state.current_bet {
+        let raise_size = amount - state.current_bet;
+
+        // Only a full raise reopens the betting for players that already acted
+        if raise_size >= state.min_raise {
+            state.min_raise = raise_size;
+            for (other_user_id, player) in state.user_states.iter_mut() {
+                if let Some(player) = player {
+                    if other_user_id != user_id {
+                        player.has_acted = false;
+                    }
+                }
+            }
+        }
+
+        state.current_bet = amount;
+    }
+}
+
+fn commit_chips(state: &mut PokerState, user_id: &str, amount: f64) {
+    let Some(player) = get_player_mut(state, user_id) else { return };
+    let amount = amount.min(player.money_left).max(0.0);
+
+    player.money_left -= amount;
+    player.round_bet += amount;
+    player.hand_bet += amount;
+    if player.money_left <= 0.0 {
+        player.money_left = 0.0;
+        player.go_all_in = true;
+    }
+
+    state.pot_size += amount;
+}
+
+fn deal_card(state: &mut PokerState) -> Option<String> {
+    if state.deck.is_empty() {
+        return None;
+    }
+    Some(state.deck.remove(0))
+}
+
+
+fn start_hand(state: &mut PokerState) {
+    let alive_seats: Vec<usize> = (0..state.seats.len())
+        .filter(|seat| get_player(state, &state.seats[*seat]).map(|player| player.money_left > 0.0).unwrap_or(false))
+        .collect();
+
+    if alive_seats.len() < MIN_PLAYERS {
+        state.street = GAME_OVER_STREET.to_string();
+        state.current_turn = "".to_string();
+        state.deck = vec![];
+        return;
+    }
+
+    state.hand_number += 1;
+    let has_chips = |player: &Poker| player.money_left > 0.0;
+    if state.hand_number > 1 {
+        state.dealer_seat = next_seat(state, state.dealer_seat, has_chips).unwrap();
+    } else {
+        state.dealer_seat = alive_seats[0];
+    }
+
+    for player in state.user_states.values_mut().flatten() {
+        player.hole_cards = vec![];
+        player.folded = false;
+        player.go_all_in = false;
+        player.round_bet = 0.0;
+        player.hand_bet = 0.0;
+        player.has_acted = false;
+    }
+
+    state.deck = shuffled_deck(state.seed, state.hand_number);
+    state.board = vec![];
+    state.street = "preflop".to_string();
+    state.pot_size = 0.0;
+    state.current_bet = 0.0;
+    state.min_raise = state.big_blind;
+
+    // Two cards each, one at a time starting left of the dealer
+    for _ in 0..2 {
+        let mut seat = state.dealer_seat;
+        for _ in 0..alive_seats.len() {
+            seat = next_seat(state, seat, has_chips).unwrap();
+            let card = deal_card(state).unwrap();
+            let user_id = state.seats[seat].clone();
+            get_player_mut(state, &user_id).unwrap().hole_cards.push(card);
+        }
+    }
+
+    // Heads up the dealer posts the small blind and acts first before the flop
+    let small_blind_seat = if alive_seats.len() == 2 { state.dealer_seat } else { next_seat(state, state.dealer_seat, has_chips).unwrap() };
+    let big_blind_seat = next_seat(state, small_blind_seat, has_chips).unwrap();
+
+    let small_blind_user = state.seats[small_blind_seat].clone();
+    let big_blind_user = state.seats[big_blind_seat].clone();
+    commit_chips(state, &small_blind_user, state.small_blind);
+    commit_chips(state, &big_blind_user, state.big_blind);
+    state.current_bet = state.big_blind;
+
+    progress(state, big_blind_seat);
+}
+
+// Moves the hand forward after an action: next player, next street, or settling the hand
+fn progress(state: &mut PokerState, mut last_seat: usize) {
+    loop {
+        let players_in_hand = state.user_states.values().flatten().filter(|player| is_in_hand(player)).count();
+        if players_in_hand <= 1 {
+            finish_hand(state, false);
+            return;
+        }
+
+        if !is_round_complete(state) {
+            let next = next_seat(state, last_seat, |player| needs_to_act(state, player)).unwrap();
+            state.current_turn = state.seats[next].clone();
+            return;
+        }
+
+        for player in state.user_states.values_mut().flatten() {
+            player.round_bet = 0.0;
+            player.has_acted = false;
+        }
+        state.current_bet = 0.0;
+        state.min_raise = state.big_blind;
+
+        let players_who_can_act = state.user_states.values().flatten().filter(|player| can_act(player)).count();
+        if state.street == "river" || players_who_can_act <= 1 {
+            // Nobody can bet anymore, run the board out and go to showdown
+            while state.board.len() < 5 {
+                deal_street(state);
+            }
+            finish_hand(state, true);
+            return;
+        }
+
+        deal_street(state);
+        last_seat = state.dealer_seat;
+    }
+}
+
+fn is_round_complete(state: &PokerState) -> bool {
+    let acting: Vec<&Poker> = state.user_states.values().flatten().filter(|player| can_act(player)).collect();
+    let highest_bet = state.user_states.values().flatten().filter(|player| is_in_hand(player)).map(|player| player.round_bet).fold(0.0, f64::max);
+
+    // A single player left with chips does not need to act if nobody bet more than them
+    if acting.len() == 1 && acting[0].round_bet >= highest_bet {
+        return true;
+    }
+
+    acting.iter().all(|player| !needs_to_act(state, player))
+}
+
+fn deal_street(state: &mut PokerState) {
+    // Burn one card before every street
+    deal_card(state);
+
+    let (cards, street) = match state.board.len() {
+        0 => (3, "flop"),
+        3 => (1, "turn"),
+        _ => (1, "river"),
+    };
+
+    for _ in 0..cards {
+        if let Some(card) = deal_card(state) {
+            state.board.push(card);
+        }
+    }
+    state.street = street.to_string();
+}
+
+
+fn finish_hand(state: &mut PokerState, showdown: bool) {
+    let board: Vec<Card> = state.board.iter().filter_map(|card| Card::parse(card)).collect();
+
+    // Side pots: one pot per distinct amount committed, only players that put in at least that much can win it
+    let mut levels: Vec<f64> = state.user_states.values().flatten().map(|player| player.hand_bet).filter(|bet| *bet > 0.0).collect();
+    levels.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    levels.dedup();
+
+    let mut pots: Vec<(f64, Vec<String>)> = vec![];
+    let mut carried = 0.0;
+    let mut previous_level = 0.0;
+    for level in levels {
+        let amount: f64 = carried + state.user_states.values().flatten().map(|player| player.hand_bet.min(level) - player.hand_bet.min(previous_level)).sum::<f64>();
+        let eligible: Vec<String> = state.seats.iter()
+            .filter(|user_id| get_player(state, user_id).map(|player| is_in_hand(player) && player.hand_bet >= level).unwrap_or(false))
+            .cloned()
+            .collect();
+
+        if eligible.is_empty() {
+            match pots.last_mut() {
+                Some(pot) => pot.0 += amount,
+                None => carried = amount,
+            }
+        } else {
+            pots.push((amount, eligible));
+            carried = 0.0;
+        }
+        previous_level = level;
+    }
+
+    let mut shown_cards = HashMap::new();
+    let mut pot_results = vec![];
+
+    for (amount, eligible) in pots {
+        let (winners, hand_rank) = if showdown {
+            let hands: Vec<(String, _)> = eligible.iter()
+                .map(|user_id| {
+                    let player = get_player(state, user_id).unwrap();
+                    let mut cards: Vec<Card> = player.hole_cards.iter().filter_map(|card| Card::parse(card)).collect();
+                    cards.extend(board.iter().cloned());
+                    shown_cards.insert(user_id.clone(), player.hole_cards.clone());
+                    (user_id.clone(), best_hand(&cards))
+                })
+                .collect();
+
+            let best = hands.iter().map(|(_, hand)| hand.clone()).max().flatten();
+            let winners: Vec<String> = hands.iter().filter(|(_, hand)| *hand == best).map(|(user_id, _)| user_id.clone()).collect();
+            (winners, best.map(|hand| hand.category.name().to_string()))
+        } else {
+            (eligible, None)
+        };
+
+        award_pot(state, amount, &winners);
+        pot_results.push(PokerPotResult { amount, winners, hand_rank });
+    }
+
+    state.last_hand = Some(PokerHandSummary {
+        hand_number: state.hand_number,
+        board: state.board.clone(),
+        pots: pot_results,
+        shown_cards,
+    });
+    state.pot_size = 0.0;
+
+    // Players without chips are out of the game
+    let busted: Vec<String> = state.seats.iter().filter(|user_id| get_player(state, user_id).map(|player| player.money_left <= 0.0).unwrap_or(false)).cloned().collect();
+    for user_id in &busted {
+        state.user_states.insert(user_id.clone(), None);
+    }
+    if !busted.is_empty() {
+        state.busted.push(busted);
+    }
+
+    start_hand(state);
+}
+
+// Split pots are shared equally, odd chips go to the winners closest to the left of the dealer
+fn award_pot(state: &mut PokerState, amount: f64, winners: &[String]) {
+    if winners.is_empty() {
+        return;
+    }
+
+    let seat_count = state.seats.len();
+    let mut ordered_winners: Vec<&String> = winners.iter().collect();
+    ordered_winners.sort_by_key(|user_id| (seat_of(state, user_id).unwrap_or(0) + seat_count - state.dealer_seat - 1) % seat_count);
+
+    let share = (amount / winners.len() as f64).floor();
+    let mut odd_chips = amount - share * winners.len() as f64;
+
+    for user_id in ordered_winners {
+        let bonus = if odd_chips >= 1.0 { 1.0 } else { odd_chips };
+        odd_chips -= bonus;
+
+        if let Some(player) = get_player_mut(state, user_id) {
+            player.money_left += share + bonus;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::models::user_turn_model::TurnModel;
+
+    use super::*;
+
+    const GAME_ID: &str = "2b0c4bb4-7f57-4b3e-9f0e-6a55c3a1e2d1";
+
+    fn turn_mapping(players: &[&str]) -> UserTurnMapping {
+        UserTurnMapping {
+            host_id: players[0].to_string(),
+            game_id: GAME_ID.to_string(),
+            turn_mappings: players.iter().enumerate().map(|(seat, user_id)| TurnModel {
+                count_id: seat as i64,
+                user_id: user_id.to_string(),
+                username: user_id.to_string(),
+                status: "".to_string(),
+            }).collect(),
+        }
+    }
+
+    fn action(user_id: &str, action_type: &str, payload: &str) -> EngineAction {
+        EngineAction { user_id: user_id.to_string(), player_type: "".to_string(), action_type: action_type.to_string(), payload: payload.to_string(), received_at: 0 }
+    }
+
+    // Chips a player owns, whether they are still in front of them or already bet in the current hand
+    fn chips(state: &PokerState, user_id: &str) -> f64 {
+        get_player(state, user_id).map(|player| player.money_left + player.hand_bet).unwrap_or(0.0)
+    }
+
+    fn river_player(hole_cards: [&str; 2], money_left: f64, hand_bet: f64) -> Option<Poker> {
+        Some(Poker {
+            money_left,
+            go_all_in: money_left == 0.0,
+            hole_cards: hole_cards.iter().map(|card| card.to_string()).collect(),
+            hand_bet,
+            has_acted: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn heads_up_dealer_posts_the_small_blind_and_acts_first() {
+        let engine = PokerEngine::default();
+        let state = engine.initial_state(&turn_mapping(&["a", "b"]), 7).unwrap();
+
+        assert_eq!(state.hand_number, 1);
+        assert_eq!(state.dealer_seat, 0);
+        assert_eq!(state.street, "preflop");
+        assert_eq!(state.current_turn, "a");
+        assert_eq!(state.pot_size, 15.0);
+        assert_eq!(get_player(&state, "a").unwrap().round_bet, 5.0);
+        assert_eq!(get_player(&state, "b").unwrap().round_bet, 10.0);
+        assert_eq!(state.deck.len(), 48);
+    }
+
+    #[test]
+    fn same_seed_deals_the_same_cards() {
+        let engine = PokerEngine::default();
+        let first = engine.initial_state(&turn_mapping(&["a", "b", "c"]), 99).unwrap();
+        let second = engine.initial_state(&turn_mapping(&["a", "b", "c"]), 99).unwrap();
+        let other = engine.initial_state(&turn_mapping(&["a", "b", "c"]), 100).unwrap();
+
+        for user_id in ["a", "b", "c"] {
+            assert_eq!(get_player(&first, user_id).unwrap().hole_cards, get_player(&second, user_id).unwrap().hole_cards);
+        }
+        assert_eq!(first.deck, second.deck);
+        assert_ne!(first.deck, other.deck);
+    }
+
+    #[test]
+    fn views_hide_the_seed_deck_and_other_hole_cards() {
+        let engine = PokerEngine::default();
+        let state = engine.initial_state(&turn_mapping(&["a", "b"]), 7).unwrap();
+
+        let public = engine.deserialize_state(&engine.public_state(&state).unwrap()).unwrap();
+        assert_eq!(public.seed, 0);
+        assert!(public.deck.is_empty());
+        assert!(public.user_states.values().flatten().all(|player| player.hole_cards.is_empty()));
+
+        for private_state in engine.private_states(&state).unwrap() {
+            let view = engine.deserialize_state(&private_state.state).unwrap();
+            assert_eq!(view.seed, 0);
+            assert!(view.deck.is_empty());
+            for (user_id, player) in &view.user_states {
+                let player = player.as_ref().unwrap();
+                assert_eq!(player.hole_cards.is_empty(), *user_id != private_state.user_id);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_turn_and_illegal_bets() {
+        let engine = PokerEngine::default();
+        let state = engine.initial_state(&turn_mapping(&["a", "b"]), 7).unwrap();
+
+        assert!(matches!(engine.validate_action(&state, &action("b", "call", "")), Err(EngineError::NotPlayersTurn)));
+        assert!(matches!(engine.validate_action(&state, &action("a", "check", "")), Err(EngineError::IllegalAction(_))));
+        assert!(matches!(engine.validate_action(&state, &action("a", "bet", r#"{"amount":40}"#)), Err(EngineError::IllegalAction(_))));
+        // Raising to 15 is only a 5 chip raise, the big blind is the minimum
+        assert!(matches!(engine.validate_action(&state, &action("a", "raise", r#"{"amount":15}"#)), Err(EngineError::IllegalAction(_))));
+        assert!(engine.validate_action(&state, &action("a", "raise", r#"{"amount":20}"#)).is_ok());
+        assert!(engine.validate_action(&state, &action("a", "call", "")).is_ok());
+    }
+
+    #[test]
+    fn fold_gives_the_blinds_to_the_other_player_and_moves_the_button() {
+        let engine = PokerEngine::default();
+        let state = engine.initial_state(&turn_mapping(&["a", "b"]), 7).unwrap();
+        let state = engine.apply_action(&state, &action("a", "fold", "")).unwrap();
+
+        let last_hand = state.last_hand.as_ref().unwrap();
+        assert_eq!(last_hand.pots.len(), 2);
+        assert!(last_hand.pots.iter().all(|pot| pot.winners == vec!["b".to_string()] && pot.hand_rank.is_none()));
+        assert!(last_hand.shown_cards.is_empty());
+
+        assert_eq!(state.hand_number, 2);
+        assert_eq!(state.dealer_seat, 1);
+        assert_eq!(chips(&state, "a"), 995.0);
+        assert_eq!(chips(&state, "b"), 1005.0);
+    }
+
+    #[test]
+    fn all_in_player_only_wins_the_main_pot() {
+        let mut state = PokerState {
+            game_id: Uuid::parse_str(GAME_ID).unwrap(),
+            seats: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            user_states: HashMap::from([
+                // Trip kings, pair of aces and king high
+                ("a".to_string(), river_player(["Kh", "Kd"], 0.0, 100.0)),
+                ("b".to_string(), river_player(["Ah", "Ad"], 200.0, 300.0)),
+                ("c".to_string(), river_player(["Qh", "3d"], 0.0, 300.0)),
+            ]),
+            hand_number: 1,
+            seed: 7,
+            street: "river".to_string(),
+            board: ["2c", "7d", "9h", "Jc", "Ks"].iter().map(|card| card.to_string()).collect(),
+            small_blind: 5.0,
+            big_blind: 10.0,
+            pot_size: 700.0,
+            ..Default::default()
+        };
+
+        finish_hand(&mut state, true);
+
+        let pots: Vec<(f64, Vec<String>, Option<String>)> = state.last_hand.as_ref().unwrap().pots.iter()
+            .map(|pot| (pot.amount, pot.winners.clone(), pot.hand_rank.clone()))
+            .collect();
+        assert_eq!(pots, vec![
+            (300.0, vec!["a".to_string()], Some("three_of_a_kind".to_string())),
+            (400.0, vec!["b".to_string()], Some("pair".to_string())),
+        ]);
+
+        assert_eq!(state.busted, vec![vec!["c".to_string()]]);
+        assert!(state.user_states["c"].is_none());
+        assert_eq!(chips(&state, "a"), 300.0);
+        assert_eq!(chips(&state, "b"), 600.0);
+    }
+
+    #[test]
+    fn odd_chip_goes_to_the_first_winner_left_of_the_dealer() {
+        let mut state = PokerState {
+            seats: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            user_states: ["a", "b", "c"].iter().map(|user_id| (user_id.to_string(), Some(Poker::default()))).collect(),
+            dealer_seat: 1,
+            ..Default::default()
+        };
+
+        award_pot(&mut state, 25.0, &["a".to_string(), "c".to_string()]);
+
+        assert_eq!(get_player(&state, "c").unwrap().money_left, 13.0);
+        assert_eq!(get_player(&state, "a").unwrap().money_left, 12.0);
+        assert_eq!(get_player(&state, "b").unwrap().money_left, 0.0);
+    }
+
+    #[test]
+    fn leaving_player_forfeits_and_the_last_player_wins() {
+        let engine = PokerEngine::default();
+        let state = engine.initial_state(&turn_mapping(&["a", "b"]), 7).unwrap();
+        let state = engine.remove_player(&state, "a", 0).unwrap();
+
+        assert!(engine.is_terminal(&state));
+        assert_eq!(engine.eliminated_players(&state), vec!["a".to_string()]);
+
+        let result = engine.result(&state).unwrap();
+        assert_eq!(result.winner.as_deref(), Some("b"));
+        assert_eq!(get_player(&state, "b").unwrap().money_left, 1005.0);
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use super::{checkers::CheckersEngine, chess::ChessEngine, monopoly::MonopolyEngine, poker::PokerEngine, scribble::ScribbleEngine, DynGameEngine, EngineError, GameEngine};
+
+
+// Maps Game.game_type to the engine implementing its rules
+pub struct GameEngineRegistry {
+    engines: HashMap<String, Box<dyn DynGameEngine>>,
+    // Lobby size configured per game type, within the limits of the engine
+    max_players: HashMap<String, usize>,
+}
+
+impl GameEngineRegistry {
+    pub fn new() -> Self {
+        GameEngineRegistry { engines: HashMap::new(), max_players: HashMap::new() }
+    }
+
+    // Registry with every game supported by vortex
+    pub fn with_default_engines() -> Self {
+        let mut registry = GameEngineRegistry::new();
+        registry.register(ChessEngine);
+        registry.register(CheckersEngine::english());
+        registry.register(CheckersEngine::international());
+        registry.register(PokerEngine::default());
+        registry.register(ScribbleEngine::default());
+        registry.register(MonopolyEngine::default());
+        registry
+    }
+
+    // Registering a game type again replaces its engine
+    pub fn register<E>(&mut self, engine: E) where E: GameEngine + 'static {
+        self.engines.insert(GameEngine::game_type(&engine).to_string(), Box::new(engine));
+    }
+
+    pub fn get(&self, game_type: &str) -> Result<&dyn DynGameEngine, EngineError> {
+        self.engines
+            .get(&game_type.to_lowercase())
+            .map(|engine| engine.as_ref())
+            .ok_or(EngineError::UnknownGameType(game_type.to_string()))
+    }
+
+    // Smaller tables for a game type, e.g. 6 seat poker. Values outside the engine limits are clamped
+    pub fn set_max_players(&mut self, game_type: &str, max_players: usize) -> Result<(), EngineError> {
+        let engine = self.get(game_type)?;
+        let max_players = max_players.clamp(engine.min_players(), engine.max_players());
+        self.max_players.insert(game_type.to_lowercase(), max_players);
+        Ok(())
+    }
+
+    // (min, max) players a lobby of this game type can seat
+    pub fn player_limits(&self, game_type: &str) -> Result<(usize, usize), EngineError> {
+        let engine = self.get(game_type)?;
+        let max_players = self.max_players.get(&game_type.to_lowercase()).copied().unwrap_or(engine.max_players());
+        Ok((engine.min_players(), max_players))
+    }
+
+    pub fn game_types(&self) -> Vec<String> {
+        self.engines.keys().cloned().collect()
+    }
+}
+
+impl Default for GameEngineRegistry {
+    fn default() -> Self {
+        GameEngineRegistry::with_default_engines()
+    }
+}
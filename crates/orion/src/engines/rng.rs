@@ -0,0 +1,116 @@
+// Small deterministic PRNG (SplitMix64). Same seed always gives the same sequence so every shuffle / dice roll
+// can be replayed from the seed stored with the game state
+#[derive(Debug, Clone)]
+pub struct DeterministicRng {
+    state: u64,
+}
+
+impl DeterministicRng {
+    pub fn new(seed: u64) -> Self {
+        DeterministicRng { state: seed }
+    }
+
+    // Independent sequence for a sub stream of a game (hand number, turn number ...)
+    pub fn for_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = DeterministicRng::new(seed ^ stream.wrapping_mul(0xD1B5_4A32_D192_ED03));
+        rng.next_u64();
+        rng
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform value in 0..upper without modulo bias
+    pub fn gen_range(&mut self, upper: u64) -> u64 {
+        if upper == 0 {
+            return 0;
+        }
+
+        let zone = u64::MAX - (u64::MAX % upper);
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % upper;
+            }
+        }
+    }
+
+    // Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.gen_range(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::DeterministicRng;
+
+    #[test]
+    fn matches_the_splitmix64_reference_output() {
+        let mut rng = DeterministicRng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+    }
+
+    #[test]
+    fn same_seed_replays_the_same_sequence() {
+        let mut first = DeterministicRng::new(1234);
+        let mut second = DeterministicRng::new(1234);
+        let first_values: Vec<u64> = (0..16).map(|_| first.next_u64()).collect();
+        let second_values: Vec<u64> = (0..16).map(|_| second.next_u64()).collect();
+        assert_eq!(first_values, second_values);
+
+        let mut other = DeterministicRng::new(1235);
+        let other_values: Vec<u64> = (0..16).map(|_| other.next_u64()).collect();
+        assert_ne!(first_values, other_values);
+    }
+
+    #[test]
+    fn streams_of_the_same_seed_are_independent() {
+        let mut hand_one = DeterministicRng::for_stream(99, 1);
+        let mut hand_one_again = DeterministicRng::for_stream(99, 1);
+        let mut hand_two = DeterministicRng::for_stream(99, 2);
+
+        let value = hand_one.next_u64();
+        assert_eq!(value, hand_one_again.next_u64());
+        assert_ne!(value, hand_two.next_u64());
+    }
+
+    #[test]
+    fn gen_range_stays_below_the_upper_bound() {
+        let mut rng = DeterministicRng::new(7);
+        assert_eq!(rng.gen_range(0), 0);
+        assert_eq!(rng.gen_range(1), 0);
+
+        let mut seen = [false; 6];
+        for _ in 0..600 {
+            let roll = rng.gen_range(6);
+            assert!(roll < 6);
+            seen[roll as usize] = true;
+        }
+        assert!(seen.iter().all(|seen| *seen));
+    }
+
+    #[test]
+    fn shuffle_is_a_replayable_permutation() {
+        let mut items: Vec<u32> = (0..52).collect();
+        DeterministicRng::new(42).shuffle(&mut items);
+
+        let mut replayed: Vec<u32> = (0..52).collect();
+        DeterministicRng::new(42).shuffle(&mut replayed);
+        assert_eq!(items, replayed);
+        assert_ne!(items, (0..52).collect::<Vec<u32>>());
+
+        items.sort_unstable();
+        assert_eq!(items, (0..52).collect::<Vec<u32>>());
+    }
+}
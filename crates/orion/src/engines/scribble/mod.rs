@@ -0,0 +1,685 @@
+use std::collections::HashMap;
+
+use crate::{constants::SCRIBBLE_STATE_REDIS_KEY, models::{scribble_events::{ScribbleChooseWordEvent, ScribbleGuessEvent, ScribbleStrokeEvent}, scribble_model::{ScribbleGuess, ScribbleLeaderboardEntry, ScribbleState}, user_turn_model::UserTurnMapping}};
+
+use super::{rng::DeterministicRng, EngineAction, EngineError, GameEngine, GameResult, Placement, PrivateState};
+
+pub mod words;
+
+const MIN_PLAYERS: usize = 3;
+const MAX_PLAYERS: usize = 12;
+const MAX_RECENT_GUESSES: usize = 20;
+const MAX_STROKE_POINTS: usize = 500;
+const MAX_GUESS_LENGTH: usize = 100;
+// Points for a correct guess go from MAX to MIN as the drawing time runs out
+const MAX_GUESS_POINTS: i64 = 500;
+const MIN_GUESS_POINTS: i64 = 50;
+const DRAWER_POINTS_PER_GUESS: i64 = 50;
+
+const CHOOSING_PHASE: &str = "choosing";
+const DRAWING_PHASE: &str = "drawing";
+const GAME_OVER_PHASE: &str = "game_over";
+
+
+// Draw and guess. Every player draws once per round, in the order of the UserTurnMapping.
+// Time is driven by received_at of the actions plus "tick" actions sent when a deadline passes
+pub struct ScribbleEngine {
+    pub words: Vec<String>,
+    pub rounds: u32,
+    pub word_choice_count: usize,
+    pub choose_time_ms: i64,
+    pub draw_time_ms: i64,
+}
+
+impl Default for ScribbleEngine {
+    fn default() -> Self {
+        ScribbleEngine {
+            words: words::DEFAULT_WORDS.iter().map(|word| word.to_string()).collect(),
+            rounds: 3,
+            word_choice_count: 3,
+            choose_time_ms: 15_000,
+            draw_time_ms: 80_000,
+        }
+    }
+}
+
+impl ScribbleEngine {
+    // Engine using a custom word list, falls back to the default list if the given one is empty
+    pub fn with_words(words: Vec<String>) -> Self {
+        let words: Vec<String> = words.into_iter().map(|word| normalize(&word)).filter(|word| !word.is_empty()).collect();
+        if words.is_empty() {
+            return ScribbleEngine::default();
+        }
+
+        ScribbleEngine { words, ..ScribbleEngine::default() }
+    }
+
+    fn phase_duration(&self, phase: &str) -> i64 {
+        if phase == CHOOSING_PHASE { self.choose_time_ms } else { self.draw_time_ms }
+    }
+
+    fn start_choosing(&self, state: &mut ScribbleState, now: i64) {
+        state.turn_number += 1;
+        state.phase = CHOOSING_PHASE.to_string();
+        state.turn_scores = HashMap::new();
+        state.word_choices = self.pick_words(state);
+        state.phase_started_at = now;
+        state.phase_deadline = if now > 0 { now + self.choose_time_ms } else { 0 };
+    }
+
+    // Words offered to the drawer, avoiding words already played in this game while there are enough left
+    fn pick_words(&self, state: &ScribbleState) -> Vec<String> {
+        let mut candidates: Vec<&String> = self.words.iter().filter(|word| !state.used_words.contains(word)).collect();
+        if candidates.len() < self.word_choice_count {
+            candidates = self.words.iter().collect();
+        }
+
+        DeterministicRng::for_stream(state.seed, state.turn_number).shuffle(&mut candidates);
+        candidates.into_iter().take(self.word_choice_count).cloned().collect()
+    }
+
+    fn start_drawing(&self, state: &mut ScribbleState, word_index: usize, now: i64) {
+        let word = state.word_choices.get(word_index).cloned().unwrap_or_default();
+
+        state.masked_word = mask_word(&word);
+        state.used_words.push(word.clone());
+        state.word = word;
+        state.word_choices = vec![];
+        state.phase = DRAWING_PHASE.to_string();
+        state.phase_started_at = now;
+        state.phase_deadline = now + self.draw_time_ms;
+    }
+
+    fn end_turn(&self, state: &mut ScribbleState, now: i64) {
+        state.last_word = std::mem::take(&mut state.word);
+        state.masked_word = "".to_string();
+        state.word_choices = vec![];
+        state.guessed = vec![];
+        state.recent_guesses = vec![];
+        state.close_guesses = HashMap::new();
+
+        loop {
+            state.drawer_index += 1;
+            if state.drawer_index >= state.players.len() {
+                state.drawer_index = 0;
+                state.round += 1;
+            }
+
+            if !state.left_players.contains(&state.players[state.drawer_index]) {
+                break;
+            }
+        }
+
+        if state.round > state.total_rounds {
+            state.round = state.total_rounds;
+            finish_game(state);
+            return;
+        }
+
+        self.start_choosing(state, now);
+    }
+
+    fn guess(&self, state: &mut ScribbleState, user_id: &str, guess: &str, now: i64) {
+        let guess = normalize(guess);
+
+        if guess == state.word {
+            let remaining = (state.phase_deadline - now).clamp(0, self.draw_time_ms);
+            let points = MIN_GUESS_POINTS + (MAX_GUESS_POINTS - MIN_GUESS_POINTS) * remaining / self.draw_time_ms.max(1);
+            let drawer = state.players[state.drawer_index].clone();
+
+            *state.scores.entry(user_id.to_string()).or_insert(0) += points;
+            *state.turn_scores.entry(user_id.to_string()).or_insert(0) += points;
+            *state.scores.entry(drawer.clone()).or_insert(0) += DRAWER_POINTS_PER_GUESS;
+            *state.turn_scores.entry(drawer).or_insert(0) += DRAWER_POINTS_PER_GUESS;
+
+            state.guessed.push(user_id.to_string());
+            state.close_guesses.remove(user_id);
+
+            if everyone_guessed(state) {
+                self.end_turn(state, now);
+            }
+            return;
+        }
+
+        // Close guesses are only hinted to the player who made them so they do not give the word away
+        if is_close_guess(&guess, &state.word) {
+            state.close_guesses.insert(user_id.to_string(), guess);
+            return;
+        }
+
+        state.recent_guesses.push(ScribbleGuess { user_id: user_id.to_string(), guess });
+        if state.recent_guesses.len() > MAX_RECENT_GUESSES {
+            state.recent_guesses.remove(0);
+        }
+    }
+}
+
+impl GameEngine for ScribbleEngine {
+    type State = ScribbleState;
+
+    fn game_type(&self) -> &'static str {
+        "scribble"
+    }
+
+    fn state_key_prefix(&self) -> &'static str {
+        SCRIBBLE_STATE_REDIS_KEY
+    }
+
+    fn min_players(&self) -> usize {
+        MIN_PLAYERS
+    }
+
+    fn max_players(&self) -> usize {
+        MAX_PLAYERS
+    }
+
+    fn initial_state(&self, turn_mapping: &UserTurnMapping, seed: u64) -> Result<Self::State, EngineError> {
+        let player_count = turn_mapping.turn_mappings.len();
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&player_count) {
+            return Err(EngineError::InvalidPlayerCount { min: MIN_PLAYERS, max: MAX_PLAYERS, found: player_count })
+        }
+
+        let mut turns: Vec<_> = turn_mapping.turn_mappings.iter().collect();
+        turns.sort_by_key(|turn| turn.count_id);
+
+        let mut state = ScribbleState {
+            game_id: turn_mapping.game_id.clone(),
+            seed,
+            players: turns.iter().map(|turn| turn.user_id.clone()).collect(),
+            scores: turns.iter().map(|turn| (turn.user_id.clone(), 0)).collect(),
+            round: 1,
+            total_rounds: self.rounds,
+            ..Default::default()
+        };
+
+        // The clock starts with the first action received for the game
+        self.start_choosing(&mut state, 0);
+        Ok(state)
+    }
+
+    fn validate_action(&self, state: &Self::State, action: &EngineAction) -> Result<(), EngineError> {
+        let now = action.received_at;
+
+        if action.action_type == "tick" {
+            if state.phase_deadline > 0 && now < state.phase_deadline {
+                return Err(EngineError::IllegalAction("Turn is not over yet".to_string()))
+            }
+            return Ok(())
+        }
+
+        if !state.players.contains(&action.user_id) {
+            return Err(EngineError::InvalidAction("Player is not part of this game".to_string()))
+        }
+        if state.left_players.contains(&action.user_id) {
+            return Err(EngineError::IllegalAction("Player left the game".to_string()))
+        }
+
+        if state.phase_deadline > 0 && now > state.phase_deadline {
+            return Err(EngineError::IllegalAction("Time is up".to_string()))
+        }
+
+        let is_drawer = state.players[state.drawer_index] == action.user_id;
+
+        match action.action_type.as_str() {
+            "choose_word" => {
+                if state.phase != CHOOSING_PHASE || !is_drawer {
+                    return Err(EngineError::NotPlayersTurn)
+                }
+
+                let choose_event: ScribbleChooseWordEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid choose word payload".to_string()))?;
+                if choose_event.word_index >= state.word_choices.len() {
+                    return Err(EngineError::InvalidAction("Unknown word choice".to_string()))
+                }
+                Ok(())
+            },
+            "guess" => {
+                if state.phase != DRAWING_PHASE {
+                    return Err(EngineError::IllegalAction("Nothing to guess right now".to_string()))
+                }
+                if is_drawer {
+                    return Err(EngineError::IllegalAction("The drawer cannot guess".to_string()))
+                }
+                if state.guessed.contains(&action.user_id) {
+                    return Err(EngineError::IllegalAction("Word already guessed".to_string()))
+                }
+
+                let guess_event: ScribbleGuessEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid guess payload".to_string()))?;
+                let guess = normalize(&guess_event.guess);
+                if guess.is_empty() || guess.chars().count() > MAX_GUESS_LENGTH {
+                    return Err(EngineError::InvalidAction("Guess must be between 1 and 100 characters".to_string()))
+                }
+                Ok(())
+            },
+            "stroke" | "clear_canvas" => {
+                if state.phase != DRAWING_PHASE || !is_drawer {
+                    return Err(EngineError::NotPlayersTurn)
+                }
+
+                if action.action_type == "stroke" {
+                    let stroke_event: ScribbleStrokeEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid stroke payload".to_string()))?;
+                    let points_in_canvas = stroke_event.points.iter().all(|point| (0.0..=1.0).contains(&point.x) && (0.0..=1.0).contains(&point.y));
+
+                    if stroke_event.points.is_empty() || stroke_event.points.len() > MAX_STROKE_POINTS || !points_in_canvas || stroke_event.width <= 0.0 {
+                        return Err(EngineError::InvalidAction("Invalid stroke".to_string()))
+                    }
+                }
+                Ok(())
+            },
+            other => Err(EngineError::InvalidAction(format!("Unknown scribble action {}", other))),
+        }
+    }
+
+    fn apply_action(&self, state: &Self::State, action: &EngineAction) -> Result<Self::State, EngineError> {
+        let mut state = state.clone();
+        let now = action.received_at;
+
+        if state.phase_deadline == 0 {
+            state.phase_started_at = now;
+            state.phase_deadline = now + self.phase_duration(&state.phase);
+
+            if action.action_type == "tick" {
+                return Ok(state);
+            }
+        }
+
+        match action.action_type.as_str() {
+            "tick" => {
+                // The drawer did not pick in time, the first word is used
+                if state.phase == CHOOSING_PHASE {
+                    self.start_drawing(&mut state, 0, now);
+                } else {
+                    self.end_turn(&mut state, now);
+                }
+            },
+            "choose_word" => {
+                let choose_event: ScribbleChooseWordEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid choose word payload".to_string()))?;
+                self.start_drawing(&mut state, choose_event.word_index, now);
+            },
+            "guess" => {
+                let guess_event: ScribbleGuessEvent = serde_json::from_str(&action.payload).map_err(|_| EngineError::InvalidAction("Invalid guess payload".to_string()))?;
+                self.guess(&mut state, &action.user_id, &guess_event.guess, now);
+            },
+            _ => {},
+        }
+
+        Ok(state)
+    }
+
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        state.phase == GAME_OVER_PHASE
+    }
+
+    fn result(&self, state: &Self::State) -> Option<GameResult> {
+        if !self.is_terminal(state) {
+            return None
+        }
+
+        // A shared first place is a draw
+        let winners: Vec<&ScribbleLeaderboardEntry> = state.leaderboard.iter().filter(|entry| entry.rank == 1).collect();
+        let winner = if winners.len() == 1 { Some(winners[0].user_id.clone()) } else { None };
+
+        let placements = state.leaderboard.iter().map(|entry| Placement { player: entry.user_id.clone(), place: entry.rank }).collect();
+
+        Some(GameResult { winner, reason: "leaderboard".to_string(), placements })
+    }
+
+    fn serialize_state(&self, state: &Self::State) -> Result<String, EngineError> {
+        serde_json::to_string(state).map_err(|e| EngineError::InvalidState(e.to_string()))
+    }
+
+    fn deserialize_state(&self, raw_state: &str) -> Result<Self::State, EngineError> {
+        serde_json::from_str(raw_state).map_err(|e| EngineError::InvalidState(e.to_string()))
+    }
+
+    fn public_state(&self, state: &Self::State) -> Result<String, EngineError> {
+        self.serialize_state(&public_view(state))
+    }
+
+    // The drawer gets the word choices and the word, players who guessed get the word, close guesses go to their owner
+    fn private_states(&self, state: &Self::State) -> Result<Vec<PrivateState>, EngineError> {
+        let public = public_view(state);
+        let mut private_states = vec![];
+
+        for (player_index, user_id) in state.players.iter().enumerate() {
+            let is_drawer = player_index == state.drawer_index && state.phase != GAME_OVER_PHASE;
+            let has_guessed = state.guessed.contains(user_id);
+            let close_guess = state.close_guesses.get(user_id);
+
+            if !is_drawer && !has_guessed && close_guess.is_none() {
+                continue;
+            }
+
+            let mut view = public.clone();
+            if is_drawer {
+                view.word_choices = state.word_choices.clone();
+            }
+            if is_drawer || has_guessed {
+                view.word = state.word.clone();
+            }
+            if let Some(close_guess) = close_guess {
+                view.close_guesses.insert(user_id.clone(), close_guess.clone());
+            }
+
+            private_states.push(PrivateState { user_id: user_id.clone(), state: self.serialize_state(&view)? });
+        }
+
+        Ok(private_states)
+    }
+
+    fn is_transient_action(&self, action: &EngineAction) -> bool {
+        action.action_type == "stroke" || action.action_type == "clear_canvas"
+    }
+
+    fn next_deadline(&self, state: &Self::State) -> Option<i64> {
+        if state.phase == GAME_OVER_PHASE || state.phase_deadline == 0 {
+            return None
+        }
+        Some(state.phase_deadline)
+    }
+
+    // The game ends once a single player is left, a leaving drawer ends their turn
+    fn remove_player(&self, state: &Self::State, user_id: &str, now: i64) -> Option<Self::State> {
+        let mut state = state.clone();
+        if !state.players.iter().any(|player| player == user_id) || state.left_players.iter().any(|player| player == user_id) {
+            return Some(state)
+        }
+
+        state.left_players.push(user_id.to_string());
+        state.guessed.retain(|player| player != user_id);
+        state.close_guesses.remove(user_id);
+
+        if state.players.len() - state.left_players.len() < 2 {
+            finish_game(&mut state);
+        } else if state.players[state.drawer_index] == user_id || (state.phase == DRAWING_PHASE && everyone_guessed(&state)) {
+            self.end_turn(&mut state, now);
+        }
+
+        Some(state)
+    }
+}
+
+
+fn public_view(state: &ScribbleState) -> ScribbleState {
+    let mut view = state.clone();
+    view.word_choices = vec![];
+    view.word = "".to_string();
+    view.close_guesses = HashMap::new();
+
+    // Revealing the seed earlier would reveal the upcoming words
+    if view.phase != GAME_OVER_PHASE {
+        view.seed = 0;
+        view.used_words = vec![];
+    }
+
+    view
+}
+
+fn finish_game(state: &mut ScribbleState) {
+    state.phase = GAME_OVER_PHASE.to_string();
+    state.phase_deadline = 0;
+    state.leaderboard = leaderboard(state);
+}
+
+// Every player still in the game except the drawer
+fn everyone_guessed(state: &ScribbleState) -> bool {
+    state.guessed.len() >= state.players.len() - state.left_players.len() - 1
+}
+
+fn leaderboard(state: &ScribbleState) -> Vec<ScribbleLeaderboardEntry> {
+    let mut entries: Vec<ScribbleLeaderboardEntry> = state.players.iter()
+        .map(|user_id| ScribbleLeaderboardEntry { user_id: user_id.clone(), score: *state.scores.get(user_id).unwrap_or(&0), rank: 0 })
+        .collect();
+    // Stable sort keeps the seat order between players with the same score
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+
+    for index in 0..entries.len() {
+        entries[index].rank = if index > 0 && entries[index].score == entries[index - 1].score {
+            entries[index - 1].rank
+        } else {
+            index as u32 + 1
+        };
+    }
+
+    entries
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ").to_lowercase()
+}
+
+fn mask_word(word: &str) -> String {
+    word.chars().map(|c| if c.is_alphanumeric() { '_' } else { c }).collect()
+}
+
+// One typo away, or two for longer words
+fn is_close_guess(guess: &str, word: &str) -> bool {
+    let allowed_distance = if word.chars().count() >= 8 { 2 } else { 1 };
+    levenshtein(guess, word) <= allowed_distance
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b_chars.len()]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::models::user_turn_model::TurnModel;
+
+    use super::*;
+
+    const GAME_ID: &str = "6f1d2c3b-4a5e-4f60-8b7a-9c0d1e2f3a4b";
+
+    fn engine(rounds: u32) -> ScribbleEngine {
+        ScribbleEngine { rounds, ..ScribbleEngine::with_words(vec!["kite".to_string(), "elephant".to_string(), "apple".to_string()]) }
+    }
+
+    fn turn_mapping(players: &[&str]) -> UserTurnMapping {
+        UserTurnMapping {
+            host_id: players[0].to_string(),
+            game_id: GAME_ID.to_string(),
+            turn_mappings: players.iter().enumerate().map(|(seat, user_id)| TurnModel {
+                count_id: seat as i64,
+                user_id: user_id.to_string(),
+                username: user_id.to_string(),
+                status: "".to_string(),
+            }).collect(),
+        }
+    }
+
+    fn action(user_id: &str, action_type: &str, payload: &str, received_at: i64) -> EngineAction {
+        EngineAction { user_id: user_id.to_string(), player_type: "".to_string(), action_type: action_type.to_string(), payload: payload.to_string(), received_at }
+    }
+
+    fn play(engine: &ScribbleEngine, state: &ScribbleState, action: EngineAction) -> ScribbleState {
+        engine.validate_action(state, &action).unwrap();
+        engine.apply_action(state, &action).unwrap()
+    }
+
+    fn tick(engine: &ScribbleEngine, state: &ScribbleState, received_at: i64) -> ScribbleState {
+        play(engine, state, action("", "tick", "", received_at))
+    }
+
+    fn guess(engine: &ScribbleEngine, state: &ScribbleState, user_id: &str, guess: &str, received_at: i64) -> ScribbleState {
+        play(engine, state, action(user_id, "guess", &format!("{{\"guess\":\"{}\"}}", guess), received_at))
+    }
+
+    // The drawer picks the given word at t=1000, which also starts the clock
+    fn drawing(engine: &ScribbleEngine, players: &[&str], word: &str) -> ScribbleState {
+        let state = engine.initial_state(&turn_mapping(players), 11).unwrap();
+        let word_index = state.word_choices.iter().position(|choice| choice == word).unwrap();
+        play(engine, &state, action(players[0], "choose_word", &format!("{{\"word_index\":{}}}", word_index), 1000))
+    }
+
+    fn drawer(state: &ScribbleState) -> &str {
+        &state.players[state.drawer_index]
+    }
+
+    #[test]
+    fn faster_guesses_score_more_and_the_drawer_scores_per_guess() {
+        let engine = engine(3);
+        let state = drawing(&engine, &["a", "b", "c"], "kite");
+        assert_eq!(state.phase_deadline, 81_000);
+        assert_eq!(state.masked_word, "____");
+
+        // Half of the drawing time left
+        let state = guess(&engine, &state, "b", " KITE ", 41_000);
+        assert_eq!(state.scores["b"], 275);
+        assert_eq!(state.scores["a"], 50);
+        assert_eq!(state.guessed, vec!["b"]);
+        assert!(engine.validate_action(&state, &action("b", "guess", "{\"guess\":\"kite\"}", 42_000)).is_err());
+        assert!(engine.validate_action(&state, &action("a", "guess", "{\"guess\":\"kite\"}", 42_000)).is_err());
+
+        // The last guesser gets the minimum and ends the turn
+        let state = guess(&engine, &state, "c", "kite", 81_000);
+        assert_eq!(state.scores["c"], 50);
+        assert_eq!(state.scores["a"], 100);
+        assert_eq!(state.last_word, "kite");
+        assert_eq!(state.phase, CHOOSING_PHASE);
+        assert_eq!(drawer(&state), "b");
+        assert!(state.turn_scores.is_empty());
+    }
+
+    #[test]
+    fn close_guesses_are_only_hinted_to_their_owner() {
+        assert!(is_close_guess("kit", "kite"));
+        assert!(!is_close_guess("kt", "kite"));
+        assert!(is_close_guess("elefant", "elephant"));
+        assert!(!is_close_guess("elfant", "elephant"));
+
+        let engine = engine(3);
+        let state = drawing(&engine, &["a", "b", "c"], "kite");
+        let state = guess(&engine, &state, "b", "kites", 2000);
+        let state = guess(&engine, &state, "c", "kitten", 3000);
+
+        assert_eq!(state.close_guesses.get("b"), Some(&"kites".to_string()));
+        assert_eq!(state.recent_guesses.iter().map(|recent| recent.guess.as_str()).collect::<Vec<&str>>(), vec!["kitten"]);
+        assert_eq!(state.scores["b"], 0);
+
+        let public = engine.deserialize_state(&engine.public_state(&state).unwrap()).unwrap();
+        assert!(public.close_guesses.is_empty());
+        assert!(public.word.is_empty());
+
+        let private_states = engine.private_states(&state).unwrap();
+        assert_eq!(private_states.iter().map(|private| private.user_id.as_str()).collect::<Vec<&str>>(), vec!["a", "b"]);
+        let drawer_view = engine.deserialize_state(&private_states[0].state).unwrap();
+        assert_eq!(drawer_view.word, "kite");
+        assert!(drawer_view.close_guesses.is_empty());
+        let guesser_view = engine.deserialize_state(&private_states[1].state).unwrap();
+        assert_eq!(guesser_view.close_guesses.get("b"), Some(&"kites".to_string()));
+        assert!(guesser_view.word.is_empty());
+
+        // The hint goes away once the word is found
+        let state = guess(&engine, &state, "b", "kite", 4000);
+        assert!(state.close_guesses.is_empty());
+    }
+
+    #[test]
+    fn ticks_start_the_clock_and_advance_the_phases() {
+        let engine = engine(3);
+        let state = engine.initial_state(&turn_mapping(&["a", "b", "c"]), 5).unwrap();
+        assert_eq!(engine.next_deadline(&state), None);
+
+        let state = tick(&engine, &state, 1000);
+        assert_eq!(state.phase, CHOOSING_PHASE);
+        assert_eq!(engine.next_deadline(&state), Some(16_000));
+        assert!(engine.validate_action(&state, &action("", "tick", "", 15_999)).is_err());
+
+        // The drawer did not choose in time so the first word is drawn
+        let first_choice = state.word_choices[0].clone();
+        let state = tick(&engine, &state, 16_000);
+        assert_eq!(state.phase, DRAWING_PHASE);
+        assert_eq!(state.word, first_choice);
+        assert_eq!(engine.next_deadline(&state), Some(96_000));
+        assert!(engine.validate_action(&state, &action("b", "guess", "{\"guess\":\"kite\"}", 96_001)).is_err());
+
+        let state = tick(&engine, &state, 96_000);
+        assert_eq!(state.phase, CHOOSING_PHASE);
+        assert_eq!(state.last_word, first_choice);
+        assert_eq!(drawer(&state), "b");
+        assert_eq!(engine.next_deadline(&state), Some(111_000));
+    }
+
+    #[test]
+    fn every_player_draws_once_per_round() {
+        let engine = engine(2);
+        let mut state = engine.initial_state(&turn_mapping(&["a", "b", "c"]), 5).unwrap();
+        let mut now = 1000;
+        state = tick(&engine, &state, now);
+
+        let mut drawers = vec![];
+        while state.phase != GAME_OVER_PHASE {
+            drawers.push((state.round, drawer(&state).to_string()));
+            now = state.phase_deadline;
+            state = tick(&engine, &state, now);
+            now = state.phase_deadline;
+            state = tick(&engine, &state, now);
+        }
+
+        let expected: Vec<(u32, String)> = [(1, "a"), (1, "b"), (1, "c"), (2, "a"), (2, "b"), (2, "c")].iter().map(|(round, user_id)| (*round, user_id.to_string())).collect();
+        assert_eq!(drawers, expected);
+        assert_eq!(state.round, 2);
+        assert_eq!(engine.next_deadline(&state), None);
+
+        // Nobody scored, so everyone shares the first place
+        let result = engine.result(&state).unwrap();
+        assert_eq!(result.winner, None);
+        assert!(result.placements.iter().all(|placement| placement.place == 1));
+    }
+
+    #[test]
+    fn drawer_leaving_ends_their_turn_and_is_skipped_afterwards() {
+        let engine = engine(2);
+        let state = drawing(&engine, &["a", "b", "c", "d"], "apple");
+        let state = guess(&engine, &state, "b", "apple", 41_000);
+
+        let state = engine.remove_player(&state, "a", 50_000).unwrap();
+        assert_eq!(state.left_players, vec!["a"]);
+        assert_eq!(state.phase, CHOOSING_PHASE);
+        assert_eq!(drawer(&state), "b");
+        assert_eq!(state.last_word, "apple");
+        assert!(state.guessed.is_empty());
+        assert_eq!(state.phase_deadline, 65_000);
+        // Points already earned are kept
+        assert_eq!(state.scores["a"], 50);
+        assert_eq!(state.scores["b"], 275);
+        assert!(engine.validate_action(&state, &action("a", "guess", "{\"guess\":\"apple\"}", 51_000)).is_err());
+
+        // c and d draw, then the new round starts with b instead of a
+        let mut state = state;
+        for expected_drawer in ["c", "d", "b"] {
+            state = tick(&engine, &state, state.phase_deadline);
+            state = tick(&engine, &state, state.phase_deadline);
+            assert_eq!(drawer(&state), expected_drawer);
+        }
+        assert_eq!(state.round, 2);
+    }
+
+    #[test]
+    fn guesser_leaving_can_end_the_turn_or_the_game() {
+        let engine = engine(3);
+        let state = drawing(&engine, &["a", "b", "c"], "kite");
+        let state = guess(&engine, &state, "b", "kite", 2000);
+
+        // c was the only one left to guess
+        let state = engine.remove_player(&state, "c", 3000).unwrap();
+        assert_eq!(state.phase, CHOOSING_PHASE);
+        assert_eq!(drawer(&state), "b");
+
+        let state = engine.remove_player(&state, "a", 4000).unwrap();
+        assert_eq!(state.phase, GAME_OVER_PHASE);
+        assert_eq!(engine.result(&state).unwrap().winner, Some("b".to_string()));
+    }
+}
@@ -0,0 +1,13 @@
+// Word list used when no list is configured
+pub const DEFAULT_WORDS: &[&str] = &[
+    "apple", "banana", "guitar", "elephant", "bicycle", "rainbow", "volcano", "pirate", "castle", "rocket",
+    "penguin", "umbrella", "lighthouse", "dragon", "snowman", "camera", "pizza", "giraffe", "island", "robot",
+    "butterfly", "tornado", "compass", "octopus", "anchor", "cactus", "telescope", "igloo", "kangaroo", "ladder",
+    "mermaid", "necklace", "parachute", "pyramid", "scarecrow", "skateboard", "spider web", "sunflower", "teapot", "treasure",
+    "unicorn", "vampire", "waterfall", "windmill", "zebra", "astronaut", "backpack", "campfire", "dinosaur", "fireworks",
+    "hamburger", "helicopter", "hot air balloon", "jellyfish", "lawn mower", "magnet", "mountain", "mushroom", "ninja", "owl",
+    "peacock", "pineapple", "popcorn", "roller coaster", "sandcastle", "satellite", "submarine", "swing", "toothbrush", "traffic light",
+    "trophy", "violin", "wizard", "yo-yo", "alarm clock", "bridge", "chess", "crown", "dice", "fishing rod",
+    "ghost", "hammer", "key", "kite", "lemon", "map", "moon", "piano", "rose", "sailboat",
+    "shark", "snail", "stethoscope", "tent", "train", "turtle", "wallet", "whale", "zipper", "bee",
+];
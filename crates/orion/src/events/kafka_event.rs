@@ -41,6 +41,39 @@ pub struct UserGameInviteKafkaEvent {
     pub game_id: String,
     pub game_name: String,
     pub game_type: String,
+    #[serde(default)]
+    pub invite_id: String,
+    // Unix timestamp (ms) after which the invite can no longer be accepted
+    #[serde(default)]
+    pub expires_at: i64,
+}
+
+// Sent to the inviter when the invitee accepts or declines
+#[derive(Clone , Serialize , Deserialize)]
+pub struct UserGameInviteResponseKafkaEvent {
+    pub invite_id: String,
+    pub game_id: String,
+    pub user_who_responded_id: String,
+    pub user_who_responded_username: String,
+    pub user_who_we_are_sending_event: String,
+    pub accepted: bool,
+}
+
+// Sent to each player of a game created by the matchmaker
+#[derive(Clone , Serialize , Deserialize)]
+pub struct MatchFoundKafkaEvent {
+    pub user_who_we_are_sending_event: String,
+    pub game_id: String,
+    pub session_id: String,
+    pub game_type: String,
+    pub time_control: Option<String>,
+    pub is_staked: bool,
+    // Lowest stake both players accepted, only set for staked games
+    pub stake_amount: Option<f64>,
+    pub player_type: String,
+    pub opponent_id: String,
+    pub opponent_username: String,
+    pub opponent_rating: f64,
 }
 
 #[derive(Clone , Serialize , Deserialize , Debug)]
@@ -114,6 +147,8 @@ pub struct GenerateGameBetSettleEvents {
     pub game_id: String,
     pub session_id: String,
     pub winner_id: String,
+    #[serde(default)]
+    pub winner_ids: Vec<String>,
     pub is_game_valid: bool
 }
 
@@ -124,7 +159,23 @@ pub struct GameOverEvent {
     pub game_id: String,
     pub session_id: String,
     pub winner_id: String,
-    pub is_game_valid: bool
+    // Every player placed first, winner_id is kept for single winner games
+    #[serde(default)]
+    pub winner_ids: Vec<String>,
+    pub is_game_valid: bool,
+    // Player who abandoned the game when it is not valid, everyone else is compensated
+    #[serde(default)]
+    pub absent_player_id: String,
+}
+
+
+// winner_ids when the producer sent them, otherwise the single winner_id. Empty for a stalemate
+pub fn game_winner_ids(winner_id: &str, winner_ids: &[String]) -> Vec<String> {
+    if !winner_ids.is_empty() {
+        return winner_ids.to_vec()
+    }
+
+    if winner_id.is_empty() { vec![] } else { vec![winner_id.to_string()] }
 }
 
 
@@ -135,6 +186,8 @@ pub struct GameUserBetSettleEvent {
     pub session_id: String,
     pub user_id: String,
     pub winner_id: String,
+    #[serde(default)]
+    pub winner_ids: Vec<String>,
     pub is_game_valid: bool,
     pub is_error: bool
 }
@@ -146,6 +199,8 @@ pub struct GameSettleBetErrorRedisPayload {
     pub game_id: String,
     pub session_id: String,
     pub winner_id: String,
+    #[serde(default)]
+    pub winner_ids: Vec<String>,
     pub is_game_valid: bool
 }
 
@@ -187,6 +242,8 @@ pub struct GameBetSettleKafkaPayload {
     pub game_id: String,
     pub session_id: String,
     pub winner_id: String,
+    #[serde(default)]
+    pub winner_ids: Vec<String>,
     pub user_id: String,
     pub user_betting_on: String,
     pub record_id: String,
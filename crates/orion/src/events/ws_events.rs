@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -28,7 +30,9 @@ pub struct UpdateUserStatusPayload {
 pub struct GameStartPayload {
     pub admin_id: String,
     pub game_name: String,
-    pub game_id: String
+    pub game_id: String,
+    #[serde(default)]
+    pub session_id: String,
 }
 
 #[derive(Deserialize , Serialize)]
@@ -36,7 +40,18 @@ pub struct GameMessagePayload {
     pub user_id: String,
     pub username: String,
     pub message: String,
-    pub game_id: String
+    pub game_id: String,
+    #[serde(default)]
+    pub message_id: String,
+    #[serde(default)]
+    pub channel: String,
+}
+
+#[derive(Deserialize , Serialize)]
+pub struct GameMessageDeletedPayload {
+    pub game_id: String,
+    pub message_id: String,
+    pub channel: String,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -47,7 +62,89 @@ pub struct GetUserTurnsMappingWSPayload {
 #[derive(Deserialize, Serialize)]
 pub struct ErrorMessagePayload {
     pub game_id: String,
-    pub error_message: String
+    pub error_message: String,
+    // User the error is addressed to. Empty means the error is for the whole game room
+    #[serde(default)]
+    pub user_who_we_are_sending_event: String,
+    // Authoritative state and state index so the client can resync after a rejected move
+    #[serde(default)]
+    pub current_state: Option<String>,
+    #[serde(default)]
+    pub state_index: Option<i64>,
+}
+
+// Game state containing hidden information (e.g. poker hole cards) that only the addressed user may see
+#[derive(Deserialize , Serialize)]
+pub struct PlayerPrivateStatePayload {
+    pub game_id: String,
+    pub user_who_we_are_sending_event: String,
+    pub state: String,
+    pub state_index: i64,
+}
+
+// Public game state sent to the whole game room after every applied action
+#[derive(Deserialize , Serialize)]
+pub struct GameStateUpdatePayload {
+    pub game_id: String,
+    pub state: String,
+    pub state_index: i64,
+}
+
+// Action that does not change the stored state (e.g. scribble strokes), forwarded as is to the game room
+#[derive(Deserialize , Serialize)]
+pub struct GameTransientActionPayload {
+    pub game_id: String,
+    pub user_id: String,
+    pub action_type: String,
+    pub payload: String,
+}
+
+// Deadline (unix ms) of the current turn, sent whenever a timed game starts a new turn
+#[derive(Deserialize , Serialize)]
+pub struct GameClockPayload {
+    pub game_id: String,
+    pub deadline: i64,
+    pub state_index: i64,
+}
+
+// Money bet on each player (keyed by user_id) for the current session of the game
+#[derive(Deserialize , Serialize)]
+pub struct GameBetPoolPayload {
+    pub game_id: String,
+    pub session_id: String,
+    pub total_amount: f64,
+    pub pools: HashMap<String, f64>,
+}
+
+// Rematch asked for or declined by a player. requested_by holds every player who wants the rematch so far
+#[derive(Deserialize , Serialize)]
+pub struct GameRematchPayload {
+    pub game_id: String,
+    pub user_id: String,
+    pub username: String,
+    pub requested_by: Vec<String>,
+}
+
+#[derive(Deserialize , Serialize)]
+pub struct GameSpectatorCountPayload {
+    pub game_id: String,
+    pub spectator_count: i64,
+}
+
+// Final result of a game sent to the game room. winner_ids is empty when every player tied
+#[derive(Deserialize , Serialize)]
+pub struct GameResultPayload {
+    pub game_id: String,
+    pub winner_ids: Vec<String>,
+    pub placements: Vec<PlayerPlacementPayload>,
+    pub reason: String,
+}
+
+#[derive(Deserialize , Serialize)]
+pub struct PlayerPlacementPayload {
+    pub user_id: String,
+    pub place: u32,
+    pub score_change: i32,
 }
 
 #[derive(Deserialize , Serialize)]
@@ -67,3 +164,49 @@ pub struct VerifyingStatusPayload {
     pub user_id: String,
     pub game_id: String
 }
+
+
+// Frame sent by clients to the websocket gateway, payload is the JSON of the event payload
+#[derive(Deserialize , Serialize)]
+pub struct WsClientMessage {
+    pub event: String,
+    #[serde(default)]
+    pub game_id: String,
+    #[serde(default)]
+    pub payload: String,
+}
+
+// Frame sent by the gateway to clients, event is the kafka key and payload the kafka payload
+#[derive(Deserialize , Serialize)]
+pub struct WsServerMessage {
+    pub event: String,
+    pub payload: String,
+}
+
+// Move sent over the websocket, the gateway fills in user_id and game_id
+#[derive(Deserialize , Serialize)]
+pub struct WsGameMovePayload {
+    pub move_type: String,
+    #[serde(default)]
+    pub user_move: String,
+}
+
+// chat-mute-user / chat-unmute-user / chat-timeout-user, duration_secs is only read for timeouts
+#[derive(Deserialize , Serialize)]
+pub struct ChatUserActionPayload {
+    pub user_id: String,
+    #[serde(default)]
+    pub duration_secs: u64,
+}
+
+#[derive(Deserialize , Serialize)]
+pub struct ChatMessageActionPayload {
+    pub message_id: String,
+}
+
+// Answer to join-game-room / leave-game-room
+#[derive(Deserialize , Serialize)]
+pub struct GameRoomPayload {
+    pub game_id: String,
+    pub is_spectator: bool,
+}
@@ -1,3 +1,5 @@
 pub mod models;
 pub mod events;
-pub mod constants;
\ No newline at end of file
+pub mod constants;
+pub mod engines;
+pub mod ratings;
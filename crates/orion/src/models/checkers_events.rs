@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+
+// Squares use the PDN numbering. path holds the starting square followed by every square the piece stops on
+#[derive(Debug, Deserialize , Serialize , Clone)]
+pub struct CheckersMoveEvent {
+    pub path: Vec<usize>,
+}
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Debug, Serialize , Deserialize , Clone, Default)]
+pub struct CheckersState {
+    // english or international
+    pub variant: String,
+    // Positions are PDN FEN strings, e.g. "B:W21,22,K5:B1,2,3"
+    pub initial_fen: String,
+    pub fen: String,
+    // Every move played so far in PDN move text ("11-15", "22x15x8")
+    pub moves: Vec<String>,
+    // Positions since the last man move or capture, a position can only repeat among these
+    pub repetition_fens: Vec<String>,
+    // Plies played since the last man move or capture
+    pub quiet_plies: u32,
+}
@@ -0,0 +1,19 @@
+use bson::DateTime;
+use serde::{Deserialize, Serialize};
+
+
+// Chat message of a game session. Deleted messages are kept for moderation but no longer served
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GameMessageRecord {
+    pub id: String,
+    pub game_id: String,
+    #[serde(default)]
+    pub session_id: String,
+    pub channel: String,
+    pub user_id: String,
+    pub username: String,
+    pub message: String,
+    #[serde(default)]
+    pub deleted: bool,
+    pub created_at: DateTime,
+}
@@ -5,6 +5,8 @@ use bson::DateTime;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::constants::DEFAULT_SPECTATOR_DELAY_SECS;
+
 
 
 #[derive( Serialize , Deserialize , Clone)]
@@ -19,32 +21,133 @@ pub struct Game {
     pub chess_state: String,
     pub is_match: bool,
     pub state_index: i64,
-    // Description contains the status of game -> LOBBY or IN_PROGRESS or INIT_STATE (only possible if its a match between users)
+    // Description contains the status of game -> LOBBY, IN_PROGRESS, AWAITING_BETS, GAME_OVER or INIT_STATE (only possible if its a match between users)
     pub description: String,
     pub staked_money_state: Option<String>,
     pub poker_state: Option<String>, 
+    // Serialised engine state for games whose engine keeps it in mongo instead of redis
+    #[serde(default)]
+    pub game_state: Option<String>,
+    // Session of the current game, every rematch gets a new one
+    #[serde(default)]
+    pub session_id: Option<String>,
+    // Players who asked for a rematch once the game is over
+    #[serde(default)]
+    pub rematch_requests: Vec<String>,
+    // None lets anyone watch
+    #[serde(default)]
+    pub spectator_limit: Option<i64>,
+    // Only used for staked games, see broadcast_delay_secs
+    #[serde(default)]
+    pub spectator_delay_secs: Option<i64>,
+    // Code to join the lobby without an invite, only valid while the game is in the lobby
+    #[serde(default)]
+    pub join_code: Option<String>,
+    // Argon2 hash, required from players joining with the code
+    #[serde(default)]
+    pub join_password: Option<String>,
+    // Only rated games change the players' ratings
+    #[serde(default)]
+    pub is_casual: bool,
+    // "base+increment" in seconds, None for games without a clock. Not enforced yet, only decides which rating the game counts for
+    #[serde(default)]
+    pub time_control: Option<String>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
 
-#[derive(Serialize, Deserialize , Clone)]
+impl Game {
+    // Spectators of staked games are kept behind the players so nobody can relay moves to someone still betting
+    pub fn broadcast_delay_secs(&self) -> u64 {
+        if !self.is_staked {
+            return 0
+        }
+
+        self.spectator_delay_secs.unwrap_or(DEFAULT_SPECTATOR_DELAY_SECS).max(0) as u64
+    }
+}
+
+
+#[derive(Serialize, Deserialize , Clone, Default)]
 pub struct PokerState { 
     #[serde(with = "bson::serde_helpers::uuid_1_as_binary")]
     pub id: Uuid,
     pub game_id: Uuid,
     pub pot_size: f64,
     pub current_turn: String,
-    pub user_states: HashMap<String , Option<Poker>>
+    // None means the player has busted out of the table
+    pub user_states: HashMap<String , Option<Poker>>,
+    // Player ids in seat order (from UserTurnMapping count_id)
+    #[serde(default)]
+    pub seats: Vec<String>,
+    #[serde(default)]
+    pub dealer_seat: usize,
+    #[serde(default)]
+    pub hand_number: u64,
+    // Table seed, every hand's deck is shuffled from (seed, hand_number). Only kept in the server side state, never sent to clients
+    #[serde(default)]
+    pub seed: u64,
+    // preflop, flop, turn, river or game_over
+    #[serde(default)]
+    pub street: String,
+    #[serde(default)]
+    pub board: Vec<String>,
+    #[serde(default)]
+    pub deck: Vec<String>,
+    #[serde(default)]
+    pub small_blind: f64,
+    #[serde(default)]
+    pub big_blind: f64,
+    // Highest bet of the current betting round and the minimum size of the next raise
+    #[serde(default)]
+    pub current_bet: f64,
+    #[serde(default)]
+    pub min_raise: f64,
+    #[serde(default)]
+    pub last_hand: Option<PokerHandSummary>,
+    // Players who busted out, one group per hand (earliest first), used for the final placements
+    #[serde(default)]
+    pub busted: Vec<Vec<String>>,
 }
 
 
-#[derive(Serialize , Deserialize , Clone)]
+#[derive(Serialize , Deserialize , Clone, Default)]
 
 pub struct Poker {
     pub money_left: f64,
     pub go_all_in: bool,
     pub turns_left: i64,
+    #[serde(default)]
+    pub hole_cards: Vec<String>,
+    #[serde(default)]
+    pub folded: bool,
+    // Chips put in during the current betting round and during the whole hand (used for side pots)
+    #[serde(default)]
+    pub round_bet: f64,
+    #[serde(default)]
+    pub hand_bet: f64,
+    #[serde(default)]
+    pub has_acted: bool,
+}
+
+
+#[derive(Serialize , Deserialize , Clone, Default)]
+pub struct PokerHandSummary {
+    pub hand_number: u64,
+    pub board: Vec<String>,
+    pub pots: Vec<PokerPotResult>,
+    // Hole cards revealed at showdown
+    pub shown_cards: HashMap<String , Vec<String>>,
+}
+
+
+#[derive(Serialize , Deserialize , Clone, Default)]
+pub struct PokerPotResult {
+    pub amount: f64,
+    pub winners: Vec<String>,
+    // Name of the winning hand. None when everyone else folded
+    pub hand_rank: Option<String>,
 }
 
 #[derive(Serialize , Deserialize , Clone)]
@@ -0,0 +1,21 @@
+use bson::DateTime;
+use serde::{Deserialize, Serialize};
+
+
+// Every applied action of a game with the public state it produced. state_index is the index sent in
+// GameStateUpdatePayload, the first record (move_type "start") holds the initial state
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GameMoveRecord {
+    pub game_id: String,
+    #[serde(default)]
+    pub session_id: String,
+    pub state_index: i64,
+    pub user_id: String,
+    pub move_type: String,
+    pub user_move: String,
+    pub public_state: String,
+    // Engine evaluation of the position in pawns from the first player's view, set once the game has been analysed
+    #[serde(default)]
+    pub evaluation: Option<f64>,
+    pub created_at: DateTime,
+}
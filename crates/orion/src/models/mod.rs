@@ -2,6 +2,15 @@ pub mod game_model;
 pub mod user_game_relation_model;
 pub mod user_turn_model;
 pub mod user_game_event;
+pub mod game_move_model;
+pub mod game_message_model;
 pub mod chess_events;
 pub mod game_bet_events;
-pub mod user_score_update_event;
\ No newline at end of file
+pub mod user_score_update_event;
+pub mod poker_events;
+pub mod scribble_model;
+pub mod scribble_events;
+pub mod monopoly_model;
+pub mod monopoly_events;
+pub mod checkers_model;
+pub mod checkers_events;
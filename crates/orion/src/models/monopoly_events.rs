@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+
+// Payload of build, sell_house, mortgage and unmortgage moves
+#[derive(Debug, Deserialize , Serialize , Clone)]
+pub struct MonopolySquareEvent {
+    pub square: usize,
+}
+
+
+#[derive(Debug, Deserialize , Serialize , Clone)]
+pub struct MonopolyBidEvent {
+    pub amount: i64,
+}
+
+
+#[derive(Debug, Deserialize , Serialize , Clone)]
+pub struct MonopolyTradeEvent {
+    pub to_user: String,
+    #[serde(default)]
+    pub offer_cash: i64,
+    #[serde(default)]
+    pub request_cash: i64,
+    #[serde(default)]
+    pub offer_squares: Vec<usize>,
+    #[serde(default)]
+    pub request_squares: Vec<usize>,
+    #[serde(default)]
+    pub offer_jail_free_cards: u8,
+    #[serde(default)]
+    pub request_jail_free_cards: u8,
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Debug, Serialize , Deserialize , Clone, Default)]
+pub struct MonopolyState {
+    pub game_id: String,
+    // Every dice roll and deck shuffle comes from this seed, so a game can be replayed from its actions
+    pub seed: u64,
+    pub roll_count: u64,
+    pub players: Vec<MonopolyPlayer>,
+    pub current_player: usize,
+    // roll, buy_decision, auction, debt, post_roll or game_over
+    pub phase: String,
+    // True at the start of a turn and after rolling doubles
+    pub can_roll: bool,
+    pub doubles_count: u8,
+    pub last_dice: Vec<u8>,
+    pub properties: Vec<MonopolyProperty>,
+    pub houses_left: u8,
+    pub hotels_left: u8,
+    // Remaining card order, drawn from the front and put back at the bottom
+    pub chance_deck: Vec<u8>,
+    pub community_chest_deck: Vec<u8>,
+    // Square the current player landed on and may buy
+    pub pending_purchase: Option<usize>,
+    pub auction: Option<MonopolyAuction>,
+    // Payments that could not be made yet, the first debtor has to raise money or go bankrupt
+    pub debts: Vec<MonopolyDebt>,
+    pub pending_trade: Option<MonopolyTrade>,
+    pub log: Vec<String>,
+    // Players in the order they went bankrupt
+    #[serde(default)]
+    pub bankruptcies: Vec<String>,
+}
+
+
+#[derive(Debug, Serialize , Deserialize , Clone, Default)]
+pub struct MonopolyPlayer {
+    pub user_id: String,
+    pub cash: i64,
+    pub position: usize,
+    pub in_jail: bool,
+    pub jail_turns: u8,
+    // Deck the held "get out of jail free" cards came from (chance / community_chest)
+    pub jail_free_cards: Vec<String>,
+    pub bankrupt: bool,
+}
+
+
+#[derive(Debug, Serialize , Deserialize , Clone, Default)]
+pub struct MonopolyProperty {
+    pub square: usize,
+    pub owner: Option<String>,
+    // 5 means a hotel
+    pub houses: u8,
+    pub mortgaged: bool,
+}
+
+
+#[derive(Debug, Serialize , Deserialize , Clone, Default)]
+pub struct MonopolyAuction {
+    pub square: usize,
+    pub highest_bid: i64,
+    pub highest_bidder: Option<String>,
+    // Players still bidding, in bidding order
+    pub bidders: Vec<String>,
+    pub current_bidder: usize,
+}
+
+
+#[derive(Debug, Serialize , Deserialize , Clone, Default)]
+pub struct MonopolyDebt {
+    pub debtor: String,
+    // None means the bank
+    pub creditor: Option<String>,
+    pub amount: i64,
+}
+
+
+#[derive(Debug, Serialize , Deserialize , Clone, Default)]
+pub struct MonopolyTrade {
+    pub from_user: String,
+    pub to_user: String,
+    pub offer_cash: i64,
+    pub request_cash: i64,
+    pub offer_squares: Vec<usize>,
+    pub request_squares: Vec<usize>,
+    pub offer_jail_free_cards: u8,
+    pub request_jail_free_cards: u8,
+}
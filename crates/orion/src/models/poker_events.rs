@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+
+// Payload of "bet" and "raise" poker moves. amount is the total the player wants in front of them for this betting round
+#[derive(Debug, Deserialize , Serialize , Clone)]
+pub struct PokerBetEvent {
+    pub amount: f64,
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Debug, Deserialize , Serialize , Clone)]
+pub struct ScribbleChooseWordEvent {
+    pub word_index: usize,
+}
+
+
+#[derive(Debug, Deserialize , Serialize , Clone)]
+pub struct ScribbleGuessEvent {
+    pub guess: String,
+}
+
+
+// Part of a line drawn by the drawer. Coordinates are relative to the canvas size (0.0 - 1.0)
+#[derive(Debug, Deserialize , Serialize , Clone)]
+pub struct ScribbleStrokeEvent {
+    pub stroke_id: String,
+    pub color: String,
+    pub width: f64,
+    pub points: Vec<ScribbleStrokePoint>,
+}
+
+
+#[derive(Debug, Deserialize , Serialize , Clone)]
+pub struct ScribbleStrokePoint {
+    pub x: f64,
+    pub y: f64,
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Debug, Serialize , Deserialize , Clone, Default)]
+pub struct ScribbleState {
+    pub game_id: String,
+    // Seed used to pick the words offered to each drawer. Hidden until the game is over
+    pub seed: u64,
+    // Player ids in the order of UserTurnMapping count_id, the drawer rotates through this list
+    pub players: Vec<String>,
+    // Players who left the game keep their score but are skipped as drawer
+    #[serde(default)]
+    pub left_players: Vec<String>,
+    pub scores: HashMap<String , i64>,
+    pub round: u32,
+    pub total_rounds: u32,
+    pub drawer_index: usize,
+    pub turn_number: u64,
+    // choosing, drawing or game_over
+    pub phase: String,
+    pub phase_started_at: i64,
+    // 0 until the first action starts the clock
+    pub phase_deadline: i64,
+    // Only the drawer sees the choices and the word
+    pub word_choices: Vec<String>,
+    pub word: String,
+    // Word with letters replaced by '_' for guessers
+    pub masked_word: String,
+    pub last_word: String,
+    pub used_words: Vec<String>,
+    // Players who guessed the word this turn, in order
+    pub guessed: Vec<String>,
+    pub turn_scores: HashMap<String , i64>,
+    // Wrong guesses are shown to the room like chat messages
+    pub recent_guesses: Vec<ScribbleGuess>,
+    // Last close guess of each player, sent only to that player
+    pub close_guesses: HashMap<String , String>,
+    pub leaderboard: Vec<ScribbleLeaderboardEntry>,
+}
+
+
+#[derive(Debug, Serialize , Deserialize , Clone)]
+pub struct ScribbleGuess {
+    pub user_id: String,
+    pub guess: String,
+}
+
+
+#[derive(Debug, Serialize , Deserialize , Clone)]
+pub struct ScribbleLeaderboardEntry {
+    pub user_id: String,
+    pub score: i64,
+    pub rank: u32,
+}
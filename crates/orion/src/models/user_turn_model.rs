@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+use crate::constants::{PLAYER_ACTIVE, PLAYER_DISCONNECTED, PLAYER_ELIMINATED};
 
-#[derive(Deserialize , Serialize)]
+
+#[derive(Deserialize , Serialize , Clone)]
 pub struct UserTurnMapping {
     pub host_id: String,
     pub game_id: String,
@@ -10,9 +12,99 @@ pub struct UserTurnMapping {
 
 
 
-#[derive(Deserialize , Serialize)]
+#[derive(Deserialize , Serialize , Clone)]
 pub struct TurnModel {
+    // Seat of the player, turns go around the table in count_id order
     pub count_id: i64,
     pub user_id: String,
     pub username: String,
+    // active, eliminated or disconnected. Older records without a status are active
+    #[serde(default)]
+    pub status: String,
+}
+
+impl TurnModel {
+    pub fn is_active(&self) -> bool {
+        self.status.is_empty() || self.status == PLAYER_ACTIVE
+    }
+}
+
+
+impl UserTurnMapping {
+    // Gives the player the lowest free seat. Returns the seat the player already has if they joined before, None if the lobby is full
+    pub fn assign_seat(&mut self, user_id: &str, username: &str, capacity: usize) -> Option<i64> {
+        if let Some(turn) = self.turn_mappings.iter().find(|turn| turn.user_id == user_id) {
+            return Some(turn.count_id)
+        }
+
+        let seat = (0..capacity as i64).find(|seat| !self.turn_mappings.iter().any(|turn| turn.count_id == *seat))?;
+        self.turn_mappings.push(TurnModel {
+            count_id: seat,
+            user_id: user_id.to_string(),
+            username: username.to_string(),
+            status: PLAYER_ACTIVE.to_string(),
+        });
+
+        Some(seat)
+    }
+
+    pub fn remove_player(&mut self, user_id: &str) -> bool {
+        let player_count = self.turn_mappings.len();
+        self.turn_mappings.retain(|turn| turn.user_id != user_id);
+        self.turn_mappings.len() != player_count
+    }
+
+    pub fn set_status(&mut self, user_id: &str, status: &str) -> bool {
+        match self.turn_mappings.iter_mut().find(|turn| turn.user_id == user_id) {
+            Some(turn) => {
+                turn.status = status.to_string();
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn eliminate(&mut self, user_id: &str) -> bool {
+        self.set_status(user_id, PLAYER_ELIMINATED)
+    }
+
+    pub fn disconnect(&mut self, user_id: &str) -> bool {
+        self.set_status(user_id, PLAYER_DISCONNECTED)
+    }
+
+    // Players who did not leave the game, in seat order. Eliminated players keep their seat for a rematch
+    pub fn seated_turns(&self) -> Vec<&TurnModel> {
+        let mut turns: Vec<&TurnModel> = self.turn_mappings.iter().filter(|turn| turn.status != PLAYER_DISCONNECTED).collect();
+        turns.sort_by_key(|turn| turn.count_id);
+        turns
+    }
+
+    // Players still taking turns, in seat order
+    pub fn active_turns(&self) -> Vec<&TurnModel> {
+        let mut turns: Vec<&TurnModel> = self.turn_mappings.iter().filter(|turn| turn.is_active()).collect();
+        turns.sort_by_key(|turn| turn.count_id);
+        turns
+    }
+
+    // Moves every player one seat along and makes them active again, so colours swap on a rematch
+    pub fn rotate_seats(&mut self) {
+        let mut seats: Vec<i64> = self.turn_mappings.iter().map(|turn| turn.count_id).collect();
+        seats.sort();
+        self.turn_mappings.sort_by_key(|turn| turn.count_id);
+
+        let seat_count = seats.len();
+        for (index, turn) in self.turn_mappings.iter_mut().enumerate() {
+            turn.count_id = seats[(index + 1) % seat_count];
+            turn.status = PLAYER_ACTIVE.to_string();
+        }
+    }
+
+    // Copy of the mapping with only the active players, used to deal a new game
+    pub fn with_active_players(&self) -> UserTurnMapping {
+        UserTurnMapping {
+            host_id: self.host_id.clone(),
+            game_id: self.game_id.clone(),
+            turn_mappings: self.active_turns().into_iter().cloned().collect(),
+        }
+    }
 }
@@ -0,0 +1,74 @@
+use std::fmt;
+
+
+// Ratings are kept per game type and, for chess, per time control category
+pub const CATEGORY_BULLET: &str = "bullet";
+pub const CATEGORY_BLITZ: &str = "blitz";
+pub const CATEGORY_RAPID: &str = "rapid";
+pub const CATEGORY_CLASSICAL: &str = "classical";
+pub const CATEGORY_CORRESPONDENCE: &str = "correspondence";
+// Single category of every game type other than chess
+pub const CATEGORY_STANDARD: &str = "standard";
+
+// A rating is shown as provisional until the player has this many rated games in its category
+pub const PROVISIONAL_GAMES: i32 = 20;
+
+// Moves assumed per game when turning the increment into an expected game length
+const EXPECTED_MOVES: u64 = 40;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    pub base_secs: u64,
+    pub increment_secs: u64,
+}
+
+impl TimeControl {
+    // Parses "base+increment" in seconds, e.g. "300+3"
+    pub fn parse(time_control: &str) -> Option<Self> {
+        let (base_secs, increment_secs) = time_control.split_once('+')?;
+        let time_control = TimeControl {
+            base_secs: base_secs.trim().parse().ok()?,
+            increment_secs: increment_secs.trim().parse().ok()?,
+        };
+
+        (time_control.base_secs > 0 || time_control.increment_secs > 0).then_some(time_control)
+    }
+
+    // Same boundaries as lichess, on the expected length base + 40 * increment
+    pub fn category(&self) -> &'static str {
+        match self.base_secs + EXPECTED_MOVES * self.increment_secs {
+            0..=179 => CATEGORY_BULLET,
+            180..=479 => CATEGORY_BLITZ,
+            480..=1499 => CATEGORY_RAPID,
+            _ => CATEGORY_CLASSICAL,
+        }
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}+{}", self.base_secs, self.increment_secs)
+    }
+}
+
+
+// Chess games without a clock are correspondence games. An unparsable time control falls back to the same
+pub fn rating_category(game_type: &str, time_control: Option<&str>) -> &'static str {
+    if game_type != "chess" {
+        return CATEGORY_STANDARD;
+    }
+
+    time_control
+        .and_then(TimeControl::parse)
+        .map(|time_control| time_control.category())
+        .unwrap_or(CATEGORY_CORRESPONDENCE)
+}
+
+pub fn is_valid_category(game_type: &str, category: &str) -> bool {
+    if game_type != "chess" {
+        return category == CATEGORY_STANDARD;
+    }
+
+    [CATEGORY_BULLET, CATEGORY_BLITZ, CATEGORY_RAPID, CATEGORY_CLASSICAL, CATEGORY_CORRESPONDENCE].contains(&category)
+}
@@ -0,0 +1,158 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+
+// Conversion factor between the Glicko and the Glicko-2 scale
+const GLICKO2_SCALE: f64 = 173.7178;
+// Constrains how fast the volatility changes, smaller values suit games with few upsets
+const TAU: f64 = 0.5;
+const CONVERGENCE_TOLERANCE: f64 = 0.000001;
+
+pub const DEFAULT_RATING: f64 = 1500.0;
+pub const DEFAULT_DEVIATION: f64 = 350.0;
+pub const DEFAULT_VOLATILITY: f64 = 0.06;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Glicko2Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for Glicko2Rating {
+    fn default() -> Self {
+        Glicko2Rating { rating: DEFAULT_RATING, deviation: DEFAULT_DEVIATION, volatility: DEFAULT_VOLATILITY }
+    }
+}
+
+impl Glicko2Rating {
+    // Rating after one rating period. results holds every opponent with the score against them (1 win, 0.5 draw, 0 loss),
+    // without results only the deviation grows
+    pub fn update(&self, results: &[(Glicko2Rating, f64)]) -> Glicko2Rating {
+        let mu = (self.rating - DEFAULT_RATING) / GLICKO2_SCALE;
+        let phi = self.deviation / GLICKO2_SCALE;
+
+        if results.is_empty() {
+            let phi_star = (phi.powi(2) + self.volatility.powi(2)).sqrt();
+            return Glicko2Rating { deviation: (phi_star * GLICKO2_SCALE).min(DEFAULT_DEVIATION), ..*self }
+        }
+
+        let mut inverse_variance = 0.0;
+        let mut score_sum = 0.0;
+        for (opponent, score) in results {
+            let opponent_mu = (opponent.rating - DEFAULT_RATING) / GLICKO2_SCALE;
+            let opponent_g = g(opponent.deviation / GLICKO2_SCALE);
+            let expected = 1.0 / (1.0 + (-opponent_g * (mu - opponent_mu)).exp());
+
+            inverse_variance += opponent_g.powi(2) * expected * (1.0 - expected);
+            score_sum += opponent_g * (score - expected);
+        }
+
+        let variance = 1.0 / inverse_variance;
+        let delta = variance * score_sum;
+        let volatility = new_volatility(phi, self.volatility, variance, delta);
+
+        let phi_star = (phi.powi(2) + volatility.powi(2)).sqrt();
+        let new_phi = 1.0 / (1.0 / phi_star.powi(2) + 1.0 / variance).sqrt();
+        let new_mu = mu + new_phi.powi(2) * score_sum;
+
+        Glicko2Rating {
+            rating: new_mu * GLICKO2_SCALE + DEFAULT_RATING,
+            deviation: (new_phi * GLICKO2_SCALE).min(DEFAULT_DEVIATION),
+            volatility,
+        }
+    }
+}
+
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi.powi(2) / PI.powi(2)).sqrt()
+}
+
+// Step 5 of the Glicko-2 paper, solved with the Illinois algorithm
+fn new_volatility(phi: f64, volatility: f64, variance: f64, delta: f64) -> f64 {
+    let a = volatility.powi(2).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        ex * (delta.powi(2) - phi.powi(2) - variance - ex) / (2.0 * (phi.powi(2) + variance + ex).powi(2)) - (x - a) / TAU.powi(2)
+    };
+
+    let mut lower = a;
+    let mut upper = if delta.powi(2) > phi.powi(2) + variance {
+        (delta.powi(2) - phi.powi(2) - variance).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * TAU) < 0.0 {
+            k += 1.0;
+        }
+        a - k * TAU
+    };
+
+    let mut f_lower = f(lower);
+    let mut f_upper = f(upper);
+    while (upper - lower).abs() > CONVERGENCE_TOLERANCE {
+        let candidate = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+        let f_candidate = f(candidate);
+
+        if f_candidate * f_upper <= 0.0 {
+            lower = upper;
+            f_lower = f_upper;
+        } else {
+            f_lower /= 2.0;
+        }
+        upper = candidate;
+        f_upper = f_candidate;
+    }
+
+    (lower / 2.0).exp()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rating(rating: f64, deviation: f64) -> Glicko2Rating {
+        Glicko2Rating { rating, deviation, volatility: DEFAULT_VOLATILITY }
+    }
+
+    // Worked example from Glickman's "Example of the Glicko-2 system"
+    #[test]
+    fn matches_the_paper_example() {
+        let player = rating(1500.0, 200.0);
+        let updated = player.update(&[(rating(1400.0, 30.0), 1.0), (rating(1550.0, 100.0), 0.0), (rating(1700.0, 300.0), 0.0)]);
+
+        assert!((updated.rating - 1464.06).abs() < 0.01, "{}", updated.rating);
+        assert!((updated.deviation - 151.52).abs() < 0.01, "{}", updated.deviation);
+        assert!((updated.volatility - 0.05999).abs() < 0.00001, "{}", updated.volatility);
+    }
+
+    #[test]
+    fn deviation_grows_without_games_up_to_the_default() {
+        let player = rating(1500.0, 50.0);
+        let updated = player.update(&[]);
+
+        assert_eq!(updated.rating, 1500.0);
+        assert_eq!(updated.volatility, DEFAULT_VOLATILITY);
+        assert!((updated.deviation - (50.0f64.powi(2) + (DEFAULT_VOLATILITY * GLICKO2_SCALE).powi(2)).sqrt()).abs() < 0.000001);
+
+        assert_eq!(Glicko2Rating::default().update(&[]).deviation, DEFAULT_DEVIATION);
+    }
+
+    #[test]
+    fn results_between_equal_players_are_symmetric() {
+        let player = Glicko2Rating::default();
+
+        let drawn = player.update(&[(player, 0.5)]);
+        assert!((drawn.rating - DEFAULT_RATING).abs() < 0.000001);
+        assert!(drawn.deviation < DEFAULT_DEVIATION);
+
+        let winner = player.update(&[(player, 1.0)]);
+        let loser = player.update(&[(player, 0.0)]);
+        assert!(winner.rating > DEFAULT_RATING);
+        assert!((winner.rating - DEFAULT_RATING - (DEFAULT_RATING - loser.rating)).abs() < 0.000001);
+        assert!((winner.deviation - loser.deviation).abs() < 0.000001);
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+
+use crate::constants::LEADERBOARD_KEY;
+
+
+// Every leaderboard is kept per game type and rating category
+pub const LEADERBOARD_GLOBAL: &str = "global";
+pub const LEADERBOARD_FRIENDS: &str = "friends";
+pub const LEADERBOARD_COUNTRY: &str = "country";
+// Weekly and monthly leaderboards rank the rating points gained in the current week (monday to sunday, UTC) or month
+pub const LEADERBOARD_WEEKLY: &str = "weekly";
+pub const LEADERBOARD_MONTHLY: &str = "monthly";
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderboardPeriod {
+    Weekly,
+    Monthly,
+}
+
+impl LeaderboardPeriod {
+    pub fn from_scope(scope: &str) -> Option<Self> {
+        match scope {
+            LEADERBOARD_WEEKLY => Some(LeaderboardPeriod::Weekly),
+            LEADERBOARD_MONTHLY => Some(LeaderboardPeriod::Monthly),
+            _ => None,
+        }
+    }
+
+    pub fn scope(&self) -> &'static str {
+        match self {
+            LeaderboardPeriod::Weekly => LEADERBOARD_WEEKLY,
+            LeaderboardPeriod::Monthly => LEADERBOARD_MONTHLY,
+        }
+    }
+
+    // First instant of the period containing at
+    pub fn start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let date = at.date_naive();
+        let first_day = match self {
+            LeaderboardPeriod::Weekly => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            LeaderboardPeriod::Monthly => NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap(),
+        };
+
+        first_day.and_hms_opt(0, 0, 0).unwrap().and_utc()
+    }
+
+    // First instant of the period after the one containing at
+    pub fn end(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let start = self.start(at);
+        match self {
+            LeaderboardPeriod::Weekly => start + Duration::weeks(1),
+            LeaderboardPeriod::Monthly => start + Months::new(1),
+        }
+    }
+
+    // Last instant of the period before the one containing at
+    pub fn previous(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        self.start(at) - Duration::seconds(1)
+    }
+
+    // ISO week ("2026-W42") or month ("2026-10") of at
+    pub fn label(&self, at: DateTime<Utc>) -> String {
+        match self {
+            LeaderboardPeriod::Weekly => {
+                let week = at.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            },
+            LeaderboardPeriod::Monthly => format!("{}-{:02}", at.year(), at.month()),
+        }
+    }
+}
+
+
+pub fn leaderboard_key(game_type: &str, category: &str) -> String {
+    format!("{}{}:{}", LEADERBOARD_KEY, game_type, category)
+}
+
+pub fn country_leaderboard_key(game_type: &str, category: &str, country: &str) -> String {
+    format!("{}:{}:{}", leaderboard_key(game_type, category), LEADERBOARD_COUNTRY, country)
+}
+
+// Leaderboard of the period containing at
+pub fn period_leaderboard_key(game_type: &str, category: &str, period: LeaderboardPeriod, at: DateTime<Utc>) -> String {
+    format!("{}:{}:{}", leaderboard_key(game_type, category), period.scope(), period.label(at))
+}
+
+// ISO 3166-1 alpha-2 code, e.g. "IN"
+pub fn is_valid_country(country: &str) -> bool {
+    country.len() == 2 && country.chars().all(|c| c.is_ascii_uppercase())
+}
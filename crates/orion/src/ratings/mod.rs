@@ -0,0 +1,3 @@
+pub mod glicko2;
+pub mod category;
+pub mod leaderboard;
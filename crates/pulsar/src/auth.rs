@@ -0,0 +1,28 @@
+use axum::http::{header, HeaderMap};
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Error;
+
+
+// Same claims messier puts in the tokens it issues
+#[derive(Serialize, Deserialize)]
+pub struct Claims {
+    pub exp: usize,
+    pub iat: usize,
+    pub user_id: String,
+}
+
+
+// Players can only queue themselves, the user comes from the bearer token
+pub fn authenticate(headers: &HeaderMap, secret: &str) -> Result<Claims, Error> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(Error::Unauthorized)?;
+
+    decode::<Claims>(token, &DecodingKey::from_secret(secret.as_ref()), &Validation::default())
+        .map(|token_data| token_data.claims)
+        .map_err(|_| Error::Unauthorized)
+}
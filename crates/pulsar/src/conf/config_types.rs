@@ -0,0 +1,98 @@
+use serde::Deserialize;
+
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct LoggingConfiguration {
+    pub level: LogLevelConfiguration,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct LogLevelConfiguration {
+    pub root: Option<String>,
+    pub directives: Vec<LoggingDirective>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct LoggingDirective {
+    pub namespace: String,
+    pub level: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct ServerConfiguration {
+    pub port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct BrokerProperties {
+    pub urls: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct KafkaConfiguration {
+    pub broker: BrokerProperties,
+    pub producer: ProducerProperties,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct ProducerProperties {
+    pub client_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct RedisConfiguration {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct PostgresDatabaseUrl {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct MongoDatabaseConfiguration {
+    pub url: String,
+    pub connection: MongoDatabaseConnectionProperties,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct MongoDatabaseConnectionProperties {
+    pub pool: MongoDatabasePoolProperties,
+    pub connect_timeout: Option<u64>,
+    pub idle_timeout: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct MongoDatabasePoolProperties {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct JwtConfiguration {
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[allow(unused)]
+pub struct MatchmakingConfiguration {
+    pub tick_interval_ms: u64,
+    pub initial_rating_window: f64,
+    pub rating_window_growth_per_sec: f64,
+    pub max_rating_window: f64,
+    pub queue_entry_ttl_secs: u64,
+    pub repeat_pairing_cooldown_secs: u64,
+}
@@ -0,0 +1,27 @@
+use config::{Config, ConfigError, File};
+use serde::Deserialize;
+use super::config_types::{JwtConfiguration, KafkaConfiguration, LoggingConfiguration, MatchmakingConfiguration, MongoDatabaseConfiguration, PostgresDatabaseUrl, RedisConfiguration, ServerConfiguration};
+
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct Configuration {
+    pub server: ServerConfiguration,
+    pub kafka: KafkaConfiguration,
+    pub redis: RedisConfiguration,
+    pub mongo_db: MongoDatabaseConfiguration,
+    pub postgres_url: PostgresDatabaseUrl,
+    pub jwt: JwtConfiguration,
+    pub matchmaking: MatchmakingConfiguration,
+    pub logging: LoggingConfiguration
+}
+
+impl Configuration {
+    pub fn load() -> Result<Self, ConfigError> {
+        // Load always properties of application.yml
+        let builder =
+            Config::builder().add_source(File::with_name("resources/application-local.yml"));
+
+        builder.build()?.try_deserialize()
+    }
+}
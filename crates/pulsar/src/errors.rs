@@ -0,0 +1,74 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use serde_json::json;
+
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Clone, Debug, Serialize)]
+pub enum Error {
+    Unauthorized,
+    MissingParamsError,
+    UnsupportedGameType,
+    InvalidTimeControl,
+    InvalidStakeRange,
+    UserNotFound,
+    AlreadyInQueue,
+    NotInQueue,
+    QueueError,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
+        write!(fmt, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let (status_code, error_message) = self.client_status_and_error();
+        let body = Json(json!({
+            "result": {
+                "success": false
+            },
+            "error_message": format!("{:?}", error_message)
+        }));
+
+        (status_code, body).into_response()
+    }
+}
+
+impl Error {
+    pub fn client_status_and_error(&self) -> (StatusCode, ClientError) {
+        match self {
+            Self::Unauthorized => (StatusCode::UNAUTHORIZED, ClientError::NO_AUTH),
+            Self::MissingParamsError => (StatusCode::BAD_REQUEST, ClientError::INVALID_PARAMS),
+            Self::UnsupportedGameType => (StatusCode::BAD_REQUEST, ClientError::UNSUPPORTED_GAME_TYPE),
+            Self::InvalidTimeControl => (StatusCode::BAD_REQUEST, ClientError::INVALID_TIME_CONTROL),
+            Self::InvalidStakeRange => (StatusCode::BAD_REQUEST, ClientError::INVALID_STAKE_RANGE),
+            Self::UserNotFound => (StatusCode::BAD_REQUEST, ClientError::USER_NOT_FOUND),
+            Self::AlreadyInQueue => (StatusCode::CONFLICT, ClientError::ALREADY_IN_QUEUE),
+            Self::NotInQueue => (StatusCode::BAD_REQUEST, ClientError::NOT_IN_QUEUE),
+            Self::QueueError => (StatusCode::INTERNAL_SERVER_ERROR, ClientError::SERVICE_ERROR),
+        }
+    }
+}
+
+
+#[derive(Debug, strum_macros::AsRefStr)]
+#[allow(non_camel_case_types)]
+pub enum ClientError {
+    NO_AUTH,
+    INVALID_PARAMS,
+    UNSUPPORTED_GAME_TYPE,
+    INVALID_TIME_CONTROL,
+    INVALID_STAKE_RANGE,
+    USER_NOT_FOUND,
+    ALREADY_IN_QUEUE,
+    NOT_IN_QUEUE,
+    SERVICE_ERROR,
+}
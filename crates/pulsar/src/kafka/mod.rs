@@ -0,0 +1 @@
+pub mod producer;
\ No newline at end of file
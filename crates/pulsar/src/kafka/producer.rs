@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use axum::Error;
+use orion::events::kafka_event::KafkaGeneralEvent;
+use rdkafka::error::KafkaError;
+use rdkafka::producer::FutureProducer;
+use rdkafka::producer::FutureRecord;
+use rdkafka::ClientConfig;
+
+use crate::conf::config_types::KafkaConfiguration;
+
+
+// Not transactional: when one of the events of a match cannot be sent the matcher deletes the game and puts
+// both players back in the queue
+pub fn create_new_kafka_producer(config: &KafkaConfiguration) -> Result<FutureProducer, Error> {
+    let nan_id_gen = nano_id::base64::<15>();
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", config.broker.urls.clone())
+        .set("request.timeout.ms", "5000")
+        .set("delivery.timeout.ms", "5000")
+        .set("enable.idempotence", "true")
+        .set("request.required.acks", "all")
+        .set("message.send.max.retries", "3")
+        .set("client.id", config.producer.client_id.clone() +"-" + nan_id_gen.as_str())
+        .create().unwrap();
+
+    Ok(producer)
+}
+
+
+pub async fn send_kafka_events(producer: &FutureProducer , kafka_events: Vec<KafkaGeneralEvent>) -> Result<(), KafkaError> {
+    for kafka_event in kafka_events {
+        producer
+            .send(
+                FutureRecord::to(&kafka_event.topic)
+                        .payload(&kafka_event.payload)
+                        .key(&kafka_event.key),
+                Duration::from_secs(3),
+            )
+            .await
+            .map_err(|e| e.0)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{response::IntoResponse, routing::{get, post}, Router};
+use conf::config_types::ServerConfiguration;
+use orion::{constants::MONGO_DB_NAME, engines::registry::GameEngineRegistry};
+use sea_orm::Database;
+use serde_json::json;
+use state::MatchmakerState;
+use tokio::task::JoinHandle;
+use tower::ServiceBuilder;
+use tower_http::cors::CorsLayer;
+use tracing::info;
+
+
+pub mod auth;
+pub mod conf;
+pub mod errors;
+pub mod kafka;
+pub mod logging_tracing;
+pub mod match_game;
+pub mod matcher;
+pub mod mongo_pool;
+pub mod queue;
+pub mod state;
+
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+
+    let config = conf::configuration::Configuration::load().unwrap();
+    logging_tracing::init(&config)?;
+
+    let redis_conn = redis::Client::open(config.redis.url.clone()).unwrap().get_multiplexed_async_connection().await.unwrap();
+    let mongo_client = mongo_pool::init_db_client(&config.mongo_db).await.unwrap();
+    let postgres_conn = Database::connect(config.postgres_url.url.clone()).await?;
+
+    let state = MatchmakerState {
+        redis_conn,
+        postgres_conn,
+        jwt_secret: config.jwt.secret.clone(),
+        engine_registry: Arc::new(GameEngineRegistry::with_default_engines()),
+        matchmaking: Arc::new(config.matchmaking.clone()),
+    };
+
+    let producer = kafka::producer::create_new_kafka_producer(&config.kafka).unwrap();
+    let shutdown_handles = vec![matcher::start(state.clone(), mongo_client.database(MONGO_DB_NAME), producer)];
+    info!("Matchmaker started");
+
+    start_web_server(&config.server, state, shutdown_handles).await;
+
+    Ok(())
+}
+
+
+async fn start_web_server(
+    config: &ServerConfiguration,
+    state: MatchmakerState,
+    shutdown_handles: Vec<JoinHandle<()>>
+) {
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], config.port));
+    tracing::info!("listening on {addr}");
+
+    let routes_all = Router::new()
+                            .route("/api/v1/health", get(health))
+                            .route("/api/v1/matchmaking/join_queue", post(queue::join_queue))
+                            .route("/api/v1/matchmaking/leave_queue", post(queue::leave_queue))
+                            .route("/api/v1/matchmaking/queue_status", get(queue::queue_status))
+                            .layer(ServiceBuilder::new()
+                                    .layer(CorsLayer::permissive()))
+                            .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .unwrap();
+    axum::serve(listener, routes_all).with_graceful_shutdown(shutdown_signal(shutdown_handles)).await.unwrap();
+}
+
+
+pub async fn health() -> impl IntoResponse {
+    axum::Json(json!({ "Pulsar status" : "UP" }))
+}
+
+pub async fn shutdown_signal(shutdown_handles: Vec<JoinHandle<()>>) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Initialization of Ctrl+C handler failed");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Initialization of signal handler failed")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    for handle in shutdown_handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,110 @@
+use mongodb::{bson::{self, doc, DateTime, Document}, Database};
+use orion::{constants::{GAME_AWAITING_BETS_STATUS, GAME_IN_PROGRESS_STATUS, MONGO_GAMES_MODEL, MONGO_USERS_MODEL, MONGO_USER_TURNS_MODEL, PLAYER_READY}, engines::registry::GameEngineRegistry, models::{game_model::Game, user_game_relation_model::UserGameRelation, user_turn_model::UserTurnMapping}};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::queue::QueueEntry;
+
+
+pub struct MatchedPlayer {
+    pub entry: QueueEntry,
+    pub player_type: String,
+}
+
+pub struct MatchedGame {
+    pub game: Game,
+    // In seat order
+    pub players: Vec<MatchedPlayer>,
+}
+
+
+// Creates the game documents the lobby flow would leave behind once the host starts the game. Nobody hosts a matched
+// game. Staked games wait for both players to bet, the rest start right away
+pub async fn create_match_game(mongo_db: &Database, engine_registry: &GameEngineRegistry, first: &QueueEntry, second: &QueueEntry) -> Result<MatchedGame, String> {
+    let engine = engine_registry.get(&first.game_type).map_err(|e| e.to_string())?;
+    let game_id = Uuid::new_v4();
+    let session_id = Uuid::new_v4().to_string();
+
+    // Neither player picks the colour, the random game id decides who takes the first seat
+    let seated = if game_id.as_u128().is_multiple_of(2) { [first, second] } else { [second, first] };
+
+    let game = Game {
+        id: game_id,
+        user_count: seated.len() as i64,
+        host_id: None,
+        name: format!("{} vs {}", seated[0].username, seated[1].username),
+        game_type: first.game_type.clone(),
+        is_staked: first.is_staked,
+        chess_state: "".to_string(),
+        is_match: true,
+        state_index: 0,
+        description: if first.is_staked { GAME_AWAITING_BETS_STATUS.to_string() } else { GAME_IN_PROGRESS_STATUS.to_string() },
+        staked_money_state: None,
+        poker_state: None,
+        game_state: None,
+        session_id: Some(session_id),
+        rematch_requests: vec![],
+        spectator_limit: None,
+        spectator_delay_secs: None,
+        join_code: None,
+        join_password: None,
+        is_casual: false,
+        time_control: first.time_control.clone(),
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+
+    let mut turn_mapping = UserTurnMapping {
+        host_id: "".to_string(),
+        game_id: game_id.to_string(),
+        turn_mappings: vec![],
+    };
+
+    let player_types = engine.player_types();
+    let mut players = vec![];
+    let mut relations = vec![];
+    for (index, entry) in seated.into_iter().enumerate() {
+        turn_mapping.assign_seat(&entry.user_id, &entry.username, seated.len());
+
+        let player_type = player_types.get(index).copied().unwrap_or("player").to_string();
+        let user_id = Uuid::parse_str(&entry.user_id).map_err(|e| e.to_string())?;
+        let relation = UserGameRelation {
+            user_id,
+            username: entry.username.clone(),
+            game_id: game_id.to_string(),
+            player_type: player_type.clone(),
+            player_status: PLAYER_READY.to_string(),
+        };
+        relations.push(with_string_id(&relation, "user_id", &user_id));
+        players.push(MatchedPlayer { entry: entry.clone(), player_type });
+    }
+
+    let insert_res = async {
+        mongo_db.collection::<Document>(MONGO_GAMES_MODEL).insert_one(with_string_id(&game, "id", &game_id), None).await?;
+        mongo_db.collection::<UserTurnMapping>(MONGO_USER_TURNS_MODEL).insert_one(turn_mapping, None).await?;
+        mongo_db.collection::<Document>(MONGO_USERS_MODEL).insert_many(relations, None).await?;
+        Ok::<(), mongodb::error::Error>(())
+    }.await;
+
+    if let Err(e) = insert_res {
+        delete_match_game(mongo_db, &game_id.to_string()).await;
+        return Err(e.to_string())
+    }
+
+    Ok(MatchedGame { game, players })
+}
+
+
+pub async fn delete_match_game(mongo_db: &Database, game_id: &str) {
+    let _ = mongo_db.collection::<Document>(MONGO_GAMES_MODEL).delete_many(doc! { "id": game_id }, None).await;
+    let _ = mongo_db.collection::<Document>(MONGO_USER_TURNS_MODEL).delete_many(doc! { "game_id": game_id }, None).await;
+    let _ = mongo_db.collection::<Document>(MONGO_USERS_MODEL).delete_many(doc! { "game_id": game_id }, None).await;
+}
+
+
+// Ids are stored as strings like the rest of the game documents so the lookups by string keep working
+fn with_string_id<T: Serialize>(model: &T, field: &str, id: &Uuid) -> Document {
+    let mut document = bson::to_document(model).unwrap();
+    document.insert(field, id.to_string());
+    document
+}
@@ -0,0 +1,275 @@
+use std::{collections::{HashMap, HashSet}, time::Duration};
+
+use chrono::Utc;
+use mongodb::Database;
+use orion::{constants::{CREATE_NEW_GAME_RECORD, GAME_START_EVENT, MATCHMAKING_ENTRY_KEY, MATCHMAKING_LOCK_KEY, MATCHMAKING_POOLS_KEY, MATCHMAKING_QUEUE_KEY, MATCHMAKING_RECENT_OPPONENTS_KEY, MATCH_FOUND_EVENT}, events::{kafka_event::{CreateNewGamePayloadEvent, KafkaGeneralEvent, MatchFoundKafkaEvent}, ws_events::GameStartPayload}};
+use rdkafka::producer::FutureProducer;
+use redis::{aio::MultiplexedConnection, AsyncCommands, ExistenceCheck, RedisResult, Script, SetExpiry, SetOptions};
+use tokio::{task::JoinHandle, time::interval};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{conf::config_types::MatchmakingConfiguration, kafka::producer::send_kafka_events, match_game::{create_match_game, delete_match_game, MatchedGame}, queue::{add_to_pool, QueueEntry}, state::MatchmakerState};
+
+
+// A pool lock outlives a stuck instance by this many ticks
+const LOCK_TICKS: u64 = 10;
+
+// Takes both players out of the pool only if both are still queued
+const CLAIM_PAIR_SCRIPT: &str = r#"
+if redis.call('ZSCORE', KEYS[1], ARGV[1]) and redis.call('ZSCORE', KEYS[1], ARGV[2]) then
+    return redis.call('ZREM', KEYS[1], ARGV[1], ARGV[2])
+end
+return 0
+"#;
+
+const RELEASE_LOCK_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+// A player joining between the emptiness check and the removal keeps the pool listed
+const REMOVE_EMPTY_POOL_SCRIPT: &str = r#"
+if redis.call('ZCARD', KEYS[1]) == 0 then
+    return redis.call('SREM', KEYS[2], ARGV[1])
+end
+return 0
+"#;
+
+
+// Every instance runs the matcher, the pools are spread between them through the pool locks
+pub fn start(state: MatchmakerState, mongo_db: Database, producer: FutureProducer) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let instance_id = Uuid::new_v4().to_string();
+        let mut tick_interval = interval(Duration::from_millis(state.matchmaking.tick_interval_ms));
+
+        loop {
+            tick_interval.tick().await;
+
+            let mut redis_conn = state.redis_conn.clone();
+            let pools: Vec<String> = match redis_conn.smembers(MATCHMAKING_POOLS_KEY).await {
+                Ok(pools) => pools,
+                Err(e) => {
+                    warn!("Error while fetching matchmaking pools: {:?}", e);
+                    continue;
+                }
+            };
+
+            for pool in pools {
+                let lock_key = MATCHMAKING_LOCK_KEY.to_owned() + &pool;
+                let options = SetOptions::default().conditional_set(ExistenceCheck::NX).with_expiration(SetExpiry::PX(state.matchmaking.tick_interval_ms * LOCK_TICKS));
+                let locked: RedisResult<Option<String>> = redis_conn.set_options(&lock_key, &instance_id, options).await;
+                if !matches!(locked, Ok(Some(_))) {
+                    continue;
+                }
+
+                match_pool(&state, &mut redis_conn, &mongo_db, &producer, &pool).await;
+
+                let _: RedisResult<i64> = Script::new(RELEASE_LOCK_SCRIPT).key(&lock_key).arg(&instance_id).invoke_async(&mut redis_conn).await;
+            }
+        }
+    })
+}
+
+
+async fn match_pool(state: &MatchmakerState, redis_conn: &mut MultiplexedConnection, mongo_db: &Database, producer: &FutureProducer, pool: &str) {
+    let queue_key = MATCHMAKING_QUEUE_KEY.to_owned() + pool;
+    let user_ids: Vec<String> = match redis_conn.zrange(&queue_key, 0, -1).await {
+        Ok(user_ids) => user_ids,
+        Err(e) => {
+            warn!("Error while fetching matchmaking pool {}: {:?}", pool, e);
+            return;
+        }
+    };
+
+    if user_ids.is_empty() {
+        let _: RedisResult<i64> = Script::new(REMOVE_EMPTY_POOL_SCRIPT).key(&queue_key).key(MATCHMAKING_POOLS_KEY).arg(pool).invoke_async(redis_conn).await;
+        return;
+    }
+
+    let entry_keys: Vec<String> = user_ids.iter().map(|user_id| MATCHMAKING_ENTRY_KEY.to_owned() + user_id).collect();
+    let raw_entries: Vec<Option<String>> = match redis::cmd("MGET").arg(&entry_keys).query_async(redis_conn).await {
+        Ok(raw_entries) => raw_entries,
+        Err(e) => {
+            warn!("Error while fetching matchmaking entries of pool {}: {:?}", pool, e);
+            return;
+        }
+    };
+
+    // Entries expire when a player never leaves the queue, or belong to another pool after a rejoin
+    let mut entries = vec![];
+    let mut stale_user_ids = vec![];
+    for (user_id, raw_entry) in user_ids.into_iter().zip(raw_entries) {
+        match raw_entry.and_then(|raw_entry| serde_json::from_str::<QueueEntry>(&raw_entry).ok()) {
+            Some(entry) if entry.pool == pool => entries.push(entry),
+            _ => stale_user_ids.push(user_id),
+        }
+    }
+    if !stale_user_ids.is_empty() {
+        let _: RedisResult<i64> = redis_conn.zrem(&queue_key, &stale_user_ids).await;
+    }
+
+    let now_ms = Utc::now().timestamp_millis();
+    let since_ms = now_ms - state.matchmaking.repeat_pairing_cooldown_secs as i64 * 1000;
+    let mut recent_opponents = HashMap::new();
+    for entry in &entries {
+        let opponents: Vec<String> = redis_conn.zrangebyscore(MATCHMAKING_RECENT_OPPONENTS_KEY.to_owned() + &entry.user_id, since_ms, "+inf").await.unwrap_or_default();
+        recent_opponents.insert(entry.user_id.clone(), opponents.into_iter().collect::<HashSet<String>>());
+    }
+
+    for (first, second) in pair_entries(&entries, &recent_opponents, now_ms, &state.matchmaking) {
+        let (first, second) = (&entries[first], &entries[second]);
+
+        let claimed: RedisResult<i64> = Script::new(CLAIM_PAIR_SCRIPT).key(&queue_key).arg(&first.user_id).arg(&second.user_id).invoke_async(redis_conn).await;
+        if !matches!(claimed, Ok(2)) {
+            continue;
+        }
+
+        let matched_game = match create_match_game(mongo_db, &state.engine_registry, first, second).await {
+            Ok(matched_game) => matched_game,
+            Err(e) => {
+                warn!("Error while creating game for {} and {} in pool {}: {}", first.user_id, second.user_id, pool, e);
+                requeue(redis_conn, &[first, second]).await;
+                continue;
+            }
+        };
+
+        let game_id = matched_game.game.id.to_string();
+        if let Err(e) = send_kafka_events(producer, match_events(&matched_game)).await {
+            warn!("Error while sending match events for game_id={}: {:?}", game_id, e);
+            delete_match_game(mongo_db, &game_id).await;
+            requeue(redis_conn, &[first, second]).await;
+            continue;
+        }
+
+        let _: RedisResult<()> = redis_conn.del(&[MATCHMAKING_ENTRY_KEY.to_owned() + &first.user_id, MATCHMAKING_ENTRY_KEY.to_owned() + &second.user_id]).await;
+        remember_opponents(redis_conn, first, second, now_ms, since_ms, state.matchmaking.repeat_pairing_cooldown_secs).await;
+
+        info!("Matched {} ({}) and {} ({}) in pool {} into game_id={}", first.user_id, first.rating, second.user_id, second.rating, pool, game_id);
+    }
+}
+
+
+// Oldest entries pick first and take the closest rating they may play. Both players have to accept the rating gap
+// with the window they have reached, so a long wait does not pull a new player into a lopsided game
+pub fn pair_entries(entries: &[QueueEntry], recent_opponents: &HashMap<String, HashSet<String>>, now_ms: i64, config: &MatchmakingConfiguration) -> Vec<(usize, usize)> {
+    let mut order: Vec<usize> = (0..entries.len()).collect();
+    order.sort_by_key(|index| entries[*index].joined_at);
+
+    let mut paired = vec![false; entries.len()];
+    let mut pairs = vec![];
+    for &index in &order {
+        if paired[index] {
+            continue;
+        }
+
+        let entry = &entries[index];
+        let opponent = order.iter().copied()
+            .filter(|other| *other != index && !paired[*other])
+            .filter(|other| can_pair(entry, &entries[*other], recent_opponents, now_ms, config))
+            .min_by(|a, b| (entries[*a].rating - entry.rating).abs().total_cmp(&(entries[*b].rating - entry.rating).abs()));
+
+        if let Some(opponent) = opponent {
+            paired[index] = true;
+            paired[opponent] = true;
+            pairs.push((index, opponent));
+        }
+    }
+
+    pairs
+}
+
+fn can_pair(entry: &QueueEntry, other: &QueueEntry, recent_opponents: &HashMap<String, HashSet<String>>, now_ms: i64, config: &MatchmakingConfiguration) -> bool {
+    if entry.user_id == other.user_id {
+        return false
+    }
+
+    let rating_window = entry.rating_window(now_ms, config).min(other.rating_window(now_ms, config));
+    if (entry.rating - other.rating).abs() > rating_window {
+        return false
+    }
+
+    let met_recently = |user_id: &str, opponent_id: &str| recent_opponents.get(user_id).is_some_and(|opponents| opponents.contains(opponent_id));
+    if met_recently(&entry.user_id, &other.user_id) || met_recently(&other.user_id, &entry.user_id) {
+        return false
+    }
+
+    !entry.is_staked || entry.agreed_stake(other).is_some()
+}
+
+
+fn match_events(matched_game: &MatchedGame) -> Vec<KafkaGeneralEvent> {
+    let game = &matched_game.game;
+    let game_id = game.id.to_string();
+    let session_id = game.session_id.clone().unwrap_or_default();
+
+    let new_game_record = CreateNewGamePayloadEvent {
+        game_id: game_id.clone(),
+        session_id: session_id.clone(),
+    };
+    let mut kafka_events = vec![KafkaGeneralEvent {
+        topic: CREATE_NEW_GAME_RECORD.to_string(),
+        payload: serde_json::to_string(&new_game_record).unwrap(),
+        key: CREATE_NEW_GAME_RECORD.to_string(),
+    }];
+
+    let stake_amount = matched_game.players[0].entry.agreed_stake(&matched_game.players[1].entry);
+    for (index, player) in matched_game.players.iter().enumerate() {
+        let opponent = &matched_game.players[1 - index].entry;
+        let match_found = MatchFoundKafkaEvent {
+            user_who_we_are_sending_event: player.entry.user_id.clone(),
+            game_id: game_id.clone(),
+            session_id: session_id.clone(),
+            game_type: game.game_type.clone(),
+            time_control: game.time_control.clone(),
+            is_staked: game.is_staked,
+            stake_amount,
+            player_type: player.player_type.clone(),
+            opponent_id: opponent.user_id.clone(),
+            opponent_username: opponent.username.clone(),
+            opponent_rating: opponent.rating,
+        };
+        kafka_events.push(KafkaGeneralEvent {
+            topic: "user".to_string(),
+            payload: serde_json::to_string(&match_found).unwrap(),
+            key: MATCH_FOUND_EVENT.to_string(),
+        });
+    }
+
+    // Staked games start through messier once both bets are in
+    if !game.is_staked {
+        let game_start_payload = GameStartPayload {
+            admin_id: "".to_string(),
+            game_name: game.name.clone(),
+            game_id,
+            session_id,
+        };
+        kafka_events.push(KafkaGeneralEvent {
+            topic: "game".to_string(),
+            payload: serde_json::to_string(&game_start_payload).unwrap(),
+            key: GAME_START_EVENT.to_string(),
+        });
+    }
+
+    kafka_events
+}
+
+
+async fn requeue(redis_conn: &mut MultiplexedConnection, entries: &[&QueueEntry]) {
+    for entry in entries {
+        if let Err(e) = add_to_pool(redis_conn, entry).await {
+            warn!("Error while putting user_id={} back in pool {}: {:?}", entry.user_id, entry.pool, e);
+        }
+    }
+}
+
+async fn remember_opponents(redis_conn: &mut MultiplexedConnection, first: &QueueEntry, second: &QueueEntry, now_ms: i64, since_ms: i64, cooldown_secs: u64) {
+    for (entry, opponent) in [(first, second), (second, first)] {
+        let key = MATCHMAKING_RECENT_OPPONENTS_KEY.to_owned() + &entry.user_id;
+        let _: RedisResult<()> = redis_conn.zadd(&key, &opponent.user_id, now_ms).await;
+        let _: RedisResult<()> = redis_conn.zrembyscore(&key, "-inf", since_ms).await;
+        let _: RedisResult<()> = redis_conn.expire(&key, cooldown_secs as i64).await;
+    }
+}
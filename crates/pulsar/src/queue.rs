@@ -0,0 +1,239 @@
+use std::str::FromStr;
+
+use axum::{extract::State, http::HeaderMap, Json};
+use chrono::Utc;
+use orion::{constants::{MATCHMAKING_ENTRY_KEY, MATCHMAKING_POOLS_KEY, MATCHMAKING_QUEUE_KEY}, ratings::{category::{rating_category, TimeControl}, glicko2::DEFAULT_RATING}};
+use redis::{aio::MultiplexedConnection, AsyncCommands, ExistenceCheck, RedisResult, SetExpiry, SetOptions};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use ton::models::{user_ratings::Entity as UserRatings, users::Entity as Users};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{auth::authenticate, conf::config_types::MatchmakingConfiguration, errors::{Error, Result}, state::MatchmakerState};
+
+
+// Matchmaking only pairs two players
+const MATCH_PLAYERS: usize = 2;
+
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct JoinQueuePayload {
+    pub game_type: String,
+    // "base+increment" in seconds, leave empty for games without a clock
+    #[serde(default)]
+    pub time_control: Option<String>,
+    #[serde(default)]
+    pub is_staked: bool,
+    // Range of stakes the player accepts, required for staked games
+    #[serde(default)]
+    pub min_stake: Option<f64>,
+    #[serde(default)]
+    pub max_stake: Option<f64>,
+}
+
+
+// A queued player, stored as json under MATCHMAKING_ENTRY_KEY + user_id
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QueueEntry {
+    pub user_id: String,
+    pub username: String,
+    pub game_type: String,
+    pub time_control: Option<String>,
+    pub is_staked: bool,
+    pub min_stake: Option<f64>,
+    pub max_stake: Option<f64>,
+    // Rating of the player in the category of the queued game when joining
+    pub rating: f64,
+    pub pool: String,
+    // Unix timestamp (ms)
+    pub joined_at: i64,
+}
+
+impl QueueEntry {
+    // Widest rating difference the player accepts after waiting until now_ms
+    pub fn rating_window(&self, now_ms: i64, config: &MatchmakingConfiguration) -> f64 {
+        let waited_secs = (now_ms - self.joined_at).max(0) as f64 / 1000.0;
+        (config.initial_rating_window + config.rating_window_growth_per_sec * waited_secs).min(config.max_rating_window)
+    }
+
+    // Lowest stake inside both ranges, None when the ranges do not overlap or the entries are not staked
+    pub fn agreed_stake(&self, other: &QueueEntry) -> Option<f64> {
+        let (min_stake, max_stake) = (self.min_stake?.max(other.min_stake?), self.max_stake?.min(other.max_stake?));
+        (min_stake <= max_stake).then_some(min_stake)
+    }
+}
+
+
+// Staked and normal players never share a pool, and only players of the same game and clock meet
+pub fn pool_name(game_type: &str, time_control: Option<&str>, is_staked: bool) -> String {
+    format!("{}:{}:{}", game_type, time_control.unwrap_or("none"), if is_staked { "staked" } else { "normal" })
+}
+
+
+pub async fn join_queue(
+    headers: HeaderMap,
+    State(state): State<MatchmakerState>,
+    Json(payload): Json<JoinQueuePayload>,
+) -> Result<Json<Value>> {
+    let claims = authenticate(&headers, &state.jwt_secret)?;
+    let user_id = Uuid::from_str(&claims.user_id).map_err(|_| Error::Unauthorized)?;
+
+    let game_type = payload.game_type.to_lowercase();
+    if game_type.is_empty() {
+        return Err(Error::MissingParamsError)
+    }
+
+    let (min_players, max_players) = state.engine_registry.player_limits(&game_type).map_err(|_| Error::UnsupportedGameType)?;
+    if !(min_players..=max_players).contains(&MATCH_PLAYERS) {
+        return Err(Error::UnsupportedGameType)
+    }
+
+    // Normalised so "300+3" and "300 + 3" end up in the same pool
+    let time_control = match payload.time_control.as_deref().filter(|time_control| !time_control.is_empty()) {
+        Some(time_control) => Some(TimeControl::parse(time_control).ok_or(Error::InvalidTimeControl)?.to_string()),
+        None => None,
+    };
+
+    let (min_stake, max_stake) = if payload.is_staked {
+        match (payload.min_stake, payload.max_stake) {
+            (Some(min_stake), Some(max_stake)) if min_stake > 0.0 && min_stake <= max_stake => (Some(min_stake), Some(max_stake)),
+            _ => return Err(Error::InvalidStakeRange),
+        }
+    } else {
+        (None, None)
+    };
+
+    let user = Users::find_by_id(user_id)
+        .one(&state.postgres_conn)
+        .await
+        .map_err(|_| Error::QueueError)?
+        .ok_or(Error::UserNotFound)?;
+
+    let category = rating_category(&game_type, time_control.as_deref());
+    let rating = UserRatings::find_by_category(&user_id, &game_type, category)
+        .one(&state.postgres_conn)
+        .await
+        .map_err(|_| Error::QueueError)?
+        .map(|user_rating| user_rating.rating)
+        .unwrap_or(DEFAULT_RATING);
+
+    let pool = pool_name(&game_type, time_control.as_deref(), payload.is_staked);
+    let entry = QueueEntry {
+        user_id: user_id.to_string(),
+        username: user.username,
+        game_type,
+        time_control,
+        is_staked: payload.is_staked,
+        min_stake,
+        max_stake,
+        rating,
+        pool: pool.clone(),
+        joined_at: Utc::now().timestamp_millis(),
+    };
+
+    let mut redis_conn = state.redis_conn.clone();
+    let options = SetOptions::default().conditional_set(ExistenceCheck::NX).with_expiration(SetExpiry::EX(state.matchmaking.queue_entry_ttl_secs));
+    let entry_res: RedisResult<Option<String>> = redis_conn.set_options(MATCHMAKING_ENTRY_KEY.to_owned() + &entry.user_id, serde_json::to_string(&entry).unwrap(), options).await;
+    match entry_res {
+        Ok(Some(_)) => {},
+        Ok(None) => return Err(Error::AlreadyInQueue),
+        Err(e) => {
+            warn!("Error while queueing user_id={}: {:?}", entry.user_id, e);
+            return Err(Error::QueueError)
+        }
+    }
+
+    if let Err(e) = add_to_pool(&mut redis_conn, &entry).await {
+        warn!("Error while queueing user_id={}: {:?}", entry.user_id, e);
+        let _: RedisResult<()> = redis_conn.del(MATCHMAKING_ENTRY_KEY.to_owned() + &entry.user_id).await;
+        return Err(Error::QueueError)
+    }
+
+    let body = Json(json!({
+        "result": {
+            "success": true
+        },
+        "pool": pool,
+        "category": category,
+        "rating": rating,
+    }));
+
+    Ok(body)
+}
+
+
+pub async fn leave_queue(
+    headers: HeaderMap,
+    State(state): State<MatchmakerState>,
+) -> Result<Json<Value>> {
+    let claims = authenticate(&headers, &state.jwt_secret)?;
+    let mut redis_conn = state.redis_conn.clone();
+
+    let entry = get_entry(&mut redis_conn, &claims.user_id).await?.ok_or(Error::NotInQueue)?;
+
+    // Nothing is removed once the matcher has taken the player out of the pool, the match found event follows
+    let removed: i64 = redis_conn.zrem(MATCHMAKING_QUEUE_KEY.to_owned() + &entry.pool, &entry.user_id).await.map_err(|_| Error::QueueError)?;
+    if removed == 0 {
+        return Err(Error::NotInQueue)
+    }
+    let _: RedisResult<()> = redis_conn.del(MATCHMAKING_ENTRY_KEY.to_owned() + &entry.user_id).await;
+
+    let body = Json(json!({
+        "result": {
+            "success": true
+        }
+    }));
+
+    Ok(body)
+}
+
+
+pub async fn queue_status(
+    headers: HeaderMap,
+    State(state): State<MatchmakerState>,
+) -> Result<Json<Value>> {
+    let claims = authenticate(&headers, &state.jwt_secret)?;
+    let mut redis_conn = state.redis_conn.clone();
+
+    let body = match get_entry(&mut redis_conn, &claims.user_id).await? {
+        Some(entry) => {
+            let now_ms = Utc::now().timestamp_millis();
+            let queued_players: i64 = redis_conn.zcard(MATCHMAKING_QUEUE_KEY.to_owned() + &entry.pool).await.unwrap_or_default();
+
+            Json(json!({
+                "result": {
+                    "success": true
+                },
+                "in_queue": true,
+                "game_type": entry.game_type,
+                "time_control": entry.time_control,
+                "is_staked": entry.is_staked,
+                "rating": entry.rating,
+                "rating_window": entry.rating_window(now_ms, &state.matchmaking),
+                "waited_ms": now_ms - entry.joined_at,
+                "queued_players": queued_players,
+            }))
+        },
+        None => Json(json!({
+            "result": {
+                "success": true
+            },
+            "in_queue": false,
+        })),
+    };
+
+    Ok(body)
+}
+
+
+pub async fn get_entry(redis_conn: &mut MultiplexedConnection, user_id: &str) -> Result<Option<QueueEntry>> {
+    let entry: Option<String> = redis_conn.get(MATCHMAKING_ENTRY_KEY.to_owned() + user_id).await.map_err(|_| Error::QueueError)?;
+    Ok(entry.and_then(|entry| serde_json::from_str(&entry).ok()))
+}
+
+// Also used to put players back when their game could not be created
+pub async fn add_to_pool(redis_conn: &mut MultiplexedConnection, entry: &QueueEntry) -> RedisResult<()> {
+    let _: () = redis_conn.zadd(MATCHMAKING_QUEUE_KEY.to_owned() + &entry.pool, &entry.user_id, entry.rating).await?;
+    redis_conn.sadd(MATCHMAKING_POOLS_KEY, &entry.pool).await
+}
@@ -0,0 +1,16 @@
+use std::sync::Arc;
+
+use orion::engines::registry::GameEngineRegistry;
+use redis::aio::MultiplexedConnection;
+use sea_orm::DatabaseConnection;
+
+use crate::conf::config_types::MatchmakingConfiguration;
+
+#[derive(Clone)]
+pub struct MatchmakerState {
+    pub redis_conn: MultiplexedConnection,
+    pub postgres_conn: DatabaseConnection,
+    pub jwt_secret: String,
+    pub engine_registry: Arc<GameEngineRegistry>,
+    pub matchmaking: Arc<MatchmakingConfiguration>,
+}
@@ -0,0 +1,37 @@
+use axum::http::{header, HeaderMap};
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+
+
+// Same claims messier puts in the tokens it issues
+#[derive(Serialize, Deserialize)]
+pub struct Claims {
+    pub exp: usize,
+    pub iat: usize,
+    pub user_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct TokenQuery {
+    pub token: Option<String>,
+}
+
+
+// Browsers cannot set headers on a websocket handshake, so the token may also come as ?token=
+pub fn extract_token(headers: &HeaderMap, query: &TokenQuery) -> Option<String> {
+    if let Some(token) = &query.token {
+        return Some(token.clone())
+    }
+
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.to_string())
+}
+
+pub fn decode_jwt(token: &str, secret: &str) -> Option<Claims> {
+    decode::<Claims>(token, &DecodingKey::from_secret(secret.as_ref()), &Validation::default())
+        .ok()
+        .map(|token_data| token_data.claims)
+}
@@ -0,0 +1,264 @@
+use std::{collections::HashSet, fs};
+
+use mongodb::bson::{doc, DateTime};
+use orion::{constants::{CHAT_MUTED_USERS_KEY, CHAT_RATE_LIMIT_KEY, CHAT_SPECTATORS_CHANNEL, CHAT_TIMEOUT_KEY, GAME_MESSAGE_DELETED_EVENT, GAME_MESSAGE_EVENT, MONGO_GAMES_MODEL, MONGO_GAME_MESSAGES_MODEL}, events::{kafka_event::KafkaGeneralEvent, ws_events::{GameMessageDeletedPayload, GameMessagePayload, WsServerMessage}}, models::{game_message_model::GameMessageRecord, game_model::Game}};
+use redis::{AsyncCommands, RedisResult};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{conf::config_types::ChatConfiguration, kafka::producer::send_kafka_event, rooms::{game_room, spectator_room}, state::GatewayState};
+
+
+// Top level domains treated as links when written without a scheme, e.g. example.com
+const LINK_DOMAINS: [&str; 11] = ["com", "net", "org", "io", "gg", "co", "me", "ly", "tv", "xyz", "ru"];
+
+
+pub struct ChatPolicy {
+    pub max_message_length: usize,
+    pub rate_limit_messages: u64,
+    pub rate_limit_window_secs: u64,
+    pub block_links: bool,
+    pub blocked_words: HashSet<String>,
+    pub moderator_ids: HashSet<String>,
+    pub default_timeout_secs: u64,
+}
+
+impl ChatPolicy {
+    pub fn from_config(config: &ChatConfiguration) -> Self {
+        let mut blocked_words: HashSet<String> = config.blocked_words.iter().map(|word| word.to_lowercase()).collect();
+
+        for path in &config.blocked_words_files {
+            match fs::read_to_string(path) {
+                Ok(words) => blocked_words.extend(words.lines().map(|word| word.trim().to_lowercase()).filter(|word| !word.is_empty())),
+                Err(e) => warn!("Could not read blocked words file {}: {:?}", path, e),
+            }
+        }
+
+        ChatPolicy {
+            max_message_length: config.max_message_length,
+            rate_limit_messages: config.rate_limit_messages,
+            rate_limit_window_secs: config.rate_limit_window_secs,
+            block_links: config.block_links,
+            blocked_words,
+            moderator_ids: config.moderator_ids.iter().cloned().collect(),
+            default_timeout_secs: config.default_timeout_secs,
+        }
+    }
+
+    pub fn is_moderator(&self, user_id: &str) -> bool {
+        self.moderator_ids.contains(user_id)
+    }
+
+    // Message as it is stored and broadcast, blocked words are masked
+    pub fn filter(&self, message: &str) -> Result<String, &'static str> {
+        let message = message.trim();
+
+        if message.chars().count() > self.max_message_length {
+            return Err("Message is too long")
+        }
+
+        if self.block_links && contains_link(message) {
+            return Err("Links are not allowed in chat")
+        }
+
+        Ok(message.split(' ').map(|word| self.mask(word)).collect::<Vec<String>>().join(" "))
+    }
+
+    fn mask(&self, word: &str) -> String {
+        // Punctuation around the word should not let it through
+        let normalized: String = word.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+
+        if self.blocked_words.contains(&normalized) {
+            "*".repeat(word.chars().count())
+        } else {
+            word.to_string()
+        }
+    }
+}
+
+fn contains_link(message: &str) -> bool {
+    let message = message.to_lowercase();
+    if message.contains("://") || message.contains("www.") {
+        return true
+    }
+
+    message.split_whitespace().any(|word| {
+        let mut parts = word.split('.');
+        let has_name = parts.next().map(|name| !name.is_empty()).unwrap_or_default();
+        has_name && parts.any(|part| LINK_DOMAINS.contains(&part.split('/').next().unwrap_or_default()))
+    })
+}
+
+
+pub fn chat_room(game_id: &str, channel: &str) -> String {
+    if channel == CHAT_SPECTATORS_CHANNEL {
+        spectator_room(game_id)
+    } else {
+        game_room(game_id)
+    }
+}
+
+
+// Stores the message for the current session of the game and sends it to the members of the channel
+pub async fn send_message(state: &GatewayState, game_id: &str, user_id: &str, username: String, channel: &str, message: &str) -> Result<(), &'static str> {
+    let mut redis_conn = state.redis_conn.clone();
+
+    let timed_out: bool = redis_conn.exists(CHAT_TIMEOUT_KEY.to_owned() + user_id).await.unwrap_or_default();
+    if timed_out {
+        return Err("You are timed out from chat")
+    }
+
+    let message = state.chat.filter(message)?;
+
+    if !within_rate_limit(state, user_id).await {
+        return Err("You are sending messages too fast")
+    }
+
+    let session_id = match state.mongo_db.collection::<Game>(MONGO_GAMES_MODEL).find_one(doc! { "id": game_id }, None).await {
+        Ok(Some(game)) => game.session_id.unwrap_or_default(),
+        Ok(None) => return Err("Game not found"),
+        Err(e) => {
+            warn!("Error while fetching game_id={} for chat: {:?}", game_id, e);
+            return Err("Message could not be sent")
+        }
+    };
+
+    let record = GameMessageRecord {
+        id: Uuid::new_v4().to_string(),
+        game_id: game_id.to_string(),
+        session_id,
+        channel: channel.to_string(),
+        user_id: user_id.to_string(),
+        username,
+        message,
+        deleted: false,
+        created_at: DateTime::now(),
+    };
+
+    if let Err(e) = state.mongo_db.collection::<GameMessageRecord>(MONGO_GAME_MESSAGES_MODEL).insert_one(&record, None).await {
+        warn!("Could not store message for game_id={}: {:?}", game_id, e);
+        return Err("Message could not be sent")
+    }
+
+    let game_message = GameMessagePayload {
+        user_id: record.user_id,
+        username: record.username,
+        message: record.message,
+        game_id: record.game_id,
+        message_id: record.id,
+        channel: record.channel,
+    };
+
+    // Goes through kafka so members connected to other gateway instances receive it as well
+    let kafka_event = KafkaGeneralEvent {
+        topic: "game".to_string(),
+        payload: serde_json::to_string(&game_message).unwrap(),
+        key: GAME_MESSAGE_EVENT.to_string(),
+    };
+
+    send_kafka_event(&state.producer, kafka_event).await.map_err(|e| {
+        warn!("Could not forward message for game_id={}: {:?}", game_id, e);
+        "Message could not be sent"
+    })
+}
+
+// Fixed window per user, the counter expires at the end of the window
+async fn within_rate_limit(state: &GatewayState, user_id: &str) -> bool {
+    let key = CHAT_RATE_LIMIT_KEY.to_owned() + user_id;
+    let mut redis_conn = state.redis_conn.clone();
+
+    let sent: u64 = match redis_conn.incr(&key, 1).await {
+        Ok(sent) => sent,
+        Err(e) => {
+            warn!("Could not check chat rate limit of user_id={}: {:?}", user_id, e);
+            return true
+        }
+    };
+
+    if sent == 1 {
+        let _: RedisResult<()> = redis_conn.expire(&key, state.chat.rate_limit_window_secs as i64).await;
+    }
+
+    sent <= state.chat.rate_limit_messages
+}
+
+
+pub async fn delete_message(state: &GatewayState, moderator_id: &str, game_id: &str, message_id: &str) -> Result<(), &'static str> {
+    if !state.chat.is_moderator(moderator_id) {
+        return Err("Only moderators can delete messages")
+    }
+
+    let record = state.mongo_db.collection::<GameMessageRecord>(MONGO_GAME_MESSAGES_MODEL).find_one_and_update(
+        doc! { "id": message_id, "game_id": game_id },
+        doc! { "$set": { "deleted": true } },
+        None
+    ).await;
+
+    let record = match record {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err("Message not found"),
+        Err(e) => {
+            warn!("Could not delete message_id={} of game_id={}: {:?}", message_id, game_id, e);
+            return Err("Message could not be deleted")
+        }
+    };
+
+    let payload = GameMessageDeletedPayload { game_id: game_id.to_string(), message_id: record.id, channel: record.channel.clone() };
+    let frame = WsServerMessage { event: GAME_MESSAGE_DELETED_EVENT.to_string(), payload: serde_json::to_string(&payload).unwrap() };
+    state.rooms.publish(&chat_room(game_id, &record.channel), serde_json::to_string(&frame).unwrap()).await;
+
+    Ok(())
+}
+
+// Timeouts apply to every game, 0 seconds uses the configured default
+pub async fn timeout_user(state: &GatewayState, moderator_id: &str, user_id: &str, duration_secs: u64) -> Result<(), &'static str> {
+    if !state.chat.is_moderator(moderator_id) {
+        return Err("Only moderators can time out users")
+    }
+
+    let duration_secs = if duration_secs == 0 { state.chat.default_timeout_secs } else { duration_secs };
+
+    let mut redis_conn = state.redis_conn.clone();
+    let redis_res: RedisResult<()> = redis_conn.set_ex(CHAT_TIMEOUT_KEY.to_owned() + user_id, moderator_id, duration_secs).await;
+
+    redis_res.map_err(|e| {
+        warn!("Could not time out user_id={}: {:?}", user_id, e);
+        "User could not be timed out"
+    })
+}
+
+
+pub async fn muted_users(state: &GatewayState, user_id: &str) -> HashSet<String> {
+    let mut redis_conn = state.redis_conn.clone();
+    redis_conn.smembers(CHAT_MUTED_USERS_KEY.to_owned() + user_id).await.unwrap_or_default()
+}
+
+pub async fn set_muted(state: &GatewayState, user_id: &str, muted_user_id: &str, muted: bool) -> Result<(), &'static str> {
+    let key = CHAT_MUTED_USERS_KEY.to_owned() + user_id;
+    let mut redis_conn = state.redis_conn.clone();
+
+    let redis_res: RedisResult<()> = if muted {
+        redis_conn.sadd(key, muted_user_id).await
+    } else {
+        redis_conn.srem(key, muted_user_id).await
+    };
+
+    redis_res.map_err(|e| {
+        warn!("Could not update muted users of user_id={}: {:?}", user_id, e);
+        "Mute could not be updated"
+    })
+}
+
+// Chat frames from muted users are dropped before they reach the connection
+pub fn is_muted_frame(frame: &str, muted_users: &HashSet<String>) -> bool {
+    if muted_users.is_empty() {
+        return false
+    }
+
+    match serde_json::from_str::<WsServerMessage>(frame) {
+        Ok(server_message) if server_message.event == GAME_MESSAGE_EVENT => serde_json::from_str::<GameMessagePayload>(&server_message.payload)
+            .map(|game_message| muted_users.contains(&game_message.user_id))
+            .unwrap_or_default(),
+        _ => false,
+    }
+}
@@ -0,0 +1,118 @@
+use serde::Deserialize;
+
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct LoggingConfiguration {
+    pub level: LogLevelConfiguration,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct LogLevelConfiguration {
+    pub root: Option<String>,
+    pub directives: Vec<LoggingDirective>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct LoggingDirective {
+    pub namespace: String,
+    pub level: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct ServerConfiguration {
+    pub port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct BrokerProperties {
+    pub urls: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct ConsumerConfiguration {
+    pub id: String,
+    pub topic: Vec<String>,
+    pub client_id: String,
+    pub group_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct KafkaConfiguration {
+    pub broker: BrokerProperties,
+    pub consumer: Vec<ConsumerConfiguration>,
+    pub producer: ProducerProperties,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct ProducerProperties {
+    pub client_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct RedisConfiguration {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct MongoDatabaseConfiguration {
+    pub url: String,
+    pub connection: MongoDatabaseConnectionProperties,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct MongoDatabaseConnectionProperties {
+    pub pool: MongoDatabasePoolProperties,
+    pub connect_timeout: Option<u64>,
+    pub idle_timeout: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct MongoDatabasePoolProperties {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct JwtConfiguration {
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct HeartbeatConfiguration {
+    pub interval_secs: u64,
+    // How long a player may be gone from an in progress game before it is invalidated
+    pub grace_period_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct ChatConfiguration {
+    pub max_message_length: usize,
+    // Messages a user may send in every window, across all games
+    pub rate_limit_messages: u64,
+    pub rate_limit_window_secs: u64,
+    pub block_links: bool,
+    // Words masked in messages, the files hold one word per line
+    #[serde(default)]
+    pub blocked_words: Vec<String>,
+    #[serde(default)]
+    pub blocked_words_files: Vec<String>,
+    // Users allowed to delete messages and time out users in every game
+    #[serde(default)]
+    pub moderator_ids: Vec<String>,
+    pub default_timeout_secs: u64,
+}
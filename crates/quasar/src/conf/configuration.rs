@@ -0,0 +1,27 @@
+use config::{Config, ConfigError, File};
+use serde::Deserialize;
+use super::config_types::{ChatConfiguration, HeartbeatConfiguration, JwtConfiguration, KafkaConfiguration, LoggingConfiguration, MongoDatabaseConfiguration, RedisConfiguration, ServerConfiguration};
+
+
+#[derive(Debug, Deserialize)]
+#[allow(unused)]
+pub struct Configuration {
+    pub server: ServerConfiguration,
+    pub kafka: KafkaConfiguration,
+    pub redis: RedisConfiguration,
+    pub mongo_db: MongoDatabaseConfiguration,
+    pub jwt: JwtConfiguration,
+    pub heartbeat: HeartbeatConfiguration,
+    pub chat: ChatConfiguration,
+    pub logging: LoggingConfiguration
+}
+
+impl Configuration {
+    pub fn load() -> Result<Self, ConfigError> {
+        // Load always properties of application.yml
+        let builder =
+            Config::builder().add_source(File::with_name("resources/application-local.yml"));
+
+        builder.build()?.try_deserialize()
+    }
+}
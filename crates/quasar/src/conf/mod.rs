@@ -0,0 +1,2 @@
+pub mod config_types;
+pub mod configuration;
\ No newline at end of file
@@ -0,0 +1,151 @@
+use std::convert::Infallible;
+
+use axum::{extract::{Path, State}, http::{HeaderMap, StatusCode}, response::{sse::{Event, KeepAlive, Sse}, IntoResponse, Response}};
+use futures::{stream, StreamExt, TryStreamExt};
+use mongodb::{bson::{doc, DateTime, Document}, options::FindOptions};
+use orion::{constants::{GAME_BET_POOL_EVENT, GAME_CLOCK_EVENT, GAME_RESULT_EVENT, GAME_STATE_UPDATE_EVENT, MONGO_GAMES_MODEL, MONGO_GAME_MOVES_MODEL}, events::ws_events::{GameStateUpdatePayload, WsServerMessage}, models::{game_model::Game, game_move_model::GameMoveRecord}};
+use tokio::sync::mpsc;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{rooms::game_room, spectators::{self, anonymous_spectator}, state::GatewayState};
+
+
+// Leaves the game room once the client goes away and the stream is dropped
+struct FeedGuard {
+    state: GatewayState,
+    game_id: String,
+    connection_id: String,
+}
+
+impl Drop for FeedGuard {
+    fn drop(&mut self) {
+        let (state, game_id, connection_id) = (self.state.clone(), self.game_id.clone(), self.connection_id.clone());
+        tokio::spawn(async move {
+            state.rooms.leave(&game_room(&game_id), &connection_id).await;
+            spectators::leave(&state, &game_id, &anonymous_spectator(&connection_id)).await;
+        });
+    }
+}
+
+
+// Read only stream of a game for clients that cannot hold a websocket. Starts with the current snapshot, or with the
+// moves after Last-Event-ID when the client reconnects, followed by move, clock, bet-pool and game-over events
+pub async fn game_feed(
+    Path(game_id): Path<String>,
+    headers: HeaderMap,
+    State(state): State<GatewayState>,
+) -> Response {
+    let game = match state.mongo_db.collection::<Game>(MONGO_GAMES_MODEL).find_one(doc! { "id": game_id.clone() }, None).await {
+        Ok(Some(game)) => game,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            warn!("Error while fetching game_id={} for feed: {:?}", game_id, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    };
+
+    let connection_id = Uuid::new_v4().to_string();
+    if !spectators::join(&state, &game_id, &game, &anonymous_spectator(&connection_id)).await {
+        return StatusCode::TOO_MANY_REQUESTS.into_response()
+    }
+
+    // Join before reading the history so nothing published in between is lost, duplicates are dropped by state_index
+    let (sender, receiver) = mpsc::unbounded_channel::<String>();
+    let guard = FeedGuard { state: state.clone(), game_id: game_id.clone(), connection_id };
+    state.rooms.join(&game_room(&game_id), &guard.connection_id, spectators::room_sender(sender, &game)).await;
+
+    // Moves still inside the broadcast delay are left for the live events
+    let mut move_filter = doc! { "game_id": game_id.clone() };
+    let delay_secs = game.broadcast_delay_secs();
+    if delay_secs > 0 {
+        let visible_until = DateTime::from_millis(DateTime::now().timestamp_millis() - delay_secs as i64 * 1000);
+        move_filter.insert("created_at", doc! { "$lte": visible_until });
+    }
+
+    let last_event_id = headers.get("last-event-id").and_then(|value| value.to_str().ok()).and_then(|value| value.parse::<i64>().ok());
+
+    let mut initial_events = vec![];
+    let mut last_state_index = -1;
+
+    match last_event_id {
+        Some(last_event_id) => {
+            move_filter.insert("state_index", doc! { "$gt": last_event_id });
+            for game_move in find_moves(&state, &game_id, move_filter, 1, None).await {
+                last_state_index = game_move.state_index;
+                initial_events.push(state_event("move", &game_id, game_move.public_state, game_move.state_index));
+            }
+            last_state_index = last_state_index.max(last_event_id);
+        },
+        None => {
+            match find_moves(&state, &game_id, move_filter, -1, Some(1)).await.pop() {
+                Some(game_move) => {
+                    last_state_index = game_move.state_index;
+                    initial_events.push(state_event("snapshot", &game_id, game_move.public_state, game_move.state_index));
+                },
+                // Nothing played yet, the initial state comes as the first move event
+                None => {
+                    last_state_index = game.state_index - 1;
+                    initial_events.push(state_event("snapshot", &game_id, "".to_string(), game.state_index));
+                }
+            }
+        }
+    }
+
+    let live_events = stream::unfold((receiver, guard, last_state_index), |(mut receiver, guard, mut last_state_index)| async move {
+        loop {
+            let frame = receiver.recv().await?;
+            if let Some(event) = feed_event(&frame, &mut last_state_index) {
+                return Some((event, (receiver, guard, last_state_index)))
+            }
+        }
+    });
+
+    let events = stream::iter(initial_events).chain(live_events).map(Ok::<Event, Infallible>);
+
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+
+async fn find_moves(state: &GatewayState, game_id: &str, filter: Document, order: i32, limit: Option<i64>) -> Vec<GameMoveRecord> {
+    let options = FindOptions::builder().sort(doc! { "state_index": order }).limit(limit).build();
+
+    let cursor = match state.mongo_db.collection::<GameMoveRecord>(MONGO_GAME_MOVES_MODEL).find(filter, options).await {
+        Ok(cursor) => cursor,
+        Err(e) => {
+            warn!("Error while fetching moves for game_id={}: {:?}", game_id, e);
+            return vec![]
+        }
+    };
+
+    cursor.try_collect().await.unwrap_or_default()
+}
+
+// Same payload as the game-state-update-event, whether it comes from the history or from kafka
+fn state_event(event: &str, game_id: &str, state: String, state_index: i64) -> Event {
+    let payload = GameStateUpdatePayload { game_id: game_id.to_string(), state, state_index };
+    Event::default().event(event).id(state_index.to_string()).data(serde_json::to_string(&payload).unwrap())
+}
+
+// Chat, transient actions and errors are left to websocket clients
+fn feed_event(frame: &str, last_state_index: &mut i64) -> Option<Event> {
+    let message: WsServerMessage = serde_json::from_str(frame).ok()?;
+
+    let event = match message.event.as_str() {
+        GAME_STATE_UPDATE_EVENT => {
+            let payload: GameStateUpdatePayload = serde_json::from_str(&message.payload).ok()?;
+            if payload.state_index <= *last_state_index {
+                return None
+            }
+
+            *last_state_index = payload.state_index;
+            return Some(state_event("move", &payload.game_id, payload.state, payload.state_index))
+        },
+        GAME_CLOCK_EVENT => "clock",
+        GAME_BET_POOL_EVENT => "bet-pool",
+        GAME_RESULT_EVENT => "game-over",
+        _ => return None,
+    };
+
+    Some(Event::default().event(event).data(message.payload))
+}
@@ -0,0 +1,61 @@
+use chrono::Utc;
+use mongodb::bson::{doc, Document};
+use orion::{constants::{GAME_IN_PROGRESS_STATUS, MONGO_GAMES_MODEL, MONGO_USER_TURNS_MODEL, PLAYER_HEARTBEAT_DATA, PLAYER_HEARTBEAT_KEY}, events::kafka_event::GameOverEvent, models::user_turn_model::UserTurnMapping};
+use redis::{AsyncCommands, RedisResult};
+use tracing::warn;
+
+use crate::state::GatewayState;
+
+
+// Keeps the player's heartbeat alive while the game is in progress. When it expires nova publishes the stored
+// GameOverEvent, so the event is rebuilt on every beat to follow the current session and players
+pub async fn beat(state: &GatewayState, game_id: &str, user_id: &str) {
+    let key_id = game_id.to_owned() + "_" + user_id;
+
+    let game = match state.mongo_db.collection::<Document>(MONGO_GAMES_MODEL).find_one(doc! { "id": game_id }, None).await {
+        Ok(game) => game,
+        Err(e) => {
+            warn!("Error while fetching game_id={} for heartbeat: {:?}", game_id, e);
+            return
+        }
+    };
+
+    let session_id = game.as_ref()
+        .filter(|game| game.get_str("description").unwrap_or_default() == GAME_IN_PROGRESS_STATUS)
+        .and_then(|game| game.get_str("session_id").ok())
+        .map(|session_id| session_id.to_string());
+
+    let Some(session_id) = session_id else {
+        stop(state, &key_id).await;
+        return
+    };
+
+    let other_players: Vec<String> = match state.mongo_db.collection::<UserTurnMapping>(MONGO_USER_TURNS_MODEL).find_one(doc! { "game_id": game_id }, None).await {
+        Ok(Some(turn_mapping)) => turn_mapping.active_turns().iter().filter(|turn| turn.user_id != user_id).map(|turn| turn.user_id.clone()).collect(),
+        _ => vec![],
+    };
+
+    let game_over_event = GameOverEvent {
+        game_id: game_id.to_string(),
+        session_id,
+        winner_id: other_players.first().cloned().unwrap_or_default(),
+        winner_ids: other_players,
+        is_game_valid: false,
+        absent_player_id: user_id.to_string(),
+    };
+
+    let mut redis_conn = state.redis_conn.clone();
+    let grace_period_secs = state.heartbeat_grace_period_secs;
+
+    // Data key outlives the heartbeat so nova can still read it on expiry. The heartbeat holds the last time the player was seen
+    let _: RedisResult<()> = redis_conn.set_ex(PLAYER_HEARTBEAT_DATA.to_owned() + &key_id, serde_json::to_string(&game_over_event).unwrap(), grace_period_secs + 60).await;
+    let _: RedisResult<()> = redis_conn.set_ex(PLAYER_HEARTBEAT_KEY.to_owned() + &key_id, Utc::now().timestamp_millis(), grace_period_secs).await;
+}
+
+
+async fn stop(state: &GatewayState, key_id: &str) {
+    let mut redis_conn = state.redis_conn.clone();
+    let _: RedisResult<()> = redis_conn.del(PLAYER_HEARTBEAT_KEY.to_owned() + key_id).await;
+    let _: RedisResult<()> = redis_conn.del(PLAYER_HEARTBEAT_DATA.to_owned() + key_id).await;
+}
+
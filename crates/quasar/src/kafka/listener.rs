@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use orion::{constants::CHAT_SPECTATORS_CHANNEL, events::ws_events::WsServerMessage};
+use rdkafka::{consumer::StreamConsumer, Message};
+use serde_json::Value;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::rooms::{game_room, spectator_room, user_room, RoomRegistry};
+
+
+pub fn listen(stream_consumer: StreamConsumer, rooms: Arc<RoomRegistry>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        do_listen(&stream_consumer, rooms).await;
+    })
+}
+
+// Events on the user and game topics are keyed by their ws event name
+async fn do_listen(stream_consumer: &StreamConsumer, rooms: Arc<RoomRegistry>) {
+    loop {
+        match stream_consumer.recv().await {
+            Err(e) => warn!("Error: {}", e),
+            Ok(message) => {
+                let (Some(Ok(payload)), Some(Ok(event))) = (message.payload_view::<str>(), message.key_view::<str>()) else {
+                    continue;
+                };
+
+                let Some(room) = event_room(message.topic(), payload) else {
+                    warn!("No room found for event {} on topic {}", event, message.topic());
+                    continue;
+                };
+
+                let frame = WsServerMessage { event: event.to_string(), payload: payload.to_string() };
+                rooms.publish(&room, serde_json::to_string(&frame).unwrap()).await;
+            }
+        }
+    }
+}
+
+// Game topic events addressed to a single user (rejected moves) go to that user only
+fn event_room(topic: &str, payload: &str) -> Option<String> {
+    let payload: Value = serde_json::from_str(payload).ok()?;
+    let field = |name: &str| payload.get(name).and_then(Value::as_str).filter(|value| !value.is_empty());
+
+    if let Some(user_id) = field("user_who_we_are_sending_event") {
+        return Some(user_room(user_id))
+    }
+
+    match topic {
+        "game" => field("game_id").map(|game_id| match field("channel") {
+            // Spectator chat must not reach the players
+            Some(CHAT_SPECTATORS_CHANNEL) => spectator_room(game_id),
+            _ => game_room(game_id),
+        }),
+        _ => None,
+    }
+}
@@ -0,0 +1,3 @@
+pub mod consumer;
+pub mod listener;
+pub mod producer;
\ No newline at end of file
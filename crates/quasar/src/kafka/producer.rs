@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use axum::Error;
+use orion::events::kafka_event::KafkaGeneralEvent;
+use rdkafka::error::KafkaError;
+use rdkafka::producer::FutureProducer;
+use rdkafka::producer::FutureRecord;
+use rdkafka::ClientConfig;
+
+use crate::conf::config_types::KafkaConfiguration;
+
+
+// Not transactional: every connection sends on the same producer and each client frame is a single event,
+// so there is nothing to group and overlapping transactions would fail
+pub fn create_new_kafka_producer(config: &KafkaConfiguration) -> Result<FutureProducer, Error> {
+    let nan_id_gen = nano_id::base64::<15>();
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", config.broker.urls.clone())
+        .set("request.timeout.ms", "5000")
+        .set("delivery.timeout.ms", "5000")
+        .set("enable.idempotence", "true")
+        .set("linger.ms", "5") // Moves and chat should go out right away
+        .set("request.required.acks", "all")
+        .set("message.send.max.retries", "3")
+        .set("client.id", config.producer.client_id.clone() +"-" + nan_id_gen.as_str())
+        .create().unwrap();
+
+    Ok(producer)
+}
+
+
+pub async fn send_kafka_event(producer: &FutureProducer , kafka_event: KafkaGeneralEvent) -> Result<(), KafkaError> {
+    producer
+        .send(
+            FutureRecord::to(&kafka_event.topic)
+                    .payload(&kafka_event.payload)
+                    .key(&kafka_event.key),
+            Duration::from_secs(3),
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| e.0)
+}
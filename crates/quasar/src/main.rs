@@ -0,0 +1,131 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{response::IntoResponse, routing::get, Router};
+use conf::config_types::ServerConfiguration;
+use orion::constants::MONGO_DB_NAME;
+use rooms::RoomRegistry;
+use serde_json::json;
+use state::GatewayState;
+use tokio::task::JoinHandle;
+use tower::ServiceBuilder;
+use tower_http::cors::CorsLayer;
+use tracing::{info, warn};
+
+
+pub mod auth;
+pub mod chat;
+pub mod conf;
+pub mod feed;
+pub mod heartbeat;
+pub mod kafka;
+pub mod logging_tracing;
+pub mod mongo_pool;
+pub mod rooms;
+pub mod socket;
+pub mod spectators;
+pub mod state;
+
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+
+    let config = conf::configuration::Configuration::load().unwrap();
+    logging_tracing::init(&config)?;
+
+    let client = redis::Client::open(config.redis.url.clone()).unwrap();
+    let redis_conn = client.get_multiplexed_async_connection().await.unwrap();
+    let mongo_client = mongo_pool::init_db_client(&config.mongo_db).await.unwrap();
+
+    let rooms = Arc::new(RoomRegistry::new(redis_conn.clone()));
+    info!("Gateway instance {} starting", rooms.instance_id);
+
+    let mut shutdown_handles: Vec<JoinHandle<()>> = vec![];
+
+    // Frames for rooms whose members are connected to this instance, sent by the other instances
+    let listener_rooms = rooms.clone();
+    shutdown_handles.push(tokio::spawn(async move {
+        if let Err(e) = listener_rooms.listen(client).await {
+            warn!("Gateway pubsub listener stopped: {:?}", e);
+        }
+    }));
+
+    let kafka_consumers = kafka::consumer::init_consumers(&config.kafka).unwrap();
+    for (_, stream_consumer) in kafka_consumers.into_iter() {
+        shutdown_handles.push(kafka::listener::listen(stream_consumer, rooms.clone()));
+    }
+
+    let state = GatewayState {
+        rooms: rooms.clone(),
+        producer: kafka::producer::create_new_kafka_producer(&config.kafka).unwrap(),
+        mongo_db: mongo_client.database(MONGO_DB_NAME),
+        jwt_secret: config.jwt.secret.clone(),
+        redis_conn,
+        heartbeat_interval_secs: config.heartbeat.interval_secs,
+        heartbeat_grace_period_secs: config.heartbeat.grace_period_secs,
+        chat: Arc::new(chat::ChatPolicy::from_config(&config.chat)),
+    };
+
+    start_web_server(&config.server, state, shutdown_handles).await;
+
+    rooms.clear().await;
+
+    Ok(())
+}
+
+
+async fn start_web_server(
+    config: &ServerConfiguration,
+    state: GatewayState,
+    shutdown_handles: Vec<JoinHandle<()>>
+) {
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], config.port));
+    tracing::info!("listening on {addr}");
+
+    let routes_all = Router::new()
+                            .route("/api/v1/health", get(health))
+                            .route("/ws", get(socket::ws_handler))
+                            .route("/api/v1/game/:game_id/feed", get(feed::game_feed))
+                            .layer(ServiceBuilder::new()
+                                    .layer(CorsLayer::permissive()))
+                            .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .unwrap();
+    axum::serve(listener, routes_all).with_graceful_shutdown(shutdown_signal(shutdown_handles)).await.unwrap();
+}
+
+
+pub async fn health() -> impl IntoResponse {
+    axum::Json(json!({ "Quasar status" : "UP" }))
+}
+
+
+pub async fn shutdown_signal(shutdown_handles: Vec<JoinHandle<()>>) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Initialization of Ctrl+C handler failed");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Initialization of signal handler failed")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    for handle in shutdown_handles {
+        handle.abort();
+    }
+}
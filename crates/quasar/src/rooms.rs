@@ -0,0 +1,152 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use futures::StreamExt;
+use orion::constants::{WS_GATEWAY_CHANNEL, WS_ROOM_KEY};
+use redis::{aio::MultiplexedConnection, AsyncCommands, RedisResult};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::warn;
+use uuid::Uuid;
+
+
+pub type ConnectionSender = UnboundedSender<String>;
+
+pub fn user_room(user_id: &str) -> String {
+    format!("user:{}", user_id)
+}
+
+pub fn game_room(game_id: &str) -> String {
+    format!("game:{}", game_id)
+}
+
+pub fn spectator_room(game_id: &str) -> String {
+    format!("spectators:{}", game_id)
+}
+
+
+// Frame forwarded to the gateway instance holding members of the room
+#[derive(Deserialize, Serialize)]
+struct RoomFrame {
+    room: String,
+    frame: String,
+}
+
+
+// Connections of this instance per room. Redis keeps, for every room, the set of gateway instances with members in it,
+// so an event consumed by one instance reaches the connections held by the others
+pub struct RoomRegistry {
+    pub instance_id: String,
+    redis_conn: MultiplexedConnection,
+    rooms: RwLock<HashMap<String, HashMap<String, ConnectionSender>>>,
+}
+
+impl RoomRegistry {
+    pub fn new(redis_conn: MultiplexedConnection) -> Self {
+        RoomRegistry {
+            instance_id: Uuid::new_v4().to_string(),
+            redis_conn,
+            rooms: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn join(&self, room: &str, connection_id: &str, sender: ConnectionSender) {
+        let first_member = {
+            let mut rooms = self.rooms.write().unwrap();
+            let members = rooms.entry(room.to_string()).or_default();
+            members.insert(connection_id.to_string(), sender);
+            members.len() == 1
+        };
+
+        if first_member {
+            let mut redis_conn = self.redis_conn.clone();
+            let redis_res: RedisResult<()> = redis_conn.sadd(WS_ROOM_KEY.to_owned() + room, &self.instance_id).await;
+            if let Err(e) = redis_res {
+                warn!("Could not register room {}: {:?}", room, e);
+            }
+        }
+    }
+
+    pub async fn leave(&self, room: &str, connection_id: &str) {
+        let last_member = {
+            let mut rooms = self.rooms.write().unwrap();
+            match rooms.get_mut(room) {
+                Some(members) => {
+                    members.remove(connection_id);
+                    let empty = members.is_empty();
+                    if empty {
+                        rooms.remove(room);
+                    }
+                    empty
+                },
+                None => false,
+            }
+        };
+
+        if last_member {
+            let mut redis_conn = self.redis_conn.clone();
+            let _: RedisResult<()> = redis_conn.srem(WS_ROOM_KEY.to_owned() + room, &self.instance_id).await;
+        }
+    }
+
+    // Sends the frame to the connections of this instance. Closed connections are dropped when they leave
+    pub fn deliver_local(&self, room: &str, frame: &str) {
+        let rooms = self.rooms.read().unwrap();
+        if let Some(members) = rooms.get(room) {
+            for sender in members.values() {
+                let _ = sender.send(frame.to_string());
+            }
+        }
+    }
+
+    // Sends the frame to every member of the room, whichever instance holds them
+    pub async fn publish(&self, room: &str, frame: String) {
+        let mut redis_conn = self.redis_conn.clone();
+        let instances: Vec<String> = match redis_conn.smembers(WS_ROOM_KEY.to_owned() + room).await {
+            Ok(instances) => instances,
+            Err(e) => {
+                warn!("Could not read room {} from redis: {:?}", room, e);
+                vec![self.instance_id.clone()]
+            }
+        };
+
+        for instance_id in instances {
+            if instance_id == self.instance_id {
+                self.deliver_local(room, &frame);
+                continue;
+            }
+
+            let room_frame = RoomFrame { room: room.to_string(), frame: frame.clone() };
+            let _: RedisResult<()> = redis_conn.publish(WS_GATEWAY_CHANNEL.to_owned() + &instance_id, serde_json::to_string(&room_frame).unwrap()).await;
+        }
+    }
+
+    // Frames other instances forward to this one
+    pub async fn listen(&self, redis_client: redis::Client) -> RedisResult<()> {
+        let mut pubsub = redis_client.get_async_pubsub().await?;
+        pubsub.subscribe(WS_GATEWAY_CHANNEL.to_owned() + &self.instance_id).await?;
+
+        let mut messages = pubsub.on_message();
+        while let Some(message) = messages.next().await {
+            let payload: String = match message.get_payload() {
+                Ok(payload) => payload,
+                Err(_) => continue,
+            };
+
+            if let Ok(room_frame) = serde_json::from_str::<RoomFrame>(&payload) {
+                self.deliver_local(&room_frame.room, &room_frame.frame);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Called on shutdown so the other instances stop forwarding to this one
+    pub async fn clear(&self) {
+        let rooms: Vec<String> = self.rooms.read().unwrap().keys().cloned().collect();
+        let mut redis_conn = self.redis_conn.clone();
+
+        for room in rooms {
+            let _: RedisResult<()> = redis_conn.srem(WS_ROOM_KEY.to_owned() + &room, &self.instance_id).await;
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use std::{collections::{HashMap, HashSet}, str::FromStr, sync::{Arc, RwLock}, time::Duration};
+
+use axum::{extract::{ws::{Message, WebSocket}, Query, State, WebSocketUpgrade}, http::{HeaderMap, StatusCode}, response::{IntoResponse, Response}};
+use futures_util::{SinkExt, StreamExt};
+use mongodb::bson::{doc, Binary};
+use orion::{constants::{CHAT_DELETE_MESSAGE, CHAT_MUTE_USER, CHAT_PLAYERS_CHANNEL, CHAT_SPECTATORS_CHANNEL, CHAT_TIMEOUT_USER, CHAT_UNMUTE_USER, ERROR_EVENT, GAME_MESSAGE_EVENT, JOIN_GAME_ROOM, LEAVE_GAME_ROOM, MONGO_GAMES_MODEL, MONGO_USERS_MODEL, USER_GAME_EVENTS, USER_GAME_MOVE}, events::{kafka_event::KafkaGeneralEvent, ws_events::{ChatMessageActionPayload, ChatUserActionPayload, ErrorMessagePayload, GameRoomPayload, WsClientMessage, WsGameMovePayload, WsServerMessage}}, models::{game_model::Game, user_game_event::UserGameMove, user_game_relation_model::UserGameRelation}};
+use tokio::{sync::mpsc::{self, UnboundedSender}, time::interval};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{auth::{decode_jwt, extract_token, TokenQuery}, chat, heartbeat, kafka::producer::send_kafka_event, rooms::{game_room, spectator_room, user_room}, spectators, state::GatewayState};
+
+
+struct Connection {
+    id: String,
+    user_id: String,
+    sender: UnboundedSender<String>,
+    // Game rooms joined by the connection with the player relation, None for spectators
+    games: HashMap<String, Option<UserGameRelation>>,
+    // Users whose chat messages are not delivered to this connection
+    muted_users: Arc<RwLock<HashSet<String>>>,
+}
+
+
+pub async fn ws_handler(
+    ws: WebSocketUpgrade,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+    State(state): State<GatewayState>,
+) -> Response {
+    let claims = match extract_token(&headers, &query).and_then(|token| decode_jwt(&token, &state.jwt_secret)) {
+        Some(claims) => claims,
+        None => return StatusCode::UNAUTHORIZED.into_response(),
+    };
+
+    ws.on_upgrade(move |socket| handle_socket(socket, claims.user_id, state))
+}
+
+
+async fn handle_socket(socket: WebSocket, user_id: String, state: GatewayState) {
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+    let (sender, mut receiver) = mpsc::unbounded_channel::<String>();
+
+    let muted_users = Arc::new(RwLock::new(chat::muted_users(&state, &user_id).await));
+    let send_muted_users = muted_users.clone();
+
+    let send_task = tokio::spawn(async move {
+        while let Some(frame) = receiver.recv().await {
+            if chat::is_muted_frame(&frame, &send_muted_users.read().unwrap()) {
+                continue;
+            }
+            if ws_sender.send(Message::Text(frame)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut connection = Connection {
+        id: Uuid::new_v4().to_string(),
+        user_id: user_id.clone(),
+        sender: sender.clone(),
+        games: HashMap::new(),
+        muted_users,
+    };
+
+    info!("User {} connected on connection {}", user_id, connection.id);
+    state.rooms.join(&user_room(&user_id), &connection.id, sender).await;
+
+    let mut heartbeat_interval = interval(Duration::from_secs(state.heartbeat_interval_secs));
+
+    loop {
+        tokio::select! {
+            message = ws_receiver.next() => {
+                match message {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<WsClientMessage>(&text) {
+                            Ok(client_message) => handle_client_message(&state, &mut connection, client_message).await,
+                            Err(_) => send_error(&connection, "", "Invalid message"),
+                        }
+                    },
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    _ => {}
+                }
+            },
+            // Players of the joined games stay present while the connection is open
+            _ = heartbeat_interval.tick() => {
+                for (game_id, relation) in connection.games.iter() {
+                    if relation.is_some() {
+                        heartbeat::beat(&state, game_id, &connection.user_id).await;
+                    }
+                }
+            },
+        }
+    }
+
+    state.rooms.leave(&user_room(&connection.user_id), &connection.id).await;
+    for (game_id, relation) in connection.games.iter() {
+        state.rooms.leave(&game_room(game_id), &connection.id).await;
+        if relation.is_none() {
+            state.rooms.leave(&spectator_room(game_id), &connection.id).await;
+            spectators::leave(&state, game_id, &connection.user_id).await;
+        }
+    }
+
+    send_task.abort();
+    info!("User {} disconnected from connection {}", connection.user_id, connection.id);
+}
+
+
+async fn handle_client_message(state: &GatewayState, connection: &mut Connection, client_message: WsClientMessage) {
+    if client_message.game_id.is_empty() {
+        send_error(connection, "", "game_id is required");
+        return
+    }
+
+    match client_message.event.as_str() {
+        JOIN_GAME_ROOM => {
+            // Joining again would count the spectator twice
+            if let Some(relation) = connection.games.get(&client_message.game_id) {
+                send_frame(connection, JOIN_GAME_ROOM, &GameRoomPayload { game_id: client_message.game_id.clone(), is_spectator: relation.is_none() });
+                return
+            }
+
+            let relation = find_user_game_relation(state, &connection.user_id, &client_message.game_id).await;
+            let is_spectator = relation.is_none();
+            let mut room_sender = connection.sender.clone();
+
+            if is_spectator {
+                let Some(game) = find_game(state, &client_message.game_id).await else {
+                    send_error(connection, &client_message.game_id, "Game not found");
+                    return
+                };
+
+                if !spectators::join(state, &client_message.game_id, &game, &connection.user_id).await {
+                    send_error(connection, &client_message.game_id, "Spectator limit reached for this game");
+                    return
+                }
+
+                room_sender = spectators::room_sender(room_sender, &game);
+                // Spectator chat is not delayed, it never reaches the players
+                state.rooms.join(&spectator_room(&client_message.game_id), &connection.id, connection.sender.clone()).await;
+            }
+
+            state.rooms.join(&game_room(&client_message.game_id), &connection.id, room_sender).await;
+            // A player coming back within the grace period keeps the game valid
+            if !is_spectator {
+                heartbeat::beat(state, &client_message.game_id, &connection.user_id).await;
+            }
+            connection.games.insert(client_message.game_id.clone(), relation);
+
+            send_frame(connection, JOIN_GAME_ROOM, &GameRoomPayload { game_id: client_message.game_id, is_spectator });
+        },
+        LEAVE_GAME_ROOM => {
+            if let Some(relation) = connection.games.remove(&client_message.game_id) {
+                state.rooms.leave(&game_room(&client_message.game_id), &connection.id).await;
+                if relation.is_none() {
+                    state.rooms.leave(&spectator_room(&client_message.game_id), &connection.id).await;
+                    spectators::leave(state, &client_message.game_id, &connection.user_id).await;
+                }
+                send_frame(connection, LEAVE_GAME_ROOM, &GameRoomPayload { game_id: client_message.game_id, is_spectator: relation.is_none() });
+            }
+        },
+        USER_GAME_MOVE => {
+            // Spectators are turned away here already, cerotis rejects moves from users without a seat as well
+            if !matches!(connection.games.get(&client_message.game_id), Some(Some(_))) {
+                send_error(connection, &client_message.game_id, "Only players of the game can send moves");
+                return
+            }
+
+            let move_payload: WsGameMovePayload = match serde_json::from_str(&client_message.payload) {
+                Ok(move_payload) => move_payload,
+                Err(_) => {
+                    send_error(connection, &client_message.game_id, "Invalid move payload");
+                    return
+                }
+            };
+
+            let user_game_move = UserGameMove {
+                user_id: connection.user_id.clone(),
+                game_id: client_message.game_id.clone(),
+                move_type: move_payload.move_type,
+                user_move: move_payload.user_move,
+            };
+
+            let kafka_event = KafkaGeneralEvent {
+                topic: USER_GAME_EVENTS.to_string(),
+                payload: serde_json::to_string(&user_game_move).unwrap(),
+                // Keyed by game so the moves of a game stay ordered on one partition
+                key: client_message.game_id.clone(),
+            };
+
+            if let Err(e) = send_kafka_event(&state.producer, kafka_event).await {
+                warn!("Could not forward move for game_id={}: {:?}", client_message.game_id, e);
+                send_error(connection, &client_message.game_id, "Move could not be sent");
+            }
+        },
+        GAME_MESSAGE_EVENT => {
+            let (username, channel) = match connection.games.get(&client_message.game_id) {
+                Some(Some(relation)) => (relation.username.clone(), CHAT_PLAYERS_CHANNEL),
+                // Spectators have no relation to take the username from
+                Some(None) => (connection.user_id.clone(), CHAT_SPECTATORS_CHANNEL),
+                None => {
+                    send_error(connection, &client_message.game_id, "Join the game room before sending messages");
+                    return
+                }
+            };
+
+            if client_message.payload.trim().is_empty() {
+                return
+            }
+
+            if let Err(error_message) = chat::send_message(state, &client_message.game_id, &connection.user_id, username, channel, &client_message.payload).await {
+                send_error(connection, &client_message.game_id, error_message);
+            }
+        },
+        CHAT_MUTE_USER | CHAT_UNMUTE_USER => {
+            let Ok(action) = serde_json::from_str::<ChatUserActionPayload>(&client_message.payload) else {
+                send_error(connection, &client_message.game_id, "Invalid chat payload");
+                return
+            };
+
+            if action.user_id == connection.user_id {
+                send_error(connection, &client_message.game_id, "You cannot mute yourself");
+                return
+            }
+
+            let muted = client_message.event == CHAT_MUTE_USER;
+            if let Err(error_message) = chat::set_muted(state, &connection.user_id, &action.user_id, muted).await {
+                send_error(connection, &client_message.game_id, error_message);
+                return
+            }
+
+            {
+                let mut muted_users = connection.muted_users.write().unwrap();
+                if muted {
+                    muted_users.insert(action.user_id.clone());
+                } else {
+                    muted_users.remove(&action.user_id);
+                }
+            }
+
+            send_frame(connection, &client_message.event, &action);
+        },
+        CHAT_DELETE_MESSAGE => {
+            let Ok(action) = serde_json::from_str::<ChatMessageActionPayload>(&client_message.payload) else {
+                send_error(connection, &client_message.game_id, "Invalid chat payload");
+                return
+            };
+
+            if let Err(error_message) = chat::delete_message(state, &connection.user_id, &client_message.game_id, &action.message_id).await {
+                send_error(connection, &client_message.game_id, error_message);
+            }
+        },
+        CHAT_TIMEOUT_USER => {
+            let Ok(action) = serde_json::from_str::<ChatUserActionPayload>(&client_message.payload) else {
+                send_error(connection, &client_message.game_id, "Invalid chat payload");
+                return
+            };
+
+            match chat::timeout_user(state, &connection.user_id, &action.user_id, action.duration_secs).await {
+                Ok(()) => send_frame(connection, CHAT_TIMEOUT_USER, &action),
+                Err(error_message) => send_error(connection, &client_message.game_id, error_message),
+            }
+        },
+        _ => send_error(connection, &client_message.game_id, "Unknown event"),
+    }
+}
+
+
+async fn find_user_game_relation(state: &GatewayState, user_id: &str, game_id: &str) -> Option<UserGameRelation> {
+    let user_uuid = Uuid::from_str(user_id).ok()?;
+
+    // Lobbies created by messier store user_id as a string, older documents as a binary uuid
+    let filter = doc! { "game_id": game_id, "user_id": { "$in": [user_id, Binary::from_uuid(mongodb::bson::Uuid::from_bytes(user_uuid.into_bytes()))] } };
+
+    match state.mongo_db.collection::<UserGameRelation>(MONGO_USERS_MODEL).find_one(filter, None).await {
+        Ok(relation) => relation,
+        Err(e) => {
+            warn!("Error while fetching user game relation for game_id={}: {:?}", game_id, e);
+            None
+        }
+    }
+}
+
+async fn find_game(state: &GatewayState, game_id: &str) -> Option<Game> {
+    match state.mongo_db.collection::<Game>(MONGO_GAMES_MODEL).find_one(doc! { "id": game_id }, None).await {
+        Ok(game) => game,
+        Err(e) => {
+            warn!("Error while fetching game_id={}: {:?}", game_id, e);
+            None
+        }
+    }
+}
+
+fn send_frame<T: serde::Serialize>(connection: &Connection, event: &str, payload: &T) {
+    let frame = WsServerMessage { event: event.to_string(), payload: serde_json::to_string(payload).unwrap() };
+    let _ = connection.sender.send(serde_json::to_string(&frame).unwrap());
+}
+
+fn send_error(connection: &Connection, game_id: &str, error_message: &str) {
+    send_frame(connection, ERROR_EVENT, &ErrorMessagePayload {
+        game_id: game_id.to_string(),
+        error_message: error_message.to_string(),
+        user_who_we_are_sending_event: connection.user_id.clone(),
+        current_state: None,
+        state_index: None,
+    });
+}
@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use orion::{constants::{GAME_SPECTATORS_KEY, GAME_SPECTATOR_COUNT_EVENT}, events::ws_events::{GameSpectatorCountPayload, WsServerMessage}, models::game_model::Game};
+use redis::{AsyncCommands, RedisResult};
+use tokio::{sync::mpsc, time::{sleep_until, Instant}};
+use tracing::warn;
+
+use crate::{rooms::{game_room, ConnectionSender}, state::GatewayState};
+
+
+// Feed clients are not logged in, each one is tracked under its own connection id
+pub fn anonymous_spectator(connection_id: &str) -> String {
+    format!("anonymous:{}", connection_id)
+}
+
+
+// Counts the spectator in, unless the game is already at its spectator limit. A user watching from several
+// connections only takes one place
+pub async fn join(state: &GatewayState, game_id: &str, game: &Game, spectator_id: &str) -> bool {
+    let key = GAME_SPECTATORS_KEY.to_owned() + game_id;
+    let mut redis_conn = state.redis_conn.clone();
+
+    let connections: i64 = match redis_conn.hincr(&key, spectator_id, 1).await {
+        Ok(connections) => connections,
+        Err(e) => {
+            warn!("Could not add spectator to game_id={}: {:?}", game_id, e);
+            return true
+        }
+    };
+
+    let spectator_count: i64 = redis_conn.hlen(&key).await.unwrap_or_default();
+
+    if connections == 1 {
+        if let Some(spectator_limit) = game.spectator_limit {
+            if spectator_count > spectator_limit {
+                remove_connection(state, &key, spectator_id).await;
+                return false
+            }
+        }
+
+        publish_count(state, game_id, spectator_count).await;
+    }
+
+    true
+}
+
+pub async fn leave(state: &GatewayState, game_id: &str, spectator_id: &str) {
+    let key = GAME_SPECTATORS_KEY.to_owned() + game_id;
+
+    if remove_connection(state, &key, spectator_id).await {
+        let mut redis_conn = state.redis_conn.clone();
+        let spectator_count: i64 = redis_conn.hlen(&key).await.unwrap_or_default();
+        publish_count(state, game_id, spectator_count).await;
+    }
+}
+
+// True if it was the spectator's last connection
+async fn remove_connection(state: &GatewayState, key: &str, spectator_id: &str) -> bool {
+    let mut redis_conn = state.redis_conn.clone();
+
+    let connections: i64 = match redis_conn.hincr(key, spectator_id, -1).await {
+        Ok(connections) => connections,
+        Err(_) => return false,
+    };
+
+    if connections > 0 {
+        return false
+    }
+
+    let _: RedisResult<()> = redis_conn.hdel(key, spectator_id).await;
+    true
+}
+
+async fn publish_count(state: &GatewayState, game_id: &str, spectator_count: i64) {
+    let payload = GameSpectatorCountPayload { game_id: game_id.to_string(), spectator_count };
+    let frame = WsServerMessage { event: GAME_SPECTATOR_COUNT_EVENT.to_string(), payload: serde_json::to_string(&payload).unwrap() };
+    state.rooms.publish(&game_room(game_id), serde_json::to_string(&frame).unwrap()).await;
+}
+
+
+// Sender for a room member that gets every frame delay after it was published, in the same order
+pub fn delayed_sender(sender: ConnectionSender, delay: Duration) -> ConnectionSender {
+    let (delayed_sender, mut receiver) = mpsc::unbounded_channel::<String>();
+    let (timed_sender, mut timed_receiver) = mpsc::unbounded_channel::<(Instant, String)>();
+
+    tokio::spawn(async move {
+        while let Some(frame) = receiver.recv().await {
+            if timed_sender.send((Instant::now() + delay, frame)).is_err() {
+                break;
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        while let Some((deliver_at, frame)) = timed_receiver.recv().await {
+            sleep_until(deliver_at).await;
+            if sender.send(frame).is_err() {
+                break;
+            }
+        }
+    });
+
+    delayed_sender
+}
+
+// Members watching with a broadcast delay get their own delayed copy of the connection sender
+pub fn room_sender(sender: ConnectionSender, game: &Game) -> ConnectionSender {
+    match game.broadcast_delay_secs() {
+        0 => sender,
+        delay_secs => delayed_sender(sender, Duration::from_secs(delay_secs)),
+    }
+}
@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+use mongodb::Database;
+use rdkafka::producer::FutureProducer;
+use redis::aio::MultiplexedConnection;
+
+use crate::{chat::ChatPolicy, rooms::RoomRegistry};
+
+#[derive(Clone)]
+pub struct GatewayState {
+    pub rooms: Arc<RoomRegistry>,
+    pub producer: FutureProducer,
+    pub mongo_db: Database,
+    pub jwt_secret: String,
+    pub redis_conn: MultiplexedConnection,
+    pub heartbeat_interval_secs: u64,
+    pub heartbeat_grace_period_secs: u64,
+    pub chat: Arc<ChatPolicy>,
+}
@@ -123,13 +123,35 @@ impl Entity {
         )
     }
 
+    // Games with several winners (ties, multi player placements). A bet on any of the winners is a winning bet
+    pub fn find_by_game_id_and_session_id_with_progress_with_winner_ids(game_id: Uuid ,  session_id: String , status: String , winner_ids: Vec<Uuid>) -> Select<Entity> {
+        Self::find().filter(
+            Condition::all()
+            .add(Column::GameId.eq(game_id))
+            .add(Column::SessionId.eq(session_id))
+            .add(Column::Status.eq(status))
+            .add(Column::UserIdBettingOn.is_in(winner_ids))
+        )
+    }
+
+    pub fn find_by_game_id_and_session_id_with_progress_not_in_winner_ids(game_id: Uuid ,  session_id: String , status: String , winner_ids: Vec<Uuid>) -> Select<Entity> {
+        Self::find().filter(
+            Condition::all()
+            .add(Column::GameId.eq(game_id))
+            .add(Column::SessionId.eq(session_id))
+            .add(Column::Status.eq(status))
+            .add(Column::UserIdBettingOn.is_not_in(winner_ids))
+        )
+    }
+
+    // Stake of the player who left an invalidated game, the only bet that is not compensated
     pub fn find_invalid_user_by_game_id_and_session_id_for_invalid_game(game_id: Uuid ,  session_id: String , player_id: Uuid) -> Select<Entity> {
         Self::find().filter(
             Condition::all()
             .add(Column::GameId.eq(game_id))
             .add(Column::SessionId.eq(session_id))
             .add(Column::IsPlayer.eq(true))
-            .add(Column::UserId.ne(player_id))
+            .add(Column::UserId.eq(player_id))
         )
     }
     
@@ -0,0 +1,56 @@
+use sea_orm::{entity::prelude::*, Condition};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+
+// One finished session of a game, kept after the mongo documents are removed. result is win, draw, abandoned or expired
+#[derive(Clone, Debug, Deserialize , Serialize, DeriveEntityModel)]
+#[sea_orm(table_name = "game_history")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub game_id: Uuid,
+    pub session_id: String,
+    pub game_type: String,
+    pub game_name: String,
+    pub result: String,
+    pub termination_reason: String,
+    pub time_control: Option<String>,
+    pub move_count: i32,
+    // Last public state of the game, the FEN for chess
+    pub final_state: Option<String>,
+    pub pgn: Option<String>,
+    pub started_at: DateTime,
+    pub ended_at: DateTime,
+    // Replays of private lobbies are only shown to their players
+    pub is_private: bool,
+}
+
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::game_history_players::Entity")]
+    GameHistoryPlayers,
+}
+
+impl Related<super::game_history_players::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GameHistoryPlayers.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    pub fn find_by_id(id: &Uuid) -> Select<Entity> {
+        Self::find().filter(Column::Id.eq(*id))
+    }
+
+    pub fn find_by_game_id_and_session_id(game_id: &Uuid, session_id: String) -> Select<Entity> {
+        Self::find().filter(
+            Condition::all()
+            .add(Column::GameId.eq(*game_id))
+            .add(Column::SessionId.eq(session_id))
+        )
+    }
+}
@@ -0,0 +1,66 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+
+// Player of an archived game. place is None when the game ended without a result
+#[derive(Clone, Debug, Deserialize , Serialize, DeriveEntityModel)]
+#[sea_orm(table_name = "game_history_players")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub game_history_id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    // Colour or seat name, empty for games keyed by user
+    pub player_type: String,
+    pub seat: i64,
+    pub place: Option<i32>,
+    pub score_change: i32,
+    pub is_winner: bool,
+}
+
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::game_history::Entity",
+        from = "Column::GameHistoryId",
+        to = "super::game_history::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    GameHistory,
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Users,
+}
+
+impl Related<super::game_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GameHistory.def()
+    }
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    pub fn find_by_game_history_id(game_history_id: &Uuid) -> Select<Entity> {
+        Self::find().filter(Column::GameHistoryId.eq(*game_history_id))
+    }
+
+    pub fn find_by_user_id(user_id: &Uuid) -> Select<Entity> {
+        Self::find().filter(Column::UserId.eq(*user_id))
+    }
+}
@@ -0,0 +1,66 @@
+use sea_orm::{entity::prelude::*, Condition};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+
+// Invite to a lobby sent to a friend. status is pending, accepted or declined
+#[derive(Clone, Debug, Deserialize , Serialize, DeriveEntityModel)]
+#[sea_orm(table_name = "game_invites")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub game_id: Uuid,
+    pub user_sent_id: Uuid,
+    pub user_sent_username: String,
+    pub user_recieved_id: Uuid,
+    pub status: String,
+    pub expires_at: DateTime,
+    pub created_at: DateTime,
+}
+
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserSentId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Users,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    pub fn find_by_id(id: &Uuid) -> Select<Entity> {
+        Self::find().filter(Column::Id.eq(*id))
+    }
+
+    // Invites that have not expired yet
+    pub fn find_by_game_id_received_id_and_status(game_id: &Uuid, received_id: &Uuid, status: String, now: DateTime) -> Select<Entity> {
+        Self::find().filter(
+            Condition::all()
+            .add(Column::GameId.eq(*game_id))
+            .add(Column::UserRecievedId.eq(*received_id))
+            .add(Column::Status.eq(status))
+            .add(Column::ExpiresAt.gt(now))
+        )
+    }
+
+    pub fn find_by_received_id_and_status(received_id: &Uuid, status: String, now: DateTime) -> Select<Entity> {
+        Self::find().filter(
+            Condition::all()
+            .add(Column::UserRecievedId.eq(*received_id))
+            .add(Column::Status.eq(status))
+            .add(Column::ExpiresAt.gt(now))
+        )
+    }
+}
@@ -3,4 +3,9 @@ pub mod users_friends;
 pub mod users_wallet_keys;
 pub mod users_friends_requests;
 pub mod game_bets;
-pub mod game;
\ No newline at end of file
+pub mod game;
+pub mod game_invites;
+pub mod game_history;
+pub mod game_history_players;
+pub mod rating_history;
+pub mod user_ratings;
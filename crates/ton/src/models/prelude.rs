@@ -1,4 +1,9 @@
 pub use super::users_friends::Entity as UsersFriends;
 pub use super::users::Entity as Users;
 pub use super::users_wallet_keys::Entity as UsersWallets;
-pub use super::users_friends_requests::Entity as UsersFriendsRequests;
\ No newline at end of file
+pub use super::users_friends_requests::Entity as UsersFriendsRequests;
+pub use super::game_invites::Entity as GameInvites;
+pub use super::game_history::Entity as GameHistory;
+pub use super::game_history_players::Entity as GameHistoryPlayers;
+pub use super::rating_history::Entity as RatingHistory;
+pub use super::user_ratings::Entity as UserRatings;
@@ -0,0 +1,59 @@
+use sea_orm::{entity::prelude::*, QueryOrder};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+
+// Rating change of a player after a rated game. opponent_id is only set for two player games, opponent_rating is the
+// average rating of the opponents before the game
+#[derive(Clone, Debug, Deserialize , Serialize, DeriveEntityModel)]
+#[sea_orm(table_name = "rating_history")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub game_id: Uuid,
+    pub session_id: String,
+    pub game_type: String,
+    pub category: String,
+    pub rating_before: f64,
+    pub rating_after: f64,
+    pub deviation_before: f64,
+    pub deviation_after: f64,
+    pub opponent_id: Option<Uuid>,
+    pub opponent_rating: f64,
+    pub created_at: DateTime,
+}
+
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Users,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    // Oldest change first
+    pub fn find_by_user_id(user_id: &Uuid) -> Select<Entity> {
+        Self::find().filter(Column::UserId.eq(*user_id)).order_by_asc(Column::CreatedAt)
+    }
+
+    pub fn find_by_category(user_id: &Uuid, game_type: &str, category: &str) -> Select<Entity> {
+        Self::find_by_user_id(user_id)
+            .filter(Column::GameType.eq(game_type))
+            .filter(Column::Category.eq(category))
+    }
+}
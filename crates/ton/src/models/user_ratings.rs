@@ -0,0 +1,55 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+
+// Glicko-2 rating of a player in one game type and time control category, computed by cerotis from game results
+#[derive(Clone, Debug, Deserialize , Serialize, DeriveEntityModel)]
+#[sea_orm(table_name = "user_ratings")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub game_type: String,
+    pub category: String,
+    pub rating: f64,
+    pub rating_deviation: f64,
+    pub rating_volatility: f64,
+    pub games_played: i32,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Users,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    pub fn find_by_user_id(user_id: &Uuid) -> Select<Entity> {
+        Self::find().filter(Column::UserId.eq(*user_id))
+    }
+
+    pub fn find_by_category(user_id: &Uuid, game_type: &str, category: &str) -> Select<Entity> {
+        Self::find()
+            .filter(Column::UserId.eq(*user_id))
+            .filter(Column::GameType.eq(game_type))
+            .filter(Column::Category.eq(category))
+    }
+}
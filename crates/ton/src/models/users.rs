@@ -16,10 +16,11 @@ pub struct Model {
     pub first_name: String,
     pub last_name: String,
     pub verified: bool,
-    pub score: i32,
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub is_online: bool,
+    // ISO 3166-1 alpha-2 code, picks the country leaderboards the player shows up on
+    pub country: Option<String>,
 }
 
 